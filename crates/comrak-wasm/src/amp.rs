@@ -0,0 +1,93 @@
+//! Conversion of rendered HTML into markup that passes the AMP validator.
+//!
+//! AMP disallows a number of elements and attributes that Comrak may emit
+//! (particularly when `unsafe` is enabled), and requires images to be written
+//! as `<amp-img>` elements with explicit dimensions. Rather than maintaining a
+//! separate formatter, we post-process the regular HTML output.
+
+use wasm_bindgen::JsValue;
+
+use crate::html::Action;
+use crate::html::Tag;
+use crate::html::TagKind;
+use crate::html::rewrite_tags;
+use crate::html::unescape;
+use crate::images::resolve_dimensions;
+use crate::options::RenderOptions;
+
+/// Elements that are never allowed in an AMP document body, and are removed
+/// along with their content.
+const DISALLOWED_ELEMENTS: &[&str] = &[
+  "applet", "base", "embed", "frame", "frameset", "iframe", "link", "meta",
+  "object", "param", "script", "style",
+];
+
+/// Rewrites Comrak's HTML output into AMP-compatible markup.
+///
+/// - `<img>` becomes `<amp-img>`, with `width`, `height`, and a `responsive`
+///   layout when the `imageDimensions` callback resolves the image's size.
+///   Images of unknown size fall back to `layout="fill"`, which requires the
+///   surrounding container to be sized by the page's own CSS.
+/// - Disallowed elements (`<script>`, `<iframe>`, `<style>`, ...) are removed.
+/// - Inline `style` attributes, `on*` event handlers, and `javascript:` URLs
+///   are stripped, since AMP forbids all three.
+pub fn to_amp(html: &str, options: &RenderOptions) -> Result<String, JsValue> {
+  let mut error = None;
+  let out = rewrite_tags(html, |tag| {
+    if DISALLOWED_ELEMENTS.contains(&&*tag.name) {
+      return Action::DropElement;
+    }
+    if !tag.is_start() {
+      return Action::Keep;
+    }
+    strip_disallowed_attrs(tag);
+    if tag.name != "img" {
+      return Action::Keep;
+    }
+    let dimensions = match options.image_dimensions {
+      | Some(ref resolver) if error.is_none() => {
+        let src = unescape(tag.get("src").unwrap_or_default());
+        resolve_dimensions(resolver, &src).unwrap_or_else(|e| {
+          error = Some(e);
+          None
+        })
+      }
+      | _ => None,
+    };
+    tag.name = "amp-img".to_string();
+    tag.kind = TagKind::Open;
+    match dimensions {
+      | Some(d) => {
+        tag.set("width", &d.width.to_string());
+        tag.set("height", &d.height.to_string());
+        tag.set("layout", "responsive");
+      }
+      | None if tag.has("width") && tag.has("height") => {}
+      | None => tag.set("layout", "fill"),
+    }
+    Action::Replace(format!("{tag}</amp-img>"))
+  });
+  match error {
+    | Some(e) => Err(e),
+    | None => Ok(out),
+  }
+}
+
+fn strip_disallowed_attrs(tag: &mut Tag) {
+  tag.retain(|attr| {
+    let name = attr.name.to_ascii_lowercase();
+    let is_url = matches!(&*name, "href" | "src");
+    let dangerous = is_url
+      && attr.value.as_deref().is_some_and(|v| {
+        unescape(v)
+          .trim_start()
+          .to_ascii_lowercase()
+          .starts_with("javascript:")
+      });
+    !(name == "style"
+      || name.starts_with("on")
+      || name.starts_with("xmlns")
+      || name.starts_with("i-amp-")
+      || dangerous)
+  });
+}
@@ -0,0 +1,73 @@
+//! Filtering of the attributes of rendered HTML through a JS callback, for the
+//! `attribute_filter` render option.
+//!
+//! Like the sanitizer, this post-processes the rendered HTML with the tag
+//! rewriter, so it applies to every element, including those Comrak renders
+//! itself and raw HTML passed through with `unsafe` enabled.
+
+use js_sys::Function;
+use wasm_bindgen::JsValue;
+
+use crate::html::Action;
+use crate::html::escape_attr;
+use crate::html::rewrite_tags;
+use crate::html::unescape;
+use crate::map_err;
+
+/// Calls `filter` with the tag name, attribute name, and unescaped value of
+/// every attribute in `html`, replacing the value with the string it returns,
+/// or removing the attribute when it returns `null` or `undefined`. An
+/// attribute without a value is passed as an empty string.
+///
+/// The first exception thrown by the callback is returned, as is an error for
+/// a return value that is neither a string nor `null`.
+pub fn filter_attributes(
+  html: &str,
+  filter: &Function,
+) -> Result<String, JsValue> {
+  let mut error = None;
+  let out = rewrite_tags(html, |tag| {
+    if !tag.is_start() || error.is_some() {
+      return Action::Keep;
+    }
+    let name = JsValue::from_str(&tag.name);
+    tag.attrs.retain_mut(|attr| {
+      if error.is_some() {
+        return true;
+      }
+      let value = unescape(attr.value.as_deref().unwrap_or_default());
+      let result = filter.call3(
+        &JsValue::NULL,
+        &name,
+        &JsValue::from_str(&attr.name),
+        &JsValue::from_str(&value),
+      );
+      match result {
+        | Ok(v) if v.is_null() || v.is_undefined() => false,
+        | Ok(v) => match v.as_string() {
+          | Some(s) if attr.value.is_none() && s.is_empty() => true,
+          | Some(s) => {
+            attr.value = Some(escape_attr(&s));
+            true
+          }
+          | None => {
+            error = Some(map_err(format!(
+              "attributeFilter returned a non-string value for {:?}",
+              attr.name
+            )));
+            true
+          }
+        },
+        | Err(e) => {
+          error = Some(e);
+          true
+        }
+      }
+    });
+    Action::Keep
+  });
+  match error {
+    | Some(e) => Err(e),
+    | None => Ok(out),
+  }
+}
@@ -0,0 +1,315 @@
+//! Structural diagnostics for Markdown documents, as a lint layer over the
+//! parse.
+//!
+//! Comrak never fails to parse a document: a table row with the wrong number
+//! of cells is padded or truncated, a code fence that is never closed runs to
+//! the end of its container, and a link to an undefined reference is left as
+//! plain text. None of these are reported, so they're found here instead,
+//! from the AST and the source it was parsed from.
+
+use std::collections::HashSet;
+use std::ops::Range;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::sync::PoisonError;
+
+use comrak::ResolvedReference;
+use comrak::nodes::AstNode;
+use comrak::nodes::NodeCodeBlock;
+use comrak::nodes::NodeValue;
+use comrak::nodes::Sourcepos;
+use comrak::options::BrokenLinkCallback as ComrakBrokenLinkCallback;
+use comrak::options::BrokenLinkReference;
+use serde::Serialize;
+
+use crate::source::Source;
+use crate::transform::collect;
+
+/// The kind of problem reported by a [`Diagnostic`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum DiagnosticKind {
+  /// A table row whose number of cells differs from the header's.
+  TableColumns,
+  /// A code fence that is never closed.
+  UnclosedFence,
+  /// An inline link whose destination is never closed, or is invalid, which
+  /// is kept as text.
+  UnclosedLink,
+  /// A full or collapsed reference link whose label isn't defined.
+  UndefinedReference,
+  /// A run of emphasis delimiters that doesn't match any other, which is kept
+  /// as text.
+  UnmatchedEmphasis,
+}
+
+/// A structural problem found in a document.
+#[derive(Debug, Serialize)]
+pub struct Diagnostic {
+  pub kind:      DiagnosticKind,
+  pub message:   String,
+  pub sourcepos: Sourcepos,
+}
+
+/// A broken link callback that records the labels of the references that
+/// neither the document nor the callback it wraps could resolve.
+pub struct Undefined<'c> {
+  inner:  Option<Arc<dyn ComrakBrokenLinkCallback + 'c>>,
+  labels: Mutex<Vec<String>>,
+}
+
+impl<'c> Undefined<'c> {
+  pub fn new(inner: Option<Arc<dyn ComrakBrokenLinkCallback + 'c>>) -> Self {
+    Self { inner, labels: Mutex::default() }
+  }
+}
+
+impl ComrakBrokenLinkCallback for Undefined<'_> {
+  fn resolve(
+    &self,
+    reference: BrokenLinkReference,
+  ) -> Option<ResolvedReference> {
+    let label = reference.original.to_string();
+    let resolved = self.inner.as_ref().and_then(|cb| cb.resolve(reference));
+    if resolved.is_none() && !label.is_empty() {
+      let mut labels =
+        self.labels.lock().unwrap_or_else(PoisonError::into_inner);
+      labels.push(label);
+    }
+    resolved
+  }
+}
+
+/// Returns the diagnostics for the document parsed from `md` into `root`, in
+/// the order of their source positions.
+pub fn diagnose<'a>(
+  root: &'a AstNode<'a>,
+  md: &str,
+  undefined: &Undefined,
+) -> Vec<Diagnostic> {
+  let source = Source::new(md);
+  let mut diagnostics = Vec::new();
+  for node in root.descendants() {
+    let data = node.data.borrow();
+    match data.value {
+      | NodeValue::Table(ref nt) => {
+        let columns = nt.alignments.len();
+        table_rows(node, &source, columns, &mut diagnostics);
+      }
+      | NodeValue::CodeBlock(ref ncb)
+        if ncb.fenced && !is_closed(&source, data.sourcepos, ncb) =>
+      {
+        diagnostics.push(unclosed_fence(data.sourcepos));
+      }
+      | _ => {}
+    }
+  }
+  undefined_references(root, undefined, &mut diagnostics);
+  diagnostics.sort_by_key(|d| d.sourcepos.start);
+  diagnostics
+}
+
+/// Returns the constructs that Comrak recovered from while parsing `md` into
+/// `root`, in the order of their source positions: code fences that are never
+/// closed, runs of emphasis delimiters that don't match any other, and inline
+/// links whose destination is never closed. The latter two are kept as text.
+pub fn recoveries<'a>(root: &'a AstNode<'a>, md: &str) -> Vec<Diagnostic> {
+  let source = Source::new(md);
+  let mut diagnostics = Vec::new();
+  for node in root.descendants() {
+    let data = node.data.borrow();
+    match data.value {
+      | NodeValue::CodeBlock(ref ncb)
+        if ncb.fenced && !is_closed(&source, data.sourcepos, ncb) =>
+      {
+        diagnostics.push(unclosed_fence(data.sourcepos));
+      }
+      | NodeValue::Text(_) => {
+        if let Some(range) = source.range(data.sourcepos) {
+          literal_syntax(md, range, data.sourcepos, &mut diagnostics);
+        }
+      }
+      | _ => {}
+    }
+  }
+  diagnostics.sort_by_key(|d| d.sourcepos.start);
+  diagnostics
+}
+
+fn unclosed_fence(sourcepos: Sourcepos) -> Diagnostic {
+  Diagnostic {
+    kind: DiagnosticKind::UnclosedFence,
+    message: "code fence is never closed".to_string(),
+    sourcepos,
+  }
+}
+
+/// Reports the syntax left in the source of a text node, at `range` of `md`:
+/// runs of `*` or `_` that could have opened or closed emphasis, and the `](`
+/// of an inline link. Backslash escaped characters are skipped.
+fn literal_syntax(
+  md: &str,
+  range: Range<usize>,
+  sourcepos: Sourcepos,
+  diagnostics: &mut Vec<Diagnostic>,
+) {
+  let text = &md[range.clone()];
+  let bytes = text.as_bytes();
+  let mut i = 0;
+  while i < bytes.len() {
+    let (kind, message, len) = match bytes[i] {
+      | b'\\' => {
+        i += 2;
+        continue;
+      }
+      | b @ (b'*' | b'_') => {
+        let len = bytes[i..].iter().take_while(|&&c| c == b).count();
+        let before = md[..range.start + i].chars().next_back();
+        let after = md[range.start + i + len..].chars().next();
+        if !can_delimit(b, before, after) {
+          i += len;
+          continue;
+        }
+        let message = format!("{:?} is not matched", &text[i..i + len]);
+        (DiagnosticKind::UnmatchedEmphasis, message, len)
+      }
+      | b']' if bytes.get(i + 1) == Some(&b'(') => {
+        let message = "link destination is invalid or never closed";
+        (DiagnosticKind::UnclosedLink, message.to_string(), 2)
+      }
+      | _ => {
+        i += 1;
+        continue;
+      }
+    };
+    // text nodes never span lines, so the columns are offset from the start
+    let mut sp = sourcepos;
+    sp.start.column += i;
+    sp.end = sp.start;
+    sp.end.column += len - 1;
+    diagnostics.push(Diagnostic { kind, message, sourcepos: sp });
+    i += len;
+  }
+}
+
+/// Returns `true` if a run of `*` or `_` between the characters `before` and
+/// `after` can open or close emphasis, per the CommonMark flanking rules.
+fn can_delimit(b: u8, before: Option<char>, after: Option<char>) -> bool {
+  let space = |c: Option<char>| c.is_none_or(char::is_whitespace);
+  let punct = |c: Option<char>| {
+    c.is_some_and(|c| {
+      c.is_ascii_punctuation()
+        || !c.is_ascii() && !c.is_alphanumeric() && !c.is_whitespace()
+    })
+  };
+  let left = !space(after) && (!punct(after) || space(before) || punct(before));
+  let right =
+    !space(before) && (!punct(before) || space(after) || punct(after));
+  match b {
+    // `_` can't open or close emphasis inside a word
+    | b'_' => {
+      (left && (!right || punct(before))) || (right && (!left || punct(after)))
+    }
+    | _ => left || right,
+  }
+}
+
+/// Reports the body rows of a table whose number of cells, as written in the
+/// source, differs from the number of columns given by its header.
+fn table_rows<'a>(
+  table: &'a AstNode<'a>,
+  source: &Source,
+  columns: usize,
+  diagnostics: &mut Vec<Diagnostic>,
+) {
+  for row in table.children() {
+    let data = row.data.borrow();
+    if !matches!(data.value, NodeValue::TableRow(false)) {
+      continue;
+    }
+    let Some(text) = source.text(data.sourcepos) else {
+      continue;
+    };
+    let cells = cell_count(text);
+    if cells != columns {
+      diagnostics.push(Diagnostic {
+        kind:      DiagnosticKind::TableColumns,
+        message:   format!(
+          "table row has {cells} cells, but the header has {columns}"
+        ),
+        sourcepos: data.sourcepos,
+      });
+    }
+  }
+}
+
+/// Counts the cells of a table row, which are separated by unescaped pipes;
+/// the leading and trailing pipes are optional.
+fn cell_count(row: &str) -> usize {
+  let row = row.trim();
+  let row = row.strip_prefix('|').unwrap_or(row);
+  let mut cells = 1;
+  let mut escaped = false;
+  let mut trailing = false;
+  for b in row.bytes() {
+    trailing = b == b'|' && !escaped;
+    if trailing {
+      cells += 1;
+    }
+    escaped = b == b'\\' && !escaped;
+  }
+  if trailing { cells - 1 } else { cells }
+}
+
+/// Returns `true` if the last line of a fenced code block is a closing fence:
+/// one made of at least as many of the same characters as the opening fence.
+fn is_closed(source: &Source, sp: Sourcepos, ncb: &NodeCodeBlock) -> bool {
+  let Some(text) = source.text(sp) else {
+    return true;
+  };
+  // the opening fence is the only line of a block that is never closed
+  let lines: Vec<&str> = text.lines().collect();
+  let [_, .., last] = lines[..] else {
+    return false;
+  };
+  // the markers of any block quotes and the indentation of any list items
+  let fence = last.trim_start_matches([' ', '>']).trim_end();
+  let rest = fence.trim_start_matches(char::from(ncb.fence_char));
+  rest.is_empty() && fence.len() >= ncb.fence_length
+}
+
+/// Reports the first link to each undefined label recorded by `undefined`.
+///
+/// The label of a full (`[text][label]`) or collapsed (`[label][]`) reference
+/// link is left in the text of the document when it isn't defined, which
+/// gives its position. Shortcut references (`[label]`) are skipped, as they
+/// can't be told apart from text that happens to be in brackets.
+fn undefined_references<'a>(
+  root: &'a AstNode<'a>,
+  undefined: &Undefined,
+  diagnostics: &mut Vec<Diagnostic>,
+) {
+  let labels = undefined
+    .labels
+    .lock()
+    .unwrap_or_else(PoisonError::into_inner);
+  let texts = collect(root, |v| matches!(v, NodeValue::Text(_)));
+  let mut seen = HashSet::new();
+  for label in labels.iter().filter(|label| seen.insert(*label)) {
+    let full = format!("][{label}]");
+    let collapsed = format!("[{label}][]");
+    let found = texts.iter().find(|node| match node.data.borrow().value {
+      | NodeValue::Text(ref text) => {
+        text.contains(&*full) || text.contains(&*collapsed)
+      }
+      | _ => false,
+    });
+    if let Some(node) = found {
+      diagnostics.push(Diagnostic {
+        kind:      DiagnosticKind::UndefinedReference,
+        message:   format!("reference {label:?} is not defined"),
+        sourcepos: node.data.borrow().sourcepos,
+      });
+    }
+  }
+}
@@ -0,0 +1,571 @@
+//! Conversion of an AST into a tree of HTML elements, for front-end frameworks
+//! that create elements themselves (such as React's `createElement`) rather
+//! than rendering an HTML string.
+//!
+//! The tree mirrors the structure of the HTML that Comrak renders, so clients
+//! only need to handle HTML elements, not every variant of Comrak's AST. Raw
+//! HTML can't be represented as an element, so it is emitted as an `#html`
+//! element with the markup in its `html` prop, for use with something like
+//! React's `dangerouslySetInnerHTML`. Props use the names of the HTML
+//! attributes (e.g. `class` rather than React's `className`).
+
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+
+use comrak::Options as ComrakOptions;
+use comrak::nodes::AstNode;
+use comrak::nodes::ListType;
+use comrak::nodes::NodeValue;
+use comrak::nodes::TableAlignment;
+use serde::Serialize;
+use wasm_bindgen::JsValue;
+
+use crate::formatter::UrlContext;
+use crate::headings::Slugger;
+use crate::html::tagfilter;
+use crate::options::HtmlName;
+use crate::options::LinkTitleFallback;
+use crate::options::Options;
+use crate::sanitize::Allowlist;
+use crate::sanitize::sanitize;
+use crate::transform::text_content;
+
+/// A child of an [`Element`]: either a nested element, or a string of text.
+#[derive(Debug, Serialize)]
+#[serde(untagged)]
+pub enum Child {
+  Text(String),
+  Element(Element),
+}
+
+/// An HTML element, in the `{ type, props, children }` shape expected by
+/// front-end frameworks.
+#[derive(Debug, Serialize)]
+pub struct Element {
+  #[serde(rename = "type")]
+  tag:      String,
+  props:    BTreeMap<Cow<'static, str>, Prop>,
+  children: Vec<Child>,
+}
+
+/// The value of an attribute of an [`Element`].
+#[derive(Debug, Serialize)]
+#[serde(untagged)]
+pub enum Prop {
+  String(String),
+  Number(usize),
+  Bool(bool),
+}
+
+impl Element {
+  fn new(tag: impl Into<String>) -> Self {
+    Self {
+      tag:      tag.into(),
+      props:    BTreeMap::new(),
+      children: Vec::new(),
+    }
+  }
+
+  fn prop(
+    mut self,
+    name: impl Into<Cow<'static, str>>,
+    value: impl Into<Prop>,
+  ) -> Self {
+    self.props.insert(name.into(), value.into());
+    self
+  }
+
+  fn child(mut self, child: impl Into<Child>) -> Self {
+    self.children.push(child.into());
+    self
+  }
+
+  fn children(mut self, children: Vec<Child>) -> Self {
+    self.children = children;
+    self
+  }
+}
+
+impl From<Element> for Child {
+  fn from(element: Element) -> Self {
+    Self::Element(element)
+  }
+}
+
+impl From<String> for Child {
+  fn from(text: String) -> Self {
+    Self::Text(text)
+  }
+}
+
+impl From<String> for Prop {
+  fn from(value: String) -> Self {
+    Self::String(value)
+  }
+}
+
+impl From<&str> for Prop {
+  fn from(value: &str) -> Self {
+    Self::String(value.to_string())
+  }
+}
+
+impl From<usize> for Prop {
+  fn from(value: usize) -> Self {
+    Self::Number(value)
+  }
+}
+
+impl From<bool> for Prop {
+  fn from(value: bool) -> Self {
+    Self::Bool(value)
+  }
+}
+
+/// Converts an AST into the elements it renders as, applying the same render
+/// options as the HTML formatter where they have an equivalent.
+pub fn to_elements<'a>(
+  root: &'a AstNode<'a>,
+  options: &ComrakOptions,
+  extras: &Options,
+) -> Result<Vec<Child>, JsValue> {
+  let mut builder = Builder {
+    options,
+    extras,
+    slugger: options
+      .extension
+      .header_ids
+      .as_ref()
+      .map(|prefix| (prefix.clone(), Slugger::new(extras))),
+    allowlist: extras
+      .render
+      .sanitize_html
+      .as_ref()
+      .and_then(Allowlist::from_options),
+  };
+  let mut out = Vec::new();
+  let mut footnotes = Vec::new();
+  for node in root.children() {
+    if matches!(node.data.borrow().value, NodeValue::FootnoteDefinition(_)) {
+      builder.node(node, &mut footnotes)?;
+    } else {
+      builder.node(node, &mut out)?;
+    }
+  }
+  if !footnotes.is_empty() {
+    let list = Element::new("ol").children(footnotes);
+    let section = Element::new("section")
+      .prop("class", "footnotes")
+      .prop("data-footnotes", true)
+      .child(list);
+    out.push(Child::Element(section));
+  }
+  Ok(out)
+}
+
+struct Builder<'o, 'c> {
+  options:   &'o ComrakOptions<'c>,
+  extras:    &'o Options,
+  /// The prefix and slugger for heading IDs, if the `header_ids` extension is
+  /// enabled.
+  slugger:   Option<(String, Slugger)>,
+  /// The allowlist of the `sanitize_html` option, applied to raw HTML.
+  allowlist: Option<Allowlist>,
+}
+
+impl Builder<'_, '_> {
+  fn children<'a>(
+    &mut self,
+    node: &'a AstNode<'a>,
+  ) -> Result<Vec<Child>, JsValue> {
+    let mut out = Vec::new();
+    for child in node.children() {
+      self.node(child, &mut out)?;
+    }
+    Ok(out)
+  }
+
+  fn element<'a>(
+    &mut self,
+    tag: &str,
+    node: &'a AstNode<'a>,
+  ) -> Result<Element, JsValue> {
+    Ok(Element::new(tag).children(self.children(node)?))
+  }
+
+  /// Converts superscript or subscript text, applying the `sup_tag` and
+  /// `sup_class` or `sub_tag` and `sub_class` options.
+  fn script<'a>(
+    &mut self,
+    node: &'a AstNode<'a>,
+    superscript: bool,
+  ) -> Result<Element, JsValue> {
+    let render = &self.extras.render;
+    let (tag, class, default) = if superscript {
+      (&render.sup_tag, &render.sup_class, "sup")
+    } else {
+      (&render.sub_tag, &render.sub_class, "sub")
+    };
+    let tag = tag.as_ref().map_or(default, HtmlName::as_str).to_string();
+    let class = class.clone();
+    let element = self.element(&tag, node)?;
+    Ok(match class {
+      | Some(class) => element.prop("class", class),
+      | None => element,
+    })
+  }
+
+  /// Converts a spoiler, applying the `spoiler` option.
+  fn spoiler<'a>(&mut self, node: &'a AstNode<'a>) -> Result<Element, JsValue> {
+    let extras = self.extras;
+    let Some(ref spoiler) = extras.render.spoiler else {
+      return Ok(self.element("span", node)?.prop("class", "spoiler"));
+    };
+    let tag = spoiler.tag.as_ref().map_or("span", HtmlName::as_str);
+    let mut element = self.element(tag, node)?;
+    if !spoiler.class_name.is_empty() {
+      element = element.prop("class", spoiler.class_name.clone());
+    }
+    for (name, value) in &spoiler.attributes {
+      element = element.prop(name.as_str().to_string(), value.clone());
+    }
+    Ok(element)
+  }
+
+  fn node<'a>(
+    &mut self,
+    node: &'a AstNode<'a>,
+    out: &mut Vec<Child>,
+  ) -> Result<(), JsValue> {
+    let data = node.data.borrow();
+    let element = match data.value {
+      | NodeValue::Document
+      | NodeValue::DescriptionItem(_)
+      | NodeValue::Escaped => {
+        for child in self.children(node)? {
+          push(out, child);
+        }
+        return Ok(());
+      }
+      | NodeValue::FrontMatter(_) => return Ok(()),
+      | NodeValue::Paragraph if in_tight_list(node) => {
+        for child in self.children(node)? {
+          push(out, child);
+        }
+        return Ok(());
+      }
+      | NodeValue::Text(ref text) => {
+        push(out, Child::Text(text.to_string()));
+        return Ok(());
+      }
+      | NodeValue::EscapedTag(ref text) => {
+        push(out, Child::Text(text.to_string()));
+        return Ok(());
+      }
+      | NodeValue::ShortCode(ref nsc) => {
+        push(out, Child::Text(nsc.emoji.to_string()));
+        return Ok(());
+      }
+      | NodeValue::SoftBreak if !self.options.render.hardbreaks => {
+        push(out, Child::Text("\n".to_string()));
+        return Ok(());
+      }
+      | NodeValue::HtmlBlock(ref nhb) => {
+        self.raw(&nhb.literal, out);
+        return Ok(());
+      }
+      | NodeValue::HtmlInline(ref html) => {
+        self.raw(html, out);
+        return Ok(());
+      }
+      | NodeValue::Raw(ref html) => {
+        self.html(html, out);
+        return Ok(());
+      }
+      | NodeValue::SoftBreak | NodeValue::LineBreak => Element::new("br"),
+      | NodeValue::ThematicBreak => Element::new("hr"),
+      | NodeValue::Paragraph => self.element("p", node)?,
+      | NodeValue::BlockQuote | NodeValue::MultilineBlockQuote(_) => {
+        self.element("blockquote", node)?
+      }
+      | NodeValue::Heading(ref nh) => {
+        let element = self.element(&format!("h{}", nh.level), node)?;
+        match self.slugger {
+          | Some((ref prefix, ref mut slugger)) => {
+            let slug = slugger.slug(&text_content(node), nh.level)?;
+            element.prop("id", format!("{prefix}{slug}"))
+          }
+          | None => element,
+        }
+      }
+      | NodeValue::List(ref nl) if nl.list_type == ListType::Ordered => {
+        let element = self.element("ol", node)?;
+        match nl.start {
+          | 1 => element,
+          | start => element.prop("start", start),
+        }
+      }
+      | NodeValue::List(_) => self.element("ul", node)?,
+      | NodeValue::Item(_) => self.element("li", node)?,
+      | NodeValue::TaskItem(symbol) => {
+        let checkbox = Element::new("input")
+          .prop("type", "checkbox")
+          .prop("checked", symbol.is_some())
+          .prop("disabled", true);
+        let mut item = Element::new("li")
+          .prop("class", "task-list-item")
+          .child(checkbox)
+          .child(" ".to_string());
+        for child in self.children(node)? {
+          push(&mut item.children, child);
+        }
+        item
+      }
+      | NodeValue::DescriptionList => self.element("dl", node)?,
+      | NodeValue::DescriptionTerm => self.element("dt", node)?,
+      | NodeValue::DescriptionDetails => self.element("dd", node)?,
+      | NodeValue::CodeBlock(ref ncb) => {
+        let mut code = Element::new("code").child(ncb.literal.to_string());
+        let lang = ncb.info.split_whitespace().next().unwrap_or_default();
+        if !lang.is_empty() {
+          code = code.prop("class", format!("language-{lang}"));
+        }
+        Element::new("pre").child(code)
+      }
+      | NodeValue::Table(ref nt) => self.table(node, &nt.alignments)?,
+      | NodeValue::TableRow(_) => self.element("tr", node)?,
+      | NodeValue::TableCell => self.element("td", node)?,
+      | NodeValue::Code(ref code) => {
+        Element::new("code").child(code.literal.to_string())
+      }
+      | NodeValue::Emph => self.element("em", node)?,
+      | NodeValue::Strong => self.element("strong", node)?,
+      | NodeValue::Strikethrough => {
+        let tag = self.extras.render.strikethrough_tag;
+        self.element(tag.as_str(), node)?
+      }
+      | NodeValue::Superscript => self.script(node, true)?,
+      | NodeValue::Subscript => self.script(node, false)?,
+      | NodeValue::Underline => self.element("u", node)?,
+      | NodeValue::SpoileredText => self.spoiler(node)?,
+      | NodeValue::Link(ref nl) => {
+        let href = self.url(node, &nl.url, false);
+        let title = match self.extras.render.link_title_fallback {
+          | _ if !nl.title.is_empty() => nl.title.to_string(),
+          | LinkTitleFallback::None => String::new(),
+          | LinkTitleFallback::Text => text_content(node).trim().to_string(),
+          | LinkTitleFallback::Url => href.clone(),
+        };
+        let element = self.element("a", node)?.prop("href", href);
+        with_title(element, &title)
+      }
+      | NodeValue::WikiLink(ref nwl) => {
+        let href = self.url(node, &nwl.url, false);
+        self
+          .element("a", node)?
+          .prop("href", href)
+          .prop("data-wikilink", true)
+      }
+      | NodeValue::Image(ref nl) => {
+        let element = Element::new("img")
+          .prop("src", self.url(node, &nl.url, true))
+          .prop("alt", text_content(node));
+        with_title(element, &nl.title)
+      }
+      | NodeValue::FootnoteReference(ref nfr) => {
+        let mut id = format!("fnref-{}", nfr.name);
+        if nfr.ref_num > 1 {
+          id = format!("{id}-{}", nfr.ref_num);
+        }
+        let link = Element::new("a")
+          .prop("href", format!("#fn-{}", nfr.name))
+          .prop("id", id)
+          .prop("data-footnote-ref", true)
+          .child(nfr.ix.to_string());
+        Element::new("sup")
+          .prop("class", "footnote-ref")
+          .child(link)
+      }
+      | NodeValue::FootnoteDefinition(ref nfd) => {
+        let backref = Element::new("a")
+          .prop("href", format!("#fnref-{}", nfd.name))
+          .prop("class", "footnote-backref")
+          .prop("data-footnote-backref", true)
+          .prop("aria-label", "Back to reference")
+          .child("↩".to_string());
+        let mut children = self.children(node)?;
+        // the backreference goes at the end of the last paragraph, if any
+        match children.last_mut() {
+          | Some(Child::Element(last)) if last.tag == "p" => {
+            last.children.push(Child::Text(" ".into()));
+            last.children.push(Child::Element(backref));
+          }
+          | _ => children.push(Child::Element(backref)),
+        }
+        Element::new("li")
+          .prop("id", format!("fn-{}", nfd.name))
+          .children(children)
+      }
+      | NodeValue::Math(ref nm) => {
+        let style = if nm.display_math { "display" } else { "inline" };
+        Element::new("span")
+          .prop("data-math-style", style)
+          .child(nm.literal.to_string())
+      }
+      | NodeValue::Alert(ref alert) => {
+        let title = match alert.title {
+          | Some(ref title) => title.to_string(),
+          | None => alert.alert_type.default_title(),
+        };
+        let title = Element::new("p")
+          .prop("class", "markdown-alert-title")
+          .child(title);
+        let class = alert.alert_type.css_class();
+        let mut element = Element::new("div")
+          .prop("class", format!("markdown-alert {class}"))
+          .child(title);
+        element.children.extend(self.children(node)?);
+        element
+      }
+      | _ => {
+        for child in self.children(node)? {
+          push(out, child);
+        }
+        return Ok(());
+      }
+    };
+    out.push(Child::Element(element));
+    Ok(())
+  }
+
+  /// Converts a table, splitting its header row into a `<thead>` and the rest
+  /// into a `<tbody>`, and setting the alignment of each cell.
+  fn table<'a>(
+    &mut self,
+    node: &'a AstNode<'a>,
+    alignments: &[TableAlignment],
+  ) -> Result<Element, JsValue> {
+    let mut head = Vec::new();
+    let mut body = Vec::new();
+    for row in node.children() {
+      let header = matches!(row.data.borrow().value, NodeValue::TableRow(true));
+      let mut cells = Vec::new();
+      for (cell, alignment) in row.children().zip(alignments) {
+        let mut element =
+          self.element(if header { "th" } else { "td" }, cell)?;
+        let align = match alignment {
+          | TableAlignment::Left => Some("left"),
+          | TableAlignment::Center => Some("center"),
+          | TableAlignment::Right => Some("right"),
+          | TableAlignment::None => None,
+        };
+        if let Some(align) = align {
+          element = element.prop("align", align);
+        }
+        cells.push(element.into());
+      }
+      let row: Child = Element::new("tr").children(cells).into();
+      if header {
+        head.push(row)
+      } else {
+        body.push(row)
+      }
+    }
+    let mut table =
+      Element::new("table").child(Element::new("thead").children(head));
+    if !body.is_empty() {
+      table = table.child(Element::new("tbody").children(body));
+    }
+    Ok(table)
+  }
+
+  /// Converts raw HTML the way the HTML formatter would: as text when it is
+  /// escaped, as an `#html` element when it's allowed, or not at all. Allowed
+  /// HTML goes through the tag filter when the `tagfilter` extension is on.
+  fn raw(&self, html: &str, out: &mut Vec<Child>) {
+    if self.options.render.escape {
+      push(out, Child::Text(html.to_string()));
+    } else if self.options.render.unsafe_ {
+      match self.options.extension.tagfilter {
+        | true => self.html(&tagfilter(html), out),
+        | false => self.html(html, out),
+      }
+    }
+  }
+
+  /// Appends an `#html` element holding `html`, sanitized on its own when the
+  /// `sanitize_html` option is set, or nothing if that leaves it empty.
+  fn html(&self, html: &str, out: &mut Vec<Child>) {
+    let html = match self.allowlist {
+      | Some(ref allowlist) => sanitize(html, allowlist),
+      | None => html.to_string(),
+    };
+    if !html.is_empty() {
+      out.push(Child::Element(Element::new("#html").prop("html", html)));
+    }
+  }
+
+  /// Rewrites a URL with the user's rewriter, and removes it if it's unsafe
+  /// (unless the `unsafe` option is set), as the HTML formatter does.
+  fn url<'a>(&self, node: &'a AstNode<'a>, url: &str, image: bool) -> String {
+    let extension = &self.extras.extension;
+    let rewriter = match image {
+      | true => extension.image_url_rewriter.as_ref(),
+      | false => extension.link_url_rewriter.as_ref(),
+    };
+    let url = match rewriter {
+      | Some(rewriter) => rewriter.rewrite(url, UrlContext::of(node).as_ref()),
+      | None => url.to_string(),
+    };
+    if !self.options.render.unsafe_ && is_dangerous_url(&url) {
+      return String::new();
+    }
+    url
+  }
+}
+
+/// Appends a child, merging adjacent text into a single string.
+fn push(out: &mut Vec<Child>, child: Child) {
+  match (out.last_mut(), child) {
+    | (Some(Child::Text(last)), Child::Text(text)) => last.push_str(&text),
+    | (_, child) => out.push(child),
+  }
+}
+
+fn with_title(element: Element, title: &str) -> Element {
+  match title {
+    | "" => element,
+    | title => element.prop("title", title),
+  }
+}
+
+/// Returns `true` if a paragraph is rendered without its `<p>` tags, as it is
+/// when it's directly inside an item of a tight list.
+fn in_tight_list<'a>(node: &'a AstNode<'a>) -> bool {
+  let Some(grandparent) = node.parent().and_then(|p| p.parent()) else {
+    return false;
+  };
+  match grandparent.data.borrow().value {
+    | NodeValue::List(ref nl) => nl.tight,
+    | NodeValue::DescriptionItem(ref ndi) => ndi.tight,
+    | _ => false,
+  }
+}
+
+/// Returns `true` for the URLs that Comrak refuses to render unless the
+/// `unsafe` option is set: those using the `javascript:`, `vbscript:`, and
+/// `file:` schemes, and `data:` URLs other than common image types.
+fn is_dangerous_url(url: &str) -> bool {
+  let url = url.trim_start().to_ascii_lowercase();
+  let images = [
+    "png", "gif", "jpeg", "webp",
+  ];
+  match url.strip_prefix("data:image/") {
+    | Some(rest) => !images.iter().any(|ty| rest.starts_with(ty)),
+    | None => [
+      "javascript:", "vbscript:", "file:", "data:",
+    ]
+    .iter()
+    .any(|scheme| url.starts_with(scheme)),
+  }
+}
@@ -0,0 +1,127 @@
+//! Character reference encoding of rendered HTML, for the `encode_entities`
+//! render option.
+//!
+//! Every non-ASCII character of the output, in text as well as in attribute
+//! values, is replaced by a character reference, for pipelines (such as some
+//! email clients) that mangle anything outside of ASCII. The content of code
+//! (`<pre>` and `<code>`) and of raw text elements (such as `<script>`) is
+//! copied through verbatim, since it's meant to be shown or run as written.
+
+use crate::html::RAW_TEXT_ELEMENTS;
+use crate::html::TagKind;
+use crate::html::Token;
+use crate::html::tokenize;
+use crate::options::EncodeEntities;
+
+/// Elements whose content is copied through verbatim, besides the raw text
+/// elements.
+const CODE_ELEMENTS: &[&str] = &["code", "pre"];
+
+/// The named character references used by the `named` mode, sorted by
+/// character. Any other character is written as a numeric reference.
+const NAMED: &[(char, &str)] = &[
+  ('\u{a0}', "nbsp"),
+  ('¡', "iexcl"),
+  ('¢', "cent"),
+  ('£', "pound"),
+  ('¥', "yen"),
+  ('§', "sect"),
+  ('©', "copy"),
+  ('«', "laquo"),
+  ('®', "reg"),
+  ('°', "deg"),
+  ('±', "plusmn"),
+  ('µ', "micro"),
+  ('¶', "para"),
+  ('·', "middot"),
+  ('»', "raquo"),
+  ('¿', "iquest"),
+  ('×', "times"),
+  ('÷', "divide"),
+  ('–', "ndash"),
+  ('—', "mdash"),
+  ('‘', "lsquo"),
+  ('’', "rsquo"),
+  ('‚', "sbquo"),
+  ('“', "ldquo"),
+  ('”', "rdquo"),
+  ('„', "bdquo"),
+  ('†', "dagger"),
+  ('‡', "Dagger"),
+  ('•', "bull"),
+  ('…', "hellip"),
+  ('‰', "permil"),
+  ('′', "prime"),
+  ('″', "Prime"),
+  ('‹', "lsaquo"),
+  ('›', "rsaquo"),
+  ('€', "euro"),
+  ('™', "trade"),
+  ('←', "larr"),
+  ('↑', "uarr"),
+  ('→', "rarr"),
+  ('↓', "darr"),
+  ('↔', "harr"),
+  ('∞', "infin"),
+  ('≠', "ne"),
+  ('≤', "le"),
+  ('≥', "ge"),
+];
+
+/// Replaces the non-ASCII characters of `html` with character references in
+/// the given form, outside of code and raw text elements.
+pub fn encode(html: String, mode: EncodeEntities) -> String {
+  if mode == EncodeEntities::Utf8 || html.is_ascii() {
+    return html;
+  }
+  let mut out = String::with_capacity(html.len() * 2);
+  // how many code elements the current position is nested in
+  let mut verbatim = 0usize;
+  let mut raw_text = false;
+  for token in tokenize(&html) {
+    match token {
+      | Token::Text(text) if verbatim > 0 || raw_text => out.push_str(text),
+      | Token::Text(text) => encode_str(&mut out, text, mode),
+      | Token::Tag(tag, raw) => {
+        let name = tag.name.as_str();
+        raw_text =
+          tag.kind == TagKind::Open && RAW_TEXT_ELEMENTS.contains(&name);
+        if CODE_ELEMENTS.contains(&name) {
+          match tag.kind {
+            | TagKind::Open => verbatim += 1,
+            | TagKind::Close => verbatim = verbatim.saturating_sub(1),
+            | TagKind::SelfClosing => {}
+          }
+        }
+        // attribute values are decoded the same way text is
+        encode_str(&mut out, raw, mode);
+      }
+      | Token::Other(raw) => out.push_str(raw),
+    }
+  }
+  out
+}
+
+fn encode_str(out: &mut String, text: &str, mode: EncodeEntities) {
+  for c in text.chars() {
+    if c.is_ascii() {
+      out.push(c);
+      continue;
+    }
+    let named = match mode {
+      | EncodeEntities::Named => NAMED
+        .binary_search_by_key(&c, |&(c, _)| c)
+        .ok()
+        .map(|i| NAMED[i].1),
+      | _ => None,
+    };
+    match named {
+      | Some(name) => {
+        out.push('&');
+        out.push_str(name);
+        out.push(';');
+      }
+      | None => out.push_str(&format!("&#{};", c as u32)),
+    }
+  }
+}
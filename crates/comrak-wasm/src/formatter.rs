@@ -0,0 +1,1764 @@
+//! Output formatters used by the `markdown_to_*` and `format_*` functions.
+//!
+//! XML and CommonMark output is delegated straight to Comrak. HTML output goes
+//! through a custom formatter, which falls back to Comrak's default rendering
+//! for every node except those affected by the crate-level render options.
+
+use std::borrow::Cow;
+use std::cell::Cell;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::fmt;
+use std::fmt::Write;
+use std::mem;
+use std::rc::Rc;
+
+use comrak::Options as ComrakOptions;
+use comrak::adapters::HeadingMeta;
+use comrak::arena_tree::NodeEdge;
+use comrak::html::ChildRendering;
+use comrak::html::Context;
+use comrak::html::format_document_with_formatter;
+use comrak::html::format_node_default;
+use comrak::nodes::AstNode;
+use comrak::nodes::ListType;
+use comrak::nodes::NodeAlert;
+use comrak::nodes::NodeCodeBlock;
+use comrak::nodes::NodeFootnoteDefinition;
+use comrak::nodes::NodeFootnoteReference;
+use comrak::nodes::NodeList;
+use comrak::nodes::NodeValue;
+use comrak::nodes::TableAlignment;
+use comrak::options::Plugins;
+use js_sys::Function;
+use serde::Serialize;
+use serde_wasm_bindgen::to_value;
+use wasm_bindgen::JsValue;
+
+use crate::attributes::filter_attributes;
+use crate::entities;
+use crate::headings::Slugger;
+use crate::html::TagKind;
+use crate::html::Token;
+use crate::html::complete_prefix_len;
+use crate::html::escape_attr;
+use crate::html::escape_href;
+use crate::html::tokenize;
+use crate::images::ImageSize;
+use crate::images::resolve_dimensions;
+use crate::images::split_image_size;
+use crate::map_err;
+use crate::options::BackrefPosition;
+use crate::options::EncodeEntities;
+use crate::options::FigureCaption;
+use crate::options::Footnotes;
+use crate::options::FrameworkEscaping;
+use crate::options::HardbreaksScope;
+use crate::options::HtmlName;
+use crate::options::LineBreaks;
+use crate::options::LineEnding;
+use crate::options::LinkTitleFallback;
+use crate::options::Options;
+use crate::options::StrikethroughTag;
+use crate::sanitize::Allowlist;
+use crate::sanitize::sanitizable_prefix_len;
+use crate::sanitize::sanitize;
+use crate::standalone;
+use crate::tables::align_columns;
+use crate::transform::link_fragments;
+use crate::transform::text_content;
+use crate::whitespace::Layout;
+
+thread_local! {
+  /// The meta (the info string after the language) of the code block being
+  /// rendered. Comrak only passes the language to syntax highlighters, so the
+  /// rest of the info string is made available to them through here.
+  static CODE_BLOCK_META: RefCell<Option<String>> = const { RefCell::new(None) };
+}
+
+/// Returns the meta of the code block currently being rendered, if any.
+pub fn code_block_meta() -> Option<String> {
+  CODE_BLOCK_META.with_borrow(Clone::clone)
+}
+
+/// The context of a URL passed to the URL rewriters as their second argument.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UrlContext {
+  /// Either `link` (for links and wikilinks) or `image`.
+  context:     &'static str,
+  /// The type of the node containing the link or image, such as `Paragraph`
+  /// or `Heading`.
+  parent_type: String,
+  /// The text of the link, or the alt text of the image.
+  text:        String,
+}
+
+impl UrlContext {
+  /// Returns the context of a link, wikilink, or image node.
+  pub fn of<'a>(node: &'a AstNode<'a>) -> Option<Self> {
+    let context = match node.data.borrow().value {
+      | NodeValue::Link(_) | NodeValue::WikiLink(_) => "link",
+      | NodeValue::Image(_) => "image",
+      | _ => return None,
+    };
+    let parent_type = node
+      .parent()
+      .map(|parent| node_type(&parent.data.borrow().value))
+      .unwrap_or_default();
+    Some(Self { context, parent_type, text: text_content(node) })
+  }
+}
+
+/// Returns the name of the type of a node, as used for it in the AST returned
+/// to JS (e.g., `Paragraph`).
+fn node_type(value: &NodeValue) -> String {
+  let debug = format!("{value:?}");
+  let end = debug
+    .find(|c: char| !c.is_ascii_alphanumeric())
+    .unwrap_or(debug.len());
+  debug[..end].to_string()
+}
+
+/// State shared by the custom HTML formatter over the course of a render.
+struct State {
+  extras:              Options,
+  slugger:             Slugger,
+  /// The fragments of all in-document links, used by the
+  /// `header_ids_only_when_referenced` option.
+  anchors:             HashSet<String>,
+  /// Whether the heading being rendered has an open anchor to close on exit.
+  wrapped:             bool,
+  /// The slug of the heading being rendered, if it was already generated for
+  /// the `on_heading` callback.
+  heading_slug:        Option<String>,
+  /// The section number of the last heading at each level, from the start
+  /// level of the `number_headings` option.
+  heading_numbers:     Vec<usize>,
+  /// The number of footnote definitions rendered so far, and the index of the
+  /// last one whose backreferences have been written. These mirror Comrak's
+  /// own (private) counters, for the `footnote_backref` and `footnotes`
+  /// options.
+  footnote_ix:         u32,
+  written_footnote_ix: u32,
+  /// The size of the image being rendered, given with the image size syntax.
+  image_size:          Option<ImageSize>,
+  /// The number of task items rendered so far, used to name their checkboxes
+  /// for the `task_list_inputs` option.
+  task_ix:             usize,
+  /// Set once a top-level block has been rendered in its entirety, telling a
+  /// [`SinkWriter`] that its output can be flushed.
+  block_end:           Rc<Cell<bool>>,
+  /// Collects the output in place of the writer while set, so the rendering
+  /// of a node can be rewritten before it is written out.
+  capture:             Rc<RefCell<Option<String>>>,
+  /// The first JS exception raised during rendering, which is rethrown once
+  /// Comrak returns. This is shared, since Comrak doesn't return the user data
+  /// from a failed render.
+  error:               Rc<RefCell<Option<JsValue>>>,
+}
+
+impl State {
+  /// Records a JS exception, converting it into a formatting error.
+  fn fail(&self, e: JsValue) -> fmt::Error {
+    self.error.borrow_mut().get_or_insert(e);
+    fmt::Error
+  }
+}
+
+/// Formats an AST as HTML, applying the crate-level render options.
+pub fn format_html<'a>(
+  root: &'a AstNode<'a>,
+  options: &ComrakOptions,
+  output: &mut String,
+  plugins: &Plugins,
+  extras: &Options,
+) -> Result<(), JsValue> {
+  let layout = Layout::from_options(&extras.render)?;
+  let mut writer = LimitedWriter::new(output, extras);
+  let result =
+    run_formatter(root, options, &mut writer, plugins, extras, &Rc::default())?;
+  writer.finish()?;
+  result.map_err(map_err)?;
+  if let Some(ref filter) = extras.render.attribute_filter {
+    *output = filter_attributes(output, filter)?;
+  }
+  let option = extras.render.sanitize_html.as_ref();
+  if let Some(allowlist) = option.and_then(Allowlist::from_options) {
+    *output = sanitize(output, &allowlist);
+  }
+  if let Some(ref document) = extras.render.document {
+    let (head, tail) = standalone::shell(root, document);
+    *output = format!("{head}{output}{tail}");
+  }
+  *output = layout.apply(mem::take(output));
+  *output = entities::encode(mem::take(output), extras.render.encode_entities);
+  // the steps above can grow the output past what the writer allowed
+  match extras.render.max_output_bytes {
+    | Some(limit) if output.len() > limit => Err(output_limit_error(limit)),
+    | _ => Ok(()),
+  }
+}
+
+/// Formats an AST as HTML like [`format_html`], but passes the output to the
+/// JS `sink` function in chunks as it is rendered, rather than buffering all
+/// of it. A chunk is flushed after each top-level block, and is minified or
+/// pretty-printed on its own when the `minify` or `pretty_print` options are
+/// set. With the `sanitize_html` or `attribute_filter` options, a block that
+/// leaves a tag or a removed element open is held back until it's closed.
+pub fn format_html_streaming<'a>(
+  root: &'a AstNode<'a>,
+  options: &ComrakOptions,
+  sink: &Function,
+  plugins: &Plugins,
+  extras: &Options,
+) -> Result<(), JsValue> {
+  let layout = Layout::from_options(&extras.render)?;
+  let block_end = Rc::default();
+  let mut writer = SinkWriter {
+    sink,
+    buffer: String::new(),
+    block_end: Rc::clone(&block_end),
+    allowlist: extras
+      .render
+      .sanitize_html
+      .as_ref()
+      .and_then(Allowlist::from_options),
+    filter: extras.render.attribute_filter.as_ref(),
+    layout,
+    encoding: extras.render.encode_entities,
+    written: 0,
+    limit: extras.render.max_output_bytes,
+    error: None,
+  };
+  let document = extras.render.document.as_ref();
+  let (head, tail) = document
+    .map(|document| standalone::shell(root, document))
+    .unwrap_or_default();
+  let result = match writer.emit(head) {
+    | Ok(()) => {
+      run_formatter(root, options, &mut writer, plugins, extras, &block_end)?
+    }
+    | Err(e) => Err(e),
+  }
+  .and_then(|()| writer.finish())
+  .and_then(|()| writer.emit(tail));
+  if let Some(e) = writer.error {
+    return Err(e);
+  }
+  result.map_err(map_err)
+}
+
+/// Runs the custom HTML formatter over an AST, returning the first JS
+/// exception raised by a callback, if any, ahead of the formatting result.
+fn run_formatter<'a>(
+  root: &'a AstNode<'a>,
+  options: &ComrakOptions,
+  output: &mut dyn Write,
+  plugins: &Plugins,
+  extras: &Options,
+  block_end: &Rc<Cell<bool>>,
+) -> Result<fmt::Result, JsValue> {
+  let error = Rc::default();
+  let capture = Rc::default();
+  let mut output = CaptureWriter { output, capture: Rc::clone(&capture) };
+  let state = State {
+    extras:              extras.clone(),
+    slugger:             Slugger::new(extras),
+    anchors:             if extras.render.header_ids_only_when_referenced {
+      link_fragments(root).collect()
+    } else {
+      HashSet::new()
+    },
+    wrapped:             false,
+    heading_slug:        None,
+    heading_numbers:     Vec::new(),
+    footnote_ix:         0,
+    written_footnote_ix: 0,
+    task_ix:             0,
+    image_size:          None,
+    block_end:           Rc::clone(block_end),
+    capture:             Rc::clone(&capture),
+    error:               Rc::clone(&error),
+  };
+  let result = format_document_with_formatter(
+    root, options, &mut output, plugins, formatter, state,
+  );
+  match error.take() {
+    | Some(e) => Err(e),
+    | None => Ok(result),
+  }
+}
+
+/// Formats an AST as CommonMark XML.
+pub fn format_xml<'a>(
+  root: &'a AstNode<'a>,
+  options: &ComrakOptions,
+  output: &mut String,
+  plugins: &Plugins,
+  extras: &Options,
+) -> Result<(), JsValue> {
+  let mut writer = LimitedWriter::new(output, extras);
+  let result =
+    comrak::format_xml_with_plugins(root, options, &mut writer, plugins);
+  writer.finish()?;
+  result.map_err(map_err)
+}
+
+/// Formats an AST as CommonMark.
+pub fn format_commonmark<'a>(
+  root: &'a AstNode<'a>,
+  options: &ComrakOptions,
+  output: &mut String,
+  plugins: &Plugins,
+  extras: &Options,
+) -> Result<(), JsValue> {
+  // soft breaks are only joined into reflowed lines when wrapping to a width
+  let mut unwrapped;
+  let options = if extras.render.preserve_soft_breaks {
+    unwrapped = options.clone();
+    unwrapped.render.width = 0;
+    &unwrapped
+  } else {
+    options
+  };
+  let mut writer = LimitedWriter::new(output, extras);
+  let result =
+    comrak::format_commonmark_with_plugins(root, options, &mut writer, plugins);
+  writer.finish()?;
+  result.map_err(map_err)?;
+  if extras.render.align_table_columns {
+    *output = align_columns(output);
+  }
+  if extras.render.line_ending == LineEnding::Crlf {
+    *output = output.replace("\r\n", "\n").replace('\n', "\r\n");
+  }
+  Ok(())
+}
+
+/// Formats an AST as plain text, keeping only the text content of the
+/// document. Each paragraph, heading, code block, and table row is written on
+/// its own line, with the cells of a table row separated by tabs. Raw HTML and
+/// front matter are omitted.
+pub fn format_plaintext<'a>(
+  root: &'a AstNode<'a>,
+  _options: &ComrakOptions,
+  output: &mut String,
+  _plugins: &Plugins,
+  extras: &Options,
+) -> Result<(), JsValue> {
+  let mut writer = LimitedWriter::new(output, extras);
+  let result = write_plaintext(root, &mut writer);
+  writer.finish()?;
+  result.map_err(map_err)
+}
+
+fn write_plaintext<'a>(
+  root: &'a AstNode<'a>,
+  output: &mut dyn Write,
+) -> fmt::Result {
+  for edge in root.traverse() {
+    match edge {
+      | NodeEdge::Start(node) => match node.data.borrow().value {
+        | NodeValue::Text(ref literal) => output.write_str(literal)?,
+        | NodeValue::Code(ref code) => output.write_str(&code.literal)?,
+        | NodeValue::Math(ref math) => output.write_str(&math.literal)?,
+        | NodeValue::CodeBlock(ref ncb) => {
+          output.write_str(ncb.literal.trim_end_matches('\n'))?
+        }
+        | NodeValue::SoftBreak => output.write_char(' ')?,
+        | NodeValue::LineBreak => output.write_char('\n')?,
+        | _ => {}
+      },
+      | NodeEdge::End(node) => match node.data.borrow().value {
+        | NodeValue::Paragraph
+        | NodeValue::Heading(_)
+        | NodeValue::CodeBlock(_)
+        | NodeValue::TableRow(_) => output.write_char('\n')?,
+        | NodeValue::TableCell if node.next_sibling().is_some() => {
+          output.write_char('\t')?
+        }
+        | _ => {}
+      },
+    }
+  }
+  Ok(())
+}
+
+/// The output formats of [`markdown_to`](crate::markdown_to).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputFormat {
+  Html,
+  Xml,
+  CommonMark,
+  Plaintext,
+}
+
+impl OutputFormat {
+  /// Parses the name of an output format, as used by the JS API.
+  pub fn from_name(name: &str) -> Option<Self> {
+    match name {
+      | "html" => Some(Self::Html),
+      | "xml" => Some(Self::Xml),
+      | "commonmark" => Some(Self::CommonMark),
+      | "plaintext" => Some(Self::Plaintext),
+      | _ => None,
+    }
+  }
+
+  /// Formats an AST with the formatter for this output format.
+  pub fn format<'a>(
+    self,
+    root: &'a AstNode<'a>,
+    options: &ComrakOptions,
+    output: &mut String,
+    plugins: &Plugins,
+    extras: &Options,
+  ) -> Result<(), JsValue> {
+    let format = match self {
+      | Self::Html => format_html,
+      | Self::Xml => format_xml,
+      | Self::CommonMark => format_commonmark,
+      | Self::Plaintext => format_plaintext,
+    };
+    format(root, options, output, plugins, extras)
+  }
+}
+
+/// Wraps the output of a formatter, failing as soon as it would exceed the
+/// `max_output_bytes` option. This bounds the memory used on untrusted input
+/// without rendering the entire document first.
+struct LimitedWriter<'w> {
+  output:   &'w mut String,
+  start:    usize,
+  limit:    Option<usize>,
+  exceeded: bool,
+}
+
+impl<'w> LimitedWriter<'w> {
+  fn new(output: &'w mut String, extras: &Options) -> Self {
+    Self {
+      start: output.len(),
+      output,
+      limit: extras.render.max_output_bytes,
+      exceeded: false,
+    }
+  }
+
+  /// Returns an error if the limit was exceeded while writing.
+  fn finish(self) -> Result<(), JsValue> {
+    match self.limit {
+      | Some(limit) if self.exceeded => Err(output_limit_error(limit)),
+      | _ => Ok(()),
+    }
+  }
+}
+
+fn output_limit_error(limit: usize) -> JsValue {
+  map_err(format!(
+    "rendered output exceeds the maximum of {limit} bytes"
+  ))
+}
+
+impl Write for LimitedWriter<'_> {
+  fn write_str(&mut self, s: &str) -> fmt::Result {
+    let len = self.output.len() - self.start + s.len();
+    if self.limit.is_some_and(|limit| len > limit) {
+      self.exceeded = true;
+      return Err(fmt::Error);
+    }
+    self.output.push_str(s);
+    Ok(())
+  }
+}
+
+/// Passes the output of the HTML formatter through to the underlying writer,
+/// except while [`State::capture`] is set, when it is collected there instead.
+struct CaptureWriter<'w> {
+  output:  &'w mut dyn Write,
+  capture: Rc<RefCell<Option<String>>>,
+}
+
+impl Write for CaptureWriter<'_> {
+  fn write_str(&mut self, s: &str) -> fmt::Result {
+    match *self.capture.borrow_mut() {
+      | Some(ref mut buffer) => {
+        buffer.push_str(s);
+        Ok(())
+      }
+      | None => self.output.write_str(s),
+    }
+  }
+}
+
+/// Buffers the output of the HTML formatter, passing it to a JS function each
+/// time a top-level block ends. Exceptions thrown by the function abort the
+/// render, and are kept to be rethrown once Comrak returns.
+struct SinkWriter<'s> {
+  sink:      &'s Function,
+  buffer:    String,
+  block_end: Rc<Cell<bool>>,
+  allowlist: Option<Allowlist>,
+  filter:    Option<&'s Function>,
+  layout:    Layout,
+  encoding:  EncodeEntities,
+  /// The number of bytes passed to the sink so far.
+  written:   usize,
+  limit:     Option<usize>,
+  error:     Option<JsValue>,
+}
+
+impl SinkWriter<'_> {
+  /// Passes the buffered output to the sink, up to where it can be filtered
+  /// and sanitized apart from what follows it. Anything after a tag, comment,
+  /// or removed element left open by raw HTML is held back until it's closed.
+  fn flush(&mut self) -> fmt::Result {
+    let end = match self.allowlist {
+      | Some(_) => sanitizable_prefix_len(&self.buffer),
+      | None if self.filter.is_some() => complete_prefix_len(&self.buffer),
+      | None => self.buffer.len(),
+    };
+    let chunk = self.buffer.drain(..end).collect();
+    self.process(chunk)
+  }
+
+  /// Passes all of the buffered output to the sink, once rendering is done.
+  fn finish(&mut self) -> fmt::Result {
+    let chunk = mem::take(&mut self.buffer);
+    self.process(chunk)
+  }
+
+  fn process(&mut self, mut chunk: String) -> fmt::Result {
+    if chunk.is_empty() {
+      return Ok(());
+    }
+    if let Some(filter) = self.filter {
+      chunk = filter_attributes(&chunk, filter).map_err(|e| self.fail(e))?;
+    }
+    if let Some(ref allowlist) = self.allowlist {
+      chunk = sanitize(&chunk, allowlist);
+    }
+    self.emit(chunk)
+  }
+
+  /// Passes a chunk to the sink, bypassing the sanitizer.
+  fn emit(&mut self, chunk: String) -> fmt::Result {
+    if chunk.is_empty() {
+      return Ok(());
+    }
+    let chunk = entities::encode(self.layout.apply(chunk), self.encoding);
+    self.written += chunk.len();
+    if let Some(limit) = self.limit.filter(|&limit| self.written > limit) {
+      return Err(self.fail(output_limit_error(limit)));
+    }
+    match self.sink.call1(&JsValue::NULL, &chunk.into()) {
+      | Ok(_) => Ok(()),
+      | Err(e) => Err(self.fail(e)),
+    }
+  }
+
+  fn fail(&mut self, e: JsValue) -> fmt::Error {
+    self.error.get_or_insert(e);
+    fmt::Error
+  }
+}
+
+impl Write for SinkWriter<'_> {
+  fn write_str(&mut self, s: &str) -> fmt::Result {
+    if self.block_end.take() {
+      self.flush()?;
+    }
+    // the chunk being buffered counts towards the limit before it's emitted
+    let len = self.written + self.buffer.len() + s.len();
+    if let Some(limit) = self.limit.filter(|&limit| len > limit) {
+      return Err(self.fail(output_limit_error(limit)));
+    }
+    self.buffer.push_str(s);
+    Ok(())
+  }
+}
+
+type Rendering = Result<ChildRendering, fmt::Error>;
+
+fn formatter<'a>(
+  context: &mut Context<State>,
+  node: &'a AstNode<'a>,
+  entering: bool,
+) -> Rendering {
+  let rendering = format_node(context, node, entering)?;
+  if !entering && node.parent().is_some_and(is_document) {
+    context.user.block_end.set(true);
+  }
+  Ok(rendering)
+}
+
+/// Returns `true` if a node is in a paragraph outside of any list item, for
+/// the `paragraphs` scope of the `hardbreaks_scope` option.
+fn in_paragraph<'a>(node: &'a AstNode<'a>) -> bool {
+  let mut blocks = node
+    .ancestors()
+    .skip(1)
+    .filter(|n| n.data.borrow().value.block());
+  let in_paragraph = blocks
+    .next()
+    .is_some_and(|n| matches!(n.data.borrow().value, NodeValue::Paragraph));
+  in_paragraph
+    && !blocks.any(|n| {
+      matches!(
+        n.data.borrow().value,
+        NodeValue::Item(_) | NodeValue::TaskItem(_)
+      )
+    })
+}
+
+fn is_document<'a>(node: &'a AstNode<'a>) -> bool {
+  matches!(node.data.borrow().value, NodeValue::Document)
+}
+
+fn format_node<'a>(
+  context: &mut Context<State>,
+  node: &'a AstNode<'a>,
+  entering: bool,
+) -> Rendering {
+  let render = &context.user.extras.render;
+  let bullets = render.preserve_bullet_style_in_html;
+  let inline_code = render.highlight_inline_code;
+  let escaping = render.framework_escaping != FrameworkEscaping::None;
+  let code_blocks = render.code_block_renderer.is_some()
+    || context
+      .plugins
+      .render
+      .codefence_syntax_highlighter
+      .is_some();
+  let headings = (render.heading_anchor_wrap_heading
+    || render.header_ids_only_when_referenced
+    || render.id_scope.is_some()
+    || !render.heading_classes.is_empty())
+    && context.plugins.render.heading_adapter.is_none();
+  let footnotes =
+    render.footnote_backref.is_some() || render.footnotes.is_some();
+  let list_start = render.respect_ordered_list_start;
+  let task_inputs = render.task_list_inputs.is_some();
+  let explicit_breaks = render.line_breaks == Some(LineBreaks::Explicit);
+  let hardbreaks_scope = render.hardbreaks_scope;
+  let math = render.math_renderer.is_some();
+  let images = render.images.is_some();
+  let dimensions = render.image_dimensions.is_some();
+  let captions = render.figure_caption != FigureCaption::Title
+    && context.options.render.figure_with_caption;
+  let align_classes = render.table_align_classes;
+  let alerts = render.alerts.is_some();
+  let strikethrough_tag = render.strikethrough_tag;
+  let sup = render.sup_tag.is_some() || render.sup_class.is_some();
+  let sub = render.sub_tag.is_some() || render.sub_class.is_some();
+  let spoiler = render.spoiler.is_some();
+  let on_heading = render.on_heading.is_some();
+  let numbered = render.number_headings.as_ref().is_some_and(|n| n.enabled);
+  let link_titles = render.link_title_fallback != LinkTitleFallback::None;
+  let line_numbers = render
+    .code_line_numbers
+    .as_ref()
+    .filter(|n| n.enabled)
+    .map(|n| n.start);
+  let dl_class = render
+    .description_lists
+    .as_ref()
+    .and_then(|dl| dl.class_name.clone());
+  if context.user.extras.extension.image_size_syntax && entering {
+    strip_image_size(context, node);
+  }
+  if dimensions && entering {
+    resolve_image_size(context, node)?;
+  }
+  if entering {
+    rewrite_url(context, node);
+  }
+  if on_heading && entering {
+    report_heading(context, node)?;
+  }
+  if link_titles && entering {
+    fill_link_title(context, node);
+  }
+  match node.data.borrow().value {
+    | NodeValue::Document if footnotes && !entering => {
+      if context.user.footnote_ix > 0 {
+        context.write_str("</ol>\n</section>\n")?;
+      }
+      Ok(ChildRendering::HTML)
+    }
+    | NodeValue::FootnoteReference(ref nfr) if footnotes && entering => {
+      render_footnote_reference(context, node, nfr)
+    }
+    | NodeValue::FootnoteDefinition(ref nfd) if footnotes => {
+      render_footnote_definition(context, node, nfd, entering)
+    }
+    | NodeValue::Paragraph if footnotes && is_footnote_paragraph(node) => {
+      render_footnote_paragraph(context, node, entering)
+    }
+    | NodeValue::List(ref nl) if bullets && nl.list_type == ListType::Bullet => {
+      render_bullet_list(context, node, nl, entering)
+    }
+    | NodeValue::Item(ref nl) if bullets && nl.list_type == ListType::Bullet => {
+      render_bullet_item(context, node, nl, entering)
+    }
+    | NodeValue::List(ref nl)
+      if !list_start && nl.list_type == ListType::Ordered =>
+    {
+      render_ordered_list(context, node, nl, entering)
+    }
+    | NodeValue::TaskItem(symbol) if task_inputs && is_list_item(node) => {
+      render_task_item(context, node, symbol.is_some(), entering)
+    }
+    | NodeValue::DescriptionList if entering && dl_class.is_some() => {
+      context.cr()?;
+      context.write_str("<dl")?;
+      render_sourcepos(context, node)?;
+      let class = escape_attr(dl_class.as_deref().unwrap_or_default());
+      writeln!(context, " class=\"{class}\">")?;
+      Ok(ChildRendering::HTML)
+    }
+    | NodeValue::Alert(ref alert) if alerts => {
+      render_alert(context, node, alert, entering)
+    }
+    | NodeValue::Strikethrough if strikethrough_tag != StrikethroughTag::Del => {
+      let tag = strikethrough_tag.as_str();
+      if entering {
+        write!(context, "<{tag}")?;
+        render_sourcepos(context, node)?;
+        context.write_str(">")?;
+      } else {
+        write!(context, "</{tag}>")?;
+      }
+      Ok(ChildRendering::HTML)
+    }
+    | NodeValue::Superscript if sup => {
+      render_script(context, node, true, entering)
+    }
+    | NodeValue::Subscript if sub => {
+      render_script(context, node, false, entering)
+    }
+    | NodeValue::SpoileredText if spoiler => {
+      render_spoiler(context, node, entering)
+    }
+    | NodeValue::TableCell if align_classes => {
+      render_table_cell(context, node, entering)
+    }
+    | NodeValue::Image(ref nl)
+      if !entering
+        && (images || captions || context.user.image_size.is_some()) =>
+    {
+      render_image_end(context, node, &nl.title)
+    }
+    | NodeValue::Code(ref code) if inline_code && entering => {
+      match split_inline_lang(&code.literal) {
+        | Some((lang, code)) => render_inline_code(context, lang, code),
+        | None if escaping => render_code(context, &code.literal),
+        | None => format_node_default(context, node, entering),
+      }
+    }
+    | NodeValue::Code(ref code) if escaping && entering => {
+      render_code(context, &code.literal)
+    }
+    | NodeValue::Math(ref nm) if math && entering => {
+      render_math(context, node, &nm.literal, nm.display_math)
+    }
+    | NodeValue::CodeBlock(ref ncb) if math && entering && ncb.info == "math" => {
+      render_math(context, node, &ncb.literal, true)
+    }
+    | NodeValue::CodeBlock(ref ncb) if entering && line_numbers.is_some() => {
+      let start = line_numbers.unwrap_or(1);
+      render_numbered_code_block(context, node, ncb, start)
+    }
+    | NodeValue::CodeBlock(ref ncb) if code_blocks && entering => {
+      render_code_block(context, node, ncb)
+    }
+    | NodeValue::Text(ref text) if escaping && entering => {
+      let text = escape_text(context, text);
+      context.write_str(&text)?;
+      Ok(ChildRendering::HTML)
+    }
+    | NodeValue::SoftBreak if entering && hardbreaks_scope.is_some() => {
+      let hard = match hardbreaks_scope {
+        | Some(HardbreaksScope::All) => true,
+        | Some(HardbreaksScope::Paragraphs) => in_paragraph(node),
+        | _ => false,
+      };
+      context.write_str(if hard { "<br />\n" } else { "\n" })?;
+      Ok(ChildRendering::HTML)
+    }
+    | NodeValue::SoftBreak if explicit_breaks && entering => {
+      context.write_str(" ")?;
+      Ok(ChildRendering::HTML)
+    }
+    | NodeValue::Heading(ref nh) if headings || numbered => {
+      let rendering = match headings {
+        | true => render_heading(context, node, nh.level, entering)?,
+        | false => format_node_default(context, node, entering)?,
+      };
+      if numbered && entering {
+        number_heading(context, nh.level)?;
+      }
+      Ok(rendering)
+    }
+    | _ => format_node_default(context, node, entering),
+  }
+}
+
+fn render_sourcepos<'a>(
+  context: &mut Context<State>,
+  node: &'a AstNode<'a>,
+) -> fmt::Result {
+  let sp = node.data.borrow().sourcepos;
+  if context.options.render.sourcepos && sp.start.line > 0 {
+    write!(context, " data-sourcepos=\"{sp}\"")?;
+  }
+  Ok(())
+}
+
+fn render_bullet_list<'a>(
+  context: &mut Context<State>,
+  node: &'a AstNode<'a>,
+  nl: &NodeList,
+  entering: bool,
+) -> Rendering {
+  if entering {
+    context.cr()?;
+    context.write_str("<ul")?;
+    if nl.is_task_list && context.options.render.tasklist_classes {
+      context.write_str(" class=\"contains-task-list\"")?;
+    }
+    write!(context, " data-bullet=\"{}\"", nl.bullet_char as char)?;
+    render_sourcepos(context, node)?;
+    context.write_str(">\n")?;
+  } else {
+    context.write_str("</ul>\n")?;
+  }
+  Ok(ChildRendering::HTML)
+}
+
+/// Renders an ordered list without its `start` attribute, so that it always
+/// starts at 1 in the browser.
+fn render_ordered_list<'a>(
+  context: &mut Context<State>,
+  node: &'a AstNode<'a>,
+  nl: &NodeList,
+  entering: bool,
+) -> Rendering {
+  if entering {
+    context.cr()?;
+    context.write_str("<ol")?;
+    if nl.is_task_list && context.options.render.tasklist_classes {
+      context.write_str(" class=\"contains-task-list\"")?;
+    }
+    render_sourcepos(context, node)?;
+    context.write_str(">\n")?;
+  } else {
+    context.write_str("</ol>\n")?;
+  }
+  Ok(ChildRendering::HTML)
+}
+
+fn render_bullet_item<'a>(
+  context: &mut Context<State>,
+  node: &'a AstNode<'a>,
+  nl: &NodeList,
+  entering: bool,
+) -> Rendering {
+  if entering {
+    context.cr()?;
+    write!(context, "<li data-bullet=\"{}\"", nl.bullet_char as char)?;
+    render_sourcepos(context, node)?;
+    context.write_str(">")?;
+  } else {
+    context.write_str("</li>\n")?;
+  }
+  Ok(ChildRendering::HTML)
+}
+
+/// Renders an alert like Comrak does, but with its title taken from the
+/// `alerts.titles` option when it doesn't have one of its own, and with an
+/// icon before it when `alerts.include_icon` is set.
+fn render_alert<'a>(
+  context: &mut Context<State>,
+  node: &'a AstNode<'a>,
+  alert: &NodeAlert,
+  entering: bool,
+) -> Rendering {
+  if !entering {
+    context.cr()?;
+    context.write_str("</div>\n")?;
+    return Ok(ChildRendering::HTML);
+  }
+  let options = context.user.extras.render.alerts.as_ref();
+  let default_title = alert.alert_type.default_title();
+  let title = match alert.title {
+    | Some(ref title) => title.to_string(),
+    | None => options
+      .and_then(|a| {
+        let mut titles = a.titles.iter();
+        titles.find(|(ty, _)| ty.eq_ignore_ascii_case(&default_title))
+      })
+      .map_or(default_title, |(_, title)| title.clone()),
+  };
+  let icon = options.is_some_and(|a| a.include_icon);
+  context.cr()?;
+  write!(
+    context,
+    "<div class=\"markdown-alert {}\"",
+    alert.alert_type.css_class()
+  )?;
+  render_sourcepos(context, node)?;
+  context.write_str(">\n<p class=\"markdown-alert-title\">")?;
+  if icon {
+    context.write_str(
+      "<span class=\"markdown-alert-icon\" aria-hidden=\"true\"></span>",
+    )?;
+  }
+  writeln!(context, "{}</p>", escape_attr(&title))?;
+  Ok(ChildRendering::HTML)
+}
+
+/// Renders superscript or subscript text with the tag and class given by the
+/// `sup_tag` and `sup_class` or `sub_tag` and `sub_class` options.
+fn render_script<'a>(
+  context: &mut Context<State>,
+  node: &'a AstNode<'a>,
+  superscript: bool,
+  entering: bool,
+) -> Rendering {
+  let render = &context.user.extras.render;
+  let (tag, class, default) = if superscript {
+    (&render.sup_tag, &render.sup_class, "sup")
+  } else {
+    (&render.sub_tag, &render.sub_class, "sub")
+  };
+  let tag = tag.as_ref().map_or(default, HtmlName::as_str).to_string();
+  if !entering {
+    write!(context, "</{tag}>")?;
+    return Ok(ChildRendering::HTML);
+  }
+  let class = class.as_deref().map(escape_attr);
+  write!(context, "<{tag}")?;
+  render_sourcepos(context, node)?;
+  if let Some(class) = class {
+    write!(context, " class=\"{class}\"")?;
+  }
+  context.write_str(">")?;
+  Ok(ChildRendering::HTML)
+}
+
+/// Renders a spoiler with the tag, class, and attributes given by the
+/// `spoiler` option.
+fn render_spoiler<'a>(
+  context: &mut Context<State>,
+  node: &'a AstNode<'a>,
+  entering: bool,
+) -> Rendering {
+  let Some(ref spoiler) = context.user.extras.render.spoiler else {
+    return Ok(ChildRendering::HTML);
+  };
+  let tag = spoiler.tag.as_ref().map_or("span", HtmlName::as_str);
+  if !entering {
+    let close = format!("</{tag}>");
+    context.write_str(&close)?;
+    return Ok(ChildRendering::HTML);
+  }
+  let mut attrs = String::new();
+  if !spoiler.class_name.is_empty() {
+    write!(attrs, " class=\"{}\"", escape_attr(&spoiler.class_name))?;
+  }
+  for (name, value) in &spoiler.attributes {
+    write!(attrs, " {}=\"{}\"", name.as_str(), escape_attr(value))?;
+  }
+  let open = format!("<{tag}");
+  context.write_str(&open)?;
+  render_sourcepos(context, node)?;
+  context.write_str(&attrs)?;
+  context.write_str(">")?;
+  Ok(ChildRendering::HTML)
+}
+
+/// Renders a task item with a named checkbox, for the `task_list_inputs`
+/// option. The checkbox is only disabled if the inputs aren't interactive.
+fn render_task_item<'a>(
+  context: &mut Context<State>,
+  node: &'a AstNode<'a>,
+  checked: bool,
+  entering: bool,
+) -> Rendering {
+  if !entering {
+    context.write_str("</li>\n")?;
+    return Ok(ChildRendering::HTML);
+  }
+  let classes = context.options.render.tasklist_classes;
+  context.cr()?;
+  context.write_str("<li")?;
+  if classes {
+    context.write_str(" class=\"task-list-item\"")?;
+  }
+  render_sourcepos(context, node)?;
+  context.write_str("><input type=\"checkbox\"")?;
+  if classes {
+    context.write_str(" class=\"task-list-item-checkbox\"")?;
+  }
+  let inputs = context.user.extras.render.task_list_inputs.as_ref();
+  let (prefix, interactive) =
+    inputs.map_or(("task", false), |i| (&*i.name_prefix, i.interactive));
+  let name = format!("{}-{}", escape_attr(prefix), context.user.task_ix);
+  context.user.task_ix += 1;
+  write!(context, " name=\"{name}\"")?;
+  if checked {
+    context.write_str(" checked=\"\"")?;
+  }
+  if !interactive {
+    context.write_str(" disabled=\"\"")?;
+  }
+  context.write_str(" /> ")?;
+  Ok(ChildRendering::HTML)
+}
+
+/// Returns `true` if the node is an item of a list, rather than (for example)
+/// a task item within a table cell.
+fn is_list_item<'a>(node: &'a AstNode<'a>) -> bool {
+  node.parent().is_some_and(|parent| {
+    matches!(parent.data.borrow().value, NodeValue::List(_))
+  })
+}
+
+/// Renders a table cell with a `text-{alignment}` class for its column's
+/// alignment, for the `table_align_classes` option, so that no inline styles
+/// are needed to align it.
+fn render_table_cell<'a>(
+  context: &mut Context<State>,
+  node: &'a AstNode<'a>,
+  entering: bool,
+) -> Rendering {
+  let Some(row) = node.parent() else {
+    return Ok(ChildRendering::HTML);
+  };
+  let tag = match row.data.borrow().value {
+    | NodeValue::TableRow(true) => "th",
+    | _ => "td",
+  };
+  if !entering {
+    write!(context, "</{tag}>")?;
+    return Ok(ChildRendering::HTML);
+  }
+  let column = node.preceding_siblings().count() - 1;
+  let alignment =
+    row
+      .parent()
+      .and_then(|table| match table.data.borrow().value {
+        | NodeValue::Table(ref nt) => nt.alignments.get(column).copied(),
+        | _ => None,
+      });
+  let class = match alignment {
+    | Some(TableAlignment::Left) => Some("text-left"),
+    | Some(TableAlignment::Center) => Some("text-center"),
+    | Some(TableAlignment::Right) => Some("text-right"),
+    | Some(TableAlignment::None) | None => None,
+  };
+  context.cr()?;
+  write!(context, "<{tag}")?;
+  render_sourcepos(context, node)?;
+  if let Some(class) = class {
+    write!(context, " class=\"{class}\"")?;
+  }
+  context.write_str(">")?;
+  Ok(ChildRendering::HTML)
+}
+
+/// Strips the size suffix of the image size syntax from the URL of an image
+/// that's about to be rendered, so Comrak renders the URL without it. The size
+/// is saved to be rendered once the image is exited.
+fn strip_image_size<'a>(context: &mut Context<State>, node: &'a AstNode<'a>) {
+  let mut data = node.data.borrow_mut();
+  let NodeValue::Image(ref mut nl) = data.value else {
+    return;
+  };
+  if let Some((url, size)) = split_image_size(&nl.url) {
+    nl.url = url.to_string();
+    context.user.image_size = Some(size);
+  }
+}
+
+/// Rewrites the URL of an image or link that's about to be rendered with the
+/// user's URL rewriter, in place, so it's called exactly once per node and
+/// everything rendered after it sees the rewritten URL. Comrak then removes the
+/// rewritten URL if it's unsafe, unless the `unsafe` option is set.
+fn rewrite_url<'a>(context: &mut Context<State>, node: &'a AstNode<'a>) {
+  let extension = &context.user.extras.extension;
+  let rewriter = match node.data.borrow().value {
+    | NodeValue::Image(_) => extension.image_url_rewriter.as_ref(),
+    | NodeValue::Link(_) => extension.link_url_rewriter.as_ref(),
+    | _ => return,
+  };
+  let Some(rewriter) = rewriter else {
+    return;
+  };
+  let url_context = UrlContext::of(node);
+  if let NodeValue::Image(ref mut nl) | NodeValue::Link(ref mut nl) =
+    node.data.borrow_mut().value
+  {
+    nl.url = rewriter.rewrite(&nl.url, url_context.as_ref());
+  }
+}
+
+/// Fills in the title of a link without one that's about to be rendered, per
+/// the `link_title_fallback` option. The URL is the one the link is rendered
+/// with, already rewritten by [`rewrite_url`].
+fn fill_link_title<'a>(context: &mut Context<State>, node: &'a AstNode<'a>) {
+  let title = match node.data.borrow().value {
+    | NodeValue::Link(ref nl) if nl.title.is_empty() => {
+      match context.user.extras.render.link_title_fallback {
+        | LinkTitleFallback::None => return,
+        | LinkTitleFallback::Text => text_content(node).trim().to_string(),
+        | LinkTitleFallback::Url => nl.url.to_string(),
+      }
+    }
+    | _ => return,
+  };
+  if let NodeValue::Link(ref mut nl) = node.data.borrow_mut().value {
+    nl.title = title.into();
+  }
+}
+
+/// Resolves the size of an image that's about to be rendered with the
+/// `image_dimensions` callback, to be rendered once the image is exited. A size
+/// given with the image size syntax takes precedence, so the callback isn't
+/// called for it.
+fn resolve_image_size<'a>(
+  context: &mut Context<State>,
+  node: &'a AstNode<'a>,
+) -> fmt::Result {
+  let url = match node.data.borrow().value {
+    | NodeValue::Image(ref nl) if context.user.image_size.is_none() => {
+      nl.url.clone()
+    }
+    | _ => return Ok(()),
+  };
+  let Some(ref resolver) = context.user.extras.render.image_dimensions else {
+    return Ok(());
+  };
+  match resolve_dimensions(resolver, &url) {
+    | Ok(Some(d)) => {
+      let (width, height) = (Some(d.width), Some(d.height));
+      context.user.image_size = Some(ImageSize { width, height });
+      Ok(())
+    }
+    | Ok(None) => Ok(()),
+    | Err(e) => Err(context.user.fail(e)),
+  }
+}
+
+/// Finishes rendering an image in place of Comrak, adding the `width` and
+/// `height` attributes saved by [`strip_image_size`] to the `<img>` tag, along
+/// with the loading attributes of the `images` render option, and the caption
+/// chosen by the `figure_caption` render option.
+fn render_image_end<'a>(
+  context: &mut Context<State>,
+  node: &'a AstNode<'a>,
+  title: &str,
+) -> Rendering {
+  let render = &context.user.extras.render;
+  let (max_width, max_height) =
+    (render.max_image_width, render.max_image_height);
+  let size = context.user.image_size.take().unwrap_or_default();
+  let size = size.clamp(max_width, max_height);
+  if !title.is_empty() {
+    write!(context, "\" title=\"{}", escape_attr(title))?;
+  }
+  context.write_str("\"")?;
+  if let Some(width) = size.width {
+    write!(context, " width=\"{width}\"")?;
+  }
+  if let Some(height) = size.height {
+    write!(context, " height=\"{height}\"")?;
+  }
+  let images = context
+    .user
+    .extras
+    .render
+    .images
+    .clone()
+    .unwrap_or_default();
+  if images.lazy {
+    context.write_str(" loading=\"lazy\"")?;
+  }
+  if let Some(decoding) = images.decoding {
+    write!(context, " decoding=\"{}\"", decoding.as_str())?;
+  }
+  context.write_str(" />")?;
+  if context.options.render.figure_with_caption {
+    let caption = match context.user.extras.render.figure_caption {
+      | FigureCaption::Title => title.to_string(),
+      | FigureCaption::Alt => text_content(node),
+      | FigureCaption::None => String::new(),
+    };
+    if !caption.is_empty() {
+      write!(
+        context,
+        "<figcaption>{}</figcaption>",
+        escape_attr(&caption)
+      )?;
+    }
+    context.write_str("</figure>")?;
+  }
+  Ok(ChildRendering::HTML)
+}
+
+/// Renders a heading in place of Comrak's own rendering, with the class given
+/// by the `heading_classes` render option. With `header_ids`, the anchor
+/// either wraps the entire content of the heading, or is omitted when the
+/// heading isn't the target of any in-document link, depending on the
+/// `heading_anchor_wrap_heading` and `header_ids_only_when_referenced` render
+/// options. The `id_scope` render option is prepended to the `href` of the
+/// anchor as well as to its `id`.
+fn render_heading<'a>(
+  context: &mut Context<State>,
+  node: &'a AstNode<'a>,
+  level: u8,
+  entering: bool,
+) -> Rendering {
+  if !entering {
+    if std::mem::take(&mut context.user.wrapped) {
+      context.write_str("</a>")?;
+    }
+    writeln!(context, "</h{level}>")?;
+    return Ok(ChildRendering::HTML);
+  }
+  let render = &context.user.extras.render;
+  let class = render.heading_classes.get(&level).map(|c| escape_attr(c));
+  context.cr()?;
+  write!(context, "<h{level}")?;
+  if let Some(class) = class {
+    write!(context, " class=\"{class}\"")?;
+  }
+  render_sourcepos(context, node)?;
+  context.write_str(">")?;
+  let Some(prefix) = context.options.extension.header_ids.clone() else {
+    return Ok(ChildRendering::HTML);
+  };
+  // every heading is slugged, so that duplicate slugs are numbered the same
+  // regardless of which headings end up with an anchor
+  let slug = match context.user.heading_slug.take() {
+    | Some(slug) => slug,
+    | None => heading_slug(context, node, level)?,
+  };
+  let id = format!("{prefix}{slug}");
+  let render = &context.user.extras.render;
+  let wrap = render.heading_anchor_wrap_heading;
+  let linked = !render.header_ids_only_when_referenced
+    || context.user.anchors.contains(&id)
+    || context.user.anchors.contains(&slug);
+  if linked {
+    let scope = render.id_scope.as_deref().unwrap_or_default();
+    let href = escape_attr(&format!("{scope}{slug}"));
+    let id = escape_attr(&id);
+    if wrap {
+      write!(context, "<a href=\"#{href}\" class=\"anchor\" id=\"{id}\">")?;
+    } else {
+      write!(
+        context,
+        "<a href=\"#{href}\" aria-hidden=\"true\" class=\"anchor\" \
+         id=\"{id}\"></a>"
+      )?;
+    }
+    context.user.wrapped = wrap;
+  }
+  Ok(ChildRendering::HTML)
+}
+
+/// Writes the section number of a heading, for the `number_headings` option.
+/// Headings above the start level aren't numbered, but restart the numbering
+/// of the headings below them, and skipped levels are numbered `0`.
+fn number_heading(context: &mut Context<State>, level: u8) -> fmt::Result {
+  let Some(ref numbering) = context.user.extras.render.number_headings else {
+    return Ok(());
+  };
+  let numbers = &mut context.user.heading_numbers;
+  let Some(depth) = level.checked_sub(numbering.start_level.max(1)) else {
+    numbers.clear();
+    return Ok(());
+  };
+  let depth = usize::from(depth);
+  numbers.resize(depth + 1, 0);
+  numbers[depth] += 1;
+  let number: Vec<_> = numbers.iter().map(usize::to_string).collect();
+  let number = number.join(".");
+  let separator = escape_attr(&numbering.separator);
+  write!(context, "{number}{separator}")
+}
+
+/// Generates the next slug for a heading, as used in its `id`.
+fn heading_slug<'a>(
+  context: &mut Context<State>,
+  node: &'a AstNode<'a>,
+  level: u8,
+) -> Result<String, fmt::Error> {
+  let text = text_content(node);
+  context
+    .user
+    .slugger
+    .slug(&text, level)
+    .map_err(|e| context.user.fail(e))
+}
+
+/// Calls the `on_heading` callback with the level, text, `id`, and source
+/// position of a heading. The `id` is empty unless `header_ids` is enabled.
+fn report_heading<'a>(
+  context: &mut Context<State>,
+  node: &'a AstNode<'a>,
+) -> fmt::Result {
+  let (level, sourcepos) = {
+    let data = node.data.borrow();
+    match data.value {
+      | NodeValue::Heading(ref nh) => (nh.level, data.sourcepos),
+      | _ => return Ok(()),
+    }
+  };
+  let id = match context.options.extension.header_ids.clone() {
+    | Some(prefix) => {
+      let slug = heading_slug(context, node, level)?;
+      let id = format!("{prefix}{slug}");
+      context.user.heading_slug = Some(slug);
+      id
+    }
+    | None => String::new(),
+  };
+  let heading = HeadingMeta { level, content: text_content(node) };
+  let Some(ref f) = context.user.extras.render.on_heading else {
+    return Ok(());
+  };
+  let id = JsValue::from_str(&id);
+  let result = match (to_value(&heading), to_value(&sourcepos)) {
+    | (Ok(heading), Ok(sourcepos)) => {
+      f.call3(&JsValue::NULL, &heading, &id, &sourcepos)
+    }
+    | (Err(e), _) | (_, Err(e)) => Err(e.into()),
+  };
+  match result {
+    | Ok(_) => Ok(()),
+    | Err(e) => Err(context.user.fail(e)),
+  }
+}
+
+/// Splits a `lang:code` prefix off the content of an inline code span. The
+/// language must start with a letter, and the code must be non-empty; this
+/// avoids mistaking paths such as `std::io` for a language prefix.
+fn split_inline_lang(literal: &str) -> Option<(&str, &str)> {
+  let (lang, code) = literal.split_once(':')?;
+  let valid_lang = lang.starts_with(|c: char| c.is_ascii_alphabetic())
+    && lang
+      .chars()
+      .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '+' | '#'));
+  (valid_lang && !code.is_empty() && !code.starts_with(':'))
+    .then_some((lang, code))
+}
+
+/// Renders an inline code span with a language prefix, highlighting it with
+/// the configured syntax highlighter (if any).
+fn render_inline_code(
+  context: &mut Context<State>,
+  lang: &str,
+  code: &str,
+) -> Rendering {
+  let class = format!("language-{lang}");
+  match context.plugins.render.codefence_syntax_highlighter {
+    | Some(highlighter) => {
+      let attrs = HashMap::from([("class", Cow::from(class))]);
+      highlighter.write_code_tag(context, attrs)?;
+      highlighter.write_highlighted(context, Some(lang), code)?;
+    }
+    | None => {
+      write!(context, "<code class=\"{}\">", escape_attr(&class))?;
+      let code = escape_text(context, code);
+      context.write_str(&code)?;
+    }
+  }
+  context.write_str("</code>")?;
+  Ok(ChildRendering::Skip)
+}
+
+/// Renders a code block with the `code_block_renderer` render option (if set),
+/// falling back to Comrak's default rendering if it returns `null`.
+fn render_code_block<'a>(
+  context: &mut Context<State>,
+  node: &'a AstNode<'a>,
+  ncb: &NodeCodeBlock,
+) -> Rendering {
+  let (lang, meta) = split_info(&ncb.info);
+  let or_null = |s: &str| match s {
+    | "" => JsValue::NULL,
+    | s => JsValue::from_str(s),
+  };
+  let result = match context.user.extras.render.code_block_renderer {
+    | Some(ref f) => {
+      let code = JsValue::from_str(&ncb.literal);
+      f.call3(&JsValue::NULL, &code, &or_null(lang), &or_null(meta))
+    }
+    | None => return render_code_block_default(context, node, meta),
+  };
+  match result {
+    | Ok(html) if html.is_null() || html.is_undefined() => {
+      render_code_block_default(context, node, meta)
+    }
+    | Ok(html) => {
+      let Some(html) = html.as_string() else {
+        let e = map_err("codeBlockRenderer must return a string or null");
+        return Err(context.user.fail(e));
+      };
+      context.cr()?;
+      context.write_str(&html)?;
+      context.cr()?;
+      Ok(ChildRendering::HTML)
+    }
+    | Err(e) => Err(context.user.fail(e)),
+  }
+}
+
+/// Renders a code block like [`render_code_block`], then wraps each line of
+/// its code in a `<span class="line">` with its number, for the
+/// `code_line_numbers` render option.
+fn render_numbered_code_block<'a>(
+  context: &mut Context<State>,
+  node: &'a AstNode<'a>,
+  ncb: &NodeCodeBlock,
+  start: usize,
+) -> Rendering {
+  context.user.capture.replace(Some(String::new()));
+  let result = render_code_block(context, node, ncb);
+  let html = context.user.capture.take().unwrap_or_default();
+  let rendering = result?;
+  context.write_str(&number_lines(&html, start))?;
+  Ok(rendering)
+}
+
+/// Wraps each line of the content of the `<code>` element in `html` in a
+/// `<span class="line" data-line="N">`, counting from `start`. Elements that
+/// span several lines, such as the highlighting of a block comment, are closed
+/// at the end of each line and reopened at the start of the next, so that the
+/// line spans are well nested.
+fn number_lines(html: &str, start: usize) -> String {
+  let content = html.find("<code").and_then(|open| {
+    let start = open + html[open..].find('>')? + 1;
+    let end = html.rfind("</code>").filter(|&end| end >= start)?;
+    Some(start..end)
+  });
+  let Some(content) = content else {
+    return html.to_string();
+  };
+  let mut out = String::with_capacity(html.len() * 2);
+  out.push_str(&html[..content.start]);
+  // the elements open at the current position, which are closed at the end of
+  // each line
+  let mut open: Vec<(String, &str)> = Vec::new();
+  let mut line = start;
+  let mut in_line = false;
+  let start_line = |out: &mut String, open: &[(String, &str)], line: usize| {
+    out.push_str(&format!("<span class=\"line\" data-line=\"{line}\">"));
+    for (_, raw) in open {
+      out.push_str(raw);
+    }
+  };
+  let end_line = |out: &mut String, open: &[(String, &str)]| {
+    for (name, _) in open.iter().rev() {
+      out.push_str(&format!("</{name}>"));
+    }
+    out.push_str("</span>");
+  };
+  for token in tokenize(&html[content.clone()]) {
+    match token {
+      | Token::Text(text) => {
+        for piece in text.split_inclusive('\n') {
+          if !in_line {
+            start_line(&mut out, &open, line);
+            in_line = true;
+          }
+          let code = piece.strip_suffix('\n');
+          out.push_str(code.unwrap_or(piece));
+          if code.is_some() {
+            end_line(&mut out, &open);
+            out.push('\n');
+            line += 1;
+            in_line = false;
+          }
+        }
+      }
+      | Token::Tag(tag, raw) => match tag.kind {
+        | TagKind::Close => {
+          // a closing tag at the start of a line was already closed at the
+          // end of the last one
+          if in_line {
+            out.push_str(raw);
+          }
+          if let Some(i) = open.iter().rposition(|(name, _)| *name == tag.name)
+          {
+            open.truncate(i);
+          }
+        }
+        | kind => {
+          if !in_line {
+            start_line(&mut out, &open, line);
+            in_line = true;
+          }
+          out.push_str(raw);
+          if kind == TagKind::Open {
+            open.push((tag.name, raw));
+          }
+        }
+      },
+      | Token::Other(raw) => out.push_str(raw),
+    }
+  }
+  if in_line {
+    end_line(&mut out, &open);
+  }
+  out.push_str(&html[content.end..]);
+  out
+}
+
+/// Renders a math span or block with the `math_renderer` callback, falling
+/// back to the default rendering if it returns `null`. Math code blocks are
+/// always rendered in display mode.
+fn render_math<'a>(
+  context: &mut Context<State>,
+  node: &'a AstNode<'a>,
+  latex: &str,
+  display: bool,
+) -> Rendering {
+  let result = match context.user.extras.render.math_renderer {
+    | Some(ref f) => f.call2(
+      &JsValue::NULL,
+      &JsValue::from_str(latex),
+      &JsValue::from_bool(display),
+    ),
+    | None => return format_node_default(context, node, true),
+  };
+  match result {
+    | Ok(html) if html.is_null() || html.is_undefined() => {
+      format_node_default(context, node, true)
+    }
+    | Ok(html) => {
+      let Some(html) = html.as_string() else {
+        let e = map_err("mathRenderer must return a string or null");
+        return Err(context.user.fail(e));
+      };
+      let block = node.data.borrow().value.block();
+      if block {
+        context.cr()?;
+      }
+      context.write_str(&html)?;
+      if block {
+        context.cr()?;
+      }
+      Ok(ChildRendering::HTML)
+    }
+    | Err(e) => Err(context.user.fail(e)),
+  }
+}
+
+/// Renders a code block with Comrak's default rendering, exposing its meta to
+/// the syntax highlighter through [`code_block_meta`].
+fn render_code_block_default<'a>(
+  context: &mut Context<State>,
+  node: &'a AstNode<'a>,
+  meta: &str,
+) -> Rendering {
+  let meta = (!meta.is_empty()).then(|| meta.to_string());
+  CODE_BLOCK_META.set(meta);
+  let result = format_node_default(context, node, true);
+  CODE_BLOCK_META.set(None);
+  result
+}
+
+/// Splits the info string of a code block into its language and its meta.
+pub(crate) fn split_info(info: &str) -> (&str, &str) {
+  let info = info.trim();
+  match info.split_once(char::is_whitespace) {
+    | Some((lang, meta)) => (lang, meta.trim_start()),
+    | None => (info, ""),
+  }
+}
+
+/// Renders an inline code span, escaping its content with [`escape_text`].
+fn render_code(context: &mut Context<State>, code: &str) -> Rendering {
+  let code = escape_text(context, code);
+  write!(context, "<code>{code}</code>")?;
+  Ok(ChildRendering::HTML)
+}
+
+/// Escapes text content for HTML, along with any framework template syntax
+/// selected by the `framework_escaping` render option.
+fn escape_text(context: &Context<State>, text: &str) -> String {
+  let html = escape_attr(text);
+  match context.user.extras.render.framework_escaping {
+    | FrameworkEscaping::None => html,
+    | FrameworkEscaping::Vue => html
+      .replace("{{", "&#123;&#123;")
+      .replace("}}", "&#125;&#125;"),
+    | FrameworkEscaping::Angular => html
+      .replace('{', "&#123;")
+      .replace('}', "&#125;")
+      .replace("[(", "&#91;(")
+      .replace(")]", ")&#93;")
+      .replace('*', "&#42;"),
+  }
+}
+
+/// Renders a footnote reference, using the ID prefixes from the `footnotes`
+/// option.
+fn render_footnote_reference<'a>(
+  context: &mut Context<State>,
+  node: &'a AstNode<'a>,
+  nfr: &NodeFootnoteReference,
+) -> Rendering {
+  let footnotes = footnote_options(context);
+  let mut ref_id = format!("{}{}", footnotes.ref_prefix, nfr.name);
+  if nfr.ref_num > 1 {
+    ref_id = format!("{ref_id}-{}", nfr.ref_num);
+  }
+  let def_id = format!("{}{}", footnotes.def_prefix, nfr.name);
+  context.write_str("<sup")?;
+  render_sourcepos(context, node)?;
+  write!(
+    context,
+    " class=\"footnote-ref\"><a href=\"#{}\" id=\"{}\" \
+     data-footnote-ref>{}</a></sup>",
+    escape_href(&def_id),
+    escape_href(&ref_id),
+    nfr.ix,
+  )?;
+  Ok(ChildRendering::HTML)
+}
+
+/// Renders a footnote definition, placing its backreferences according to
+/// the `footnote_backref` option.
+fn render_footnote_definition<'a>(
+  context: &mut Context<State>,
+  node: &'a AstNode<'a>,
+  nfd: &NodeFootnoteDefinition,
+  entering: bool,
+) -> Rendering {
+  let position = backref_position(context);
+  if entering {
+    if context.user.footnote_ix == 0 {
+      context.write_str("<section")?;
+      render_sourcepos(context, node)?;
+      context.write_str(" class=\"footnotes\" data-footnotes>\n<ol>\n")?;
+    }
+    context.user.footnote_ix += 1;
+    context.write_str("<li")?;
+    render_sourcepos(context, node)?;
+    let def_id =
+      format!("{}{}", footnote_options(context).def_prefix, nfd.name);
+    write!(context, " id=\"{}\">", escape_href(&def_id))?;
+    let first_is_paragraph = node
+      .first_child()
+      .is_some_and(|n| matches!(n.data.borrow().value, NodeValue::Paragraph));
+    // with a leading paragraph, the backreferences go inside of it instead
+    if position == BackrefPosition::Start
+      && !first_is_paragraph
+      && put_footnote_backref(context, nfd)?
+    {
+      context.write_str("\n")?;
+    }
+  } else {
+    if position == BackrefPosition::End && put_footnote_backref(context, nfd)? {
+      context.write_str("\n")?;
+    }
+    context.write_str("</li>\n")?;
+  }
+  Ok(ChildRendering::HTML)
+}
+
+/// Renders a paragraph directly inside of a footnote definition, which holds
+/// the backreferences if it's the first (or last) child of the definition.
+fn render_footnote_paragraph<'a>(
+  context: &mut Context<State>,
+  node: &'a AstNode<'a>,
+  entering: bool,
+) -> Rendering {
+  let parent = node.parent().expect("footnote paragraph has a parent");
+  let NodeValue::FootnoteDefinition(ref nfd) = parent.data.borrow().value
+  else {
+    unreachable!("footnote paragraph is inside of a footnote definition");
+  };
+  let position = backref_position(context);
+  if entering {
+    context.cr()?;
+    context.write_str("<p")?;
+    render_sourcepos(context, node)?;
+    context.write_str(">")?;
+    if position == BackrefPosition::Start
+      && node.previous_sibling().is_none()
+      && put_footnote_backref(context, nfd)?
+    {
+      context.write_str(" ")?;
+    }
+  } else {
+    if position == BackrefPosition::End && node.next_sibling().is_none() {
+      context.write_str(" ")?;
+      put_footnote_backref(context, nfd)?;
+    }
+    context.write_str("</p>\n")?;
+  }
+  Ok(ChildRendering::HTML)
+}
+
+/// Returns `true` if the node is a direct child of a footnote definition.
+fn is_footnote_paragraph<'a>(node: &'a AstNode<'a>) -> bool {
+  node.parent().is_some_and(|parent| {
+    matches!(parent.data.borrow().value, NodeValue::FootnoteDefinition(_))
+  })
+}
+
+/// Returns the `footnotes` option, or its defaults if it isn't set.
+fn footnote_options(context: &Context<State>) -> Footnotes {
+  let footnotes = context.user.extras.render.footnotes.clone();
+  footnotes.unwrap_or_default()
+}
+
+/// Returns the configured position of the footnote backreference links.
+fn backref_position(context: &Context<State>) -> BackrefPosition {
+  let backref = context.user.extras.render.footnote_backref.as_ref();
+  backref.map_or(BackrefPosition::End, |b| b.position)
+}
+
+/// Writes the backreference links of the current footnote definition, unless
+/// they were already written. Returns `true` if anything was written.
+fn put_footnote_backref(
+  context: &mut Context<State>,
+  nfd: &NodeFootnoteDefinition,
+) -> Result<bool, fmt::Error> {
+  let fnix = context.user.footnote_ix;
+  if context.user.written_footnote_ix >= fnix {
+    return Ok(false);
+  }
+  context.user.written_footnote_ix = fnix;
+  let footnotes = footnote_options(context);
+  let ref_id = escape_href(&format!("{}{}", footnotes.ref_prefix, nfd.name));
+  let label = escape_attr(&footnotes.backref_label);
+  let backref = context.user.extras.render.footnote_backref.as_ref();
+  let symbol = escape_attr(backref.map_or("↩", |b| &b.symbol));
+  for ref_num in 1..=nfd.total_references {
+    let (suffix, superscript) = match ref_num {
+      | 1 => (String::new(), String::new()),
+      | n => {
+        context.write_str(" ")?;
+        (
+          format!("-{n}"),
+          format!("<sup class=\"footnote-ref\">{n}</sup>"),
+        )
+      }
+    };
+    write!(
+      context,
+      "<a href=\"#{ref_id}{suffix}\" class=\"footnote-backref\" \
+       data-footnote-backref data-footnote-backref-idx=\"{fnix}{suffix}\" \
+       aria-label=\"{label} {fnix}{suffix}\">{symbol}{superscript}</a>"
+    )?;
+  }
+  Ok(true)
+}
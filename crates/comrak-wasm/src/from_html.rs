@@ -0,0 +1,704 @@
+//! Conversion of HTML back to Markdown.
+//!
+//! The HTML is split up by the minimal tokenizer in [`crate::html`], rather
+//! than parsed by a full HTML5 parser, and walked into a Comrak AST, which is
+//! then formatted as CommonMark like any other document. Only a pragmatic
+//! subset of HTML has a Markdown equivalent: headings, paragraphs, block
+//! quotes, lists, code, links, images, emphasis, line breaks, thematic breaks,
+//! and tables. The other elements are kept as raw HTML or dropped, as chosen
+//! by [`Unsupported`].
+//!
+//! Missing end tags are implied wherever the next element can't be nested in
+//! the open one, much like an HTML parser would: a `<p>` ends the paragraph
+//! before it, a `<li>` ends the item before it, and so on.
+
+use comrak::Arena;
+use comrak::nodes::AstNode;
+use comrak::nodes::ListDelimType;
+use comrak::nodes::ListType;
+use comrak::nodes::NodeCode;
+use comrak::nodes::NodeCodeBlock;
+use comrak::nodes::NodeHeading;
+use comrak::nodes::NodeLink;
+use comrak::nodes::NodeList;
+use comrak::nodes::NodeTable;
+use comrak::nodes::NodeValue;
+use comrak::nodes::TableAlignment;
+use serde::Deserialize;
+
+use crate::html::RAW_TEXT_ELEMENTS;
+use crate::html::Tag;
+use crate::html::TagKind;
+use crate::html::Token;
+use crate::html::tokenize;
+
+/// What to do with the HTML elements that have no Markdown equivalent.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Unsupported {
+  /// Keep their tags as raw HTML, along with comments.
+  #[default]
+  Raw,
+  /// Drop their tags, keeping their content, along with comments and the
+  /// content of elements such as `<script>` and `<style>`.
+  Drop,
+}
+
+/// Options for converting HTML to Markdown.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct HtmlToMarkdownOptions {
+  pub unsupported: Unsupported,
+}
+
+/// Elements that only group other content, and are left out of the AST.
+const TRANSPARENT: &[&str] = &[
+  "html", "body", "main", "article", "section", "header", "footer", "nav",
+  "aside", "div", "thead", "tbody", "tfoot",
+];
+
+/// Elements with a Markdown equivalent, whose stray end tags are ignored.
+const SUPPORTED: &[&str] = &[
+  "p", "h1", "h2", "h3", "h4", "h5", "h6", "blockquote", "ul", "ol", "li",
+  "pre", "hr", "table", "tr", "th", "td", "img", "em", "i", "strong", "b",
+  "del", "s", "strike", "code", "br",
+];
+
+/// Converts `html` to an AST, ready to be formatted as CommonMark.
+pub fn to_ast<'a>(
+  arena: &'a Arena<'a>,
+  html: &str,
+  options: &HtmlToMarkdownOptions,
+) -> &'a AstNode<'a> {
+  let root = arena.alloc(NodeValue::Document.into());
+  let mut builder = Builder {
+    arena,
+    unsupported: options.unsupported,
+    stack: vec![(root, None)],
+    alignments: Vec::new(),
+    raw_text: false,
+    in_head: false,
+  };
+  for token in tokenize(html) {
+    match token {
+      | Token::Text(text) => builder.text(text),
+      | Token::Tag(ref tag, raw) => builder.tag(tag, raw),
+      | Token::Other(raw) if raw.starts_with("<!--") => builder.comment(raw),
+      | Token::Other(_) => {}
+    }
+  }
+  while builder.stack.len() > 1 {
+    builder.pop();
+  }
+  root
+}
+
+/// Builds an AST from the tokens of an HTML document.
+struct Builder<'a> {
+  arena:       &'a Arena<'a>,
+  unsupported: Unsupported,
+  /// The open nodes, along with the name of the element that opened each, or
+  /// `None` for the paragraphs and lists implied by stray content.
+  stack:       Vec<(&'a AstNode<'a>, Option<String>)>,
+  /// The alignments of the columns of the table being built, taken from the
+  /// cells of its first row.
+  alignments:  Vec<TableAlignment>,
+  /// Whether the next text is the content of a raw text element.
+  raw_text:    bool,
+  /// Whether the tokens are inside `<head>`, whose content is dropped.
+  in_head:     bool,
+}
+
+impl<'a> Builder<'a> {
+  fn node(&self, value: NodeValue) -> &'a AstNode<'a> {
+    self.arena.alloc(value.into())
+  }
+
+  fn top(&self) -> &'a AstNode<'a> {
+    self.stack[self.stack.len() - 1].0
+  }
+
+  fn is_open(&self, f: impl Fn(&NodeValue) -> bool) -> bool {
+    self
+      .stack
+      .iter()
+      .any(|(node, _)| f(&node.data.borrow().value))
+  }
+
+  /// Appends a node to the top of the stack and opens it.
+  fn push(&mut self, value: NodeValue, name: Option<&str>) -> &'a AstNode<'a> {
+    let node = self.node(value);
+    self.top().append(node);
+    self.stack.push((node, name.map(str::to_string)));
+    node
+  }
+
+  fn pop(&mut self) {
+    if let Some((node, _)) = self.stack.pop() {
+      self.finish(node);
+    }
+  }
+
+  fn pop_until(&mut self, f: impl Fn(&NodeValue) -> bool) {
+    while !f(&self.top().data.borrow().value) {
+      self.pop();
+    }
+  }
+
+  /// Closes the innermost element with the given name, and everything opened
+  /// after it, returning `false` if there is no such element.
+  fn close(&mut self, name: &str) -> bool {
+    let open = self
+      .stack
+      .iter()
+      .rposition(|(_, n)| n.as_deref() == Some(name));
+    let Some(i) = open else {
+      return false;
+    };
+    while self.stack.len() > i {
+      self.pop();
+    }
+    true
+  }
+
+  /// Returns the node inline content goes in, opening a paragraph for it if
+  /// need be, or `None` if it can't go anywhere (e.g. between table rows).
+  fn inline_container(&mut self) -> Option<&'a AstNode<'a>> {
+    let top = self.top();
+    let list = match top.data.borrow().value {
+      | ref value if accepts_inlines(value) => return Some(top),
+      | NodeValue::Table(_) | NodeValue::TableRow(_) => return None,
+      | NodeValue::List(nl) => Some(nl),
+      | _ => None,
+    };
+    match list {
+      // content directly in a list implies an item
+      | Some(nl) => {
+        self.push(NodeValue::Item(nl), None);
+      }
+      | None => self.pop_until(accepts_blocks),
+    }
+    Some(self.push(NodeValue::Paragraph, None))
+  }
+
+  /// Opens a block, ending the paragraph and inlines it can't be nested in.
+  /// Table cells only hold inlines, so blocks in them are left out.
+  fn block(&mut self, value: NodeValue, name: &str) -> Option<&'a AstNode<'a>> {
+    if self.is_open(|v| matches!(v, NodeValue::TableCell)) {
+      return None;
+    }
+    self.pop_until(accepts_blocks);
+    Some(self.push(value, Some(name)))
+  }
+
+  fn inline(&mut self, value: NodeValue, name: &str) {
+    if self.inline_container().is_some() {
+      self.push(value, Some(name));
+    }
+  }
+
+  /// Appends raw HTML as an inline.
+  fn raw(&mut self, html: &str) {
+    if let Some(container) = self.inline_container() {
+      let value = NodeValue::HtmlInline(html.to_string().into());
+      container.append(self.node(value));
+    }
+  }
+
+  fn comment(&mut self, raw: &str) {
+    if !self.in_head && self.unsupported == Unsupported::Raw {
+      self.raw(raw);
+    }
+  }
+
+  fn text(&mut self, text: &str) {
+    if self.in_head {
+      return;
+    }
+    if self.raw_text {
+      if self.unsupported == Unsupported::Raw {
+        self.raw(text);
+      }
+      return;
+    }
+    let text = decode(text);
+    // the content of code is kept as it is, apart from the whitespace in
+    // inline code, which is collapsed like any other text
+    match self.top().data.borrow_mut().value {
+      | NodeValue::CodeBlock(ref mut ncb) => {
+        return ncb.literal.push_str(&text);
+      }
+      | NodeValue::Code(ref mut code) => {
+        return code.literal.push_str(&collapse(&text));
+      }
+      | _ => {}
+    }
+    let text = collapse(&text);
+    if text == " " && !accepts_inlines(&self.top().data.borrow().value) {
+      return;
+    }
+    let Some(container) = self.inline_container() else {
+      return;
+    };
+    if let Some(last) = container.last_child()
+      && let NodeValue::Text(ref mut literal) = last.data.borrow_mut().value
+    {
+      let text = match literal.ends_with(' ') {
+        | true => text.trim_start(),
+        | false => &text,
+      };
+      *literal = format!("{literal}{text}").into();
+      return;
+    }
+    container.append(self.node(NodeValue::Text(text.into())));
+  }
+
+  fn tag(&mut self, tag: &Tag, raw: &str) {
+    let name = tag.name.as_str();
+    let closing = tag.kind == TagKind::Close;
+    if self.in_head {
+      self.in_head = !(closing && name == "head");
+      return;
+    }
+    if self.raw_text {
+      // the content of a raw text element always ends at its end tag
+      self.raw_text = false;
+      if self.unsupported == Unsupported::Raw {
+        self.raw(raw);
+      }
+      return;
+    }
+    // tags in code are left out, apart from the language of a code block
+    let code = match self.top().data.borrow_mut().value {
+      | NodeValue::CodeBlock(ref mut ncb) => {
+        if name == "code" && !closing && ncb.info.is_empty() {
+          ncb.info = language(tag).unwrap_or_default();
+        }
+        Some("pre")
+      }
+      | NodeValue::Code(_) => Some("code"),
+      | _ => None,
+    };
+    match code {
+      | Some(element) => {
+        if closing && name == element {
+          self.close(name);
+        }
+      }
+      | None if closing => self.end(name, raw),
+      | None => self.start(tag, raw),
+    }
+  }
+
+  fn start(&mut self, tag: &Tag, raw: &str) {
+    let name = tag.name.as_str();
+    match name {
+      | "head" => self.in_head = tag.kind == TagKind::Open,
+      | _ if TRANSPARENT.contains(&name) => {}
+      | "p" => {
+        self.block(NodeValue::Paragraph, name);
+      }
+      | "h1" | "h2" | "h3" | "h4" | "h5" | "h6" => {
+        let level = name.as_bytes()[1] - b'0';
+        let heading = NodeHeading { level, setext: false };
+        self.block(NodeValue::Heading(heading), name);
+      }
+      | "blockquote" => {
+        self.block(NodeValue::BlockQuote, name);
+      }
+      | "ul" | "ol" => {
+        let ordered = name == "ol";
+        let nl = NodeList {
+          list_type: match ordered {
+            | true => ListType::Ordered,
+            | false => ListType::Bullet,
+          },
+          start: match ordered {
+            | true => {
+              tag.get("start").and_then(|s| s.parse().ok()).unwrap_or(1)
+            }
+            | false => 0,
+          },
+          ..list()
+        };
+        self.block(NodeValue::List(nl), name);
+      }
+      | "li" => self.item(),
+      | "pre" => {
+        let ncb = NodeCodeBlock {
+          fenced: true,
+          fence_char: b'`',
+          fence_length: 3,
+          info: language(tag).unwrap_or_default(),
+          ..NodeCodeBlock::default()
+        };
+        self.block(NodeValue::CodeBlock(ncb.into()), name);
+      }
+      | "hr" => {
+        if self.block(NodeValue::ThematicBreak, name).is_some() {
+          self.pop();
+        }
+      }
+      | "table" => {
+        self.alignments.clear();
+        let table = NodeTable::default();
+        self.block(NodeValue::Table(table.into()), name);
+      }
+      | "tr" => self.row(),
+      | "th" | "td" => self.cell(tag),
+      | "a" if tag.has("href") => {
+        let link = NodeLink {
+          url:   attr(tag, "href"),
+          title: attr(tag, "title"),
+        };
+        self.inline(NodeValue::Link(link.into()), name);
+      }
+      | "img" => self.image(tag),
+      | "em" | "i" => self.inline(NodeValue::Emph, name),
+      | "strong" | "b" => self.inline(NodeValue::Strong, name),
+      | "del" | "s" | "strike" => self.inline(NodeValue::Strikethrough, name),
+      | "code" => {
+        let code = NodeCode { num_backticks: 1, literal: String::new() };
+        self.inline(NodeValue::Code(code.into()), name);
+      }
+      | "br" => {
+        if let Some(container) = self.inline_container() {
+          container.append(self.node(NodeValue::LineBreak));
+        }
+      }
+      | _ => {
+        self.raw_text =
+          tag.kind == TagKind::Open && RAW_TEXT_ELEMENTS.contains(&name);
+        if self.unsupported == Unsupported::Raw {
+          self.raw(raw);
+        }
+      }
+    }
+  }
+
+  fn end(&mut self, name: &str, raw: &str) {
+    if self.close(name)
+      || SUPPORTED.contains(&name)
+      || TRANSPARENT.contains(&name)
+    {
+      return;
+    }
+    if self.unsupported == Unsupported::Raw {
+      self.raw(raw);
+    }
+  }
+
+  /// Opens a list item, ending the item before it. An item outside of a list
+  /// implies a bullet list.
+  fn item(&mut self) {
+    if self.is_open(|v| matches!(v, NodeValue::TableCell)) {
+      return;
+    }
+    let nl = match self.is_open(|v| matches!(v, NodeValue::List(_))) {
+      | true => {
+        self.pop_until(|v| matches!(v, NodeValue::List(_)));
+        match self.top().data.borrow().value {
+          | NodeValue::List(nl) => nl,
+          | _ => unreachable!(),
+        }
+      }
+      | false => {
+        self.pop_until(accepts_blocks);
+        self.push(NodeValue::List(list()), None);
+        list()
+      }
+    };
+    self.push(NodeValue::Item(nl), Some("li"));
+  }
+
+  /// Opens a table row, ending the row before it. The first row of a table is
+  /// its header, as Markdown tables always have one.
+  fn row(&mut self) {
+    if !self.is_open(|v| matches!(v, NodeValue::Table(_))) {
+      return;
+    }
+    self.pop_until(|v| matches!(v, NodeValue::Table(_)));
+    let header = self.top().first_child().is_none();
+    self.push(NodeValue::TableRow(header), Some("tr"));
+  }
+
+  fn cell(&mut self, tag: &Tag) {
+    if !self.is_open(|v| matches!(v, NodeValue::Table(_))) {
+      return;
+    }
+    self
+      .pop_until(|v| matches!(v, NodeValue::Table(_) | NodeValue::TableRow(_)));
+    if matches!(self.top().data.borrow().value, NodeValue::Table(_)) {
+      self.row();
+    }
+    if matches!(self.top().data.borrow().value, NodeValue::TableRow(true)) {
+      self.alignments.push(alignment(tag));
+    }
+    self.push(NodeValue::TableCell, Some(tag.name.as_str()));
+  }
+
+  fn image(&mut self, tag: &Tag) {
+    let Some(container) = self.inline_container() else {
+      return;
+    };
+    let link = NodeLink { url: attr(tag, "src"), title: attr(tag, "title") };
+    let image = self.node(NodeValue::Image(link.into()));
+    let alt = collapse(&attr(tag, "alt"));
+    if !alt.is_empty() {
+      image.append(self.node(NodeValue::Text(alt.into())));
+    }
+    container.append(image);
+  }
+
+  /// Tidies up a node once it's closed.
+  fn finish(&mut self, node: &'a AstNode<'a>) {
+    let mut data = node.data.borrow_mut();
+    match data.value {
+      | NodeValue::Paragraph | NodeValue::Heading(_) | NodeValue::TableCell => {
+        let paragraph = matches!(data.value, NodeValue::Paragraph);
+        drop(data);
+        trim(node);
+        if paragraph && node.first_child().is_none() {
+          node.detach();
+        }
+      }
+      | NodeValue::Emph | NodeValue::Strong | NodeValue::Strikethrough => {
+        drop(data);
+        if node.first_child().is_none() {
+          node.detach();
+        }
+      }
+      | NodeValue::Code(ref code) => {
+        let empty = code.literal.is_empty();
+        drop(data);
+        if empty {
+          node.detach();
+        }
+      }
+      | NodeValue::CodeBlock(ref mut ncb) => {
+        // a newline right after `<pre>` isn't part of its content
+        if ncb.literal.starts_with('\n') {
+          ncb.literal.remove(0);
+        }
+        if !ncb.literal.ends_with('\n') {
+          ncb.literal.push('\n');
+        }
+      }
+      | NodeValue::List(ref mut nl) => {
+        nl.tight = node.children().all(|item| item.children().count() <= 1);
+      }
+      | NodeValue::Table(ref mut nt) => {
+        let rows: Vec<_> = node.children().collect();
+        let columns = rows.iter().map(|row| row.children().count()).max();
+        let columns = columns.unwrap_or(0);
+        // every row of a Markdown table has a cell in each column
+        for row in &rows {
+          for _ in row.children().count()..columns {
+            row.append(self.node(NodeValue::TableCell));
+          }
+        }
+        self.alignments.resize(columns, TableAlignment::None);
+        nt.alignments = std::mem::take(&mut self.alignments);
+        nt.num_columns = columns;
+        nt.num_rows = rows.len();
+        nt.num_nonempty_cells = rows
+          .iter()
+          .flat_map(|row| row.children())
+          .filter(|cell| cell.first_child().is_some())
+          .count();
+        drop(data);
+        if columns == 0 {
+          node.detach();
+        }
+      }
+      | _ => {}
+    }
+  }
+}
+
+fn accepts_blocks(value: &NodeValue) -> bool {
+  matches!(
+    value,
+    NodeValue::Document | NodeValue::BlockQuote | NodeValue::Item(_)
+  )
+}
+
+fn accepts_inlines(value: &NodeValue) -> bool {
+  matches!(
+    value,
+    NodeValue::Paragraph
+      | NodeValue::Heading(_)
+      | NodeValue::TableCell
+      | NodeValue::Link(_)
+      | NodeValue::Emph
+      | NodeValue::Strong
+      | NodeValue::Strikethrough
+  )
+}
+
+fn list() -> NodeList {
+  NodeList {
+    list_type: ListType::Bullet,
+    delimiter: ListDelimType::Period,
+    bullet_char: b'-',
+    padding: 2,
+    tight: true,
+    ..NodeList::default()
+  }
+}
+
+/// Returns the decoded value of an attribute, or an empty string.
+fn attr(tag: &Tag, name: &str) -> String {
+  tag.get(name).map(decode).unwrap_or_default()
+}
+
+/// Returns the language of a code block from the `language-` or `lang-` class
+/// of its `<pre>` or `<code>` element.
+fn language(tag: &Tag) -> Option<String> {
+  tag
+    .get("class")?
+    .split_ascii_whitespace()
+    .find_map(|c| c.strip_prefix("language-").or(c.strip_prefix("lang-")))
+    .map(decode)
+}
+
+/// Returns the alignment of a table cell, from its `align` attribute or the
+/// `text-align` property of its `style`.
+fn alignment(tag: &Tag) -> TableAlignment {
+  let style = tag.get("style").and_then(|style| {
+    style.split(';').find_map(|declaration| {
+      let (property, value) = declaration.split_once(':')?;
+      (property.trim() == "text-align").then(|| value.trim())
+    })
+  });
+  match tag.get("align").or(style) {
+    | Some("left") => TableAlignment::Left,
+    | Some("center") => TableAlignment::Center,
+    | Some("right") => TableAlignment::Right,
+    | _ => TableAlignment::None,
+  }
+}
+
+/// Collapses each run of whitespace in `text` to a single space, as HTML
+/// renders it.
+fn collapse(text: &str) -> String {
+  let mut out = String::with_capacity(text.len());
+  let mut space = false;
+  for c in text.chars() {
+    if c.is_ascii_whitespace() {
+      if !space {
+        out.push(' ');
+      }
+      space = true;
+    } else {
+      out.push(c);
+      space = false;
+    }
+  }
+  out
+}
+
+/// Decodes the named character references Comrak writes, along with `&nbsp;`,
+/// `&apos;`, and numeric ones. Anything else is left as it is.
+fn decode(text: &str) -> String {
+  let mut out = String::with_capacity(text.len());
+  let mut rest = text;
+  while let Some(i) = rest.find('&') {
+    out.push_str(&rest[..i]);
+    rest = &rest[i..];
+    let reference = rest.find(';').filter(|&end| end <= 10).and_then(|end| {
+      let c = match &rest[1..end] {
+        | "amp" => '&',
+        | "lt" => '<',
+        | "gt" => '>',
+        | "quot" => '"',
+        | "apos" => '\'',
+        | "nbsp" => '\u{a0}',
+        | name => {
+          let number = name.strip_prefix('#')?;
+          let code = match number.strip_prefix(['x', 'X']) {
+            | Some(hex) => u32::from_str_radix(hex, 16).ok()?,
+            | None => number.parse().ok()?,
+          };
+          char::from_u32(code)?
+        }
+      };
+      Some((c, end + 1))
+    });
+    match reference {
+      | Some((c, len)) => {
+        out.push(c);
+        rest = &rest[len..];
+      }
+      | None => {
+        out.push('&');
+        rest = &rest[1..];
+      }
+    }
+  }
+  out.push_str(rest);
+  out
+}
+
+/// Removes the whitespace that HTML doesn't render from the inlines of a
+/// block: at its start and end, around line breaks, and after another space.
+fn trim<'a>(block: &'a AstNode<'a>) {
+  let mut last = None;
+  trim_inlines(block, &mut true, &mut last);
+  if let Some(last) = last {
+    trim_end(last);
+  }
+}
+
+fn trim_inlines<'a>(
+  node: &'a AstNode<'a>,
+  space: &mut bool,
+  last: &mut Option<&'a AstNode<'a>>,
+) {
+  let children: Vec<_> = node.children().collect();
+  for child in children {
+    let mut data = child.data.borrow_mut();
+    match data.value {
+      | NodeValue::Text(ref mut text) => {
+        if *space && text.starts_with(' ') {
+          *text = text[1..].to_string().into();
+        }
+        if text.is_empty() {
+          drop(data);
+          child.detach();
+          continue;
+        }
+        *space = text.ends_with(' ');
+        *last = Some(child);
+      }
+      | NodeValue::LineBreak => {
+        drop(data);
+        if let Some(last) = last.take() {
+          trim_end(last);
+        }
+        *space = true;
+      }
+      | NodeValue::Image(_) | NodeValue::Code(_) | NodeValue::HtmlInline(_) => {
+        *space = false;
+        *last = None;
+      }
+      | _ => {
+        drop(data);
+        trim_inlines(child, space, last);
+      }
+    }
+  }
+}
+
+fn trim_end<'a>(node: &'a AstNode<'a>) {
+  let mut data = node.data.borrow_mut();
+  if let NodeValue::Text(ref mut text) = data.value {
+    if text.ends_with(' ') {
+      *text = text[..text.len() - 1].to_string().into();
+    }
+    if text.is_empty() {
+      drop(data);
+      node.detach();
+    }
+  }
+}
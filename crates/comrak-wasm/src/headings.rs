@@ -0,0 +1,143 @@
+//! Heading ID generation, optionally driven by a user-provided `slugify`
+//! callback in place of Comrak's default anchorizer.
+
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::collections::HashSet;
+use std::fmt;
+
+use comrak::Anchorizer;
+use comrak::Options as ComrakOptions;
+use comrak::adapters::HeadingAdapter as ComrakHeadingAdapter;
+use comrak::adapters::HeadingMeta;
+use comrak::nodes::Sourcepos;
+use js_sys::Function;
+use wasm_bindgen::JsValue;
+
+use crate::html::escape_attr;
+use crate::options::Options;
+
+/// Generates unique heading slugs, exactly as they appear in the rendered
+/// output when the `header_ids` extension is enabled.
+#[derive(Debug)]
+pub enum Slugger {
+  /// Comrak's own anchorizer.
+  Default(Anchorizer),
+  /// A JS `slugify(text, level)` function. Slugs are deduplicated the same
+  /// way as Comrak's anchorizer, by adding a `-1`, `-2`, ... suffix to any
+  /// slug that has already been used.
+  Custom(Function, HashSet<String>),
+}
+
+impl Slugger {
+  pub fn new(extras: &Options) -> Self {
+    match extras.extension.slugify {
+      | Some(ref f) => Self::Custom(f.clone(), HashSet::new()),
+      | None => Self::Default(Anchorizer::new()),
+    }
+  }
+
+  /// Returns the unique slug for a heading with the given text and level.
+  pub fn slug(&mut self, text: &str, level: u8) -> Result<String, JsValue> {
+    let (slugify, used) = match self {
+      | Self::Default(anchorizer) => return Ok(anchorizer.anchorize(text)),
+      | Self::Custom(slugify, used) => (slugify, used),
+    };
+    let text = JsValue::from_str(text);
+    let level = JsValue::from(level);
+    let slug = slugify.call2(&JsValue::NULL, &text, &level)?;
+    let slug = slug.as_string().unwrap_or_default();
+
+    let mut unique = slug.clone();
+    let mut n = 0;
+    while used.contains(&unique) {
+      n += 1;
+      unique = format!("{slug}-{n}");
+    }
+    used.insert(unique.clone());
+    Ok(unique)
+  }
+}
+
+/// A heading adapter which reproduces Comrak's `header_ids` output, but with
+/// the anchor slugs produced by a JS `slugify(text, level)` function.
+#[derive(Debug)]
+pub struct SlugifyAdapter {
+  slugger: RefCell<Slugger>,
+  prefix:  String,
+  classes: BTreeMap<u8, String>,
+  error:   RefCell<Option<JsValue>>,
+}
+
+impl SlugifyAdapter {
+  /// Creates an adapter if both `extension.slugify` and `extension.headerIDs`
+  /// are set; the slug function has no effect without a header ID prefix.
+  pub fn from_options(
+    options: &ComrakOptions,
+    extras: &Options,
+  ) -> Option<Self> {
+    extras.extension.slugify.as_ref()?;
+    // these headings are rendered by our own formatter instead
+    let render = &extras.render;
+    if render.heading_anchor_wrap_heading
+      || render.header_ids_only_when_referenced
+    {
+      return None;
+    }
+    let prefix = options.extension.header_ids.clone()?;
+    Some(Self {
+      slugger: RefCell::new(Slugger::new(extras)),
+      prefix,
+      classes: render.heading_classes.clone(),
+      error: RefCell::default(),
+    })
+  }
+
+  /// Rethrows the first exception raised by `slugify` during rendering.
+  pub fn rethrow(&self) -> Result<(), JsValue> {
+    match self.error.borrow_mut().take() {
+      | Some(e) => Err(e),
+      | None => Ok(()),
+    }
+  }
+}
+
+unsafe impl Send for SlugifyAdapter {}
+unsafe impl Sync for SlugifyAdapter {}
+
+impl ComrakHeadingAdapter for SlugifyAdapter {
+  fn enter(
+    &self,
+    out: &mut dyn fmt::Write,
+    heading: &HeadingMeta,
+    sourcepos: Option<Sourcepos>,
+  ) -> fmt::Result {
+    let mut slugger = self.slugger.borrow_mut();
+    let slug = slugger.slug(&heading.content, heading.level).map_err(|e| {
+      self.error.borrow_mut().replace(e);
+      fmt::Error
+    })?;
+    write!(out, "<h{}", heading.level)?;
+    if let Some(class) = self.classes.get(&heading.level) {
+      write!(out, " class=\"{}\"", escape_attr(class))?;
+    }
+    if let Some(sp) = sourcepos {
+      write!(out, " data-sourcepos=\"{sp}\"")?;
+    }
+    let slug = escape_attr(&slug);
+    let prefix = escape_attr(&self.prefix);
+    write!(
+      out,
+      "><a href=\"#{slug}\" aria-hidden=\"true\" class=\"anchor\" \
+       id=\"{prefix}{slug}\"></a>"
+    )
+  }
+
+  fn exit(
+    &self,
+    out: &mut dyn fmt::Write,
+    heading: &HeadingMeta,
+  ) -> fmt::Result {
+    writeln!(out, "</h{}>", heading.level)
+  }
+}
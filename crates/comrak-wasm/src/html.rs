@@ -0,0 +1,530 @@
+//! A minimal, allocation-friendly HTML tag rewriter.
+//!
+//! This is **not** a general purpose HTML parser. It is designed to operate on
+//! the well-formed output produced by Comrak's HTML formatter, where any `<` in
+//! text content has already been escaped, so every unescaped `<` begins a tag,
+//! comment, or declaration. Raw HTML passed through with `unsafe` enabled is
+//! handled on a best-effort basis, with the contents of raw text elements such
+//! as `<script>` and `<style>` copied through verbatim. The same tokenizer is
+//! exposed through [`tokenize`], for code that builds something other than
+//! HTML from its input.
+
+use std::fmt;
+
+/// The kind of tag encountered by the rewriter.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TagKind {
+  /// An opening tag, e.g. `<p>`.
+  Open,
+  /// A closing tag, e.g. `</p>`.
+  Close,
+  /// A self-closing tag, e.g. `<img />`.
+  SelfClosing,
+}
+
+/// A single attribute on a [`Tag`]. Values are kept in their raw, escaped form
+/// exactly as they appeared in the source HTML.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Attr {
+  pub name:  String,
+  pub value: Option<String>,
+}
+
+/// A parsed HTML tag, which can be inspected and modified by the callback
+/// passed to [`rewrite_tags`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Tag {
+  pub name:  String,
+  pub kind:  TagKind,
+  pub attrs: Vec<Attr>,
+}
+
+/// What to do with a tag after the rewrite callback has inspected it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Action {
+  /// Write the (possibly modified) tag back into the output.
+  Keep,
+  /// Remove the tag along with everything up to its matching closing tag.
+  DropElement,
+  /// Replace the tag with the given raw HTML.
+  Replace(String),
+}
+
+impl Tag {
+  /// Returns `true` if this is an opening or self-closing tag.
+  pub fn is_start(&self) -> bool {
+    self.kind != TagKind::Close
+  }
+
+  /// Returns the raw (escaped) value of the named attribute, if present.
+  pub fn get(&self, name: &str) -> Option<&str> {
+    self
+      .attrs
+      .iter()
+      .find(|a| a.name.eq_ignore_ascii_case(name))
+      .map(|a| a.value.as_deref().unwrap_or(""))
+  }
+
+  /// Returns `true` if the tag has the named attribute.
+  pub fn has(&self, name: &str) -> bool {
+    self.attrs.iter().any(|a| a.name.eq_ignore_ascii_case(name))
+  }
+
+  /// Sets the named attribute to the given unescaped value, replacing any
+  /// existing value.
+  pub fn set(&mut self, name: &str, value: &str) {
+    let value = Some(escape_attr(value));
+    match self
+      .attrs
+      .iter_mut()
+      .find(|a| a.name.eq_ignore_ascii_case(name))
+    {
+      | Some(attr) => attr.value = value,
+      | None => self.attrs.push(Attr { name: name.to_string(), value }),
+    }
+  }
+
+  /// Keeps only the attributes for which the predicate returns `true`.
+  pub fn retain(&mut self, f: impl FnMut(&Attr) -> bool) {
+    self.attrs.retain(f);
+  }
+}
+
+impl fmt::Display for Tag {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    if self.kind == TagKind::Close {
+      return write!(f, "</{}>", self.name);
+    }
+    write!(f, "<{}", self.name)?;
+    for attr in &self.attrs {
+      match attr.value {
+        | Some(ref v) => write!(f, " {}=\"{}\"", attr.name, v)?,
+        | None => write!(f, " {}", attr.name)?,
+      }
+    }
+    match self.kind {
+      | TagKind::SelfClosing => f.write_str(" />"),
+      | _ => f.write_str(">"),
+    }
+  }
+}
+
+/// Elements whose content is raw text and must never be scanned for tags.
+pub const RAW_TEXT_ELEMENTS: &[&str] = &[
+  "script", "style", "textarea", "xmp",
+];
+
+/// Rewrites every tag in `html` using the given callback, copying all other
+/// content (text, comments, declarations) through unchanged.
+pub fn rewrite_tags(
+  html: &str,
+  mut f: impl FnMut(&mut Tag) -> Action,
+) -> String {
+  let mut out = String::with_capacity(html.len());
+  let bytes = html.as_bytes();
+  let mut i = 0;
+  // name and nesting depth of an element currently being dropped
+  let mut dropping: Option<(String, usize)> = None;
+
+  while i < bytes.len() {
+    let Some(rel) = html[i..].find('<') else {
+      if dropping.is_none() {
+        out.push_str(&html[i..]);
+      }
+      break;
+    };
+    if dropping.is_none() {
+      out.push_str(&html[i..i + rel]);
+    }
+    i += rel;
+
+    let Some((mut tag, end)) = parse_tag(html, i) else {
+      // comments, declarations, and stray `<` characters
+      let end = skip_non_tag(html, i);
+      if dropping.is_none() {
+        out.push_str(&html[i..end]);
+      }
+      i = end;
+      continue;
+    };
+    let raw = &html[i..end];
+    i = end;
+
+    if let Some((ref name, ref mut depth)) = dropping {
+      if tag.name == *name {
+        match tag.kind {
+          | TagKind::Open => *depth += 1,
+          | TagKind::Close if *depth == 0 => dropping = None,
+          | TagKind::Close => *depth -= 1,
+          | TagKind::SelfClosing => {}
+        }
+      }
+      continue;
+    }
+
+    let is_raw_text =
+      tag.kind == TagKind::Open && RAW_TEXT_ELEMENTS.contains(&&*tag.name);
+    let before = tag.clone();
+    match f(&mut tag) {
+      | Action::Keep if tag == before => out.push_str(raw),
+      | Action::Keep => out.push_str(&tag.to_string()),
+      | Action::Replace(s) => out.push_str(&s),
+      | Action::DropElement => {
+        if tag.kind == TagKind::Open {
+          dropping = Some((before.name.clone(), 0));
+        }
+        if is_raw_text {
+          // jump straight to the closing tag, which ends the drop
+          i = skip_raw_text(html, i, &before.name);
+        }
+        continue;
+      }
+    }
+
+    if is_raw_text {
+      let end = skip_raw_text(html, i, &before.name);
+      out.push_str(&html[i..end]);
+      i = end;
+    }
+  }
+  out
+}
+
+/// A piece of HTML, as split up by [`tokenize`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Token<'h> {
+  /// Text content, in its raw, escaped form. The content of a raw text
+  /// element such as `<script>` is always a single text token.
+  Text(&'h str),
+  /// A tag, along with the source it was parsed from.
+  Tag(Tag, &'h str),
+  /// A comment, declaration, or processing instruction.
+  Other(&'h str),
+}
+
+/// Splits `html` into text, tags, and everything else, in order.
+pub fn tokenize(html: &str) -> Vec<Token<'_>> {
+  let mut tokens = Vec::new();
+  let mut i = 0;
+  while i < html.len() {
+    let Some(rel) = html[i..].find('<') else {
+      tokens.push(Token::Text(&html[i..]));
+      break;
+    };
+    if rel > 0 {
+      tokens.push(Token::Text(&html[i..i + rel]));
+    }
+    i += rel;
+
+    let Some((tag, end)) = parse_tag(html, i) else {
+      let end = skip_non_tag(html, i);
+      let raw = &html[i..end];
+      // a stray `<` is just text
+      tokens.push(match raw {
+        | "<" => Token::Text(raw),
+        | _ => Token::Other(raw),
+      });
+      i = end;
+      continue;
+    };
+    let raw_text = (tag.kind == TagKind::Open
+      && RAW_TEXT_ELEMENTS.contains(&&*tag.name))
+    .then(|| tag.name.clone());
+    tokens.push(Token::Tag(tag, &html[i..end]));
+    i = end;
+
+    if let Some(name) = raw_text {
+      let end = skip_raw_text(html, i, &name);
+      if end > i {
+        tokens.push(Token::Text(&html[i..end]));
+      }
+      i = end;
+    }
+  }
+  tokens
+}
+
+/// Parses the tag beginning at `start` (which must point at a `<`), returning
+/// it along with the offset immediately following its closing `>`.
+fn parse_tag(html: &str, start: usize) -> Option<(Tag, usize)> {
+  let bytes = html.as_bytes();
+  let mut i = start + 1;
+  let kind = if bytes.get(i) == Some(&b'/') {
+    i += 1;
+    TagKind::Close
+  } else {
+    TagKind::Open
+  };
+  if !bytes.get(i)?.is_ascii_alphabetic() {
+    return None;
+  }
+  let name_start = i;
+  while i < bytes.len() && is_name_byte(bytes[i]) {
+    i += 1;
+  }
+  let name = html[name_start..i].to_ascii_lowercase();
+  let mut tag = Tag { name, kind, attrs: Vec::new() };
+
+  loop {
+    while i < bytes.len() && bytes[i].is_ascii_whitespace() {
+      i += 1;
+    }
+    match bytes.get(i)? {
+      | b'>' => return Some((tag, i + 1)),
+      | b'/' if bytes.get(i + 1) == Some(&b'>') => {
+        if tag.kind == TagKind::Open {
+          tag.kind = TagKind::SelfClosing;
+        }
+        return Some((tag, i + 2));
+      }
+      | b'/' => {
+        i += 1;
+        continue;
+      }
+      | _ => {}
+    }
+    let attr_start = i;
+    while i < bytes.len()
+      && !bytes[i].is_ascii_whitespace()
+      && !matches!(bytes[i], b'=' | b'>' | b'/')
+    {
+      i += 1;
+    }
+    let attr_name = html[attr_start..i].to_string();
+    while i < bytes.len() && bytes[i].is_ascii_whitespace() {
+      i += 1;
+    }
+    let mut value = None;
+    if bytes.get(i) == Some(&b'=') {
+      i += 1;
+      while i < bytes.len() && bytes[i].is_ascii_whitespace() {
+        i += 1;
+      }
+      match bytes.get(i)? {
+        | &q @ (b'"' | b'\'') => {
+          let value_start = i + 1;
+          let len = html[value_start..].find(q as char)?;
+          let raw = &html[value_start..value_start + len];
+          value = Some(if q == b'\'' {
+            raw.replace('"', "&quot;")
+          } else {
+            raw.to_string()
+          });
+          i = value_start + len + 1;
+        }
+        | _ => {
+          let value_start = i;
+          while i < bytes.len()
+            && !bytes[i].is_ascii_whitespace()
+            && bytes[i] != b'>'
+          {
+            i += 1;
+          }
+          value = Some(html[value_start..i].replace('"', "&quot;"));
+        }
+      }
+    }
+    if !attr_name.is_empty() {
+      tag.attrs.push(Attr { name: attr_name, value });
+    }
+  }
+}
+
+fn is_name_byte(b: u8) -> bool {
+  b.is_ascii_alphanumeric() || matches!(b, b'-' | b':' | b'_')
+}
+
+/// Returns the end offset of a comment, declaration, or lone `<` character.
+/// An unterminated comment or declaration runs to the end of the input.
+fn skip_non_tag(html: &str, start: usize) -> usize {
+  match non_tag_end(html, start) {
+    | Some(end) => end,
+    | None if html[start + 1..].starts_with(['!', '?']) => html.len(),
+    | None => start + 1,
+  }
+}
+
+/// Returns the end offset of the complete comment, declaration, or processing
+/// instruction beginning at `start`, if there is one.
+fn non_tag_end(html: &str, start: usize) -> Option<usize> {
+  let rest = &html[start..];
+  if let Some(comment) = rest.strip_prefix("<!--") {
+    // per the HTML spec, `<!-->` and `<!--->` are complete (empty) comments,
+    // and a comment is closed by `--!>` as well as by `-->`
+    if let Some(end) = [">", "->"].iter().find(|e| comment.starts_with(**e)) {
+      return Some(start + 4 + end.len());
+    }
+    return ["-->", "--!>"]
+      .iter()
+      .filter_map(|e| comment.find(e).map(|n| n + e.len()))
+      .min()
+      .map(|n| start + 4 + n);
+  }
+  if rest.starts_with("<!") || rest.starts_with("<?") {
+    return rest.find('>').map(|n| start + n + 1);
+  }
+  None
+}
+
+/// Drops a tag cut off by the end of `html`, as browsers do, and escapes every
+/// other `<` that doesn't begin a complete tag, comment, or declaration, so
+/// that none of them can combine with whatever HTML follows `html` into a tag.
+pub fn close_incomplete(html: &str) -> String {
+  let mut out = String::with_capacity(html.len());
+  let mut i = 0;
+  while let Some(rel) = html[i..].find('<') {
+    out.push_str(&html[i..i + rel]);
+    i += rel;
+    let end = match parse_tag(html, i) {
+      | Some((tag, end))
+        if tag.kind == TagKind::Open
+          && RAW_TEXT_ELEMENTS.contains(&&*tag.name) =>
+      {
+        skip_raw_text(html, end, &tag.name)
+      }
+      | Some((_, end)) => end,
+      | None if starts_tag(&html[i..]) => return out,
+      | None => match non_tag_end(html, i) {
+        | Some(end) => end,
+        | None => {
+          out.push_str("&lt;");
+          i += 1;
+          continue;
+        }
+      },
+    };
+    out.push_str(&html[i..end]);
+    i = end;
+  }
+  out.push_str(&html[i..]);
+  out
+}
+
+/// Returns the length of the longest prefix of `html` that ends outside of any
+/// tag, comment, declaration, or raw text element, which can be rewritten apart
+/// from the rest of `html` with the same result.
+pub fn complete_prefix_len(html: &str) -> usize {
+  let mut i = 0;
+  while let Some(rel) = html[i..].find('<') {
+    let start = i + rel;
+    i = match parse_tag(html, start) {
+      | Some((tag, end))
+        if tag.kind == TagKind::Open
+          && RAW_TEXT_ELEMENTS.contains(&&*tag.name) =>
+      {
+        match skip_raw_text(html, end, &tag.name) {
+          | close if close == html.len() => return start,
+          | close => close,
+        }
+      }
+      | Some((_, end)) => end,
+      | None if starts_tag(&html[start..]) => return start,
+      | None => match non_tag_end(html, start) {
+        | Some(end) => end,
+        | None if html[start + 1..].starts_with(['!', '?']) => return start,
+        | None => start + 1,
+      },
+    };
+  }
+  html.len()
+}
+
+/// Returns `true` if `html` begins with what can only be the start of a tag,
+/// which [`parse_tag`] fails to parse only when it's cut off.
+fn starts_tag(html: &str) -> bool {
+  let name = html.strip_prefix("</").or_else(|| html.strip_prefix('<'));
+  name.is_some_and(|name| name.starts_with(|c: char| c.is_ascii_alphabetic()))
+}
+
+/// Returns the offset of the closing tag for a raw text element.
+fn skip_raw_text(html: &str, start: usize, name: &str) -> usize {
+  let needle = format!("</{name}");
+  let haystack = html[start..].to_ascii_lowercase();
+  haystack.find(&needle).map_or(html.len(), |n| start + n)
+}
+
+/// The elements disabled by GFM's tag filter.
+const TAGFILTER_TAGS: &[&str] = &[
+  "title", "textarea", "style", "xmp", "iframe", "noembed", "noframes",
+  "script", "plaintext",
+];
+
+/// Applies GFM's tag filter to raw HTML like Comrak's `tagfilter` extension,
+/// escaping the `<` of every tag of the [`TAGFILTER_TAGS`] elements.
+pub fn tagfilter(html: &str) -> String {
+  let mut out = String::with_capacity(html.len());
+  let mut last = 0;
+  for (i, _) in html.match_indices('<') {
+    out.push_str(&html[last..i]);
+    out.push_str(if is_filtered_tag(&html[i + 1..]) {
+      "&lt;"
+    } else {
+      "<"
+    });
+    last = i + 1;
+  }
+  out.push_str(&html[last..]);
+  out
+}
+
+/// Returns `true` if `rest`, following a `<`, is a tag of one of the
+/// [`TAGFILTER_TAGS`] elements.
+fn is_filtered_tag(rest: &str) -> bool {
+  let rest = rest.strip_prefix('/').unwrap_or(rest).as_bytes();
+  TAGFILTER_TAGS.iter().any(|name| {
+    let n = name.len();
+    rest.len() > n
+      && rest[..n].eq_ignore_ascii_case(name.as_bytes())
+      && (rest[n].is_ascii_whitespace()
+        || rest[n] == b'>'
+        || rest[n..].starts_with(b"/>"))
+  })
+}
+
+/// Escapes a string for use inside a double-quoted HTML attribute.
+pub fn escape_attr(value: &str) -> String {
+  let mut out = String::with_capacity(value.len());
+  for c in value.chars() {
+    match c {
+      | '&' => out.push_str("&amp;"),
+      | '"' => out.push_str("&quot;"),
+      | '<' => out.push_str("&lt;"),
+      | '>' => out.push_str("&gt;"),
+      | _ => out.push(c),
+    }
+  }
+  out
+}
+
+/// Escapes a URL for use in an `href` attribute, exactly like Comrak does:
+/// characters that are unsafe in a URL are percent-encoded, with the
+/// exception of `&` and `'`, which are escaped as HTML entities.
+pub fn escape_href(value: &str) -> String {
+  const SAFE: &[u8] = b"-_.+!*(),%#@?=;:/,+$~";
+  let mut out = String::with_capacity(value.len());
+  for &b in value.as_bytes() {
+    match b {
+      | _ if b.is_ascii_alphanumeric() || SAFE.contains(&b) => {
+        out.push(b as char)
+      }
+      | b'&' => out.push_str("&amp;"),
+      | b'\'' => out.push_str("&#x27;"),
+      | _ => out.push_str(&format!("%{b:02X}")),
+    }
+  }
+  out
+}
+
+/// Reverses the escaping Comrak applies to text and attribute values.
+pub fn unescape(value: &str) -> String {
+  if !value.contains('&') {
+    return value.to_string();
+  }
+  value
+    .replace("&quot;", "\"")
+    .replace("&#x27;", "'")
+    .replace("&#39;", "'")
+    .replace("&lt;", "<")
+    .replace("&gt;", ">")
+    .replace("&amp;", "&")
+}
@@ -0,0 +1,85 @@
+//! Helpers for resolving image metadata through user-provided callbacks.
+
+use js_sys::Function;
+use serde::Deserialize;
+use serde_wasm_bindgen::from_value;
+use wasm_bindgen::JsValue;
+
+/// The intrinsic dimensions of an image, in CSS pixels.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+pub struct Dimensions {
+  pub width:  u32,
+  pub height: u32,
+}
+
+/// Invokes an `imageDimensions` callback for the given (unescaped) URL.
+///
+/// Returns `Ok(None)` if the callback returned `null` or `undefined`, and an
+/// error if it threw or returned a value that isn't a valid dimensions object.
+pub fn resolve_dimensions(
+  resolver: &Function,
+  url: &str,
+) -> Result<Option<Dimensions>, JsValue> {
+  let url_js = JsValue::from_str(url);
+  let result = resolver.call1(&JsValue::NULL, &url_js)?;
+  if result.is_null() || result.is_undefined() {
+    return Ok(None);
+  }
+  from_value(result).map(Some).map_err(|e| {
+    crate::map_err(format!("invalid image dimensions for {url:?}: {e}"))
+  })
+}
+
+/// The size given to an image with the `=WxH` suffix of the image size syntax,
+/// where either dimension may be omitted (`=100x` or `=x200`).
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct ImageSize {
+  pub width:  Option<u32>,
+  pub height: Option<u32>,
+}
+
+impl ImageSize {
+  /// Scales the size down to fit within the given maximum dimensions, keeping
+  /// its aspect ratio when both of its dimensions are known. A dimension that
+  /// isn't known is left out, and only the other one is clamped.
+  pub fn clamp(self, max_width: Option<u32>, max_height: Option<u32>) -> Self {
+    let (Some(width), Some(height)) = (self.width, self.height) else {
+      return Self {
+        width:  self.width.map(|w| max_width.map_or(w, |max| w.min(max))),
+        height: self.height.map(|h| max_height.map_or(h, |max| h.min(max))),
+      };
+    };
+    let ratio = |size: u32, max: Option<u32>| match max {
+      | Some(max) if size > max => f64::from(max) / f64::from(size),
+      | _ => 1.0,
+    };
+    let scale = ratio(width, max_width).min(ratio(height, max_height));
+    if scale >= 1.0 {
+      return self;
+    }
+    let scaled = |size: u32| ((f64::from(size) * scale).round() as u32).max(1);
+    Self {
+      width:  Some(scaled(width)),
+      height: Some(scaled(height)),
+    }
+  }
+}
+
+/// Splits the `=WxH` size suffix off an image URL, such as `img.png =100x200`,
+/// returning the URL without it. Returns `None` if the URL has no valid size.
+pub fn split_image_size(url: &str) -> Option<(&str, ImageSize)> {
+  let (url, size) = url.trim_end().rsplit_once([' ', '\t'])?;
+  let (width, height) = size.strip_prefix('=')?.split_once('x')?;
+  // `u32::from_str` also accepts a leading `+`, which isn't valid here
+  let parse = |n: &str| match n {
+    | "" => Some(None),
+    | _ if n.bytes().all(|b| b.is_ascii_digit()) => n.parse().ok().map(Some),
+    | _ => None,
+  };
+  let size = ImageSize { width: parse(width)?, height: parse(height)? };
+  let url = url.trim_end();
+  if url.is_empty() || (size.width.is_none() && size.height.is_none()) {
+    return None;
+  }
+  Some((url, size))
+}
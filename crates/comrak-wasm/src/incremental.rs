@@ -0,0 +1,177 @@
+//! Incremental reparsing of a document after an edit, for live editors.
+//!
+//! Rather than parsing the whole document again, only the top-level blocks
+//! touched by an edit are reparsed, along with the block on either side of
+//! them, since an edit can merge a block into its neighbour (e.g. by turning a
+//! paragraph into an item of the list before it). The new blocks replace the
+//! old ones in the previous tree, and the source positions of the blocks after
+//! them are shifted by the number of lines the edit added or removed.
+//!
+//! Whenever that might not give the same tree as a full parse, the whole
+//! document is parsed instead: when link reference or footnote definitions
+//! could be involved, as they apply to the whole document, or when the block
+//! after the edit doesn't parse the same way anymore (e.g. because the edit
+//! opened a code fence that now runs past it).
+
+use std::mem::discriminant;
+use std::ops::Range;
+
+use comrak::Arena;
+use comrak::Options as ComrakOptions;
+use comrak::nodes::AstNode;
+use wasm_bindgen::JsValue;
+
+use crate::options::Options;
+use crate::source::Source;
+
+/// An edit replacing the bytes of `range` in a document with `text`.
+#[derive(Debug)]
+pub struct Edit<'t> {
+  pub range: Range<usize>,
+  pub text:  &'t str,
+}
+
+impl Edit<'_> {
+  /// Returns `md` with the edit applied.
+  pub fn apply(&self, md: &str) -> String {
+    let mut out = String::with_capacity(md.len() + self.text.len());
+    out.push_str(&md[..self.range.start]);
+    out.push_str(self.text);
+    out.push_str(&md[self.range.end..]);
+    out
+  }
+
+  /// Moves an offset after the edit to where it ends up once it's applied.
+  fn shift(&self, offset: usize) -> usize {
+    offset - self.range.end + self.range.start + self.text.len()
+  }
+}
+
+/// Updates the tree under `root`, parsed from `md`, to match the document
+/// `edited` that results from applying `edit` to it, returning its new root.
+pub fn reparse<'a>(
+  arena: &'a Arena<'a>,
+  root: &'a AstNode<'a>,
+  md: &str,
+  edited: &str,
+  edit: &Edit,
+  options: &ComrakOptions,
+  extras: &Options,
+) -> Result<&'a AstNode<'a>, JsValue> {
+  match patch(arena, root, md, edited, edit, options, extras)? {
+    | Some(root) => Ok(root),
+    | None => crate::parse(arena, edited, options, extras),
+  }
+}
+
+/// Reparses the blocks around the edit into the tree, or returns `None` if
+/// the document has to be parsed in full.
+fn patch<'a>(
+  arena: &'a Arena<'a>,
+  root: &'a AstNode<'a>,
+  md: &str,
+  edited: &str,
+  edit: &Edit,
+  options: &ComrakOptions,
+  extras: &Options,
+) -> Result<Option<&'a AstNode<'a>>, JsValue> {
+  if options.extension.footnotes || md.contains("]:") || edited.contains("]:") {
+    return Ok(None);
+  }
+  let source = Source::new(md);
+  let blocks: Vec<_> = root.children().collect();
+  let ranges: Option<Vec<_>> = blocks
+    .iter()
+    .map(|block| source.range(block.data.borrow().sourcepos))
+    .collect();
+  let Some(ranges) = ranges.filter(|ranges| !ranges.is_empty()) else {
+    return Ok(None);
+  };
+  let range = &edit.range;
+  // the blocks entirely before and after the edit, except for the neighbour
+  // on either side, are kept as they are
+  let before = ranges.iter().take_while(|r| r.end < range.start).count();
+  let after = ranges
+    .iter()
+    .rev()
+    .take_while(|r| r.start > range.end)
+    .count();
+  let first = before.saturating_sub(1);
+  let last = ranges.len() - after.saturating_sub(1);
+  let start = match first {
+    | 0 => 0,
+    | _ => md[..ranges[first].start].rfind('\n').map_or(0, |i| i + 1),
+  };
+  // like footnotes and references above, front matter depends on where the
+  // fragment starts: a `---` line would open it at the start of a fragment,
+  // but not in the middle of the document
+  if options.extension.front_matter_delimiter.is_some() && start > 0 {
+    return Ok(None);
+  }
+  let end = match last {
+    | last if last == ranges.len() => edited.len(),
+    | last => edit.shift(ranges[last - 1].end),
+  };
+
+  let fragment = &edited[start..end];
+  let reparsed = crate::parse(arena, fragment, options, extras)?;
+  // the neighbour after the edit hasn't changed, so it must still be a block
+  // of the same kind, starting at the same place
+  if after > 0 {
+    let neighbour = blocks[last - 1];
+    let offset = edit.shift(ranges[last - 1].start) - start;
+    let same = reparsed.last_child().is_some_and(|block| {
+      let data = block.data.borrow();
+      let value = &neighbour.data.borrow().value;
+      discriminant(&data.value) == discriminant(value)
+        && Source::new(fragment).offset(data.sourcepos.start) == Some(offset)
+    });
+    if !same {
+      return Ok(None);
+    }
+  }
+
+  let lines = md[..start].matches('\n').count();
+  for node in reparsed.descendants().skip(1) {
+    shift_lines(node, lines as isize);
+  }
+  let added = edit.text.matches('\n').count() as isize
+    - md[range.clone()].matches('\n').count() as isize;
+  for block in &blocks[last..] {
+    for node in block.descendants() {
+      shift_lines(node, added);
+    }
+  }
+  for block in &blocks[first..last] {
+    block.detach();
+  }
+  let new_blocks: Vec<_> = reparsed.children().collect();
+  for block in new_blocks {
+    match blocks.get(last) {
+      | Some(next) => next.insert_before(block),
+      | None => root.append(block),
+    }
+  }
+  let mut data = root.data.borrow_mut();
+  let end = &mut data.sourcepos.end;
+  if last == blocks.len() {
+    *end = reparsed.data.borrow().sourcepos.end;
+    end.line += lines;
+  } else {
+    end.line = end.line.saturating_add_signed(added);
+  }
+  Ok(Some(root))
+}
+
+/// Moves the source position of a node by the given number of lines.
+fn shift_lines<'a>(node: &'a AstNode<'a>, lines: isize) {
+  let sourcepos = &mut node.data.borrow_mut().sourcepos;
+  for pos in [
+    &mut sourcepos.start,
+    &mut sourcepos.end,
+  ] {
+    if pos.line > 0 {
+      pos.line = pos.line.saturating_add_signed(lines);
+    }
+  }
+}
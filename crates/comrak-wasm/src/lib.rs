@@ -1,7 +1,11 @@
 extern crate alloc;
 
 use std::borrow::Cow;
+use std::cell::RefCell;
 use std::collections::HashMap;
+use std::collections::HashSet;
+use std::ptr::NonNull;
+use std::rc::Rc;
 use std::sync::Arc;
 
 use comrak::Arena;
@@ -9,16 +13,14 @@ use comrak::Options as ComrakOptions;
 use comrak::ResolvedReference;
 use comrak::adapters::HeadingAdapter as ComrakHeadingAdapter;
 use comrak::adapters::HeadingMeta;
+use comrak::adapters::SyntaxHighlighterAdapter as ComrakSyntaxHighlighterAdapter;
 use comrak::nodes::AstNode;
+use comrak::nodes::NodeValue;
 use comrak::nodes::Sourcepos;
 use comrak::options::BrokenLinkCallback as ComrakBrokenLinkCallback;
 use comrak::options::BrokenLinkReference;
 use comrak::options::Plugins;
 use comrak::options::URLRewriter as ComrakURLRewriter;
-#[cfg(feature = "syntect")]
-use comrak::plugins::syntect::SyntectAdapter;
-#[cfg(feature = "syntect")]
-use comrak::plugins::syntect::SyntectAdapterBuilder;
 use js_sys::Function;
 use js_sys::Object;
 use js_sys::TypeError;
@@ -37,16 +39,66 @@ use lol_alloc::FreeListAllocator as Lol;
 ))]
 use lol_alloc::LockedAllocator as Allocator;
 use serde::Deserialize;
+use serde::Serialize;
+use serde_wasm_bindgen::Deserializer;
+use serde_wasm_bindgen::Serializer;
 use serde_wasm_bindgen::from_value;
 use serde_wasm_bindgen::to_value;
 use wasm_bindgen::JsValue;
 use wasm_bindgen::prelude::*;
+
+use crate::diagnostics::Undefined;
+use crate::formatter::OutputFormat;
+use crate::formatter::UrlContext;
+use crate::formatter::split_info;
+use crate::from_html::HtmlToMarkdownOptions;
+use crate::headings::SlugifyAdapter;
+use crate::incremental::Edit;
+#[cfg(all(target_arch = "wasm32", feature = "alloc"))]
+use crate::memory::Counting;
+use crate::options::LineBreaks;
+use crate::options::LineEnding;
+use crate::options::Options;
+use crate::options::RawHtml;
+use crate::references::ReferenceMap;
+use crate::references::Resolutions;
+use crate::references::ResolvedLink;
+use crate::source::Source;
+use crate::tabs::Expanded;
+use crate::tabs::TAB_STOP;
+
+mod amp;
+mod attributes;
+mod diagnostics;
+mod elements;
+mod entities;
+mod formatter;
+mod from_html;
+mod headings;
+mod html;
+mod images;
+mod incremental;
+mod memory;
+mod options;
+mod pattern;
+mod references;
+mod sanitize;
+mod schema;
+mod source;
+mod standalone;
+mod tables;
+mod tabs;
+mod transform;
+mod whitespace;
+mod wikilinks;
+
 // conditional global allocator configuration
 #[cfg(all(target_arch = "wasm32", feature = "alloc"))]
 #[global_allocator]
 // SAFETY: This app is single threaded, so AssumeSingleThreaded is allowed.
 #[cfg_attr(feature = "threading", allow(unused_unsafe))]
-static ALLOCATOR: Allocator<Lol> = unsafe { Allocator::new(Lol::new()) };
+static ALLOCATOR: Counting<Allocator<Lol>> =
+  Counting::new(unsafe { Allocator::new(Lol::new()) });
 
 #[wasm_bindgen(typescript_custom_section)]
 /// # IMPORTANT
@@ -68,7 +120,20 @@ import type {
   URLRewriterFunction,
 } from "../options.ts";
 import type { AST, Sourcepos } from "../nodes.ts";
+import type { OutputFormat } from "../convert.ts";
+import type { ElementChild } from "../elements.ts";
 import type { HeadingMeta } from "../adapters.ts";
+import type { FrontMatterSplit } from "../front_matter.ts";
+import type { PaginateOptions } from "../paginate.ts";
+import type {
+  ByteRange,
+  Diagnostic,
+  ExtractedCodeBlock,
+  MapNodesCallback,
+  ResolvedLink,
+} from "../parse.ts";
+import type { Feature, VersionInfo } from "../version.ts";
+import type { MemoryUsage } from "../memory.ts";
 
 /**
  * An optional type that can either be of type `T`, or `null` or `undefined`.
@@ -84,12 +149,60 @@ export type Option<T> = T | null | undefined;
 
 #[cfg(feature = "syntect")]
 mod syntax_adapter {
-  use ::core::ops::Deref;
-  use ::core::ops::DerefMut;
-  use comrak::plugins::syntect::SyntectAdapter;
-  use comrak::plugins::syntect::SyntectAdapterBuilder;
+  use std::fmt;
+  use std::io::Cursor;
+  use std::sync::Mutex;
+  use std::sync::MutexGuard;
+  use std::sync::PoisonError;
+
+  use syntect::easy::HighlightLines;
+  use syntect::highlighting::Color;
+  use syntect::highlighting::Theme;
+  use syntect::highlighting::ThemeSet;
+  use syntect::html::ClassStyle;
+  use syntect::html::ClassedHTMLGenerator;
+  use syntect::html::IncludeBackground;
+  use syntect::html::append_highlighted_html_for_styled_line;
+  use syntect::html::css_for_theme_with_class_style;
+  use syntect::parsing::SyntaxDefinition;
+  use syntect::parsing::SyntaxReference;
+  use syntect::parsing::SyntaxSet;
+  use syntect::util::LinesWithEndings;
 
   use super::*;
+  use crate::html::escape_attr;
+
+  /// Syntect's default syntaxes and themes are expensive to load, so they are
+  /// loaded once on first use. Each adapter shares the cached syntaxes, only
+  /// copying them when a syntax is added to it, and clones the one theme it
+  /// uses. The caches are emptied by `release_memory`, and reloaded the next
+  /// time they're needed.
+  static SYNTAX_SET: Cache<SyntaxSet> = Mutex::new(None);
+  static THEME_SET: Cache<ThemeSet> = Mutex::new(None);
+
+  type Cache<T> = Mutex<Option<Arc<T>>>;
+
+  fn lock<T>(cache: &Cache<T>) -> MutexGuard<'_, Option<Arc<T>>> {
+    cache.lock().unwrap_or_else(PoisonError::into_inner)
+  }
+
+  fn cached<T>(cache: &Cache<T>, load: fn() -> T) -> Arc<T> {
+    Arc::clone(lock(cache).get_or_insert_with(|| Arc::new(load())))
+  }
+
+  fn default_syntaxes() -> Arc<SyntaxSet> {
+    cached(&SYNTAX_SET, SyntaxSet::load_defaults_newlines)
+  }
+
+  fn default_themes() -> Arc<ThemeSet> {
+    cached(&THEME_SET, ThemeSet::load_defaults)
+  }
+
+  /// Empties the caches of Syntect's default syntaxes and themes.
+  pub fn clear_caches() {
+    lock(&SYNTAX_SET).take();
+    lock(&THEME_SET).take();
+  }
 
   /// A syntax highlighter adapter that uses Syntect for code block highlighting.
   ///
@@ -112,37 +225,221 @@ mod syntax_adapter {
   /// ```
   #[derive(Debug)]
   #[wasm_bindgen]
-  pub struct SyntaxHighlighterAdapter(SyntectAdapter);
+  pub struct SyntaxHighlighterAdapter {
+    theme:      Option<String>,
+    theme_set:  ThemeSet,
+    syntax_set: Arc<SyntaxSet>,
+  }
 
   #[wasm_bindgen]
   impl SyntaxHighlighterAdapter {
     /// Creates a new `SyntaxHighlighterAdapter` using the specified theme.
     #[wasm_bindgen(constructor)]
     pub fn new(theme: &str) -> Self {
-      let adapter = SyntectAdapter::new_js(theme);
-      SyntaxHighlighterAdapter(adapter)
+      // only the selected theme is needed, which saves cloning the others
+      let mut theme_set = ThemeSet::new();
+      if let Some(t) = default_themes().themes.get(theme) {
+        theme_set.themes.insert(theme.to_string(), t.clone());
+      }
+      Self::with_themes(Some(theme.to_string()), theme_set)
+    }
+
+    /// Creates a new `SyntaxHighlighterAdapter` from the source of a custom
+    /// TextMate theme (`.tmTheme`), rather than one of the bundled themes.
+    ///
+    /// Throws a `TypeError` if the theme source cannot be parsed.
+    pub fn from_theme_source(
+      #[wasm_bindgen(js_name = "themeXml")] theme_xml: &str,
+    ) -> Result<SyntaxHighlighterAdapter, JsValue> {
+      let mut reader = Cursor::new(theme_xml.as_bytes());
+      let theme = ThemeSet::load_from_reader(&mut reader).map_err(map_err)?;
+      let name = theme.name.clone().unwrap_or_else(|| "custom".to_string());
+      let mut theme_set = ThemeSet::new();
+      theme_set.themes.insert(name.clone(), theme);
+      Ok(Self::with_themes(Some(name), theme_set))
+    }
+
+    /// Creates a new `SyntaxHighlighterAdapter` that emits CSS classes (e.g.
+    /// `<span class="source rust">`) rather than inline `style` attributes.
+    /// The matching stylesheet for a theme can be generated with the
+    /// `css_for_theme` function.
+    pub fn classed() -> SyntaxHighlighterAdapter {
+      Self::with_themes(None, ThemeSet::new())
+    }
+
+    /// Registers a custom language from the source of a Sublime Text syntax
+    /// definition (`.sublime-syntax`), extending the set of languages that
+    /// can be highlighted. Code blocks are matched to the new language by its
+    /// name or any of its `file_extensions`.
+    ///
+    /// Throws a `TypeError` if the syntax definition cannot be parsed.
+    pub fn add_syntax(&mut self, source: &str) -> Result<(), JsValue> {
+      let syntax =
+        SyntaxDefinition::load_from_str(source, true, None).map_err(map_err)?;
+      // the syntaxes may be shared with other adapters, so they're copied
+      let mut builder = SyntaxSet::clone(&self.syntax_set).into_builder();
+      builder.add(syntax);
+      self.syntax_set = Arc::new(builder.build());
+      Ok(())
     }
   }
 
-  impl<'p> From<SyntaxHighlighterAdapter>
-    for &'p dyn ComrakSyntaxHighlighterAdapter
-  {
-    fn from(adapter: SyntaxHighlighterAdapter) -> Self {
-      Box::leak(Box::new(adapter.0)) as &'p dyn ComrakSyntaxHighlighterAdapter
+  impl SyntaxHighlighterAdapter {
+    fn with_themes(theme: Option<String>, theme_set: ThemeSet) -> Self {
+      let syntax_set = default_syntaxes();
+      SyntaxHighlighterAdapter { theme, theme_set, syntax_set }
+    }
+
+    fn theme(&self) -> Option<&Theme> {
+      self
+        .theme
+        .as_ref()
+        .and_then(|t| self.theme_set.themes.get(t))
+    }
+
+    /// Highlights `code` the same way as Comrak's `SyntectAdapter`: with the
+    /// inline styles of the theme, or with classes when there is none.
+    fn highlight(
+      &self,
+      code: &str,
+      syntax: &SyntaxReference,
+    ) -> Result<String, syntect::Error> {
+      let Some(theme) = self.theme() else {
+        let mut generator = ClassedHTMLGenerator::new_with_class_style(
+          syntax,
+          &self.syntax_set,
+          ClassStyle::Spaced,
+        );
+        for line in LinesWithEndings::from(code) {
+          generator.parse_html_for_line_which_includes_newline(line)?;
+        }
+        return Ok(generator.finalize());
+      };
+      let mut highlighter = HighlightLines::new(syntax, theme);
+      let background = theme.settings.background.unwrap_or(Color::WHITE);
+      let mut out = String::new();
+      for line in LinesWithEndings::from(code) {
+        let regions = highlighter.highlight_line(line, &self.syntax_set)?;
+        append_highlighted_html_for_styled_line(
+          &regions,
+          IncludeBackground::IfDifferent(background),
+          &mut out,
+        )?;
+      }
+      Ok(out)
+    }
+
+    /// Switches the adapter to class-based output, as `classed` creates, for
+    /// formats that forbid inline styles.
+    pub(crate) fn into_classed(self) -> Self {
+      Self { theme: None, ..self }
     }
   }
 
-  impl Deref for SyntaxHighlighterAdapter {
-    type Target = SyntectAdapter;
+  /// Returns the names of all themes bundled with the Syntect highlighter,
+  /// such as `base16-ocean.dark` and `InspiredGitHub`, in alphabetical order.
+  /// Any of these can be passed to the `SyntaxHighlighterAdapter` constructor.
+  #[wasm_bindgen]
+  pub fn available_themes() -> Vec<String> {
+    default_themes().themes.keys().cloned().collect()
+  }
+
+  /// Returns the language tokens recognized by the Syntect highlighter in the
+  /// info string of a code block, such as `rs` and `py`, in alphabetical
+  /// order. Languages added with `SyntaxHighlighterAdapter.add_syntax` are not
+  /// included.
+  #[wasm_bindgen]
+  pub fn available_syntaxes() -> Vec<String> {
+    let mut tokens: Vec<String> = default_syntaxes()
+      .syntaxes()
+      .iter()
+      .flat_map(|syntax| syntax.file_extensions.iter().cloned())
+      .collect();
+    tokens.sort();
+    tokens.dedup();
+    tokens
+  }
+
+  /// Returns the stylesheet for one of the bundled themes, for use with the
+  /// class-based output of `SyntaxHighlighterAdapter.classed()`.
+  ///
+  /// Throws a `TypeError` if the theme does not exist.
+  #[wasm_bindgen]
+  pub fn css_for_theme(theme: &str) -> Result<String, JsValue> {
+    let themes = default_themes();
+    let theme = themes
+      .themes
+      .get(theme)
+      .ok_or_else(|| map_err(format!("unknown theme: {theme}")))?;
+    css_for_theme_with_class_style(theme, ClassStyle::Spaced).map_err(map_err)
+  }
+
+  // implemented here rather than with Comrak's `SyntectAdapter`, which owns
+  // its syntaxes, so that they don't have to be copied for every adapter
+  impl ComrakSyntaxHighlighterAdapter for SyntaxHighlighterAdapter {
+    fn write_highlighted(
+      &self,
+      out: &mut dyn fmt::Write,
+      lang: Option<&str>,
+      code: &str,
+    ) -> fmt::Result {
+      let set = &self.syntax_set;
+      let lang = lang.filter(|l| !l.is_empty()).unwrap_or("Plain Text");
+      let syntax = set
+        .find_syntax_by_token(lang)
+        .or_else(|| set.find_syntax_by_first_line(code))
+        .unwrap_or_else(|| set.find_syntax_plain_text());
+      match self.highlight(code, syntax) {
+        | Ok(html) => out.write_str(&html),
+        | Err(_) => out.write_str(&escape_attr(code)),
+      }
+    }
+
+    fn write_pre_tag<'s>(
+      &self,
+      out: &mut dyn fmt::Write,
+      mut attrs: HashMap<&'static str, Cow<'s, str>>,
+    ) -> fmt::Result {
+      let Some(theme) = self.theme() else {
+        return out.write_str("<pre class=\"syntax-highlighting\">");
+      };
+      let c = theme.settings.background.unwrap_or(Color::WHITE);
+      let background =
+        format!("background-color:#{:02x}{:02x}{:02x};", c.r, c.g, c.b);
+      attrs
+        .entry("style")
+        .or_default()
+        .to_mut()
+        .insert_str(0, &background);
+      write_opening_tag(out, "pre", attrs)
+    }
+
+    fn write_code_tag<'s>(
+      &self,
+      out: &mut dyn fmt::Write,
+      attrs: HashMap<&'static str, Cow<'s, str>>,
+    ) -> fmt::Result {
+      write_opening_tag(out, "code", attrs)
+    }
+  }
 
-    fn deref(&self) -> &Self::Target {
-      &self.0
+  fn write_opening_tag(
+    out: &mut dyn fmt::Write,
+    tag: &str,
+    attrs: HashMap<&'static str, Cow<'_, str>>,
+  ) -> fmt::Result {
+    write!(out, "<{tag}")?;
+    for (name, value) in attrs {
+      write!(out, " {name}=\"{}\"", escape_attr(&value))?;
     }
+    out.write_str(">")
   }
 
-  impl DerefMut for SyntaxHighlighterAdapter {
-    fn deref_mut(&mut self) -> &mut Self::Target {
-      &mut self.0
+  impl<'p> From<SyntaxHighlighterAdapter>
+    for &'p dyn ComrakSyntaxHighlighterAdapter
+  {
+    fn from(adapter: SyntaxHighlighterAdapter) -> Self {
+      Box::leak(Box::new(adapter)) as &'p dyn ComrakSyntaxHighlighterAdapter
     }
   }
 }
@@ -179,7 +476,7 @@ mod syntax_adapter {
     #[wasm_bindgen(constructor)]
     pub fn new(
       #[wasm_bindgen(
-        unchecked_param_type = r#"(code: string, lang?: string | null) => string"#
+        unchecked_param_type = r#"(code: string, lang?: string | null, meta?: string | null) => string"#
       )]
       highlight: Function,
       #[wasm_bindgen(
@@ -207,9 +504,13 @@ mod syntax_adapter {
     ) -> std::fmt::Result {
       let lang_js = lang.map(|s| JsValue::from_str(s)).unwrap_or(JsValue::NULL);
       let code_js = JsValue::from_str(code);
+      let meta_js = formatter::code_block_meta()
+        .map(|s| JsValue::from_str(&s))
+        .unwrap_or(JsValue::NULL);
       // we switch the two arguments around to allow easier binding from JS
       // when highlighting codeblocks without any language metadata specified
-      let result = self.highlight.call2(&JsValue::NULL, &code_js, &lang_js);
+      let this = JsValue::NULL;
+      let result = self.highlight.call3(&this, &code_js, &lang_js, &meta_js);
       if let Ok(js) = result {
         if let Some(s) = js.as_string() {
           return out.write_str(&s);
@@ -259,6 +560,12 @@ mod syntax_adapter {
 }
 
 pub use syntax_adapter::SyntaxHighlighterAdapter;
+#[cfg(feature = "syntect")]
+pub use syntax_adapter::available_syntaxes;
+#[cfg(feature = "syntect")]
+pub use syntax_adapter::available_themes;
+#[cfg(feature = "syntect")]
+pub use syntax_adapter::css_for_theme;
 
 /// The `HeadingAdapter` API allows you to customize how headings are rendered
 /// by Comrak (`h1`, `h2`, ...) via custom `enter` and `exit` methods.
@@ -366,6 +673,9 @@ impl<'p> From<HeadingAdapter> for &'p dyn ComrakHeadingAdapter {
 #[derive(Default, Debug, Clone)]
 pub struct BrokenLinkCallback {
   resolve: Function,
+  /// The first exception thrown by `resolve`, shared between all clones of
+  /// this callback so it can be rethrown once parsing has finished.
+  error:   Rc<RefCell<Option<JsValue>>>,
 }
 
 unsafe impl Send for BrokenLinkCallback {}
@@ -378,7 +688,7 @@ impl BrokenLinkCallback {
     #[wasm_bindgen(unchecked_param_type = "BrokenLinkCallbackFunction")]
     resolve: Function,
   ) -> Self {
-    Self { resolve }
+    Self { resolve, error: Rc::default() }
   }
 
   #[wasm_bindgen(getter = resolve, unchecked_return_type = "BrokenLinkCallbackFunction")]
@@ -403,6 +713,17 @@ impl BrokenLinkCallback {
   }
 }
 
+impl BrokenLinkCallback {
+  /// Rethrows the first exception raised by the callback during parsing, if
+  /// any, clearing it in the process.
+  fn rethrow(&self) -> Result<(), JsValue> {
+    match self.error.borrow_mut().take() {
+      | Some(e) => Err(e),
+      | None => Ok(()),
+    }
+  }
+}
+
 impl ComrakBrokenLinkCallback for BrokenLinkCallback {
   fn resolve(
     &self,
@@ -414,20 +735,18 @@ impl ComrakBrokenLinkCallback for BrokenLinkCallback {
     if r#ref.is_null() || r#ref.is_undefined() {
       return None;
     }
-    let result = self.resolve.call1(&r#ref, &r#ref);
-    if let Ok(js) = result {
-      if js.is_undefined() || js.is_null() || !js.is_object() {
-        return None;
+    // once the callback has thrown, the render call is going to fail anyway
+    if self.error.borrow().is_some() {
+      return None;
+    }
+    match self.resolve.call1(&r#ref, &r#ref) {
+      | Ok(js) if js.is_object() => from_value(js).ok(),
+      | Ok(_) => None,
+      | Err(e) => {
+        self.error.borrow_mut().replace(e);
+        None
       }
-      let resolved: ResolvedReference = from_value(js)
-        .map_err(|_| JsValue::NULL)
-        .unwrap_or_else(|_| ResolvedReference {
-          url:   "".to_string(),
-          title: "".to_string(),
-        });
-      return Some(resolved);
     }
-    None
   }
 }
 
@@ -476,55 +795,102 @@ impl URLRewriter {
   }
 }
 
+impl URLRewriter {
+  /// Rewrites a URL, keeping it as-is if the rewriter throws or returns
+  /// anything but a string. The context of the link or image is passed along
+  /// when rendering, which one-argument rewriters are free to ignore.
+  pub(crate) fn rewrite(
+    &self,
+    url: &str,
+    context: Option<&UrlContext>,
+  ) -> String {
+    let result = match context.map(to_js_value) {
+      | Some(Ok(context)) => {
+        let url_js = JsValue::from_str(url);
+        self.rewriter.call2(&JsValue::NULL, &url_js, &context)
+      }
+      | Some(Err(e)) => Err(e),
+      | None => self.call(JsValue::NULL, url),
+    };
+    match result.map(|js| js.as_string()) {
+      | Ok(Some(s)) => s,
+      | _ => url.to_string(),
+    }
+  }
+}
+
 unsafe impl Send for URLRewriter {}
 unsafe impl Sync for URLRewriter {}
 
 impl ComrakURLRewriter for URLRewriter {
   fn to_html(&self, url: &str) -> String {
-    if let Ok(js) = self.call(JsValue::NULL, url) {
-      if js.is_undefined() || js.is_null() {
-        return url.to_string();
-      }
-      if let Some(s) = js.as_string() {
-        return s;
-      }
-    }
-    url.to_string()
+    self.rewrite(url, None)
   }
 }
 
 macro_rules! collect_options {
   (
     $options:ident,
+    $extras:ident,
     $broken_link_callback:expr,
     $image_url_rewriter:expr,
     $link_url_rewriter:expr $(,)?
-  ) => {
-    if let Some(cb) = $broken_link_callback {
+  ) => {{
+    let broken_link_callback =
+      $broken_link_callback.map(BrokenLinkCallback::new);
+    let references = &$extras.parse.reference_map;
+    if !references.is_empty() {
+      let fallback = broken_link_callback.clone();
       $options.parse.broken_link_callback =
-        Some(Arc::new(BrokenLinkCallback::new(cb)));
+        Some(Arc::new(ReferenceMap::new(references, fallback)));
+    } else if let Some(ref cb) = broken_link_callback {
+      $options.parse.broken_link_callback = Some(Arc::new(cb.clone()));
     }
-    if let Some(rw) = $image_url_rewriter {
-      $options.extension.image_url_rewriter =
-        Some(Arc::new(URLRewriter::new(rw)));
+    // the HTML formatter calls the rewriters itself, once per image or link
+    $extras.extension.image_url_rewriter =
+      $image_url_rewriter.map(URLRewriter::new);
+    $extras.extension.link_url_rewriter =
+      $link_url_rewriter.map(URLRewriter::new);
+    if let Some(line_breaks) = $extras.render.line_breaks {
+      $options.render.hardbreaks = line_breaks == LineBreaks::Hard;
     }
-    if let Some(rw) = $link_url_rewriter {
-      $options.extension.link_url_rewriter =
-        Some(Arc::new(URLRewriter::new(rw)));
+    if let Some(raw_html) = $extras.render.raw_html {
+      $options.render.escape = raw_html == RawHtml::Escape;
+      $options.render.unsafe_ = raw_html != RawHtml::Escape;
+      $options.extension.tagfilter = raw_html == RawHtml::Filter;
     }
-  };
+    // the raw HTML that isn't allowed through is escaped, not omitted
+    if !$extras.render.raw_html_allow_tags.is_empty()
+      && !$options.render.unsafe_
+    {
+      $options.render.escape = true;
+    }
+    if let Some(ref scope) = $extras.render.id_scope {
+      if let Some(ref mut prefix) = $options.extension.header_ids {
+        prefix.insert_str(0, scope);
+      }
+    }
+    broken_link_callback
+  }};
 }
 macro_rules! collect_plugins {
   (
     $plugins:ident,
+    $extras:ident,
     $codefence_syntax_highlighter:expr,
-    $heading_adapter:expr $(,)?
+    $heading_adapter:expr,
+    $slugify:expr $(,)?
   ) => {
-    if let Some(a) = $codefence_syntax_highlighter {
+    // the slugify adapter implements an extension option, not a plugin
+    let disabled = $extras.render.disable_plugins;
+    if let Some(a) = $codefence_syntax_highlighter.filter(|_| !disabled) {
       $plugins.render.codefence_syntax_highlighter = Some(a.into());
     }
-    if let Some(a) = $heading_adapter {
+    if let Some(a) = $heading_adapter.filter(|_| !disabled) {
       $plugins.render.heading_adapter = Some(a.into());
+    } else if let Some(ref a) = $slugify {
+      // an explicit heading adapter always takes precedence
+      $plugins.render.heading_adapter = Some(a);
     }
   };
 }
@@ -533,7 +899,7 @@ macro_rules! markdown_to_fn {
 
   (
     $(#[$meta:meta])*
-    $_:vis fn $id:ident -> $fn:ident;
+    $_:vis fn $id:ident -> $format:expr;
     $($($rest:tt)+)?
   ) => {
     $(#[$meta])*
@@ -553,24 +919,16 @@ macro_rules! markdown_to_fn {
       #[wasm_bindgen(unchecked_param_type = "Option<URLRewriterFunction>")]
       link_url_rewriter: Option<Function>,
     ) -> Result<String, JsValue> {
-      let mut options: ComrakOptions = unwrap_option_object(options)?;
-      collect_options!(
+      render_markdown(
+        md,
+        $format,
         options,
+        codefence_syntax_highlighter,
+        heading_adapter,
         broken_link_callback,
         image_url_rewriter,
         link_url_rewriter,
-      );
-      let mut plugins = Plugins::default();
-      collect_plugins!(
-        plugins,
-        codefence_syntax_highlighter,
-        heading_adapter,
-      );
-      let arena = Arena::new();
-      let ast = comrak::parse_document(&arena, md, &options);
-      let mut out = String::new();
-      comrak::$fn(ast, &options, &mut out, &plugins).map_err(map_err)?;
-      Ok(out)
+      )
     }
 
     $(markdown_to_fn! { $($rest)+ })?
@@ -581,7 +939,7 @@ macro_rules! format_fn {
 
   (
     $(#[$meta:meta])*
-    $_:vis fn $id:ident -> $fn:ident;
+    $_:vis fn $id:ident -> $fn:path;
     $($($rest:tt)+)?
   ) => {
     $(#[$meta])*
@@ -602,22 +960,33 @@ macro_rules! format_fn {
       #[wasm_bindgen(unchecked_param_type = "Option<URLRewriterFunction>")]
       link_url_rewriter: Option<Function>,
     ) -> Result<String, JsValue> {
+      let mut extras: Options = unwrap_option_object(options.clone())?;
       let mut options: ComrakOptions = unwrap_option_object(options)?;
       collect_options!(
         options,
+        extras,
         broken_link_callback,
         image_url_rewriter,
         link_url_rewriter,
       );
+      let slugify = SlugifyAdapter::from_options(&options, &extras);
       let mut plugins = Plugins::default();
       collect_plugins!(
         plugins,
+        extras,
         codefence_syntax_highlighter,
         heading_adapter,
+        slugify,
       );
       let mut out = String::new();
-      let root: &AstNode = from_value(ast.into()).map_err(map_err)?;
-      comrak::$fn(root, &options, &mut out, &plugins).map_err(map_err)?;
+      let root = ast_from_value(ast)?;
+      let arena = Arena::new();
+      transform::render(&arena, root, &options, &extras)?;
+      let result = $fn(root, &options, &mut out, &plugins, &extras);
+      if let Some(ref adapter) = slugify {
+        adapter.rethrow()?;
+      }
+      result?;
       Ok(out)
     }
 
@@ -629,6 +998,12 @@ fn map_err<T: ToString>(e: T) -> JsValue {
   TypeError::new(&e.to_string()).into()
 }
 
+/// Deserializes an options object, falling back to the default options when
+/// it is missing or isn't an object.
+///
+/// Errors are prefixed with the path to the offending field, as in
+/// `extension.frontMatterDelimiter: invalid type: ...`, to make typos and type
+/// mismatches easier to track down.
 fn unwrap_option_object<T: for<'de> Deserialize<'de> + Default>(
   obj: Option<Object>,
 ) -> Result<T, JsValue> {
@@ -636,13 +1011,75 @@ fn unwrap_option_object<T: for<'de> Deserialize<'de> + Default>(
     if o.is_undefined() || o.is_null() || !o.is_object() {
       Ok(T::default())
     } else {
-      from_value(o.into()).map_err(map_err)
+      let deserializer = Deserializer::from(JsValue::from(o));
+      serde_path_to_error::deserialize(deserializer).map_err(map_err)
     }
   } else {
     Ok(T::default())
   }
 }
 
+/// Parses a Markdown document with Comrak, then applies any crate-level
+/// transformations enabled in `extras` to the resulting AST.
+///
+/// Fails if the input is larger than the `max_input_bytes` parse option
+/// allows, before it's parsed, or if the AST has more nodes than `max_nodes`
+/// allows, before any further work is done with it. Comrak has no hook for
+/// counting nodes as it allocates them, so `max_nodes` can't stop a parse
+/// that is underway.
+fn parse<'a>(
+  arena: &'a Arena<'a>,
+  md: &str,
+  options: &ComrakOptions,
+  extras: &Options,
+) -> Result<&'a AstNode<'a>, JsValue> {
+  if let Some(max) = extras.parse.max_input_bytes
+    && md.len() > max
+  {
+    let msg = format!("document exceeds the maximum of {max} bytes");
+    return Err(map_err(msg));
+  }
+  let width = extras.parse.tab_width;
+  let expanded = (width != TAB_STOP).then(|| Expanded::new(md, width));
+  let text = expanded.as_ref().map_or(md, Expanded::text);
+  // the substitutions left out by `typography` are reverted after parsing
+  let mut smart;
+  let options = match extras.render.typography {
+    | Some(ref typography) => {
+      smart = options.clone();
+      smart.parse.smart = typography.any();
+      &smart
+    }
+    | None => options,
+  };
+  let root = comrak::parse_document(arena, text, options);
+  // stops counting as soon as the budget is exceeded
+  let exceeds = |&max: &usize| root.descendants().nth(max).is_some();
+  if let Some(max) = extras.parse.max_nodes.filter(exceeds) {
+    let msg = format!("document exceeds the maximum of {max} nodes");
+    return Err(map_err(msg));
+  }
+  transform::parse(arena, root, text, options, extras);
+  if let Some(ref expanded) = expanded {
+    expanded.restore(root);
+  }
+  if extras.parse.report_recoveries
+    && let Some(ref on_diagnostic) = extras.parse.on_diagnostic
+  {
+    for diagnostic in diagnostics::recoveries(root, md) {
+      on_diagnostic.call1(&JsValue::NULL, &to_js_value(&diagnostic)?)?;
+    }
+  }
+  Ok(root)
+}
+
+/// Serializes a value into a plain JS object, representing `None` as `null`
+/// rather than omitting it (the `serde_wasm_bindgen` default).
+fn to_js_value<T: Serialize + ?Sized>(value: &T) -> Result<JsValue, JsValue> {
+  value
+    .serialize(&Serializer::json_compatible())
+    .map_err(map_err)
+}
 
 /// Returns the version of Comrak used in this build, as a string.
 #[wasm_bindgen]
@@ -650,6 +1087,86 @@ pub fn version() -> String {
   comrak::version().to_string()
 }
 
+/// Releases the memory held by internal caches, such as the syntaxes and
+/// themes loaded for the `syntect` syntax highlighter, which are reloaded the
+/// next time they're needed.
+///
+/// The released memory is kept by the allocator for reuse, since WebAssembly
+/// linear memory can't shrink, so this doesn't reduce the memory reserved by
+/// the module. It does, however, stop the module from growing its memory any
+/// further until the released memory has been used up.
+#[wasm_bindgen]
+pub fn release_memory() {
+  #[cfg(feature = "syntect")]
+  syntax_adapter::clear_caches();
+}
+
+#[derive(Debug, Serialize)]
+struct MemoryUsage {
+  allocated: Option<usize>,
+  reserved:  usize,
+}
+
+/// Returns the number of bytes currently allocated by the module (`null`
+/// unless it was built with the `alloc` feature), along with the size of its
+/// linear memory, which includes free memory available for reuse.
+#[wasm_bindgen(unchecked_return_type = "MemoryUsage")]
+pub fn memory_usage() -> Result<JsValue, JsValue> {
+  #[cfg(all(target_arch = "wasm32", feature = "alloc"))]
+  let allocated = Some(ALLOCATOR.allocated());
+  #[cfg(not(all(target_arch = "wasm32", feature = "alloc")))]
+  let allocated = None;
+  let usage = MemoryUsage { allocated, reserved: memory::reserved() };
+  to_js_value(&usage)
+}
+
+/// The optional Cargo features of this crate that can be detected at runtime,
+/// and whether each is enabled in this build.
+const FEATURES: &[(&str, bool)] = &[
+  ("alloc", cfg!(feature = "alloc")),
+  ("shortcodes", cfg!(feature = "shortcodes")),
+  ("syntect", cfg!(feature = "syntect")),
+  ("threading", cfg!(feature = "threading")),
+];
+
+#[derive(Debug, Serialize)]
+struct VersionInfo {
+  comrak:   String,
+  #[serde(rename = "crate")]
+  krate:    String,
+  features: Vec<&'static str>,
+}
+
+/// Returns the versions of Comrak and of this crate used in this build, along
+/// with the names of the optional features it was built with, which helps to
+/// diagnose issues caused by mismatched builds.
+#[wasm_bindgen(unchecked_return_type = "VersionInfo")]
+pub fn version_info() -> Result<JsValue, JsValue> {
+  let features = FEATURES
+    .iter()
+    .filter(|&&(_, enabled)| enabled)
+    .map(|&(name, _)| name)
+    .collect();
+  let info = VersionInfo {
+    comrak: comrak::version().to_string(),
+    krate: env!("CARGO_PKG_VERSION").to_string(),
+    features,
+  };
+  to_js_value(&info)
+}
+
+/// Returns `true` if this build was compiled with the named optional feature,
+/// which is one of `"alloc"`, `"shortcodes"`, `"syntect"`, or `"threading"`.
+/// Unknown feature names return `false`.
+#[wasm_bindgen]
+pub fn has_feature(
+  #[wasm_bindgen(unchecked_param_type = "Feature")] name: &str,
+) -> bool {
+  FEATURES
+    .iter()
+    .any(|&(feature, enabled)| enabled && feature == name)
+}
+
 #[wasm_bindgen]
 pub fn default_options() -> Result<Object, JsValue> {
   let options = ComrakOptions::default();
@@ -674,7 +1191,16 @@ pub fn default_render_options() -> Result<Object, JsValue> {
   Ok(to_value(&options).map_err(map_err)?.into())
 }
 
+/// Deserializes an AST produced by `parse_document` (or `map_nodes`), making
+/// it clear in the error message that the AST itself was invalid.
+fn ast_from_value<'a>(ast: Object) -> Result<&'a AstNode<'a>, JsValue> {
+  from_value(ast.into()).map_err(|e| map_err(format!("invalid AST: {e}")))
+}
+
 /// Parses the given markdown text and returns the AST as a structured object.
+///
+/// Links resolved by the broken link callback look the same as any other in
+/// the AST; use `resolved_references` to find out which ones they are.
 #[wasm_bindgen(unchecked_return_type = "AST")]
 pub fn parse_document(
   md: &str,
@@ -688,48 +1214,1095 @@ pub fn parse_document(
   #[wasm_bindgen(unchecked_param_type = "Option<URLRewriterFunction>")]
   link_url_rewriter: Option<Function>,
 ) -> Result<JsValue, JsValue> {
+  let mut extras: Options = unwrap_option_object(options.clone())?;
   let mut options: ComrakOptions = unwrap_option_object(options)?;
-  collect_options!(
+  let broken_link_callback = collect_options!(
     options,
+    extras,
     broken_link_callback,
     image_url_rewriter,
     link_url_rewriter,
   );
   let arena = Arena::new();
-  let root = comrak::parse_document(&arena, md, &options);
+  let root = parse(&arena, md, &options, &extras)?;
+  if let Some(ref cb) = broken_link_callback {
+    cb.rethrow()?;
+  }
   to_value(&root).map_err(map_err)
 }
 
-markdown_to_fn! {
-  /// Render Markdown to HTML using plugins.
-  ////
-  /// See the documentation of the crate root for an example.
-  pub fn markdown_to_html -> format_html_with_plugins;
+/// Applies an edit to a Markdown document, replacing the UTF-8 byte range
+/// `edit_start..edit_end` of `md` with `new_text`, and returns the AST of the
+/// edited document. Rather than parsing it from scratch, `previous_ast` (the
+/// AST of `md`, parsed with the same options) is updated by only reparsing the
+/// top-level blocks around the edit, whenever that gives the same result.
+///
+/// Throws a `TypeError` if the range doesn't fall on character boundaries
+/// within `md`.
+#[wasm_bindgen(unchecked_return_type = "AST")]
+#[allow(clippy::too_many_arguments)]
+pub fn reparse_range(
+  md: &str,
+  #[wasm_bindgen(unchecked_param_type = "AST")] previous_ast: Object,
+  edit_start: usize,
+  edit_end: usize,
+  new_text: &str,
+  #[wasm_bindgen(unchecked_param_type = "Option<Options>")] options: Option<
+    Object,
+  >,
+  #[wasm_bindgen(unchecked_param_type = "Option<BrokenLinkCallbackFunction>")]
+  broken_link_callback: Option<Function>,
+  #[wasm_bindgen(unchecked_param_type = "Option<URLRewriterFunction>")]
+  image_url_rewriter: Option<Function>,
+  #[wasm_bindgen(unchecked_param_type = "Option<URLRewriterFunction>")]
+  link_url_rewriter: Option<Function>,
+) -> Result<JsValue, JsValue> {
+  if edit_start > edit_end
+    || !md.is_char_boundary(edit_start)
+    || !md.is_char_boundary(edit_end)
+  {
+    let msg = format!("invalid edit range: {edit_start}..{edit_end}");
+    return Err(map_err(msg));
+  }
+  let extras: Options = unwrap_option_object(options.clone())?;
+  let mut options: ComrakOptions = unwrap_option_object(options)?;
+  let broken_link_callback = collect_options!(
+    options,
+    extras,
+    broken_link_callback,
+    image_url_rewriter,
+    link_url_rewriter,
+  );
+  let edit = Edit { range: edit_start..edit_end, text: new_text };
+  let edited = edit.apply(md);
+  let arena = Arena::new();
+  let root = ast_from_value(previous_ast)?;
+  let root =
+    incremental::reparse(&arena, root, md, &edited, &edit, &options, &extras)?;
+  if let Some(ref cb) = broken_link_callback {
+    cb.rethrow()?;
+  }
+  to_value(&root).map_err(map_err)
+}
 
-  /// Render Markdown to XML using plugins.
-  ///
-  /// See the documentation of the crate root for an example.
-  pub fn markdown_to_xml -> format_xml_with_plugins;
+/// Serializes an AST produced by `parse_document` to JSON, in a stable schema
+/// that doesn't change with the version of Comrak, for persisting the AST.
+/// Read it back with `ast_from_json`.
+#[wasm_bindgen]
+pub fn ast_to_json(
+  #[wasm_bindgen(unchecked_param_type = "AST")] ast: Object,
+) -> Result<String, JsValue> {
+  let root = ast_from_value(ast)?;
+  let stable = to_js_value(&schema::to_stable(root))?;
+  Ok(js_sys::JSON::stringify(&stable)?.into())
+}
 
-  /// Render Markdown to CommonMark.
-  ////
-  /// See the documentation of the crate root for an example.
-  pub fn markdown_to_commonmark -> format_commonmark_with_plugins;
+/// Deserializes JSON written by `ast_to_json` back into an AST, migrating it
+/// from an older version of the schema if needed.
+///
+/// Throws a `TypeError` if the JSON is invalid, or was written with a newer
+/// version of the schema than this build supports.
+#[wasm_bindgen(unchecked_return_type = "AST")]
+pub fn ast_from_json(json: &str) -> Result<JsValue, JsValue> {
+  let value = js_sys::JSON::parse(json)?;
+  let version: schema::Version = from_value(value.clone())
+    .map_err(|e| map_err(format!("invalid AST: {e}")))?;
+  let arena = Arena::new();
+  let root = match version.schema_version {
+    // an AST returned by `parse_document`, persisted as-is
+    | 0 => ast_from_value(value.unchecked_into())?,
+    // a tree holding the values of Comrak's nodes as it serializes them
+    | 1 => {
+      let v1: schema::v1::Ast =
+        from_value(value).map_err(|e| map_err(format!("invalid AST: {e}")))?;
+      schema::v1::from_stable(&arena, v1.root)
+    }
+    | schema::SCHEMA_VERSION => {
+      let stable: schema::StableAst =
+        from_value(value).map_err(|e| map_err(format!("invalid AST: {e}")))?;
+      schema::from_stable(&arena, stable.root)
+    }
+    | v => {
+      return Err(map_err(format!(
+        "unsupported AST schema version {v} (the newest supported is {})",
+        schema::SCHEMA_VERSION
+      )));
+    }
+  };
+  to_value(&root).map_err(map_err)
 }
 
-format_fn! {
-  /// Format an AST to HTML using plugins.
-  ///
-  /// See the documentation of the crate root for an example.
-  pub fn format_html -> format_html_with_plugins;
+/// Returns the unique fragments referenced by in-document links (such as
+/// `[usage](#usage)`), without the leading `#`, in the order they first
+/// appear. Comparing these against the heading IDs of the rendered document
+/// can be used to detect broken anchor links.
+#[wasm_bindgen]
+pub fn anchor_targets(
+  md: &str,
+  #[wasm_bindgen(unchecked_param_type = "Option<Options>")] options: Option<
+    Object,
+  >,
+  #[wasm_bindgen(unchecked_param_type = "Option<BrokenLinkCallbackFunction>")]
+  broken_link_callback: Option<Function>,
+  #[wasm_bindgen(unchecked_param_type = "Option<URLRewriterFunction>")]
+  image_url_rewriter: Option<Function>,
+  #[wasm_bindgen(unchecked_param_type = "Option<URLRewriterFunction>")]
+  link_url_rewriter: Option<Function>,
+) -> Result<Vec<String>, JsValue> {
+  let mut extras: Options = unwrap_option_object(options.clone())?;
+  let mut options: ComrakOptions = unwrap_option_object(options)?;
+  let broken_link_callback = collect_options!(
+    options,
+    extras,
+    broken_link_callback,
+    image_url_rewriter,
+    link_url_rewriter,
+  );
+  let arena = Arena::new();
+  let root = parse(&arena, md, &options, &extras)?;
+  if let Some(ref cb) = broken_link_callback {
+    cb.rethrow()?;
+  }
+  let mut seen = HashSet::new();
+  let targets = transform::link_fragments(root)
+    .filter(|fragment| seen.insert(fragment.clone()))
+    .collect();
+  Ok(targets)
+}
 
-  /// Format an AST to XML using plugins.
-  ///
-  /// See the documentation of the crate root for an example.
-  pub fn format_xml -> format_xml_with_plugins;
+/// Returns the links and images in a Markdown document whose references were
+/// resolved by the broken link callback (or the `reference_map` parse option)
+/// rather than by a definition in the document, in document order. Each one
+/// includes the label it was resolved from and its source position, which
+/// matches that of the corresponding node in the AST from `parse_document`.
+#[wasm_bindgen(unchecked_return_type = "ResolvedLink[]")]
+pub fn resolved_references(
+  md: &str,
+  #[wasm_bindgen(unchecked_param_type = "Option<Options>")] options: Option<
+    Object,
+  >,
+  #[wasm_bindgen(unchecked_param_type = "Option<BrokenLinkCallbackFunction>")]
+  broken_link_callback: Option<Function>,
+  #[wasm_bindgen(unchecked_param_type = "Option<URLRewriterFunction>")]
+  image_url_rewriter: Option<Function>,
+  #[wasm_bindgen(unchecked_param_type = "Option<URLRewriterFunction>")]
+  link_url_rewriter: Option<Function>,
+) -> Result<JsValue, JsValue> {
+  let mut extras: Options = unwrap_option_object(options.clone())?;
+  let mut options: ComrakOptions = unwrap_option_object(options)?;
+  let broken_link_callback = collect_options!(
+    options,
+    extras,
+    broken_link_callback,
+    image_url_rewriter,
+    link_url_rewriter,
+  );
+  let Some(callback) = options.parse.broken_link_callback.take() else {
+    return to_js_value(&[] as &[ResolvedLink]);
+  };
+  let resolutions = Arc::new(Resolutions::new(callback));
+  let callback: Arc<dyn ComrakBrokenLinkCallback + '_> = resolutions.clone();
+  options.parse.broken_link_callback = Some(callback);
+  let arena = Arena::new();
+  let root = parse(&arena, md, &options, &extras)?;
+  if let Some(ref cb) = broken_link_callback {
+    cb.rethrow()?;
+  }
+  to_js_value(&resolutions.restore(root))
+}
 
-  /// Format an AST to CommonMark using plugins.
-  ///
+/// Reports the structural problems in a Markdown document that Comrak recovers
+/// from silently while parsing: table rows with more or fewer cells than their
+/// header, code fences that are never closed, and full or collapsed reference
+/// links to labels that aren't defined (nor resolved by the broken link
+/// callback). The diagnostics are returned in order of their source position.
+#[wasm_bindgen(unchecked_return_type = "Diagnostic[]")]
+pub fn diagnostics(
+  md: &str,
+  #[wasm_bindgen(unchecked_param_type = "Option<Options>")] options: Option<
+    Object,
+  >,
+  #[wasm_bindgen(unchecked_param_type = "Option<BrokenLinkCallbackFunction>")]
+  broken_link_callback: Option<Function>,
+  #[wasm_bindgen(unchecked_param_type = "Option<URLRewriterFunction>")]
+  image_url_rewriter: Option<Function>,
+  #[wasm_bindgen(unchecked_param_type = "Option<URLRewriterFunction>")]
+  link_url_rewriter: Option<Function>,
+) -> Result<JsValue, JsValue> {
+  let mut extras: Options = unwrap_option_object(options.clone())?;
+  let mut options: ComrakOptions = unwrap_option_object(options)?;
+  let broken_link_callback = collect_options!(
+    options,
+    extras,
+    broken_link_callback,
+    image_url_rewriter,
+    link_url_rewriter,
+  );
+  let undefined =
+    Arc::new(Undefined::new(options.parse.broken_link_callback.take()));
+  let callback: Arc<dyn ComrakBrokenLinkCallback + '_> = undefined.clone();
+  options.parse.broken_link_callback = Some(callback);
+  let arena = Arena::new();
+  let root = parse(&arena, md, &options, &extras)?;
+  if let Some(ref cb) = broken_link_callback {
+    cb.rethrow()?;
+  }
+  to_js_value(&diagnostics::diagnose(root, md, &undefined))
+}
+
+/// Returns every code block in a Markdown document, fenced or indented, in
+/// document order. Each one includes its language (the first word of its info
+/// string) and the rest of its info string as its meta, both `null` when they
+/// are empty (as they always are for indented code blocks), along with its
+/// code and source position.
+#[wasm_bindgen(unchecked_return_type = "ExtractedCodeBlock[]")]
+pub fn extract_code_blocks(
+  md: &str,
+  #[wasm_bindgen(unchecked_param_type = "Option<Options>")] options: Option<
+    Object,
+  >,
+  #[wasm_bindgen(unchecked_param_type = "Option<BrokenLinkCallbackFunction>")]
+  broken_link_callback: Option<Function>,
+  #[wasm_bindgen(unchecked_param_type = "Option<URLRewriterFunction>")]
+  image_url_rewriter: Option<Function>,
+  #[wasm_bindgen(unchecked_param_type = "Option<URLRewriterFunction>")]
+  link_url_rewriter: Option<Function>,
+) -> Result<JsValue, JsValue> {
+  let extras: Options = unwrap_option_object(options.clone())?;
+  let mut options: ComrakOptions = unwrap_option_object(options)?;
+  let broken_link_callback = collect_options!(
+    options,
+    extras,
+    broken_link_callback,
+    image_url_rewriter,
+    link_url_rewriter,
+  );
+  let arena = Arena::new();
+  let root = parse(&arena, md, &options, &extras)?;
+  if let Some(ref cb) = broken_link_callback {
+    cb.rethrow()?;
+  }
+  let non_empty = |s: &str| (!s.is_empty()).then(|| s.to_string());
+  let blocks: Vec<_> = root
+    .descendants()
+    .filter_map(|node| {
+      let data = node.data.borrow();
+      let NodeValue::CodeBlock(ref ncb) = data.value else {
+        return None;
+      };
+      let (language, meta) = split_info(&ncb.info);
+      Some(ExtractedCodeBlock {
+        language:  non_empty(language),
+        meta:      non_empty(meta),
+        code:      ncb.literal.clone(),
+        sourcepos: data.sourcepos,
+      })
+    })
+    .collect();
+  to_js_value(&blocks)
+}
+
+/// Visits every node of an AST in document order, calling `callback` with the
+/// value of each (such as `{ Text: "hi" }` or `{ Link: { url, title } }`), and
+/// returns the transformed AST.
+///
+/// The callback can return a replacement value for the node, `null` to delete
+/// the node along with its children, or `undefined` to leave it unchanged. The
+/// children of a node are visited after it, so they see any changes made to
+/// their parent, and the children of deleted nodes aren't visited at all.
+#[wasm_bindgen(unchecked_return_type = "AST")]
+pub fn map_nodes(
+  #[wasm_bindgen(unchecked_param_type = "AST")] ast: Object,
+  #[wasm_bindgen(unchecked_param_type = "MapNodesCallback")] callback: Function,
+) -> Result<JsValue, JsValue> {
+  let root = ast_from_value(ast)?;
+  map_children(root, &callback)?;
+  to_value(&root).map_err(map_err)
+}
+
+fn map_children<'a>(
+  node: &'a AstNode<'a>,
+  callback: &Function,
+) -> Result<(), JsValue> {
+  let mut next = node.first_child();
+  while let Some(child) = next {
+    next = child.next_sibling();
+    let value = to_value(&child.data.borrow().value).map_err(map_err)?;
+    let result = callback.call1(&JsValue::NULL, &value)?;
+    if result.is_null() {
+      child.detach();
+      continue;
+    }
+    if !result.is_undefined() {
+      child.data.borrow_mut().value = from_value(result).map_err(map_err)?;
+    }
+    map_children(child, callback)?;
+  }
+  Ok(())
+}
+
+/// Render Markdown to the given output format, which is one of `"html"`,
+/// `"xml"`, `"commonmark"`, or `"plaintext"`.
+///
+/// This is equivalent to calling the `markdown_to_*` function for the format,
+/// for callers that choose the format dynamically.
+#[wasm_bindgen]
+#[allow(clippy::too_many_arguments)]
+pub fn markdown_to(
+  md: &str,
+  #[wasm_bindgen(unchecked_param_type = "OutputFormat")] format: &str,
+  #[wasm_bindgen(unchecked_param_type = "Option<Options>")] options: Option<
+    Object,
+  >,
+  #[wasm_bindgen(unchecked_param_type = "Option<SyntaxHighlighterAdapter>")]
+  codefence_syntax_highlighter: Option<SyntaxHighlighterAdapter>,
+  #[wasm_bindgen(unchecked_param_type = "Option<HeadingAdapter>")]
+  heading_adapter: Option<HeadingAdapter>,
+  #[wasm_bindgen(unchecked_param_type = "Option<BrokenLinkCallbackFunction>")]
+  broken_link_callback: Option<Function>,
+  #[wasm_bindgen(unchecked_param_type = "Option<URLRewriterFunction>")]
+  image_url_rewriter: Option<Function>,
+  #[wasm_bindgen(unchecked_param_type = "Option<URLRewriterFunction>")]
+  link_url_rewriter: Option<Function>,
+) -> Result<String, JsValue> {
+  let Some(format) = OutputFormat::from_name(format) else {
+    return Err(map_err(format!("unknown output format: {format:?}")));
+  };
+  render_markdown(
+    md,
+    format,
+    options,
+    codefence_syntax_highlighter,
+    heading_adapter,
+    broken_link_callback,
+    image_url_rewriter,
+    link_url_rewriter,
+  )
+}
+
+/// Parses Markdown and renders it to the given format, applying all of the
+/// options, callbacks, and plugins. Shared by [`markdown_to`] and each of the
+/// `markdown_to_*` functions.
+#[allow(clippy::too_many_arguments)]
+fn render_markdown(
+  md: &str,
+  format: OutputFormat,
+  options: Option<Object>,
+  codefence_syntax_highlighter: Option<SyntaxHighlighterAdapter>,
+  heading_adapter: Option<HeadingAdapter>,
+  broken_link_callback: Option<Function>,
+  image_url_rewriter: Option<Function>,
+  link_url_rewriter: Option<Function>,
+) -> Result<String, JsValue> {
+  let handle = compile_options(
+    options,
+    codefence_syntax_highlighter,
+    heading_adapter,
+    broken_link_callback,
+    image_url_rewriter,
+    link_url_rewriter,
+  )?;
+  render_compiled(md, format, &handle)
+}
+
+/// Parses Markdown and renders it to the given format with options that were
+/// already compiled by [`compile_options`].
+fn render_compiled(
+  md: &str,
+  format: OutputFormat,
+  handle: &OptionsHandle,
+) -> Result<String, JsValue> {
+  let options = &handle.options;
+  // `preserve` line endings depend on the input, so they're resolved per call
+  let mut resolved;
+  let extras = if handle.extras.render.line_ending == LineEnding::Preserve {
+    resolved = handle.extras.clone();
+    resolved.render.line_ending = LineEnding::Preserve.resolve(md);
+    &resolved
+  } else {
+    &handle.extras
+  };
+  // the slugger keeps track of the IDs it has generated, so it's made anew
+  let slugify = SlugifyAdapter::from_options(options, extras);
+  let mut plugins = Plugins::default();
+  plugins.render.codefence_syntax_highlighter =
+    handle.codefence_syntax_highlighter;
+  // an explicit heading adapter always takes precedence
+  plugins.render.heading_adapter = match handle.heading_adapter {
+    | Some(adapter) => Some(adapter),
+    | None => slugify.as_ref().map(|a| a as &dyn ComrakHeadingAdapter),
+  };
+  let arena = Arena::new();
+  let ast = parse(&arena, md, options, extras)?;
+  if let Some(ref cb) = handle.broken_link_callback {
+    cb.rethrow()?;
+  }
+  transform::render(&arena, ast, options, extras)?;
+  let mut out = String::new();
+  let result = format.format(ast, options, &mut out, &plugins, extras);
+  if let Some(ref adapter) = slugify {
+    adapter.rethrow()?;
+  }
+  result?;
+  Ok(out)
+}
+
+/// A set of options, callbacks, and plugins compiled by [`compile_options`],
+/// which can be used to render any number of documents without deserializing
+/// the options on every call. Its memory is only reclaimed once `free()` is
+/// called from JS.
+#[wasm_bindgen]
+pub struct OptionsHandle {
+  options:                      ComrakOptions<'static>,
+  extras:                       Options,
+  broken_link_callback:         Option<BrokenLinkCallback>,
+  codefence_syntax_highlighter:
+    Option<&'static dyn ComrakSyntaxHighlighterAdapter>,
+  heading_adapter:              Option<&'static dyn ComrakHeadingAdapter>,
+}
+
+/// Deserializes and validates a set of options once, returning a handle that
+/// can be passed to `markdown_to_html_with` in place of the options, for hot
+/// loops that render many documents the same way.
+#[wasm_bindgen]
+pub fn compile_options(
+  #[wasm_bindgen(unchecked_param_type = "Option<Options>")] options: Option<
+    Object,
+  >,
+  #[wasm_bindgen(unchecked_param_type = "Option<SyntaxHighlighterAdapter>")]
+  codefence_syntax_highlighter: Option<SyntaxHighlighterAdapter>,
+  #[wasm_bindgen(unchecked_param_type = "Option<HeadingAdapter>")]
+  heading_adapter: Option<HeadingAdapter>,
+  #[wasm_bindgen(unchecked_param_type = "Option<BrokenLinkCallbackFunction>")]
+  broken_link_callback: Option<Function>,
+  #[wasm_bindgen(unchecked_param_type = "Option<URLRewriterFunction>")]
+  image_url_rewriter: Option<Function>,
+  #[wasm_bindgen(unchecked_param_type = "Option<URLRewriterFunction>")]
+  link_url_rewriter: Option<Function>,
+) -> Result<OptionsHandle, JsValue> {
+  let extras: Options = unwrap_option_object(options.clone())?;
+  let mut options: ComrakOptions = unwrap_option_object(options)?;
+  let broken_link_callback = collect_options!(
+    options,
+    extras,
+    broken_link_callback,
+    image_url_rewriter,
+    link_url_rewriter,
+  );
+  let disabled = extras.render.disable_plugins;
+  Ok(OptionsHandle {
+    codefence_syntax_highlighter: codefence_syntax_highlighter
+      .filter(|_| !disabled)
+      .map(Into::into),
+    heading_adapter: heading_adapter.filter(|_| !disabled).map(Into::into),
+    options,
+    extras,
+    broken_link_callback,
+  })
+}
+
+/// Render Markdown to HTML with options compiled by `compile_options`.
+#[wasm_bindgen]
+pub fn markdown_to_html_with(
+  handle: &OptionsHandle,
+  md: &str,
+) -> Result<String, JsValue> {
+  render_compiled(md, OutputFormat::Html, handle)
+}
+
+markdown_to_fn! {
+  /// Render Markdown to HTML using plugins.
+  ////
+  /// See the documentation of the crate root for an example.
+  pub fn markdown_to_html -> OutputFormat::Html;
+
+  /// Render Markdown to XML using plugins.
+  ///
+  /// See the documentation of the crate root for an example.
+  pub fn markdown_to_xml -> OutputFormat::Xml;
+
+  /// Render Markdown to CommonMark.
+  ////
+  /// See the documentation of the crate root for an example.
+  pub fn markdown_to_commonmark -> OutputFormat::CommonMark;
+}
+
+format_fn! {
+  /// Format an AST to HTML using plugins.
+  ///
   /// See the documentation of the crate root for an example.
-  pub fn format_commonmark -> format_commonmark_with_plugins;
+  pub fn format_html -> formatter::format_html;
+
+  /// Format an AST to XML using plugins.
+  ///
+  /// See the documentation of the crate root for an example.
+  pub fn format_xml -> formatter::format_xml;
+
+  /// Format an AST to CommonMark using plugins.
+  ///
+  /// See the documentation of the crate root for an example.
+  pub fn format_commonmark -> formatter::format_commonmark;
+}
+
+/// Render Markdown to [AMP]-compatible HTML.
+///
+/// This renders the document exactly like `markdown_to_html`, then rewrites
+/// the output to satisfy the AMP validator: images become `<amp-img>` elements
+/// sized by the `render.imageDimensions` callback, disallowed elements such as
+/// `<script>` and `<iframe>` are removed, and inline styles, event handlers,
+/// and `javascript:` URLs are stripped.
+///
+/// Since inline styles are not permitted, a Syntect adapter created with a
+/// theme is switched to its class-based output, as if created by
+/// `SyntaxHighlighterAdapter.classed()`, and the page has to include the
+/// theme's stylesheet (see `css_for_theme`) to color the code. The markup of
+/// a custom adapter is rewritten like any other HTML, so the inline styles it
+/// emits are stripped rather than converted to classes.
+///
+/// [AMP]: https://amp.dev/documentation/guides-and-tutorials/learn/spec/amphtml
+#[wasm_bindgen]
+pub fn markdown_to_amp(
+  md: &str,
+  #[wasm_bindgen(unchecked_param_type = "Option<Options>")] options: Option<
+    Object,
+  >,
+  #[wasm_bindgen(unchecked_param_type = "Option<SyntaxHighlighterAdapter>")]
+  codefence_syntax_highlighter: Option<SyntaxHighlighterAdapter>,
+  #[wasm_bindgen(unchecked_param_type = "Option<HeadingAdapter>")]
+  heading_adapter: Option<HeadingAdapter>,
+  #[wasm_bindgen(unchecked_param_type = "Option<BrokenLinkCallbackFunction>")]
+  broken_link_callback: Option<Function>,
+  #[wasm_bindgen(unchecked_param_type = "Option<URLRewriterFunction>")]
+  image_url_rewriter: Option<Function>,
+  #[wasm_bindgen(unchecked_param_type = "Option<URLRewriterFunction>")]
+  link_url_rewriter: Option<Function>,
+) -> Result<String, JsValue> {
+  let extras: Options = unwrap_option_object(options.clone())?;
+  #[cfg(feature = "syntect")]
+  let codefence_syntax_highlighter =
+    codefence_syntax_highlighter.map(SyntaxHighlighterAdapter::into_classed);
+  let html = markdown_to_html(
+    md,
+    options,
+    codefence_syntax_highlighter,
+    heading_adapter,
+    broken_link_callback,
+    image_url_rewriter,
+    link_url_rewriter,
+  )?;
+  amp::to_amp(&html, &extras.render)
+}
+
+/// Render Markdown to a tree of HTML elements, in the `{ type, props,
+/// children }` shape used by front-end frameworks, instead of an HTML string.
+///
+/// The tree mirrors the HTML that `markdown_to_html` renders, with the text of
+/// each element as strings in its `children`. Raw HTML (when allowed by the
+/// `unsafe` option) is represented by an `#html` element, whose `html` prop
+/// holds the markup, after the `tagfilter` extension and the `sanitizeHtml`
+/// option are applied to it.
+#[wasm_bindgen(unchecked_return_type = "ElementChild[]")]
+pub fn markdown_to_elements(
+  md: &str,
+  #[wasm_bindgen(unchecked_param_type = "Option<Options>")] options: Option<
+    Object,
+  >,
+  #[wasm_bindgen(unchecked_param_type = "Option<BrokenLinkCallbackFunction>")]
+  broken_link_callback: Option<Function>,
+  #[wasm_bindgen(unchecked_param_type = "Option<URLRewriterFunction>")]
+  image_url_rewriter: Option<Function>,
+  #[wasm_bindgen(unchecked_param_type = "Option<URLRewriterFunction>")]
+  link_url_rewriter: Option<Function>,
+) -> Result<JsValue, JsValue> {
+  let mut extras: Options = unwrap_option_object(options.clone())?;
+  let mut options: ComrakOptions = unwrap_option_object(options)?;
+  let broken_link_callback = collect_options!(
+    options,
+    extras,
+    broken_link_callback,
+    image_url_rewriter,
+    link_url_rewriter,
+  );
+  let arena = Arena::new();
+  let root = parse(&arena, md, &options, &extras)?;
+  if let Some(ref cb) = broken_link_callback {
+    cb.rethrow()?;
+  }
+  transform::render(&arena, root, &options, &extras)?;
+  to_js_value(&elements::to_elements(root, &options, &extras)?)
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+struct PaginateOptions {
+  max_blocks_per_page: usize,
+}
+
+impl Default for PaginateOptions {
+  fn default() -> Self {
+    Self { max_blocks_per_page: 10 }
+  }
+}
+
+/// Render Markdown to HTML, split into pages of at most `maxBlocksPerPage`
+/// top-level blocks each (10 by default).
+///
+/// Pages are split at block boundaries only, so a block is never split across
+/// two pages. Each page is rendered separately and wrapped in a
+/// `<div class="page">` element. Front matter doesn't count towards a page.
+#[wasm_bindgen]
+#[allow(clippy::too_many_arguments)]
+pub fn paginate(
+  md: &str,
+  #[wasm_bindgen(unchecked_param_type = "Option<Options>")] options: Option<
+    Object,
+  >,
+  #[wasm_bindgen(unchecked_param_type = "Option<SyntaxHighlighterAdapter>")]
+  codefence_syntax_highlighter: Option<SyntaxHighlighterAdapter>,
+  #[wasm_bindgen(unchecked_param_type = "Option<HeadingAdapter>")]
+  heading_adapter: Option<HeadingAdapter>,
+  #[wasm_bindgen(unchecked_param_type = "Option<BrokenLinkCallbackFunction>")]
+  broken_link_callback: Option<Function>,
+  #[wasm_bindgen(unchecked_param_type = "Option<URLRewriterFunction>")]
+  image_url_rewriter: Option<Function>,
+  #[wasm_bindgen(unchecked_param_type = "Option<URLRewriterFunction>")]
+  link_url_rewriter: Option<Function>,
+  #[wasm_bindgen(unchecked_param_type = "Option<PaginateOptions>")]
+  pagination: Option<Object>,
+) -> Result<Vec<String>, JsValue> {
+  let pagination: PaginateOptions = unwrap_option_object(pagination)?;
+  let max = pagination.max_blocks_per_page;
+  if max == 0 {
+    return Err(map_err("maxBlocksPerPage must be greater than 0"));
+  }
+  let mut extras: Options = unwrap_option_object(options.clone())?;
+  let mut options: ComrakOptions = unwrap_option_object(options)?;
+  let broken_link_callback = collect_options!(
+    options,
+    extras,
+    broken_link_callback,
+    image_url_rewriter,
+    link_url_rewriter,
+  );
+  let slugify = SlugifyAdapter::from_options(&options, &extras);
+  let mut plugins = Plugins::default();
+  collect_plugins!(
+    plugins,
+    extras,
+    codefence_syntax_highlighter,
+    heading_adapter,
+    slugify,
+  );
+  let arena = Arena::new();
+  let root = parse(&arena, md, &options, &extras)?;
+  if let Some(ref cb) = broken_link_callback {
+    cb.rethrow()?;
+  }
+  transform::render(&arena, root, &options, &extras)?;
+  let blocks: Vec<_> = root
+    .children()
+    .filter(|n| !matches!(n.data.borrow().value, NodeValue::FrontMatter(_)))
+    .collect();
+  let mut pages = Vec::with_capacity(blocks.len().div_ceil(max));
+  for chunk in blocks.chunks(max) {
+    let page = arena.alloc(NodeValue::Document.into());
+    for block in chunk {
+      page.append(block);
+    }
+    let mut out = String::from("<div class=\"page\">\n");
+    let result =
+      formatter::format_html(page, &options, &mut out, &plugins, &extras);
+    if let Some(ref adapter) = slugify {
+      adapter.rethrow()?;
+    }
+    result?;
+    out.push_str("</div>\n");
+    pages.push(out);
+  }
+  Ok(pages)
+}
+
+/// Convert HTML to Markdown.
+///
+/// The HTML is walked into an AST, which is then formatted as CommonMark just
+/// like `format_commonmark` would, with the render options given. Headings,
+/// paragraphs, block quotes, lists, code, links, images, emphasis, line breaks,
+/// thematic breaks, and tables are converted, while the tags of the other
+/// elements are kept as raw HTML, or dropped if `unsupported` is `"drop"`.
+#[wasm_bindgen]
+pub fn html_to_markdown(
+  html: &str,
+  #[wasm_bindgen(unchecked_param_type = "Option<Options>")] options: Option<
+    Object,
+  >,
+  #[wasm_bindgen(unchecked_param_type = "Option<HtmlToMarkdownOptions>")]
+  conversion: Option<Object>,
+) -> Result<String, JsValue> {
+  let conversion: HtmlToMarkdownOptions = unwrap_option_object(conversion)?;
+  let extras: Options = unwrap_option_object(options.clone())?;
+  let options: ComrakOptions = unwrap_option_object(options)?;
+  let arena = Arena::new();
+  let root = from_html::to_ast(&arena, html, &conversion);
+  let mut out = String::new();
+  let plugins = Plugins::default();
+  formatter::format_commonmark(root, &options, &mut out, &plugins, &extras)?;
+  Ok(out)
+}
+
+/// Render Markdown to HTML, passing the output to the `sink` function in
+/// chunks as it is rendered, rather than returning it as a single string. A
+/// chunk is passed to `sink` after each top-level block, which keeps the peak
+/// memory use low for large documents.
+///
+/// The chunks are exactly what [`markdown_to_html`] would return, when joined
+/// together. When sanitizing, a block whose raw HTML leaves a tag or a removed
+/// element open is held back until it's closed, so it's sanitized as a whole.
+/// An exception thrown by `sink` aborts rendering, and is rethrown to the
+/// caller.
+#[wasm_bindgen]
+#[allow(clippy::too_many_arguments)]
+pub fn markdown_to_html_streaming(
+  md: &str,
+  #[wasm_bindgen(unchecked_param_type = "Option<Options>")] options: Option<
+    Object,
+  >,
+  #[wasm_bindgen(unchecked_param_type = "Option<SyntaxHighlighterAdapter>")]
+  codefence_syntax_highlighter: Option<SyntaxHighlighterAdapter>,
+  #[wasm_bindgen(unchecked_param_type = "Option<HeadingAdapter>")]
+  heading_adapter: Option<HeadingAdapter>,
+  #[wasm_bindgen(unchecked_param_type = "Option<BrokenLinkCallbackFunction>")]
+  broken_link_callback: Option<Function>,
+  #[wasm_bindgen(unchecked_param_type = "Option<URLRewriterFunction>")]
+  image_url_rewriter: Option<Function>,
+  #[wasm_bindgen(unchecked_param_type = "Option<URLRewriterFunction>")]
+  link_url_rewriter: Option<Function>,
+  #[wasm_bindgen(unchecked_param_type = "(chunk: string) => void")]
+  sink: Function,
+) -> Result<(), JsValue> {
+  let mut extras: Options = unwrap_option_object(options.clone())?;
+  let mut options: ComrakOptions = unwrap_option_object(options)?;
+  let broken_link_callback = collect_options!(
+    options,
+    extras,
+    broken_link_callback,
+    image_url_rewriter,
+    link_url_rewriter,
+  );
+  let slugify = SlugifyAdapter::from_options(&options, &extras);
+  let mut plugins = Plugins::default();
+  collect_plugins!(
+    plugins,
+    extras,
+    codefence_syntax_highlighter,
+    heading_adapter,
+    slugify,
+  );
+  let arena = Arena::new();
+  let root = parse(&arena, md, &options, &extras)?;
+  if let Some(ref cb) = broken_link_callback {
+    cb.rethrow()?;
+  }
+  transform::render(&arena, root, &options, &extras)?;
+  let result =
+    formatter::format_html_streaming(root, &options, &sink, &plugins, &extras);
+  if let Some(ref adapter) = slugify {
+    adapter.rethrow()?;
+  }
+  result
+}
+
+#[derive(Debug, Serialize)]
+struct ByteRange {
+  start: usize,
+  end:   usize,
+}
+
+/// Converts a source position from the AST (with 1-based lines and columns)
+/// into the absolute UTF-8 byte offsets of the range it spans in `md`, which
+/// must be the Markdown the AST was parsed from. The `end` offset is exclusive.
+#[wasm_bindgen(unchecked_return_type = "ByteRange")]
+pub fn sourcepos_to_offsets(
+  md: &str,
+  #[wasm_bindgen(unchecked_param_type = "Sourcepos")] sourcepos: JsValue,
+) -> Result<JsValue, JsValue> {
+  let sourcepos: Sourcepos = from_value(sourcepos).map_err(map_err)?;
+  let range = Source::new(md).range(sourcepos).ok_or_else(|| {
+    map_err(format!("sourcepos {sourcepos} is outside of the document"))
+  })?;
+  to_js_value(&ByteRange { start: range.start, end: range.end })
+}
+
+#[derive(Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct FrontMatterSplit {
+  front_matter: Option<String>,
+  body:         String,
+}
+
+/// Splits the front matter block off the start of a Markdown document, using
+/// the same detection Comrak applies for the `front_matter_delimiter` option.
+///
+/// The returned `frontMatter` **excludes** the opening and closing delimiter
+/// lines, and is `null` if the document has no front matter. The `body` is the
+/// remainder of the document exactly as the renderer would see it.
+#[wasm_bindgen(unchecked_return_type = "FrontMatterSplit")]
+pub fn split_front_matter(
+  md: &str,
+  delimiter: &str,
+) -> Result<JsValue, JsValue> {
+  if delimiter.is_empty() {
+    let split =
+      FrontMatterSplit { front_matter: None, body: md.to_string() };
+    return to_js_value(&split);
+  }
+  let mut options = ComrakOptions::default();
+  options.extension.front_matter_delimiter = Some(delimiter.to_string());
+  let arena = Arena::new();
+  let root = comrak::parse_document(&arena, md, &options);
+  let raw =
+    root
+      .first_child()
+      .and_then(|node| match node.data.borrow().value {
+        | NodeValue::FrontMatter(ref fm) => Some(fm.to_string()),
+        | _ => None,
+      });
+  let split = match raw {
+    | Some(raw) => {
+      // comrak strips a leading BOM before matching the front matter block
+      let rest = md.trim_start_matches('\u{feff}');
+      FrontMatterSplit {
+        body:         rest[raw.len().min(rest.len())..].to_string(),
+        front_matter: Some(strip_front_matter_delimiters(&raw, delimiter)),
+      }
+    }
+    | None => {
+      FrontMatterSplit { front_matter: None, body: md.to_string() }
+    }
+  };
+  to_js_value(&split)
+}
+
+/// Removes the delimiter lines from the raw front matter block of a document.
+pub(crate) fn strip_front_matter_delimiters(
+  raw: &str,
+  delimiter: &str,
+) -> String {
+  let inner = raw.strip_prefix(delimiter).unwrap_or(raw);
+  let inner = inner
+    .strip_prefix("\r\n")
+    .or_else(|| inner.strip_prefix('\n'))
+    .unwrap_or(inner);
+  // trailing blank lines after the closing delimiter belong to the block too
+  let inner = inner.trim_end_matches(['\r', '\n']);
+  inner.strip_suffix(delimiter).unwrap_or(inner).to_string()
+}
+
+/// A parsed Markdown document, which keeps its AST in memory so that it can be
+/// rendered several times (e.g. to different formats, or with different render
+/// options) without parsing it again.
+///
+/// The AST is allocated in an arena owned by the document, which must be freed
+/// explicitly by calling `free()` from JS once the document is no longer used.
+/// Until then, the memory it occupies is never reclaimed.
+#[wasm_bindgen]
+pub struct Document {
+  // NOTE: `root` borrows from `arena`, so it must never outlive it. Neither
+  // can be exposed with its real lifetime across the FFI boundary, so the
+  // arena is leaked when parsing and reclaimed when the document is dropped.
+  root:  &'static AstNode<'static>,
+  arena: NonNull<Arena<'static>>,
+}
+
+#[wasm_bindgen]
+impl Document {
+  /// Parses the given markdown text into a new document. The parse options,
+  /// the broken link callback, and the URL rewriters are applied once, here.
+  #[wasm_bindgen(constructor)]
+  pub fn new(
+    md: &str,
+    #[wasm_bindgen(unchecked_param_type = "Option<Options>")] options: Option<
+      Object,
+    >,
+    #[wasm_bindgen(
+      unchecked_param_type = "Option<BrokenLinkCallbackFunction>"
+    )]
+    broken_link_callback: Option<Function>,
+    #[wasm_bindgen(unchecked_param_type = "Option<URLRewriterFunction>")]
+    image_url_rewriter: Option<Function>,
+    #[wasm_bindgen(unchecked_param_type = "Option<URLRewriterFunction>")]
+    link_url_rewriter: Option<Function>,
+  ) -> Result<Document, JsValue> {
+    let mut extras: Options = unwrap_option_object(options.clone())?;
+    let mut options: ComrakOptions = unwrap_option_object(options)?;
+    let broken_link_callback = collect_options!(
+      options,
+      extras,
+      broken_link_callback,
+      image_url_rewriter,
+      link_url_rewriter,
+    );
+    let arena = NonNull::from(Box::leak(Box::new(Arena::new())));
+    // SAFETY: the arena was just leaked, and is only freed by `drop` below
+    let result = parse(unsafe { arena.as_ref() }, md, &options, &extras);
+    let result = result.and_then(|root| match broken_link_callback {
+      | Some(ref cb) => cb.rethrow().map(|_| root),
+      | None => Ok(root),
+    });
+    match result {
+      | Ok(root) => Ok(Self { root, arena }),
+      | Err(e) => {
+        // SAFETY: nothing borrows from the arena once parsing has failed
+        drop(unsafe { Box::from_raw(arena.as_ptr()) });
+        Err(e)
+      }
+    }
+  }
+
+  /// Renders the document to HTML.
+  #[wasm_bindgen(js_name = "toHtml")]
+  pub fn to_html(
+    &self,
+    #[wasm_bindgen(unchecked_param_type = "Option<Options>")] options: Option<
+      Object,
+    >,
+    #[wasm_bindgen(unchecked_param_type = "Option<SyntaxHighlighterAdapter>")]
+    codefence_syntax_highlighter: Option<SyntaxHighlighterAdapter>,
+    #[wasm_bindgen(unchecked_param_type = "Option<HeadingAdapter>")]
+    heading_adapter: Option<HeadingAdapter>,
+  ) -> Result<String, JsValue> {
+    self.render(
+      OutputFormat::Html,
+      options,
+      codefence_syntax_highlighter,
+      heading_adapter,
+    )
+  }
+
+  /// Renders the document to XML.
+  #[wasm_bindgen(js_name = "toXml")]
+  pub fn to_xml(
+    &self,
+    #[wasm_bindgen(unchecked_param_type = "Option<Options>")] options: Option<
+      Object,
+    >,
+    #[wasm_bindgen(unchecked_param_type = "Option<SyntaxHighlighterAdapter>")]
+    codefence_syntax_highlighter: Option<SyntaxHighlighterAdapter>,
+    #[wasm_bindgen(unchecked_param_type = "Option<HeadingAdapter>")]
+    heading_adapter: Option<HeadingAdapter>,
+  ) -> Result<String, JsValue> {
+    self.render(
+      OutputFormat::Xml,
+      options,
+      codefence_syntax_highlighter,
+      heading_adapter,
+    )
+  }
+
+  /// Renders the document to CommonMark.
+  #[wasm_bindgen(js_name = "toCommonmark")]
+  pub fn to_commonmark(
+    &self,
+    #[wasm_bindgen(unchecked_param_type = "Option<Options>")] options: Option<
+      Object,
+    >,
+    #[wasm_bindgen(unchecked_param_type = "Option<SyntaxHighlighterAdapter>")]
+    codefence_syntax_highlighter: Option<SyntaxHighlighterAdapter>,
+    #[wasm_bindgen(unchecked_param_type = "Option<HeadingAdapter>")]
+    heading_adapter: Option<HeadingAdapter>,
+  ) -> Result<String, JsValue> {
+    self.render(
+      OutputFormat::CommonMark,
+      options,
+      codefence_syntax_highlighter,
+      heading_adapter,
+    )
+  }
+
+  /// Returns the level and text content of every heading in the document, in
+  /// document order.
+  #[wasm_bindgen(unchecked_return_type = "HeadingMeta[]")]
+  pub fn headings(&self) -> Result<JsValue, JsValue> {
+    let headings: Vec<_> = self
+      .root
+      .descendants()
+      .filter_map(|node| match node.data.borrow().value {
+        | NodeValue::Heading(ref heading) => Some(DocumentHeading {
+          level:   heading.level,
+          content: transform::text_content(node),
+        }),
+        | _ => None,
+      })
+      .collect();
+    to_js_value(&headings)
+  }
+}
+
+impl Document {
+  /// Renders a copy of the AST, since the render-time transformations modify
+  /// the tree in place, and must not affect later renders.
+  fn render(
+    &self,
+    format: OutputFormat,
+    options: Option<Object>,
+    codefence_syntax_highlighter: Option<SyntaxHighlighterAdapter>,
+    heading_adapter: Option<HeadingAdapter>,
+  ) -> Result<String, JsValue> {
+    let mut extras: Options = unwrap_option_object(options.clone())?;
+    let mut options: ComrakOptions = unwrap_option_object(options)?;
+    // callbacks that run while parsing have no effect here
+    collect_options!(options, extras, None, None, None);
+    let slugify = SlugifyAdapter::from_options(&options, &extras);
+    let mut plugins = Plugins::default();
+    collect_plugins!(
+      plugins,
+      extras,
+      codefence_syntax_highlighter,
+      heading_adapter,
+      slugify,
+    );
+    let arena = Arena::new();
+    let root = clone_tree(&arena, self.root);
+    transform::render(&arena, root, &options, &extras)?;
+    let mut out = String::new();
+    let result = format.format(root, &options, &mut out, &plugins, &extras);
+    if let Some(ref adapter) = slugify {
+      adapter.rethrow()?;
+    }
+    result?;
+    Ok(out)
+  }
+}
+
+impl Drop for Document {
+  fn drop(&mut self) {
+    // SAFETY: the arena was leaked by `new`, and `root` is never used again
+    drop(unsafe { Box::from_raw(self.arena.as_ptr()) });
+  }
+}
+
+/// A code block, as returned by [`extract_code_blocks`].
+#[derive(Debug, Serialize)]
+struct ExtractedCodeBlock {
+  language:  Option<String>,
+  meta:      Option<String>,
+  code:      String,
+  sourcepos: Sourcepos,
+}
+
+#[derive(Debug, Serialize)]
+struct DocumentHeading {
+  level:   u8,
+  content: String,
+}
+
+/// Copies a node and all of its descendants into `arena`.
+fn clone_tree<'a, 'b>(
+  arena: &'a Arena<'a>,
+  node: &'b AstNode<'b>,
+) -> &'a AstNode<'a> {
+  let copy = arena.alloc(node.data.borrow().clone().into());
+  for child in node.children() {
+    copy.append(clone_tree(arena, child));
+  }
+  copy
 }
@@ -0,0 +1,91 @@
+//! Memory accounting for the WebAssembly module.
+//!
+//! WebAssembly linear memory can only ever grow: memory freed by the allocator
+//! is reused for later allocations within the module, but it is never returned
+//! to the host. The most that can be done after a large render is to release
+//! the memory held by caches, so that it can be reused in place of growing the
+//! linear memory any further.
+
+/// The size of a page of WebAssembly linear memory, in bytes.
+#[cfg(target_arch = "wasm32")]
+const PAGE_SIZE: usize = 65536;
+
+#[cfg(all(target_arch = "wasm32", feature = "alloc"))]
+pub use counting::Counting;
+
+#[cfg(all(target_arch = "wasm32", feature = "alloc"))]
+mod counting {
+  use std::alloc::GlobalAlloc;
+  use std::alloc::Layout;
+  use std::sync::atomic::AtomicUsize;
+  use std::sync::atomic::Ordering;
+
+  /// A global allocator that keeps count of the bytes currently allocated
+  /// through the allocator it wraps.
+  pub struct Counting<A> {
+    inner:     A,
+    allocated: AtomicUsize,
+  }
+
+  impl<A> Counting<A> {
+    pub const fn new(inner: A) -> Self {
+      Self { inner, allocated: AtomicUsize::new(0) }
+    }
+
+    /// Returns the number of bytes currently allocated.
+    pub fn allocated(&self) -> usize {
+      self.allocated.load(Ordering::Relaxed)
+    }
+  }
+
+  // SAFETY: every call is forwarded to the inner allocator as-is
+  unsafe impl<A: GlobalAlloc> GlobalAlloc for Counting<A> {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+      let ptr = unsafe { self.inner.alloc(layout) };
+      if !ptr.is_null() {
+        self.allocated.fetch_add(layout.size(), Ordering::Relaxed);
+      }
+      ptr
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+      let ptr = unsafe { self.inner.alloc_zeroed(layout) };
+      if !ptr.is_null() {
+        self.allocated.fetch_add(layout.size(), Ordering::Relaxed);
+      }
+      ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+      unsafe { self.inner.dealloc(ptr, layout) };
+      self.allocated.fetch_sub(layout.size(), Ordering::Relaxed);
+    }
+
+    unsafe fn realloc(
+      &self,
+      ptr: *mut u8,
+      layout: Layout,
+      new_size: usize,
+    ) -> *mut u8 {
+      let new_ptr = unsafe { self.inner.realloc(ptr, layout, new_size) };
+      if !new_ptr.is_null() {
+        self.allocated.fetch_sub(layout.size(), Ordering::Relaxed);
+        self.allocated.fetch_add(new_size, Ordering::Relaxed);
+      }
+      new_ptr
+    }
+  }
+}
+
+/// Returns the size of the module's linear memory, in bytes, which includes
+/// both allocated and free memory.
+pub fn reserved() -> usize {
+  #[cfg(target_arch = "wasm32")]
+  {
+    core::arch::wasm32::memory_size(0) * PAGE_SIZE
+  }
+  #[cfg(not(target_arch = "wasm32"))]
+  {
+    0
+  }
+}
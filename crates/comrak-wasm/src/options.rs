@@ -0,0 +1,926 @@
+//! Options specific to this crate, layered on top of Comrak's own options.
+//!
+//! These are deserialized from the same JS options object as the native
+//! [`comrak::Options`], sharing its `extension`, `parse`, and `render`
+//! namespaces, so the TypeScript API can present them as one flat interface.
+//! Any field Comrak doesn't know about is simply ignored by its deserializer,
+//! and vice versa.
+
+use std::collections::BTreeMap;
+use std::collections::BTreeSet;
+use std::collections::HashMap;
+use std::num::NonZeroUsize;
+
+use js_sys::Function;
+use serde::Deserialize;
+
+use crate::URLRewriter;
+use crate::tabs::TAB_STOP;
+
+/// Crate-level options, mirroring the structure of [`comrak::Options`].
+#[derive(Debug, Default, Clone, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct Options {
+  pub extension: ExtensionOptions,
+  pub parse:     ParseOptions,
+  pub render:    RenderOptions,
+}
+
+/// Crate-level extension options.
+#[derive(Debug, Default, Clone, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct ExtensionOptions {
+  /// Generates the slug used for heading IDs when `header_ids` is enabled,
+  /// in place of Comrak's default anchorizer.
+  #[serde(deserialize_with = "callback::deserialize")]
+  pub slugify:            Option<Function>,
+  /// Parses a `=WxH` size suffix in image destinations, such as
+  /// `![alt](img.png =100x200)`, rendering it as `width` and `height`
+  /// attributes. Either dimension may be omitted (`=100x` or `=x200`).
+  pub image_size_syntax:  bool,
+  /// Resolves the URL, and optionally the label, of each wikilink from its
+  /// title, rather than using the title as the URL as-is.
+  #[serde(deserialize_with = "callback::deserialize")]
+  pub wikilink_resolver:  Option<Function>,
+  /// Custom URL schemes to autolink, such as `ticket:1234`, mapped to the URL
+  /// template to link them to. `{rest}` in the template is replaced with the
+  /// part of the token after the scheme.
+  pub autolink_schemes:   HashMap<String, String>,
+  /// The kinds of links the `autolink` extension creates, from the object
+  /// form of its option, which is passed to Comrak as a boolean instead.
+  pub autolink_kinds:     Option<AutolinkKinds>,
+  /// Links `@user` mentions and `#tag` hashtags in text.
+  pub mentions:           Option<Mentions>,
+  /// Rewrites the URLs of images, as passed to the API. The HTML formatter
+  /// calls this itself, once per image, in place of Comrak.
+  #[serde(skip)]
+  pub image_url_rewriter: Option<URLRewriter>,
+  /// Rewrites the URLs of links, as passed to the API. The HTML formatter
+  /// calls this itself, once per link, in place of Comrak.
+  #[serde(skip)]
+  pub link_url_rewriter:  Option<URLRewriter>,
+}
+
+/// The kinds of links created by the `autolink` extension.
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(default)]
+pub struct AutolinkKinds {
+  /// URLs with a scheme, such as `https://example.com`.
+  pub urls:  bool,
+  /// URLs starting with `www.`, such as `www.example.com`.
+  pub www:   bool,
+  /// Email addresses, such as `nick@example.com`.
+  pub email: bool,
+}
+
+impl Default for AutolinkKinds {
+  fn default() -> Self {
+    Self { urls: true, www: true, email: true }
+  }
+}
+
+/// The URL templates that `@user` mentions and `#tag` hashtags are linked to,
+/// in which `{name}` is replaced with the name after the `@` or `#`. Either
+/// kind is left as text when it has no template.
+#[derive(Debug, Default, Clone, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct Mentions {
+  pub user_url: Option<String>,
+  pub tag_url:  Option<String>,
+}
+
+/// Crate-level parse options.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct ParseOptions {
+  /// Treat a backslash at the end of a line as a hard line break, per the
+  /// CommonMark spec. When disabled, the backslash is kept as literal text and
+  /// the line ending is rendered as a soft break.
+  pub backslash_line_breaks: bool,
+  /// The maximum size of the input, in bytes. Larger documents are rejected
+  /// with an error before they are parsed, bounding the work of parsing.
+  pub max_input_bytes:       Option<usize>,
+  /// The maximum number of nodes in the parsed AST. Documents exceeding it
+  /// are rejected with an error once parsed, bounding the work done on the
+  /// AST afterwards. It doesn't bound the work of parsing itself.
+  pub max_nodes:             Option<usize>,
+  /// Link reference definitions to resolve reference links with, keyed by
+  /// their label, as if they were defined in the document itself.
+  pub reference_map:         HashMap<String, Reference>,
+  /// The quotation marks that the `smart` option produces.
+  pub smart_locale:          SmartLocale,
+  /// How the front matter matched by the `front_matter_delimiter` extension
+  /// is rendered.
+  pub front_matter_mode:     FrontMatterMode,
+  /// The width of the tab stops used when a tab indents a line, such as to
+  /// decide whether it starts an indented code block.
+  pub tab_width:             NonZeroUsize,
+  /// Reports each construct that Comrak recovers from silently while parsing
+  /// to `on_diagnostic`, such as a code fence that is never closed.
+  pub report_recoveries:     bool,
+  /// Called with each diagnostic reported while parsing.
+  #[serde(deserialize_with = "callback::deserialize")]
+  pub on_diagnostic:         Option<Function>,
+  /// Characters moved from the end of the links created by the `autolink`
+  /// extension into the text after them, on top of Comrak's own trimming.
+  pub autolink_trim_chars:   Option<String>,
+}
+
+impl Default for ParseOptions {
+  fn default() -> Self {
+    Self {
+      backslash_line_breaks: true,
+      max_input_bytes:       None,
+      max_nodes:             None,
+      reference_map:         HashMap::new(),
+      smart_locale:          SmartLocale::En,
+      front_matter_mode:     FrontMatterMode::Strip,
+      tab_width:             TAB_STOP,
+      report_recoveries:     false,
+      on_diagnostic:         None,
+      autolink_trim_chars:   None,
+    }
+  }
+}
+
+/// The conventions for the quotation marks produced by Comrak's `smart`
+/// option, which only knows English ones.
+///
+/// - `en`: `“double”` and `‘single’`, as rendered by Comrak.
+/// - `fr`: `« double »` and `‹ single ›`, with no-break spaces inside.
+/// - `de`: `„double“` and `‚single‘`.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SmartLocale {
+  #[default]
+  En,
+  Fr,
+  De,
+}
+
+impl SmartLocale {
+  /// Returns the opening and closing double quotes, followed by the opening
+  /// and closing single quotes.
+  pub fn quotes(self) -> [&'static str; 4] {
+    match self {
+      | Self::En => [
+        "\u{201c}", "\u{201d}", "\u{2018}", "\u{2019}",
+      ],
+      | Self::Fr => [
+        "\u{ab}\u{a0}",
+        "\u{a0}\u{bb}",
+        "\u{2039}\u{a0}",
+        "\u{a0}\u{203a}",
+      ],
+      | Self::De => [
+        "\u{201e}", "\u{201c}", "\u{201a}", "\u{2018}",
+      ],
+    }
+  }
+}
+
+/// How the front matter of a document is rendered.
+///
+/// - `strip`: omitted from the output, as Comrak does.
+/// - `render`: rendered as a description list of its top-level `key: value`
+///   pairs.
+/// - `codeblock`: rendered as a fenced code block, with a language of `toml`
+///   for the `+++` delimiter and `yaml` otherwise.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FrontMatterMode {
+  #[default]
+  Strip,
+  Render,
+  Codeblock,
+}
+
+/// A link reference definition from the `reference_map` parse option.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Reference {
+  pub url:   String,
+  #[serde(default)]
+  pub title: String,
+}
+
+/// Crate-level render options.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct RenderOptions {
+  /// Adds a `data-bullet` attribute to the `<ul>` and `<li>` elements of
+  /// bullet lists in HTML output, containing the list's source marker.
+  pub preserve_bullet_style_in_html:     bool,
+  /// Replaces a marker paragraph (e.g. `[[TOC]]`) with a table of contents.
+  pub table_of_contents:                 Option<TableOfContents>,
+  /// Autolinks whose URL matches any of these patterns are reverted to plain
+  /// text, along with explicit links whose text is their URL, which can't be
+  /// told apart from autolinks in the AST. See [`crate::pattern`] for the
+  /// pattern syntax.
+  pub autolink_exclude_patterns:         Vec<String>,
+  /// Wraps the entire content of each heading in its `header_ids` anchor,
+  /// rather than rendering an empty anchor before the content.
+  pub heading_anchor_wrap_heading:       bool,
+  /// The `class` attribute of the headings of each level, keyed by level.
+  #[serde(deserialize_with = "heading_levels::deserialize")]
+  pub heading_classes:                   BTreeMap<u8, String>,
+  /// Adjusts heading levels so that they never skip a level, while keeping
+  /// their relative structure intact (e.g. `h1 > h3` becomes `h1 > h2`).
+  pub fix_heading_levels:                bool,
+  /// Prepends hierarchical section numbers (e.g. `1.2.`) to headings.
+  pub number_headings:                   Option<NumberHeadings>,
+  /// Highlights inline code spans with a `lang:` prefix (e.g. `rust:let x`)
+  /// using the configured syntax highlighter, stripping the prefix.
+  pub highlight_inline_code:             bool,
+  /// Only renders `header_ids` anchors for headings that are the target of an
+  /// in-document link (e.g. `[intro](#intro)`).
+  pub header_ids_only_when_referenced:   bool,
+  /// Escapes the template syntax of a frontend framework in text, so that the
+  /// HTML can be embedded in one of its templates.
+  pub framework_escaping:                FrameworkEscaping,
+  /// Expands hard tabs in code blocks to spaces, using tab stops of the given
+  /// width.
+  pub code_tabs_to_spaces:               Option<usize>,
+  /// Numbers the lines of code blocks, wrapping each in a `<span>` with its
+  /// number.
+  pub code_line_numbers:                 Option<CodeLineNumbers>,
+  /// Maps the language of fenced code blocks (the first word of their info
+  /// string) to another, such as `js` to `javascript`.
+  pub language_aliases:                  HashMap<String, String>,
+  /// Sanitizes the rendered HTML with an allowlist of tags and attributes.
+  /// See [`crate::sanitize`] for details.
+  pub sanitize_html:                     Option<SanitizeHtml>,
+  /// Customizes the symbol and position of footnote backreference links.
+  pub footnote_backref:                  Option<FootnoteBackref>,
+  /// Aborts rendering with an error once the output would exceed this many
+  /// bytes, guarding against inputs that expand to huge amounts of HTML.
+  pub max_output_bytes:                  Option<usize>,
+  /// Inserts a caption before each task list with the number of its items
+  /// that are checked, e.g. `3/5 done`.
+  pub task_list_progress:                bool,
+  /// Counts the items of nested task lists towards the caption of their
+  /// outermost task list, rather than giving each list its own caption.
+  pub task_list_progress_include_nested: bool,
+  /// Renders the `start` attribute of ordered lists that don't start at 1.
+  /// When disabled, every ordered list starts at 1 in the HTML output.
+  pub respect_ordered_list_start:        bool,
+  /// Renumbers every ordered list in the AST to start at 1, which applies to
+  /// all output formats (including CommonMark).
+  pub force_start_one:                   bool,
+  /// Renders task list checkboxes with stable, index-based `name` attributes
+  /// so that a form can submit their state.
+  pub task_list_inputs:                  Option<TaskListInputs>,
+  /// Customizes the IDs and labels used when rendering footnotes.
+  pub footnotes:                         Option<Footnotes>,
+  /// Where footnotes are rendered.
+  pub footnote_style:                    FootnoteStyle,
+  /// Restricts the `data-sourcepos` attributes added by the `sourcepos`
+  /// option to block-level nodes, omitting them from inline nodes.
+  pub sourcepos_blocks_only:             bool,
+  /// Adds `data-sourcepos` attributes to inline nodes as well as blocks when
+  /// the `sourcepos` option is enabled. Comrak always renders them, so this
+  /// is only useful to turn them off, like `sourcepos_blocks_only`.
+  pub inline_sourcepos:                  bool,
+  /// How soft line breaks are rendered, overriding Comrak's `hardbreaks`
+  /// option when set.
+  pub line_breaks:                       Option<LineBreaks>,
+  /// Where soft line breaks are rendered as `<br />`, overriding Comrak's
+  /// `hardbreaks` option and `line_breaks` when set.
+  pub hardbreaks_scope:                  Option<HardbreaksScope>,
+  /// How raw HTML is rendered, overriding Comrak's `escape`, `unsafe_`, and
+  /// `tagfilter` options when set.
+  pub raw_html:                          Option<RawHtml>,
+  /// The tags whose raw HTML is rendered as-is, even when Comrak's `unsafe_`
+  /// option is off or its `tagfilter` extension would escape them. With
+  /// `unsafe_` off, the rest of the raw HTML is escaped rather than omitted.
+  pub raw_html_allow_tags:               BTreeSet<HtmlName>,
+  /// Resolves the intrinsic `width` and `height` of an image from its URL.
+  #[serde(deserialize_with = "callback::deserialize")]
+  pub image_dimensions:                  Option<Function>,
+  /// The largest `width` rendered for an image, which is scaled down along
+  /// with its `height` to fit.
+  pub max_image_width:                   Option<u32>,
+  /// The largest `height` rendered for an image, which is scaled down along
+  /// with its `width` to fit.
+  pub max_image_height:                  Option<u32>,
+  /// Renders a code block in its entirety, given its code, language, and the
+  /// rest of its info string. Returning `null` falls back to the default.
+  #[serde(deserialize_with = "callback::deserialize")]
+  pub code_block_renderer:               Option<Function>,
+  /// Renders a math span or block, given its LaTeX source and whether it is
+  /// in display mode. Returning `null` falls back to the default.
+  #[serde(deserialize_with = "callback::deserialize")]
+  pub math_renderer:                     Option<Function>,
+  /// Called with the level, text, `id`, and source position of each heading
+  /// as it is rendered, for collecting a table of contents in the same pass.
+  #[serde(deserialize_with = "callback::deserialize")]
+  pub on_heading:                        Option<Function>,
+  /// Removes raw HTML that consists solely of comments, in both HTML blocks
+  /// and inline HTML.
+  pub strip_comments:                    bool,
+  /// Also removes conditional comments (e.g. `<!--[if IE]>...<![endif]-->`)
+  /// when `strip_comments` is enabled, rather than keeping them.
+  pub strip_conditional_comments:        bool,
+  /// Extra attributes added to every `<img>` tag, for lazy loading.
+  pub images:                            Option<Images>,
+  /// Renders the alignment of table cells as a `text-left`, `text-center`, or
+  /// `text-right` class, rather than as an attribute.
+  pub table_align_classes:               bool,
+  /// Customizes the rendering of description lists.
+  pub description_lists:                 Option<DescriptionLists>,
+  /// Removes insignificant whitespace between tags from the HTML output. See
+  /// [`crate::whitespace`] for details.
+  pub minify:                            bool,
+  /// Indents the HTML output, putting each block-level element on its own
+  /// line. Mutually exclusive with `minify`.
+  pub pretty_print:                      bool,
+  /// Pads the columns of tables in the CommonMark output to equal widths.
+  pub align_table_columns:               bool,
+  /// Keeps the soft line breaks of paragraphs in the CommonMark output, rather
+  /// than reflowing them to the `width` option.
+  pub preserve_soft_breaks:              bool,
+  /// The line endings of the CommonMark output.
+  pub line_ending:                       LineEnding,
+  /// Ignores the syntax highlighter and heading adapter plugins, rendering as
+  /// if they weren't given.
+  pub disable_plugins:                   bool,
+  /// Wraps the HTML output in a standalone HTML5 document.
+  pub document:                          Option<HtmlDocument>,
+  /// Prepended to the `header_ids` prefix, so that the heading IDs of
+  /// documents rendered for the same page don't collide.
+  pub id_scope:                          Option<String>,
+  /// Customizes the titles and icons of GitHub-style alerts.
+  pub alerts:                            Option<Alerts>,
+  /// The tag that strikethrough text is rendered with.
+  pub strikethrough_tag:                 StrikethroughTag,
+  /// Where the `title` of links without one is taken from.
+  pub link_title_fallback:               LinkTitleFallback,
+  /// Where the `<figcaption>` of images rendered with `figure_with_caption` is
+  /// taken from.
+  pub figure_caption:                    FigureCaption,
+  /// The tag that superscript text is rendered with, in place of `<sup>`.
+  pub sup_tag:                           Option<HtmlName>,
+  /// The `class` attribute of superscript text.
+  pub sup_class:                         Option<String>,
+  /// The tag that subscript text is rendered with, in place of `<sub>`.
+  pub sub_tag:                           Option<HtmlName>,
+  /// The `class` attribute of subscript text.
+  pub sub_class:                         Option<String>,
+  /// Customizes the element that spoilers are rendered with.
+  pub spoiler:                           Option<Spoiler>,
+  /// Chooses each of the substitutions made by the `smart` parse option on
+  /// its own, in place of that option.
+  pub typography:                        Option<Typography>,
+  /// Encodes the non-ASCII characters of the output as character references.
+  /// See [`crate::entities`] for details.
+  pub encode_entities:                   EncodeEntities,
+  /// Rewrites or removes each attribute of the output, given its tag name,
+  /// name, and value. See [`crate::attributes`] for details.
+  #[serde(deserialize_with = "callback::deserialize")]
+  pub attribute_filter:                  Option<Function>,
+}
+
+impl Default for RenderOptions {
+  fn default() -> Self {
+    Self {
+      preserve_bullet_style_in_html:     false,
+      table_of_contents:                 None,
+      autolink_exclude_patterns:         Vec::new(),
+      heading_anchor_wrap_heading:       false,
+      heading_classes:                   BTreeMap::new(),
+      fix_heading_levels:                false,
+      number_headings:                   None,
+      highlight_inline_code:             false,
+      header_ids_only_when_referenced:   false,
+      framework_escaping:                FrameworkEscaping::None,
+      code_tabs_to_spaces:               None,
+      code_line_numbers:                 None,
+      language_aliases:                  HashMap::new(),
+      sanitize_html:                     None,
+      footnote_backref:                  None,
+      max_output_bytes:                  None,
+      task_list_progress:                false,
+      task_list_progress_include_nested: false,
+      respect_ordered_list_start:        true,
+      force_start_one:                   false,
+      task_list_inputs:                  None,
+      footnotes:                         None,
+      footnote_style:                    FootnoteStyle::Section,
+      sourcepos_blocks_only:             false,
+      inline_sourcepos:                  true,
+      line_breaks:                       None,
+      hardbreaks_scope:                  None,
+      raw_html:                          None,
+      raw_html_allow_tags:               BTreeSet::new(),
+      image_dimensions:                  None,
+      max_image_width:                   None,
+      max_image_height:                  None,
+      code_block_renderer:               None,
+      math_renderer:                     None,
+      on_heading:                        None,
+      strip_comments:                    false,
+      strip_conditional_comments:        false,
+      images:                            None,
+      table_align_classes:               false,
+      description_lists:                 None,
+      minify:                            false,
+      pretty_print:                      false,
+      align_table_columns:               false,
+      preserve_soft_breaks:              false,
+      line_ending:                       LineEnding::Lf,
+      disable_plugins:                   false,
+      document:                          None,
+      id_scope:                          None,
+      alerts:                            None,
+      strikethrough_tag:                 StrikethroughTag::Del,
+      link_title_fallback:               LinkTitleFallback::None,
+      figure_caption:                    FigureCaption::Title,
+      sup_tag:                           None,
+      sup_class:                         None,
+      sub_tag:                           None,
+      sub_class:                         None,
+      spoiler:                           None,
+      typography:                        None,
+      encode_entities:                   EncodeEntities::Utf8,
+      attribute_filter:                  None,
+    }
+  }
+}
+
+/// The frontend framework whose template syntax is escaped in text content,
+/// by replacing the special characters with numeric character references.
+///
+/// - `vue`: the `{{` and `}}` interpolation delimiters.
+/// - `angular`: every `{` and `}` (covering both interpolation and ICU
+///   expressions), the `[(` and `)]` of two-way bindings, and every `*` (as
+///   used by structural directives such as `*ngIf`).
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FrameworkEscaping {
+  #[default]
+  None,
+  Vue,
+  Angular,
+}
+
+/// The tag that strikethrough text is rendered with: Comrak's `<del>`, or
+/// `<s>` or `<strike>` for text that is no longer accurate rather than
+/// deleted.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum StrikethroughTag {
+  #[default]
+  Del,
+  S,
+  Strike,
+}
+
+impl StrikethroughTag {
+  pub fn as_str(self) -> &'static str {
+    match self {
+      | Self::Del => "del",
+      | Self::S => "s",
+      | Self::Strike => "strike",
+    }
+  }
+}
+
+/// Where the `title` attribute of links without a title is taken from:
+/// nowhere, the text of the link, or the URL it's rendered with.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LinkTitleFallback {
+  #[default]
+  None,
+  Text,
+  Url,
+}
+
+/// Where the caption of an image rendered with `figure_with_caption` is taken
+/// from: its title, as Comrak does, its alt text, or nowhere.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FigureCaption {
+  #[default]
+  Title,
+  Alt,
+  None,
+}
+
+/// How the non-ASCII characters of the HTML output are written: as they are,
+/// as named character references where HTML has one (falling back to numeric
+/// ones), or always as numeric character references.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EncodeEntities {
+  #[default]
+  Utf8,
+  Named,
+  Numeric,
+}
+
+/// The line endings of CommonMark output: Comrak's `\n`, `\r\n`, or whichever
+/// of the two ends most lines of the input (`\n` when formatting an AST, which
+/// has no input to go by).
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LineEnding {
+  #[default]
+  Lf,
+  Crlf,
+  Preserve,
+}
+
+impl LineEnding {
+  /// Resolves `preserve` to the line ending used by most lines of `md`.
+  pub fn resolve(self, md: &str) -> Self {
+    if self != Self::Preserve {
+      return self;
+    }
+    let lines = md.matches('\n').count();
+    let crlf = md.matches("\r\n").count();
+    if crlf * 2 > lines {
+      Self::Crlf
+    } else {
+      Self::Lf
+    }
+  }
+}
+
+/// The name of an HTML element or attribute, which is rejected unless it
+/// starts with an ASCII letter and consists solely of ASCII letters, digits,
+/// and the `-`, `_`, `:`, and `.` characters (as in custom elements and
+/// namespaced attributes).
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Deserialize)]
+#[serde(try_from = "String")]
+pub struct HtmlName(String);
+
+impl HtmlName {
+  pub fn as_str(&self) -> &str {
+    &self.0
+  }
+}
+
+impl TryFrom<String> for HtmlName {
+  type Error = String;
+
+  fn try_from(name: String) -> Result<Self, Self::Error> {
+    let mut chars = name.chars();
+    if chars.next().is_some_and(|c| c.is_ascii_alphabetic())
+      && chars.all(|c| c.is_ascii_alphanumeric() || "-_:.".contains(c))
+    {
+      Ok(Self(name))
+    } else {
+      Err(format!("invalid HTML name {name:?}"))
+    }
+  }
+}
+
+/// The rendering of soft line breaks (ordinary line endings within a
+/// paragraph). Explicit line breaks, written with a trailing backslash or two
+/// trailing spaces, are always rendered as `<br />`.
+///
+/// - `soft`: kept as line endings, like Comrak's `hardbreaks: false`.
+/// - `hard`: rendered as `<br />`, like Comrak's `hardbreaks: true`.
+/// - `explicit`: rendered as spaces, so that only explicit line breaks end a
+///   line.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LineBreaks {
+  Soft,
+  Hard,
+  Explicit,
+}
+
+/// Where soft line breaks are rendered as `<br />`, with those elsewhere kept
+/// as line endings.
+///
+/// - `all`: everywhere, like Comrak's `hardbreaks: true`.
+/// - `paragraphs`: in paragraphs, except those inside list items.
+/// - `none`: nowhere, like Comrak's `hardbreaks: false`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HardbreaksScope {
+  All,
+  Paragraphs,
+  None,
+}
+
+/// The rendering of raw HTML in the document, as a single option in place of
+/// the combination of Comrak's `escape`, `unsafe_`, and `tagfilter` options.
+///
+/// - `escape`: escaped, so that it is displayed as text.
+/// - `filter`: rendered as-is, except for the tags disallowed by GFM's tag
+///   filter (such as `<script>` and `<iframe>`), which are escaped.
+/// - `allow`: rendered as-is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RawHtml {
+  Escape,
+  Filter,
+  Allow,
+}
+
+/// The `sanitize_html` render option, which is either a boolean or a custom
+/// allowlist.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+pub enum SanitizeHtml {
+  Enabled(bool),
+  Custom(SanitizeOptions),
+}
+
+/// A custom allowlist for the `sanitize_html` render option. Omitted fields
+/// fall back to the default allowlist.
+#[derive(Debug, Default, Clone, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct SanitizeOptions {
+  /// The names of the allowed tags.
+  pub allowed_tags:       Option<Vec<String>>,
+  /// The allowed attributes, keyed by tag name, with `*` matching all tags.
+  pub allowed_attributes: Option<HashMap<String, Vec<String>>>,
+}
+
+/// Options for the backreference links of footnote definitions.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct FootnoteBackref {
+  /// The text of each backreference link.
+  pub symbol:   String,
+  /// Where the backreference links are placed within the definition.
+  pub position: BackrefPosition,
+}
+
+impl Default for FootnoteBackref {
+  fn default() -> Self {
+    Self {
+      symbol:   "↩".to_string(),
+      position: BackrefPosition::End,
+    }
+  }
+}
+
+/// Options for the IDs and labels of footnotes, e.g. for localization, or to
+/// avoid ID collisions between multiple documents on the same page.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct Footnotes {
+  /// The `aria-label` of backreference links, followed by the number of the
+  /// reference they link to.
+  pub backref_label: String,
+  /// The prefix of the `id` of each footnote reference.
+  pub ref_prefix:    String,
+  /// The prefix of the `id` of each footnote definition.
+  pub def_prefix:    String,
+}
+
+impl Default for Footnotes {
+  fn default() -> Self {
+    Self {
+      backref_label: "Back to reference".to_string(),
+      ref_prefix:    "fnref-".to_string(),
+      def_prefix:    "fn-".to_string(),
+    }
+  }
+}
+
+/// The position of footnote backreference links within their definition.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BackrefPosition {
+  Start,
+  #[default]
+  End,
+}
+
+/// Where footnotes are rendered: in Comrak's section at the end of the
+/// document, or inline at each of their references.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FootnoteStyle {
+  #[default]
+  Section,
+  Inline,
+}
+
+/// Options for rendering task list checkboxes as form inputs.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct TaskListInputs {
+  /// Whether the checkboxes can be toggled, rather than being disabled.
+  pub interactive: bool,
+  /// The prefix of each checkbox's `name`, which is followed by a `-` and the
+  /// index of the task item within the document.
+  pub name_prefix: String,
+}
+
+impl Default for TaskListInputs {
+  fn default() -> Self {
+    Self {
+      interactive: false,
+      name_prefix: "task".to_string(),
+    }
+  }
+}
+
+/// Options for the loading attributes of images.
+#[derive(Debug, Default, Clone, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct Images {
+  /// Adds `loading="lazy"`, deferring the loading of offscreen images.
+  pub lazy:     bool,
+  /// The value of the `decoding` attribute, if any.
+  pub decoding: Option<ImageDecoding>,
+}
+
+/// The `decoding` hint given to the browser for images.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ImageDecoding {
+  Async,
+  Sync,
+  Auto,
+}
+
+impl ImageDecoding {
+  pub fn as_str(self) -> &'static str {
+    match self {
+      | Self::Async => "async",
+      | Self::Sync => "sync",
+      | Self::Auto => "auto",
+    }
+  }
+}
+
+/// Options for the rendering of description lists.
+#[derive(Debug, Default, Clone, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct DescriptionLists {
+  /// The `class` of each `<dl>` element.
+  pub class_name: Option<String>,
+}
+
+/// Options for the standalone HTML document that the output is wrapped in.
+#[derive(Debug, Default, Clone, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct HtmlDocument {
+  /// The `<title>` of the document, taken from the first `<h1>` if omitted.
+  pub title:      Option<String>,
+  /// The `lang` attribute of the `<html>` element.
+  pub lang:       Option<String>,
+  /// The URL of a stylesheet to link to.
+  pub stylesheet: Option<String>,
+}
+
+/// Options for the rendering of spoilers.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct Spoiler {
+  /// The tag of the element, `span` by default.
+  pub tag:        Option<HtmlName>,
+  /// The `class` of the element, omitted when empty.
+  pub class_name: String,
+  /// Extra attributes of the element.
+  pub attributes: BTreeMap<HtmlName, String>,
+}
+
+impl Default for Spoiler {
+  fn default() -> Self {
+    Self {
+      tag:        None,
+      class_name: "spoiler".to_string(),
+      attributes: BTreeMap::new(),
+    }
+  }
+}
+
+/// Options for the rendering of GitHub-style alerts.
+#[derive(Debug, Default, Clone, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct Alerts {
+  /// The titles of alerts without an explicit one, keyed by their type (such
+  /// as `note` or `warning`), matched case-insensitively.
+  pub titles:       HashMap<String, String>,
+  /// Adds an empty `<span class="markdown-alert-icon">` before the title of
+  /// each alert, for an icon to be added with CSS.
+  pub include_icon: bool,
+}
+
+/// Options for the generated table of contents.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct TableOfContents {
+  /// The text of the paragraph to replace with the table of contents.
+  pub marker:    String,
+  /// The deepest heading level to include in the table of contents.
+  pub max_level: u8,
+}
+
+impl Default for TableOfContents {
+  fn default() -> Self {
+    Self { marker: "[[TOC]]".to_string(), max_level: 6 }
+  }
+}
+
+/// The typographic substitutions made when parsing, by the `typography` render
+/// option. Comrak's `smart` option makes all of them or none, so the document
+/// is parsed with it enabled, and the ones left out are reverted afterwards.
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(default)]
+pub struct Typography {
+  /// Curly quotes and apostrophes in place of straight ones.
+  pub quotes:   bool,
+  /// En and em dashes in place of `--` and `---`.
+  pub dashes:   bool,
+  /// An ellipsis in place of `...`.
+  pub ellipses: bool,
+}
+
+impl Default for Typography {
+  fn default() -> Self {
+    Self { quotes: true, dashes: true, ellipses: true }
+  }
+}
+
+impl Typography {
+  /// Returns `true` if any substitution is made.
+  pub fn any(&self) -> bool {
+    self.quotes || self.dashes || self.ellipses
+  }
+}
+
+/// Options for numbering headings.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct NumberHeadings {
+  pub enabled:     bool,
+  /// The level of the headings numbered `1`, `2`, and so on. Headings above
+  /// it aren't numbered, but restart the numbering of those below them.
+  pub start_level: u8,
+  /// Written between the number and the text of a heading.
+  pub separator:   String,
+}
+
+impl Default for NumberHeadings {
+  fn default() -> Self {
+    Self {
+      enabled:     false,
+      start_level: 1,
+      separator:   ". ".to_string(),
+    }
+  }
+}
+
+/// Options for numbering the lines of code blocks.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct CodeLineNumbers {
+  pub enabled: bool,
+  /// The number of the first line of each code block.
+  pub start:   usize,
+}
+
+impl Default for CodeLineNumbers {
+  fn default() -> Self {
+    Self { enabled: false, start: 1 }
+  }
+}
+
+/// Deserializes an optional JS callback, passing the function through as-is
+/// rather than attempting to convert it into a Rust value. Anything that isn't
+/// a function (including `null` and `undefined`) is treated as `None`.
+mod callback {
+  use js_sys::Function;
+  use serde::Deserializer;
+  use wasm_bindgen::JsCast;
+  use wasm_bindgen::JsValue;
+
+  pub fn deserialize<'de, D: Deserializer<'de>>(
+    de: D,
+  ) -> Result<Option<Function>, D::Error> {
+    let value: JsValue = serde_wasm_bindgen::preserve::deserialize(de)?;
+    Ok(value.dyn_into::<Function>().ok())
+  }
+}
+
+/// Deserializes a map keyed by heading level. The keys of a JS object are
+/// always strings, so each one is parsed into a level from 1 to 6.
+mod heading_levels {
+  use std::collections::BTreeMap;
+
+  use serde::Deserialize;
+  use serde::Deserializer;
+  use serde::de::Error;
+
+  pub fn deserialize<'de, D: Deserializer<'de>>(
+    de: D,
+  ) -> Result<BTreeMap<u8, String>, D::Error> {
+    let map = BTreeMap::<String, String>::deserialize(de)?;
+    map
+      .into_iter()
+      .map(|(key, value)| match key.parse() {
+        | Ok(level @ 1..=6) => Ok((level, value)),
+        | _ => Err(D::Error::custom(format!("invalid heading level: {key}"))),
+      })
+      .collect()
+  }
+}
@@ -0,0 +1,84 @@
+//! URL patterns used by options that include or exclude links by URL.
+//!
+//! A pattern written as `/source/flags` is compiled into a JS `RegExp`, and
+//! anything else is treated as a glob, where `*` matches any sequence of
+//! characters and `?` matches exactly one character.
+
+use js_sys::Array;
+use js_sys::Reflect;
+use js_sys::RegExp;
+use wasm_bindgen::JsCast;
+use wasm_bindgen::JsValue;
+
+/// A compiled URL pattern.
+#[derive(Debug, Clone)]
+pub enum Pattern {
+  Glob(Vec<char>),
+  Regex(RegExp),
+}
+
+impl Pattern {
+  /// Compiles a pattern, returning the `SyntaxError` thrown by the `RegExp`
+  /// constructor if the pattern is an invalid regular expression.
+  pub fn new(pattern: &str) -> Result<Self, JsValue> {
+    let regex = pattern
+      .strip_prefix('/')
+      .and_then(|rest| rest.rsplit_once('/'))
+      .filter(|(source, _)| !source.is_empty());
+    let Some((source, flags)) = regex else {
+      return Ok(Self::Glob(pattern.chars().collect()));
+    };
+    // `RegExp::new` can't report errors, so call the constructor ourselves
+    let ctor = RegExp::new("", "").constructor();
+    let args = Array::of2(&source.into(), &flags.into());
+    let regex = Reflect::construct(&ctor, &args)?;
+    Ok(Self::Regex(regex.unchecked_into()))
+  }
+
+  /// Compiles a list of patterns, failing on the first invalid pattern.
+  pub fn compile(patterns: &[String]) -> Result<Vec<Self>, JsValue> {
+    patterns.iter().map(|p| Self::new(p)).collect()
+  }
+
+  /// Returns `true` if the pattern matches the entire input (for globs), or
+  /// any part of it (for regular expressions, which can use `^` and `$`).
+  pub fn matches(&self, input: &str) -> bool {
+    match self {
+      | Self::Glob(glob) => {
+        glob_matches(glob, &input.chars().collect::<Vec<_>>())
+      }
+      | Self::Regex(regex) => {
+        // global and sticky regexes are stateful, so always start afresh
+        regex.set_last_index(0);
+        regex.test(input)
+      }
+    }
+  }
+}
+
+/// Iterative wildcard matching with single-star backtracking.
+fn glob_matches(glob: &[char], input: &[char]) -> bool {
+  let (mut g, mut i) = (0, 0);
+  let mut star: Option<(usize, usize)> = None;
+  while i < input.len() {
+    match glob.get(g) {
+      | Some('*') => {
+        star = Some((g, i));
+        g += 1;
+      }
+      | Some(&c) if c == '?' || c == input[i] => {
+        g += 1;
+        i += 1;
+      }
+      | _ => match star {
+        | Some((sg, si)) => {
+          g = sg + 1;
+          i = si + 1;
+          star = Some((sg, si + 1));
+        }
+        | None => return false,
+      },
+    }
+  }
+  glob[g..].iter().all(|&c| c == '*')
+}
@@ -0,0 +1,156 @@
+//! Resolution of reference links from the `reference_map` parse option.
+//!
+//! Comrak has no way to seed its table of link reference definitions, but it
+//! does consult the broken link callback for every reference it can't find.
+//! The map is consulted there instead, ahead of any user-provided callback,
+//! which only runs for the labels that are missing from the map.
+//!
+//! Links resolved by either one are indistinguishable from authored links in
+//! the AST, so [`Resolutions`] can be used to keep track of them.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::sync::PoisonError;
+
+use comrak::ResolvedReference;
+use comrak::nodes::AstNode;
+use comrak::nodes::NodeValue;
+use comrak::nodes::Sourcepos;
+use comrak::options::BrokenLinkCallback as ComrakBrokenLinkCallback;
+use comrak::options::BrokenLinkReference;
+use serde::Serialize;
+
+use crate::BrokenLinkCallback;
+use crate::options::Reference;
+
+/// The prefix of the placeholder URLs given to links resolved through
+/// [`Resolutions`]. Comrak replaces any NUL characters in its input, so these
+/// can't collide with the URL of an authored link.
+const PLACEHOLDER: &str = "\0resolved:";
+
+/// A broken link callback that resolves references from a preloaded map,
+/// falling back to the user's callback for labels that aren't in it.
+#[derive(Debug)]
+pub struct ReferenceMap {
+  references: HashMap<String, ResolvedReference>,
+  fallback:   Option<BrokenLinkCallback>,
+}
+
+impl ReferenceMap {
+  pub fn new(
+    references: &HashMap<String, Reference>,
+    fallback: Option<BrokenLinkCallback>,
+  ) -> Self {
+    let references = references
+      .iter()
+      .map(|(label, reference)| {
+        let resolved = ResolvedReference {
+          url:   reference.url.clone(),
+          title: reference.title.clone(),
+        };
+        (normalize_label(label), resolved)
+      })
+      .collect();
+    Self { references, fallback }
+  }
+}
+
+impl ComrakBrokenLinkCallback for ReferenceMap {
+  fn resolve(
+    &self,
+    reference: BrokenLinkReference,
+  ) -> Option<ResolvedReference> {
+    match self.references.get(&normalize_label(reference.original)) {
+      | Some(resolved) => Some(resolved.clone()),
+      | None => self.fallback.as_ref()?.resolve(reference),
+    }
+  }
+}
+
+/// A link or image whose reference was resolved by a broken link callback
+/// (or the reference map), rather than by a definition in the document.
+#[derive(Debug, Serialize)]
+pub struct ResolvedLink {
+  /// The label of the reference, as written in the document.
+  pub label:     String,
+  pub url:       String,
+  pub title:     String,
+  pub sourcepos: Sourcepos,
+}
+
+/// A broken link callback that records every reference resolved by the one
+/// it wraps.
+///
+/// The callback has no way of knowing which node its result is used for, so
+/// each resolved link is given a placeholder URL while parsing, which is
+/// swapped for the real one by [`Resolutions::restore`].
+pub struct Resolutions<'c> {
+  inner:    Arc<dyn ComrakBrokenLinkCallback + 'c>,
+  resolved: Mutex<Vec<(String, ResolvedReference)>>,
+}
+
+impl<'c> Resolutions<'c> {
+  pub fn new(inner: Arc<dyn ComrakBrokenLinkCallback + 'c>) -> Self {
+    Self { inner, resolved: Mutex::default() }
+  }
+
+  /// Restores the URLs of the links and images that were resolved while
+  /// parsing `root`, returning them in document order.
+  pub fn restore<'a>(&self, root: &'a AstNode<'a>) -> Vec<ResolvedLink> {
+    let resolved = self.resolved.lock().unwrap_or_else(PoisonError::into_inner);
+    let mut links = Vec::new();
+    for node in root.descendants() {
+      let mut data = node.data.borrow_mut();
+      let sourcepos = data.sourcepos;
+      let (NodeValue::Link(ref mut nl) | NodeValue::Image(ref mut nl)) =
+        data.value
+      else {
+        continue;
+      };
+      let index = nl
+        .url
+        .strip_prefix(PLACEHOLDER)
+        .and_then(|i| i.parse().ok());
+      let Some((label, reference)) = index.and_then(|i| resolved.get(i)) else {
+        continue;
+      };
+      nl.url.clone_from(&reference.url);
+      links.push(ResolvedLink {
+        label: label.clone(),
+        url: reference.url.clone(),
+        title: reference.title.clone(),
+        sourcepos,
+      });
+    }
+    links
+  }
+}
+
+impl ComrakBrokenLinkCallback for Resolutions<'_> {
+  fn resolve(
+    &self,
+    reference: BrokenLinkReference,
+  ) -> Option<ResolvedReference> {
+    let label = reference.original.to_string();
+    let resolved = self.inner.resolve(reference)?;
+    let mut list = self.resolved.lock().unwrap_or_else(PoisonError::into_inner);
+    let placeholder = ResolvedReference {
+      url:   format!("{PLACEHOLDER}{}", list.len()),
+      title: resolved.title.clone(),
+    };
+    list.push((label, resolved));
+    Some(placeholder)
+  }
+}
+
+/// Normalizes a link label for matching, as described by the CommonMark spec:
+/// surrounding whitespace is removed, inner whitespace is collapsed to single
+/// spaces, and the label is matched case-insensitively.
+fn normalize_label(label: &str) -> String {
+  label
+    .split_whitespace()
+    .collect::<Vec<_>>()
+    .join(" ")
+    .to_lowercase()
+}
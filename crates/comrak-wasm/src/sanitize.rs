@@ -0,0 +1,319 @@
+//! An allowlist-based HTML sanitizer, in the spirit of [ammonia].
+//!
+//! Like the AMP conversion, this post-processes the rendered HTML with the tag
+//! rewriter. Tags that aren't allowed are removed while keeping their content,
+//! except for elements whose content is never safe to keep (such as `<script>`
+//! and `<style>`), which are removed entirely. Attributes that aren't allowed
+//! are removed, as are URLs with a scheme other than those in [`URL_SCHEMES`].
+//!
+//! [ammonia]: https://docs.rs/ammonia
+
+use std::collections::HashMap;
+use std::collections::HashSet;
+
+use crate::html::Action;
+use crate::html::Tag;
+use crate::html::TagKind;
+use crate::html::Token;
+use crate::html::close_incomplete;
+use crate::html::complete_prefix_len;
+use crate::html::rewrite_tags;
+use crate::html::tokenize;
+use crate::html::unescape;
+use crate::options::SanitizeHtml;
+use crate::options::SanitizeOptions;
+
+/// Elements that are removed along with all of their content.
+const CLEAN_CONTENT_TAGS: &[&str] = &[
+  "embed", "iframe", "math", "noembed", "noframes", "noscript", "object",
+  "plaintext", "script", "style", "svg", "template", "textarea", "title",
+  "xmp",
+];
+
+/// The tags allowed by default, covering common formatting elements and
+/// everything Comrak renders from Markdown.
+const DEFAULT_TAGS: &[&str] = &[
+  "a", "abbr", "acronym", "area", "article", "aside", "b", "bdi", "bdo",
+  "blockquote", "br", "caption", "center", "cite", "code", "col", "colgroup",
+  "data", "dd", "del", "details", "dfn", "div", "dl", "dt", "em", "figcaption",
+  "figure", "footer", "h1", "h2", "h3", "h4", "h5", "h6", "header", "hgroup",
+  "hr", "i", "img", "input", "ins", "kbd", "li", "map", "mark", "nav", "ol",
+  "p", "pre", "q", "rp", "rt", "rtc", "ruby", "s", "samp", "section", "small",
+  "span", "strike", "strong", "sub", "summary", "sup", "table", "tbody", "td",
+  "tfoot", "th", "thead", "time", "tr", "tt", "u", "ul", "var", "wbr",
+];
+
+/// The attributes allowed by default, keyed by tag name. Attributes listed
+/// under `*` are allowed on every tag.
+const DEFAULT_ATTRIBUTES: &[(&str, &[&str])] = &[
+  (
+    "*",
+    &[
+      "class", "dir", "lang", "title",
+    ],
+  ),
+  (
+    "a",
+    &[
+      "href",
+      "hreflang",
+      "id",
+      "aria-hidden",
+      "data-footnote-ref",
+    ],
+  ),
+  ("bdo", &["dir"]),
+  ("blockquote", &["cite"]),
+  ("col", &["align", "span"]),
+  ("colgroup", &["align", "span"]),
+  (
+    "del",
+    &[
+      "cite", "datetime",
+    ],
+  ),
+  ("h1", &["id"]),
+  ("h2", &["id"]),
+  ("h3", &["id"]),
+  ("h4", &["id"]),
+  ("h5", &["id"]),
+  ("h6", &["id"]),
+  (
+    "img",
+    &[
+      "align", "alt", "decoding", "height", "loading", "src", "width",
+    ],
+  ),
+  (
+    "input",
+    &[
+      "checked", "disabled", "type",
+    ],
+  ),
+  (
+    "ins",
+    &[
+      "cite", "datetime",
+    ],
+  ),
+  ("li", &["id"]),
+  ("ol", &["start"]),
+  ("q", &["cite"]),
+  ("section", &["data-footnotes"]),
+  ("sup", &["id"]),
+  ("table", &["align"]),
+  (
+    "td",
+    &[
+      "align", "colspan", "rowspan",
+    ],
+  ),
+  (
+    "th",
+    &[
+      "align", "colspan", "rowspan", "scope",
+    ],
+  ),
+  ("time", &["datetime"]),
+];
+
+/// Attributes whose values are URLs, and are subject to the scheme check.
+const URL_ATTRIBUTES: &[&str] = &[
+  "action", "cite", "data", "formaction", "href", "poster", "src", "xlink:href",
+];
+
+/// Attributes whose values are loaded as a document, and so can run script
+/// regardless of their scheme.
+const DOCUMENT_ATTRIBUTES: &[&str] = &["srcdoc"];
+
+/// The URL schemes allowed in [`URL_ATTRIBUTES`]. Relative URLs are always
+/// allowed.
+const URL_SCHEMES: &[&str] = &[
+  "http", "https", "mailto", "tel",
+];
+
+/// The tags and attributes permitted by the sanitizer.
+#[derive(Debug, Clone)]
+pub struct Allowlist {
+  tags:       HashSet<String>,
+  attributes: HashMap<String, HashSet<String>>,
+}
+
+impl Allowlist {
+  /// Builds the allowlist for the `sanitize_html` render option, or returns
+  /// `None` if sanitizing is disabled.
+  pub fn from_options(option: &SanitizeHtml) -> Option<Self> {
+    match option {
+      | SanitizeHtml::Enabled(false) => None,
+      | SanitizeHtml::Enabled(true) => Some(Self::default()),
+      | SanitizeHtml::Custom(custom) => Some(Self::custom(custom)),
+    }
+  }
+
+  fn custom(options: &SanitizeOptions) -> Self {
+    let mut allowlist = Self::default();
+    if let Some(ref tags) = options.allowed_tags {
+      allowlist.tags = tags.iter().map(|t| t.to_ascii_lowercase()).collect();
+    }
+    if let Some(ref attributes) = options.allowed_attributes {
+      allowlist.attributes = attributes
+        .iter()
+        .map(|(tag, attrs)| {
+          let attrs = attrs.iter().map(|a| a.to_ascii_lowercase()).collect();
+          (tag.to_ascii_lowercase(), attrs)
+        })
+        .collect();
+    }
+    allowlist
+  }
+
+  fn allows_attr(&self, tag: &str, attr: &str) -> bool {
+    [tag, "*"].iter().any(|key| {
+      self
+        .attributes
+        .get(*key)
+        .is_some_and(|attrs| attrs.contains(attr))
+    })
+  }
+}
+
+impl Default for Allowlist {
+  fn default() -> Self {
+    let tags = DEFAULT_TAGS.iter().map(|t| t.to_string()).collect();
+    let attributes = DEFAULT_ATTRIBUTES
+      .iter()
+      .map(|(tag, attrs)| {
+        (
+          tag.to_string(),
+          attrs.iter().map(|a| a.to_string()).collect(),
+        )
+      })
+      .collect();
+    Self { tags, attributes }
+  }
+}
+
+/// Removes every tag and attribute from `html` that isn't in the allowlist. A
+/// tag cut off by the end of `html` is removed as well, and any other `<` that
+/// doesn't begin a tag, comment, or declaration is escaped.
+pub fn sanitize(html: &str, allowlist: &Allowlist) -> String {
+  rewrite_tags(&close_incomplete(html), |tag| {
+    if CLEAN_CONTENT_TAGS.contains(&&*tag.name) {
+      return Action::DropElement;
+    }
+    if !allowlist.tags.contains(&tag.name) {
+      return Action::Replace(String::new());
+    }
+    if tag.is_start() {
+      sanitize_attrs(tag, allowlist);
+    }
+    Action::Keep
+  })
+}
+
+fn sanitize_attrs(tag: &mut Tag, allowlist: &Allowlist) {
+  let name = tag.name.clone();
+  tag.retain(|attr| {
+    let attr_name = attr.name.to_ascii_lowercase();
+    if !allowlist.allows_attr(&name, &attr_name) {
+      return false;
+    }
+    match attr.value {
+      | Some(ref value) if URL_ATTRIBUTES.contains(&&*attr_name) => {
+        is_safe_url(&unescape(value))
+      }
+      | _ => true,
+    }
+  });
+  // raw HTML may contain unescaped angle brackets in attribute values, which
+  // some parsers (and mutation XSS payloads) treat inconsistently
+  for value in tag.attrs.iter_mut().filter_map(|a| a.value.as_mut()) {
+    if value.contains(['<', '>']) {
+      *value = value.replace('<', "&lt;").replace('>', "&gt;");
+    }
+  }
+}
+
+/// Returns the length of the longest prefix of `html` that [`sanitize`] can
+/// process apart from the rest of `html` with the same result: one that ends
+/// outside of any tag or comment, and of any element removed along with its
+/// content.
+pub fn sanitizable_prefix_len(html: &str) -> usize {
+  let html = &html[..complete_prefix_len(html)];
+  let mut len = 0;
+  let mut offset = 0;
+  // name and nesting depth of an element being removed, as in `rewrite_tags`
+  let mut dropping: Option<(String, usize)> = None;
+  for token in tokenize(html) {
+    offset += match token {
+      | Token::Text(raw) | Token::Tag(_, raw) | Token::Other(raw) => raw.len(),
+    };
+    if let Token::Tag(ref tag, _) = token {
+      match dropping {
+        | Some((ref name, ref mut depth)) if tag.name == *name => {
+          match tag.kind {
+            | TagKind::Open => *depth += 1,
+            | TagKind::Close if *depth == 0 => dropping = None,
+            | TagKind::Close => *depth -= 1,
+            | TagKind::SelfClosing => {}
+          }
+        }
+        | Some(_) => {}
+        | None
+          if tag.kind == TagKind::Open
+            && CLEAN_CONTENT_TAGS.contains(&&*tag.name) =>
+        {
+          dropping = Some((tag.name.clone(), 0));
+        }
+        | None => {}
+      }
+    }
+    if dropping.is_none() {
+      len = offset;
+    }
+  }
+  len
+}
+
+/// Returns `true` if an attribute can't be used to run script: it's neither an
+/// event handler nor a document, and any URL it holds is relative or uses an
+/// allowed scheme. The value is expected in its raw, escaped form.
+pub fn is_safe_attr(name: &str, value: Option<&str>) -> bool {
+  let name = name.to_ascii_lowercase();
+  if name.starts_with("on") || DOCUMENT_ATTRIBUTES.contains(&&*name) {
+    return false;
+  }
+  match value {
+    | Some(value) if URL_ATTRIBUTES.contains(&&*name) => {
+      is_safe_url(&unescape(value))
+    }
+    | _ => true,
+  }
+}
+
+/// Returns `true` if the URL is relative or uses an allowed scheme.
+///
+/// Browsers ignore whitespace and control characters within a scheme, so
+/// these are removed before checking it. Any character reference left in the
+/// part of the URL where a scheme could appear (e.g. `&#106;avascript:` or
+/// `javascript&colon;`) causes the URL to be rejected outright.
+fn is_safe_url(url: &str) -> bool {
+  let url: String = url
+    .chars()
+    .filter(|c| !c.is_ascii_whitespace() && !c.is_control())
+    .collect();
+  let end = url
+    .find([
+      ':', '/', '?', '#',
+    ])
+    .unwrap_or(url.len());
+  let prefix = &url[..end];
+  if prefix.contains('&') {
+    return false;
+  }
+  if url[end..].starts_with(':') {
+    URL_SCHEMES.iter().any(|s| prefix.eq_ignore_ascii_case(s))
+  } else {
+    true
+  }
+}
@@ -0,0 +1,593 @@
+//! A stable, versioned JSON schema for persisting ASTs.
+//!
+//! The AST returned by `parse_document` mirrors Comrak's arena, including the
+//! state of the parser (such as `open` and `line_offsets`), and its shape
+//! changes between versions of Comrak. The schema only keeps what is needed
+//! to render the tree again: a nested tree of nodes, each with its value,
+//! source position, and children, wrapped in an object with the version of
+//! the schema it was written with. Older versions are migrated when read.
+//!
+//! The values are the crate's own types, mapped to and from Comrak's
+//! [`NodeValue`], so a change to the fields of a Comrak node doesn't change
+//! the schema; only a change to these types does, with a new version.
+//!
+//! The versions of the schema are:
+//!
+//! - `0`: the AST returned by `parse_document` itself, as persisted before
+//!   the schema existed. It has no `schemaVersion` field.
+//! - `1`: `{ "schemaVersion": 1, "root": Node }`, where each `Node` is
+//!   `{ "value", "sourcepos", "children"? }`, and `value` and `sourcepos` are
+//!   serialized as Comrak serializes them.
+//! - `2`: the same tree, where `value` is a [`StableValue`], tagged by its
+//!   `type` and holding only the fields needed to render it, and `sourcepos`
+//!   is `[startLine, startColumn, endLine, endColumn]`.
+
+use comrak::Arena;
+use comrak::nodes::AlertType;
+use comrak::nodes::AstNode;
+use comrak::nodes::ListDelimType;
+use comrak::nodes::ListType;
+use comrak::nodes::NodeAlert;
+use comrak::nodes::NodeCode;
+use comrak::nodes::NodeCodeBlock;
+use comrak::nodes::NodeDescriptionItem;
+use comrak::nodes::NodeFootnoteDefinition;
+use comrak::nodes::NodeFootnoteReference;
+use comrak::nodes::NodeHeading;
+use comrak::nodes::NodeHtmlBlock;
+use comrak::nodes::NodeLink;
+use comrak::nodes::NodeList;
+use comrak::nodes::NodeMath;
+use comrak::nodes::NodeMultilineBlockQuote;
+use comrak::nodes::NodeShortCode;
+use comrak::nodes::NodeTable;
+use comrak::nodes::NodeValue;
+use comrak::nodes::NodeWikiLink;
+use comrak::nodes::TableAlignment;
+use serde::Deserialize;
+use serde::Serialize;
+
+/// The version of the schema written by [`to_stable`].
+pub const SCHEMA_VERSION: u32 = 2;
+
+/// A tree in the current version of the schema.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StableAst {
+  pub schema_version: u32,
+  pub root:           StableNode,
+}
+
+/// A node of a [`StableAst`], along with all of its descendants.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct StableNode {
+  pub value:     StableValue,
+  /// `[startLine, startColumn, endLine, endColumn]`
+  pub sourcepos: [usize; 4],
+  #[serde(default, skip_serializing_if = "Vec::is_empty")]
+  pub children:  Vec<StableNode>,
+}
+
+/// The value of a [`StableNode`], with one variant per kind of Comrak node.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all_fields = "camelCase")]
+pub enum StableValue {
+  Document,
+  FrontMatter {
+    literal: String,
+  },
+  BlockQuote,
+  List(StableList),
+  Item(StableList),
+  DescriptionList,
+  DescriptionItem {
+    tight: bool,
+  },
+  DescriptionTerm,
+  DescriptionDetails,
+  CodeBlock {
+    fenced:       bool,
+    fence_char:   char,
+    fence_length: usize,
+    info:         String,
+    literal:      String,
+  },
+  HtmlBlock {
+    literal: String,
+  },
+  Paragraph,
+  Heading {
+    level:  u8,
+    setext: bool,
+  },
+  ThematicBreak,
+  FootnoteDefinition {
+    name:             String,
+    total_references: u32,
+  },
+  Table {
+    alignments: Vec<StableAlignment>,
+  },
+  TableRow {
+    header: bool,
+  },
+  TableCell,
+  Text {
+    literal: String,
+  },
+  TaskItem {
+    symbol: Option<char>,
+  },
+  SoftBreak,
+  LineBreak,
+  Code {
+    num_backticks: usize,
+    literal:       String,
+  },
+  HtmlInline {
+    literal: String,
+  },
+  Raw {
+    literal: String,
+  },
+  Emph,
+  Strong,
+  Strikethrough,
+  Superscript,
+  Link {
+    url:   String,
+    title: String,
+  },
+  Image {
+    url:   String,
+    title: String,
+  },
+  FootnoteReference {
+    name:    String,
+    ref_num: u32,
+    ix:      u32,
+  },
+  ShortCode {
+    code:  String,
+    emoji: String,
+  },
+  Math {
+    dollar_math:  bool,
+    display_math: bool,
+    literal:      String,
+  },
+  MultilineBlockQuote {
+    fence_length: usize,
+    fence_offset: usize,
+  },
+  Escaped,
+  WikiLink {
+    url: String,
+  },
+  Underline,
+  Subscript,
+  SpoileredText,
+  EscapedTag {
+    literal: String,
+  },
+  Alert {
+    alert_type:   StableAlertType,
+    title:        Option<String>,
+    multiline:    bool,
+    fence_length: usize,
+    fence_offset: usize,
+  },
+  Subtext,
+}
+
+/// The fields of a list, shared by the list and each of its items.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StableList {
+  pub ordered:      bool,
+  pub start:        usize,
+  /// The delimiter of an ordered list: `.` or `)`.
+  pub delimiter:    char,
+  /// The marker of a bullet list: `-`, `+`, or `*`.
+  pub bullet_char:  char,
+  pub tight:        bool,
+  pub is_task_list: bool,
+}
+
+/// The alignment of a table column.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum StableAlignment {
+  None,
+  Left,
+  Center,
+  Right,
+}
+
+/// The type of an alert.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum StableAlertType {
+  Note,
+  Tip,
+  Important,
+  Warning,
+  Caution,
+}
+
+/// The version of a persisted tree, which is read before the rest of it to
+/// decide how it's migrated. Trees without one are version `0`.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Version {
+  #[serde(default)]
+  pub schema_version: u32,
+}
+
+/// Converts the tree under `root` into the current version of the schema.
+pub fn to_stable<'a>(root: &'a AstNode<'a>) -> StableAst {
+  StableAst {
+    schema_version: SCHEMA_VERSION,
+    root:           stable_node(root),
+  }
+}
+
+fn stable_node<'a>(node: &'a AstNode<'a>) -> StableNode {
+  let data = node.data.borrow();
+  let sp = data.sourcepos;
+  StableNode {
+    value:     stable_value(&data.value),
+    sourcepos: [
+      sp.start.line, sp.start.column, sp.end.line, sp.end.column,
+    ],
+    children:  node.children().map(stable_node).collect(),
+  }
+}
+
+/// Allocates the tree under `node` in the arena, returning its root.
+pub fn from_stable<'a>(
+  arena: &'a Arena<'a>,
+  node: StableNode,
+) -> &'a AstNode<'a> {
+  let parent = arena.alloc(node_value(node.value).into());
+  let sp = node.sourcepos;
+  parent.data.borrow_mut().sourcepos = (sp[0], sp[1], sp[2], sp[3]).into();
+  for child in node.children {
+    parent.append(from_stable(arena, child));
+  }
+  parent
+}
+
+fn stable_value(value: &NodeValue) -> StableValue {
+  let text = |s: &str| s.to_string();
+  match value {
+    | NodeValue::Document => StableValue::Document,
+    | NodeValue::FrontMatter(raw) => {
+      StableValue::FrontMatter { literal: text(raw) }
+    }
+    | NodeValue::BlockQuote => StableValue::BlockQuote,
+    | NodeValue::List(nl) => StableValue::List(stable_list(nl)),
+    | NodeValue::Item(nl) => StableValue::Item(stable_list(nl)),
+    | NodeValue::DescriptionList => StableValue::DescriptionList,
+    | NodeValue::DescriptionItem(ndi) => {
+      StableValue::DescriptionItem { tight: ndi.tight }
+    }
+    | NodeValue::DescriptionTerm => StableValue::DescriptionTerm,
+    | NodeValue::DescriptionDetails => StableValue::DescriptionDetails,
+    | NodeValue::CodeBlock(ncb) => StableValue::CodeBlock {
+      fenced:       ncb.fenced,
+      fence_char:   ncb.fence_char as char,
+      fence_length: ncb.fence_length,
+      info:         text(&ncb.info),
+      literal:      text(&ncb.literal),
+    },
+    | NodeValue::HtmlBlock(nhb) => {
+      StableValue::HtmlBlock { literal: text(&nhb.literal) }
+    }
+    | NodeValue::Paragraph => StableValue::Paragraph,
+    | NodeValue::Heading(nh) => {
+      StableValue::Heading { level: nh.level, setext: nh.setext }
+    }
+    | NodeValue::ThematicBreak => StableValue::ThematicBreak,
+    | NodeValue::FootnoteDefinition(nfd) => StableValue::FootnoteDefinition {
+      name:             text(&nfd.name),
+      total_references: nfd.total_references,
+    },
+    | NodeValue::Table(nt) => StableValue::Table {
+      alignments: nt.alignments.iter().map(|&a| a.into()).collect(),
+    },
+    | NodeValue::TableRow(header) => StableValue::TableRow { header: *header },
+    | NodeValue::TableCell => StableValue::TableCell,
+    | NodeValue::Text(literal) => StableValue::Text { literal: text(literal) },
+    | NodeValue::TaskItem(symbol) => StableValue::TaskItem { symbol: *symbol },
+    | NodeValue::SoftBreak => StableValue::SoftBreak,
+    | NodeValue::LineBreak => StableValue::LineBreak,
+    | NodeValue::Code(nc) => StableValue::Code {
+      num_backticks: nc.num_backticks,
+      literal:       text(&nc.literal),
+    },
+    | NodeValue::HtmlInline(html) => {
+      StableValue::HtmlInline { literal: text(html) }
+    }
+    | NodeValue::Raw(raw) => StableValue::Raw { literal: text(raw) },
+    | NodeValue::Emph => StableValue::Emph,
+    | NodeValue::Strong => StableValue::Strong,
+    | NodeValue::Strikethrough => StableValue::Strikethrough,
+    | NodeValue::Superscript => StableValue::Superscript,
+    | NodeValue::Link(nl) => {
+      StableValue::Link { url: text(&nl.url), title: text(&nl.title) }
+    }
+    | NodeValue::Image(nl) => {
+      StableValue::Image { url: text(&nl.url), title: text(&nl.title) }
+    }
+    | NodeValue::FootnoteReference(nfr) => StableValue::FootnoteReference {
+      name:    text(&nfr.name),
+      ref_num: nfr.ref_num,
+      ix:      nfr.ix,
+    },
+    | NodeValue::ShortCode(nsc) => {
+      StableValue::ShortCode { code: text(&nsc.code), emoji: text(&nsc.emoji) }
+    }
+    | NodeValue::Math(nm) => StableValue::Math {
+      dollar_math:  nm.dollar_math,
+      display_math: nm.display_math,
+      literal:      text(&nm.literal),
+    },
+    | NodeValue::MultilineBlockQuote(nmbq) => {
+      StableValue::MultilineBlockQuote {
+        fence_length: nmbq.fence_length,
+        fence_offset: nmbq.fence_offset,
+      }
+    }
+    | NodeValue::Escaped => StableValue::Escaped,
+    | NodeValue::WikiLink(nwl) => StableValue::WikiLink { url: text(&nwl.url) },
+    | NodeValue::Underline => StableValue::Underline,
+    | NodeValue::Subscript => StableValue::Subscript,
+    | NodeValue::SpoileredText => StableValue::SpoileredText,
+    | NodeValue::EscapedTag(tag) => {
+      StableValue::EscapedTag { literal: text(tag) }
+    }
+    | NodeValue::Alert(alert) => StableValue::Alert {
+      alert_type:   alert.alert_type.into(),
+      title:        alert.title.clone(),
+      multiline:    alert.multiline,
+      fence_length: alert.fence_length,
+      fence_offset: alert.fence_offset,
+    },
+    | NodeValue::Subtext => StableValue::Subtext,
+  }
+}
+
+fn stable_list(nl: &NodeList) -> StableList {
+  StableList {
+    ordered:      nl.list_type == ListType::Ordered,
+    start:        nl.start,
+    delimiter:    match nl.delimiter {
+      | ListDelimType::Period => '.',
+      | ListDelimType::Paren => ')',
+    },
+    bullet_char:  nl.bullet_char as char,
+    tight:        nl.tight,
+    is_task_list: nl.is_task_list,
+  }
+}
+
+fn node_value(value: StableValue) -> NodeValue {
+  match value {
+    | StableValue::Document => NodeValue::Document,
+    | StableValue::FrontMatter { literal } => {
+      NodeValue::FrontMatter(literal.into())
+    }
+    | StableValue::BlockQuote => NodeValue::BlockQuote,
+    | StableValue::List(list) => NodeValue::List(node_list(list)),
+    | StableValue::Item(list) => NodeValue::Item(node_list(list)),
+    | StableValue::DescriptionList => NodeValue::DescriptionList,
+    | StableValue::DescriptionItem { tight } => {
+      let ndi = NodeDescriptionItem { tight, ..Default::default() };
+      NodeValue::DescriptionItem(ndi.into())
+    }
+    | StableValue::DescriptionTerm => NodeValue::DescriptionTerm,
+    | StableValue::DescriptionDetails => NodeValue::DescriptionDetails,
+    | StableValue::CodeBlock {
+      fenced,
+      fence_char,
+      fence_length,
+      info,
+      literal,
+    } => {
+      let ncb = NodeCodeBlock {
+        fenced,
+        fence_char: u8::try_from(fence_char).unwrap_or(b'`'),
+        fence_length,
+        info,
+        literal,
+        ..NodeCodeBlock::default()
+      };
+      NodeValue::CodeBlock(ncb.into())
+    }
+    | StableValue::HtmlBlock { literal } => {
+      let nhb = NodeHtmlBlock { literal, ..NodeHtmlBlock::default() };
+      NodeValue::HtmlBlock(nhb.into())
+    }
+    | StableValue::Paragraph => NodeValue::Paragraph,
+    | StableValue::Heading { level, setext } => {
+      NodeValue::Heading(NodeHeading { level, setext }.into())
+    }
+    | StableValue::ThematicBreak => NodeValue::ThematicBreak,
+    | StableValue::FootnoteDefinition { name, total_references } => {
+      let nfd = NodeFootnoteDefinition { name, total_references };
+      NodeValue::FootnoteDefinition(nfd.into())
+    }
+    | StableValue::Table { alignments } => {
+      let nt = NodeTable {
+        num_columns: alignments.len(),
+        alignments: alignments.into_iter().map(Into::into).collect(),
+        ..NodeTable::default()
+      };
+      NodeValue::Table(nt.into())
+    }
+    | StableValue::TableRow { header } => NodeValue::TableRow(header),
+    | StableValue::TableCell => NodeValue::TableCell,
+    | StableValue::Text { literal } => NodeValue::Text(literal.into()),
+    | StableValue::TaskItem { symbol } => NodeValue::TaskItem(symbol),
+    | StableValue::SoftBreak => NodeValue::SoftBreak,
+    | StableValue::LineBreak => NodeValue::LineBreak,
+    | StableValue::Code { num_backticks, literal } => {
+      NodeValue::Code(NodeCode { num_backticks, literal }.into())
+    }
+    | StableValue::HtmlInline { literal } => {
+      NodeValue::HtmlInline(literal.into())
+    }
+    | StableValue::Raw { literal } => NodeValue::Raw(literal.into()),
+    | StableValue::Emph => NodeValue::Emph,
+    | StableValue::Strong => NodeValue::Strong,
+    | StableValue::Strikethrough => NodeValue::Strikethrough,
+    | StableValue::Superscript => NodeValue::Superscript,
+    | StableValue::Link { url, title } => {
+      NodeValue::Link(NodeLink { url, title }.into())
+    }
+    | StableValue::Image { url, title } => {
+      NodeValue::Image(NodeLink { url, title }.into())
+    }
+    | StableValue::FootnoteReference { name, ref_num, ix } => {
+      let nfr = NodeFootnoteReference { name, ref_num, ix };
+      NodeValue::FootnoteReference(nfr.into())
+    }
+    | StableValue::ShortCode { code, emoji } => {
+      NodeValue::ShortCode(NodeShortCode { code, emoji }.into())
+    }
+    | StableValue::Math { dollar_math, display_math, literal } => {
+      let nm = NodeMath { dollar_math, display_math, literal };
+      NodeValue::Math(nm.into())
+    }
+    | StableValue::MultilineBlockQuote { fence_length, fence_offset } => {
+      let nmbq = NodeMultilineBlockQuote { fence_length, fence_offset };
+      NodeValue::MultilineBlockQuote(nmbq.into())
+    }
+    | StableValue::Escaped => NodeValue::Escaped,
+    | StableValue::WikiLink { url } => {
+      NodeValue::WikiLink(NodeWikiLink { url }.into())
+    }
+    | StableValue::Underline => NodeValue::Underline,
+    | StableValue::Subscript => NodeValue::Subscript,
+    | StableValue::SpoileredText => NodeValue::SpoileredText,
+    | StableValue::EscapedTag { literal } => {
+      NodeValue::EscapedTag(literal.into())
+    }
+    | StableValue::Alert {
+      alert_type,
+      title,
+      multiline,
+      fence_length,
+      fence_offset,
+    } => {
+      let alert = NodeAlert {
+        alert_type: alert_type.into(),
+        title,
+        multiline,
+        fence_length,
+        fence_offset,
+      };
+      NodeValue::Alert(alert.into())
+    }
+    | StableValue::Subtext => NodeValue::Subtext,
+  }
+}
+
+fn node_list(list: StableList) -> NodeList {
+  NodeList {
+    list_type: match list.ordered {
+      | true => ListType::Ordered,
+      | false => ListType::Bullet,
+    },
+    start: list.start,
+    delimiter: match list.delimiter {
+      | ')' => ListDelimType::Paren,
+      | _ => ListDelimType::Period,
+    },
+    bullet_char: u8::try_from(list.bullet_char).unwrap_or(b'-'),
+    tight: list.tight,
+    is_task_list: list.is_task_list,
+    ..NodeList::default()
+  }
+}
+
+impl From<TableAlignment> for StableAlignment {
+  fn from(alignment: TableAlignment) -> Self {
+    match alignment {
+      | TableAlignment::None => Self::None,
+      | TableAlignment::Left => Self::Left,
+      | TableAlignment::Center => Self::Center,
+      | TableAlignment::Right => Self::Right,
+    }
+  }
+}
+
+impl From<StableAlignment> for TableAlignment {
+  fn from(alignment: StableAlignment) -> Self {
+    match alignment {
+      | StableAlignment::None => Self::None,
+      | StableAlignment::Left => Self::Left,
+      | StableAlignment::Center => Self::Center,
+      | StableAlignment::Right => Self::Right,
+    }
+  }
+}
+
+impl From<AlertType> for StableAlertType {
+  fn from(alert_type: AlertType) -> Self {
+    match alert_type {
+      | AlertType::Note => Self::Note,
+      | AlertType::Tip => Self::Tip,
+      | AlertType::Important => Self::Important,
+      | AlertType::Warning => Self::Warning,
+      | AlertType::Caution => Self::Caution,
+    }
+  }
+}
+
+impl From<StableAlertType> for AlertType {
+  fn from(alert_type: StableAlertType) -> Self {
+    match alert_type {
+      | StableAlertType::Note => Self::Note,
+      | StableAlertType::Tip => Self::Tip,
+      | StableAlertType::Important => Self::Important,
+      | StableAlertType::Warning => Self::Warning,
+      | StableAlertType::Caution => Self::Caution,
+    }
+  }
+}
+
+/// The nodes of version `1` of the schema, which held Comrak's own values.
+pub mod v1 {
+  use comrak::Arena;
+  use comrak::nodes::AstNode;
+  use comrak::nodes::NodeValue;
+  use comrak::nodes::Sourcepos;
+  use serde::Deserialize;
+
+  /// A tree in version `1` of the schema.
+  #[derive(Debug, Deserialize)]
+  pub struct Ast {
+    pub root: Node,
+  }
+
+  /// A node of a version `1` tree, along with all of its descendants.
+  #[derive(Debug, Deserialize)]
+  pub struct Node {
+    pub value:     NodeValue,
+    pub sourcepos: Sourcepos,
+    #[serde(default)]
+    pub children:  Vec<Node>,
+  }
+
+  /// Allocates the tree under `node` in the arena, returning its root.
+  pub fn from_stable<'a>(arena: &'a Arena<'a>, node: Node) -> &'a AstNode<'a> {
+    let parent = arena.alloc(node.value.into());
+    parent.data.borrow_mut().sourcepos = node.sourcepos;
+    for child in node.children {
+      parent.append(from_stable(arena, child));
+    }
+    parent
+  }
+}
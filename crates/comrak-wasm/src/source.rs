@@ -0,0 +1,65 @@
+//! Helpers for mapping Comrak's line/column source positions back onto the
+//! original Markdown input.
+
+use std::ops::Range;
+
+use comrak::nodes::LineColumn;
+use comrak::nodes::Sourcepos;
+
+/// A Markdown source string with a precomputed table of line start offsets.
+///
+/// Comrak reports positions as 1-based `(line, column)` pairs, where columns
+/// are counted in bytes from the start of the line. This type converts those
+/// positions into absolute UTF-8 byte offsets into the original input.
+#[derive(Debug, Clone)]
+pub struct Source<'s> {
+  text:  &'s str,
+  lines: Vec<usize>,
+}
+
+impl<'s> Source<'s> {
+  pub fn new(text: &'s str) -> Self {
+    let mut lines = vec![0];
+    for (i, b) in text.bytes().enumerate() {
+      if b == b'\n' {
+        lines.push(i + 1);
+      }
+    }
+    Self { text, lines }
+  }
+
+  /// Returns the absolute byte offset of the given position, or `None` if it
+  /// falls outside of the source text.
+  pub fn offset(&self, pos: LineColumn) -> Option<usize> {
+    if pos.line == 0 || pos.column == 0 {
+      return None;
+    }
+    let start = *self.lines.get(pos.line - 1)?;
+    let offset = start + pos.column - 1;
+    (offset <= self.text.len()).then_some(offset)
+  }
+
+  /// Returns the byte found at the given position, if any.
+  pub fn byte_at(&self, pos: LineColumn) -> Option<u8> {
+    self
+      .offset(pos)
+      .and_then(|i| self.text.as_bytes().get(i).copied())
+  }
+
+  /// Returns the byte range spanned by the given source position. Comrak's end
+  /// positions are inclusive, so the end of the range is the offset following
+  /// the last byte; an end column of 0 marks the end of the previous line.
+  pub fn range(&self, sp: Sourcepos) -> Option<Range<usize>> {
+    let start = self.offset(sp.start)?;
+    let end = match sp.end.column {
+      | 0 => *self.lines.get(sp.end.line.checked_sub(1)?)?,
+      | _ => self.offset(sp.end)? + 1,
+    };
+    Some(start..end.clamp(start, self.text.len()))
+  }
+
+  /// Returns the text spanned by the given source position, if any.
+  pub fn text(&self, sp: Sourcepos) -> Option<&'s str> {
+    self.text.get(self.range(sp)?)
+  }
+}
@@ -0,0 +1,46 @@
+//! Wrapping of rendered HTML in a standalone HTML5 document, for the
+//! `document` render option.
+
+use comrak::nodes::AstNode;
+use comrak::nodes::NodeValue;
+
+use crate::html::escape_attr;
+use crate::options::HtmlDocument;
+use crate::transform::text_content;
+
+/// Returns the markup that goes before and after the rendered HTML to make it
+/// a complete document. The title defaults to the text of the first level 1
+/// heading, and the `<title>` element is omitted if there is none.
+pub fn shell<'a>(
+  root: &'a AstNode<'a>,
+  document: &HtmlDocument,
+) -> (String, String) {
+  let title = document.title.clone().or_else(|| {
+    root
+      .descendants()
+      .find_map(|node| match node.data.borrow().value {
+        | NodeValue::Heading(ref nh) if nh.level == 1 => {
+          Some(text_content(node))
+        }
+        | _ => None,
+      })
+  });
+  let mut head = String::from("<!DOCTYPE html>\n<html");
+  if let Some(ref lang) = document.lang {
+    head.push_str(&format!(" lang=\"{}\"", escape_attr(lang)));
+  }
+  head.push_str(">\n<head>\n<meta charset=\"utf-8\">\n");
+  head.push_str(
+    "<meta name=\"viewport\" content=\"width=device-width, \
+     initial-scale=1\">\n",
+  );
+  if let Some(title) = title {
+    head.push_str(&format!("<title>{}</title>\n", escape_attr(&title)));
+  }
+  if let Some(ref href) = document.stylesheet {
+    let href = escape_attr(href);
+    head.push_str(&format!("<link rel=\"stylesheet\" href=\"{href}\">\n"));
+  }
+  head.push_str("</head>\n<body>\n");
+  (head, "</body>\n</html>\n".to_string())
+}
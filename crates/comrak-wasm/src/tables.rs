@@ -0,0 +1,173 @@
+//! Alignment of the columns of tables in CommonMark output, for the
+//! `align_table_columns` render option.
+//!
+//! Comrak writes every cell of a table with a single space of padding, which
+//! leaves the pipes of each row ragged. The output is post-processed instead
+//! of formatted from scratch: each table is found by its delimiter row, and
+//! its rows are rewritten with every cell padded to the width of the widest
+//! one in its column. Tables nested in block quotes and lists are aligned too,
+//! while anything inside a fenced code block is left alone.
+
+/// The minimum width of a column, which is the width of Comrak's delimiters.
+const MIN_WIDTH: usize = 3;
+
+/// The alignment of a table column, as given by its delimiter row.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Align {
+  None,
+  Left,
+  Center,
+  Right,
+}
+
+/// Pads the columns of every table in `markdown` to equal widths.
+pub fn align_columns(markdown: &str) -> String {
+  let lines: Vec<&str> = markdown.split_inclusive('\n').collect();
+  let mut out = String::with_capacity(markdown.len());
+  // the character and length of the fence of the open code block, if any
+  let mut fence: Option<(char, usize)> = None;
+  let mut i = 0;
+  while i < lines.len() {
+    let content = lines[i].trim_start_matches([' ', '>']).trim_end();
+    if let Some((c, len)) = fence {
+      if fence_of(content).is_some_and(|(d, n)| d == c && n >= len)
+        && content.trim_start_matches(c).is_empty()
+      {
+        fence = None;
+      }
+    } else if let Some(open) = fence_of(content) {
+      fence = Some(open);
+    } else if let Some(rows) = table_at(&lines[i..]) {
+      write_table(&mut out, &lines[i..i + rows]);
+      i += rows;
+      continue;
+    }
+    out.push_str(lines[i]);
+    i += 1;
+  }
+  out
+}
+
+/// Returns the character and length of the code fence opening `content`.
+fn fence_of(content: &str) -> Option<(char, usize)> {
+  let c = content.chars().next().filter(|&c| c == '`' || c == '~')?;
+  let len = content.len() - content.trim_start_matches(c).len();
+  (len >= 3).then_some((c, len))
+}
+
+/// Returns the number of rows in the table starting at the first line, if
+/// there is one: a header row, followed by a delimiter row, followed by any
+/// number of body rows, all with the same prefix and number of cells.
+fn table_at(lines: &[&str]) -> Option<usize> {
+  let (prefix, header) = split_row(lines[0])?;
+  let (delimiter_prefix, delimiter) = split_row(lines.get(1)?)?;
+  if delimiter_prefix != prefix
+    || delimiter.len() != header.len()
+    || !delimiter.iter().all(|cell| parse_align(cell).is_some())
+  {
+    return None;
+  }
+  let body = lines[2..]
+    .iter()
+    .map_while(|line| split_row(line))
+    .take_while(|(p, cells)| *p == prefix && cells.len() == header.len())
+    .count();
+  Some(2 + body)
+}
+
+/// Rewrites the rows of a table, found by [`table_at`], with its columns
+/// padded to equal widths.
+fn write_table(out: &mut String, lines: &[&str]) {
+  let rows: Vec<_> = lines.iter().filter_map(|line| split_row(line)).collect();
+  let prefix = rows[0].0;
+  let aligns: Vec<Align> = rows[1]
+    .1
+    .iter()
+    .filter_map(|cell| parse_align(cell))
+    .collect();
+  let mut widths = vec![MIN_WIDTH; aligns.len()];
+  // the delimiter row is rewritten to fit the other rows
+  for (_, cells) in rows.iter().take(1).chain(&rows[2..]) {
+    for (width, cell) in widths.iter_mut().zip(cells) {
+      *width = (*width).max(cell.chars().count());
+    }
+  }
+  for (i, (_, cells)) in rows.iter().enumerate() {
+    out.push_str(prefix);
+    out.push('|');
+    for ((cell, &align), &width) in cells.iter().zip(&aligns).zip(&widths) {
+      out.push(' ');
+      if i == 1 {
+        out.push_str(&delimiter(align, width));
+      } else {
+        out.push_str(&pad(cell, align, width));
+      }
+      out.push_str(" |");
+    }
+    out.push('\n');
+  }
+}
+
+/// Splits a table row into the prefix of its line (the markers of any block
+/// quotes and the indentation of any list items) and its trimmed cells.
+fn split_row(line: &str) -> Option<(&str, Vec<&str>)> {
+  let line = line.trim_end();
+  let (prefix, row) = line.split_at(line.find('|')?);
+  if !prefix.bytes().all(|b| b == b' ' || b == b'>') {
+    return None;
+  }
+  let inner = row.strip_prefix('|')?.strip_suffix('|')?;
+  if inner.ends_with('\\') {
+    return None;
+  }
+  let mut cells = Vec::new();
+  let mut start = 0;
+  let mut escaped = false;
+  for (i, b) in inner.bytes().enumerate() {
+    match b {
+      | b'|' if !escaped => {
+        cells.push(inner[start..i].trim());
+        start = i + 1;
+      }
+      | _ => {}
+    }
+    escaped = b == b'\\' && !escaped;
+  }
+  cells.push(inner[start..].trim());
+  Some((prefix, cells))
+}
+
+/// Parses a cell of a delimiter row, such as `:--`.
+fn parse_align(cell: &str) -> Option<Align> {
+  let left = cell.starts_with(':');
+  let right = cell.len() > 1 && cell.ends_with(':');
+  let dashes = cell.trim_start_matches(':').trim_end_matches(':');
+  if dashes.is_empty() || !dashes.bytes().all(|b| b == b'-') {
+    return None;
+  }
+  Some(match (left, right) {
+    | (true, true) => Align::Center,
+    | (true, false) => Align::Left,
+    | (false, true) => Align::Right,
+    | (false, false) => Align::None,
+  })
+}
+
+fn delimiter(align: Align, width: usize) -> String {
+  match align {
+    | Align::None => "-".repeat(width),
+    | Align::Left => format!(":{}", "-".repeat(width - 1)),
+    | Align::Center => format!(":{}:", "-".repeat(width - 2)),
+    | Align::Right => format!("{}:", "-".repeat(width - 1)),
+  }
+}
+
+fn pad(cell: &str, align: Align, width: usize) -> String {
+  let fill = width - cell.chars().count();
+  let (before, after) = match align {
+    | Align::Right => (fill, 0),
+    | Align::Center => (fill / 2, fill - fill / 2),
+    | Align::None | Align::Left => (0, fill),
+  };
+  format!("{}{cell}{}", " ".repeat(before), " ".repeat(after))
+}
@@ -0,0 +1,86 @@
+//! Tab expansion for the `tab_width` parse option.
+//!
+//! Comrak's tab stops are fixed at every 4 columns, as the CommonMark spec
+//! requires, which decides how far a tab indents a line (e.g. whether it
+//! starts an indented code block). Other widths are emulated by expanding the
+//! tabs in the indentation of each line to spaces before parsing, and mapping
+//! the source positions of the AST back onto the original input afterwards.
+
+use std::num::NonZeroUsize;
+
+use comrak::nodes::AstNode;
+use comrak::nodes::LineColumn;
+
+/// The width of Comrak's own tab stops.
+pub const TAB_STOP: NonZeroUsize = NonZeroUsize::new(4).unwrap();
+
+/// A Markdown input with the tabs in its indentation expanded to spaces.
+#[derive(Debug)]
+pub struct Expanded {
+  text:  String,
+  /// For each line whose indentation had tabs, the original column of each
+  /// byte of its expanded indentation, and the number of bytes it grew by.
+  lines: Vec<Option<(Vec<usize>, usize)>>,
+}
+
+impl Expanded {
+  /// Expands the tabs in the indentation of every line of `md`, which is the
+  /// leading run of spaces, tabs, and block quote markers, to tab stops every
+  /// `width` columns.
+  pub fn new(md: &str, width: NonZeroUsize) -> Self {
+    let width = width.get();
+    let mut text = String::with_capacity(md.len());
+    let mut lines = Vec::new();
+    for line in md.split_inclusive('\n') {
+      let indent = line.len() - line.trim_start_matches([' ', '\t', '>']).len();
+      if !line[..indent].contains('\t') {
+        text.push_str(line);
+        lines.push(None);
+        continue;
+      }
+      let mut columns = Vec::new();
+      for (i, c) in line[..indent].char_indices() {
+        let spaces = match c {
+          | '\t' => width - columns.len() % width,
+          | _ => 1,
+        };
+        for _ in 0..spaces {
+          text.push(if c == '\t' { ' ' } else { c });
+          columns.push(i + 1);
+        }
+      }
+      text.push_str(&line[indent..]);
+      let grown = columns.len() - indent;
+      lines.push(Some((columns, grown)));
+    }
+    Self { text, lines }
+  }
+
+  /// Returns the expanded input, to be parsed in place of the original.
+  pub fn text(&self) -> &str {
+    &self.text
+  }
+
+  /// Maps the source positions of every node parsed from the expanded input
+  /// back onto the original one.
+  pub fn restore<'a>(&self, root: &'a AstNode<'a>) {
+    for node in root.descendants() {
+      let mut data = node.data.borrow_mut();
+      self.restore_column(&mut data.sourcepos.start);
+      self.restore_column(&mut data.sourcepos.end);
+    }
+  }
+
+  fn restore_column(&self, pos: &mut LineColumn) {
+    let Some(Some((columns, grown))) =
+      pos.line.checked_sub(1).and_then(|i| self.lines.get(i))
+    else {
+      return;
+    };
+    pos.column = match columns.get(pos.column.wrapping_sub(1)) {
+      | Some(&column) => column,
+      | None if pos.column > *grown => pos.column - grown,
+      | None => pos.column,
+    };
+  }
+}
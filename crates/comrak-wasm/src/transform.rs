@@ -0,0 +1,1239 @@
+//! Post-parse transformations applied to the AST before it is rendered.
+//!
+//! These implement the crate-level options that Comrak itself doesn't offer,
+//! by rewriting the tree produced by [`comrak::parse_document`] in place.
+
+use std::collections::BTreeSet;
+use std::collections::HashMap;
+use std::ops::Range;
+
+use comrak::Arena;
+use comrak::Options as ComrakOptions;
+use comrak::nodes::AstNode;
+use comrak::nodes::LineColumn;
+use comrak::nodes::ListType;
+use comrak::nodes::NodeCodeBlock;
+use comrak::nodes::NodeDescriptionItem;
+use comrak::nodes::NodeLink;
+use comrak::nodes::NodeList;
+use comrak::nodes::NodeValue;
+use js_sys::Function;
+use wasm_bindgen::JsValue;
+
+use crate::headings::Slugger;
+use crate::html::Attr;
+use crate::html::Token;
+use crate::html::escape_attr;
+use crate::html::tokenize;
+use crate::images::split_image_size;
+use crate::options::AutolinkKinds;
+use crate::options::FootnoteStyle;
+use crate::options::FrontMatterMode;
+use crate::options::HtmlName;
+use crate::options::Mentions;
+use crate::options::Options;
+use crate::options::SmartLocale;
+use crate::options::TableOfContents;
+use crate::options::Typography;
+use crate::pattern::Pattern;
+use crate::sanitize::is_safe_attr;
+use crate::source::Source;
+use crate::strip_front_matter_delimiters;
+use crate::wikilinks::resolve_wikilink;
+
+/// Applies all parse-time transformations enabled in `options`.
+pub fn parse<'a>(
+  arena: &'a Arena<'a>,
+  root: &'a AstNode<'a>,
+  md: &str,
+  options: &ComrakOptions,
+  extras: &Options,
+) {
+  let source = Source::new(md);
+  if !extras.parse.backslash_line_breaks {
+    backslash_line_breaks(arena, root, &source);
+  }
+  if extras.extension.image_size_syntax {
+    image_size_syntax(arena, root, &source);
+  }
+  if let Some(kinds) = extras.extension.autolink_kinds {
+    filter_autolinks(root, &source, kinds);
+  }
+  if let Some(ref chars) = extras.parse.autolink_trim_chars {
+    trim_autolinks(arena, root, &source, chars);
+  }
+  if let Some(ref mentions) = extras.extension.mentions {
+    link_mentions(arena, root, mentions);
+  }
+  let mode = extras.parse.front_matter_mode;
+  if let Some(ref delimiter) = options.extension.front_matter_delimiter {
+    show_front_matter(arena, root, delimiter, mode);
+  }
+  if let Some(ref typography) = extras.render.typography {
+    typography_substitutions(root, &source, typography);
+  }
+  let locale = extras.parse.smart_locale;
+  if options.parse.smart && locale != SmartLocale::En {
+    localize_quotes(root, locale);
+  }
+}
+
+/// Applies all render-time transformations enabled in `extras`. Unlike the
+/// parse-time transformations, these also apply to ASTs passed directly to the
+/// `format_*` functions.
+pub fn render<'a>(
+  arena: &'a Arena<'a>,
+  root: &'a AstNode<'a>,
+  options: &ComrakOptions,
+  extras: &Options,
+) -> Result<(), JsValue> {
+  if extras.render.fix_heading_levels {
+    fix_heading_levels(root);
+  }
+  if let Some(ref toc) = extras.render.table_of_contents {
+    let prefix = options.extension.header_ids.as_deref().unwrap_or_default();
+    let mut slugger = Slugger::new(extras);
+    table_of_contents(arena, root, toc, prefix, &mut slugger)?;
+  }
+  if !extras.render.autolink_exclude_patterns.is_empty() {
+    let patterns = Pattern::compile(&extras.render.autolink_exclude_patterns)?;
+    exclude_autolinks(root, &patterns);
+  }
+  if !extras.extension.autolink_schemes.is_empty() {
+    autolink_schemes(arena, root, &extras.extension.autolink_schemes);
+  }
+  if !extras.render.language_aliases.is_empty() {
+    language_aliases(root, &extras.render.language_aliases);
+  }
+  if let Some(width) = extras.render.code_tabs_to_spaces.filter(|&w| w > 0) {
+    code_tabs_to_spaces(root, width);
+  }
+  if extras.render.force_start_one {
+    force_start_one(root);
+  }
+  if extras.render.footnote_style == FootnoteStyle::Inline {
+    inline_footnotes(arena, root);
+  }
+  if extras.render.task_list_progress {
+    let include_nested = extras.render.task_list_progress_include_nested;
+    task_list_progress(arena, root, include_nested);
+  }
+  if let Some(ref resolver) = extras.extension.wikilink_resolver {
+    resolve_wikilinks(arena, root, resolver)?;
+  }
+  let blocks_only =
+    extras.render.sourcepos_blocks_only || !extras.render.inline_sourcepos;
+  if blocks_only && options.render.sourcepos {
+    sourcepos_blocks_only(root);
+  }
+  if extras.render.strip_comments {
+    strip_comments(root, extras.render.strip_conditional_comments);
+  }
+  if !extras.render.raw_html_allow_tags.is_empty() {
+    allow_raw_html(root, &extras.render.raw_html_allow_tags);
+  }
+  Ok(())
+}
+
+/// Turns backslash hard breaks back into a literal `\` followed by a soft
+/// break. Hard breaks made with trailing spaces are left untouched.
+fn backslash_line_breaks<'a>(
+  arena: &'a Arena<'a>,
+  root: &'a AstNode<'a>,
+  source: &Source,
+) {
+  for node in collect(root, |v| matches!(v, NodeValue::LineBreak)) {
+    if !is_backslash_break(node, source) {
+      continue;
+    }
+    node.insert_before(arena.alloc(NodeValue::Text("\\".into()).into()));
+    node.data.borrow_mut().value = NodeValue::SoftBreak;
+  }
+}
+
+/// Replaces the front matter of the document with a code block or description
+/// list, per the `front_matter_mode` option.
+fn show_front_matter<'a>(
+  arena: &'a Arena<'a>,
+  root: &'a AstNode<'a>,
+  delimiter: &str,
+  mode: FrontMatterMode,
+) {
+  let Some(node) = root.first_child() else {
+    return;
+  };
+  let (raw, sourcepos) = {
+    let data = node.data.borrow();
+    match data.value {
+      | NodeValue::FrontMatter(ref raw) => (raw.to_string(), data.sourcepos),
+      | _ => return,
+    }
+  };
+  let mut literal = strip_front_matter_delimiters(&raw, delimiter);
+  let block = |value: NodeValue| {
+    let node = arena.alloc(value.into());
+    node.data.borrow_mut().sourcepos = sourcepos;
+    node
+  };
+  let replacement = match mode {
+    | FrontMatterMode::Strip => return,
+    | FrontMatterMode::Codeblock => {
+      if !literal.is_empty() && !literal.ends_with('\n') {
+        literal.push('\n');
+      }
+      let ncb = NodeCodeBlock {
+        fenced: true,
+        fence_char: b'`',
+        fence_length: 3,
+        info: if delimiter == "+++" { "toml" } else { "yaml" }.to_string(),
+        literal,
+        ..NodeCodeBlock::default()
+      };
+      block(NodeValue::CodeBlock(ncb.into()))
+    }
+    | FrontMatterMode::Render => {
+      let list = block(NodeValue::DescriptionList);
+      let item = NodeDescriptionItem { tight: true, ..Default::default() };
+      for (key, value) in front_matter_pairs(&literal) {
+        let entry = block(NodeValue::DescriptionItem(item));
+        for (container, text) in [
+          (NodeValue::DescriptionTerm, key),
+          (NodeValue::DescriptionDetails, value),
+        ] {
+          let paragraph = block(NodeValue::Paragraph);
+          paragraph.append(block(NodeValue::Text(text.to_string().into())));
+          let container = block(container);
+          container.append(paragraph);
+          entry.append(container);
+        }
+        list.append(entry);
+      }
+      list
+    }
+  };
+  // front matter without any pairs to list is still stripped
+  if replacement.first_child().is_some() || mode == FrontMatterMode::Codeblock {
+    node.insert_before(replacement);
+  }
+  node.detach();
+}
+
+/// Returns the top-level `key: value` pairs of YAML or TOML (`key = value`)
+/// front matter, with any quotes around the values removed. Nested values,
+/// comments, and other lines are skipped.
+fn front_matter_pairs(literal: &str) -> impl Iterator<Item = (&str, &str)> {
+  literal.lines().filter_map(|line| {
+    if line.starts_with(char::is_whitespace) || line.starts_with(['#', '-']) {
+      return None;
+    }
+    let (key, value) =
+      line.split_once(": ").or_else(|| line.split_once(" = "))?;
+    let value = value.trim();
+    let unquoted = ['"', '\'']
+      .iter()
+      .find_map(|&q| value.strip_prefix(q)?.strip_suffix(q));
+    Some((key.trim(), unquoted.unwrap_or(value)))
+  })
+}
+
+/// Reverts the substitutions made by the `smart` option that `typography`
+/// leaves out. A character written as such in the source of the text is kept,
+/// since it isn't the result of a substitution.
+fn typography_substitutions<'a>(
+  root: &'a AstNode<'a>,
+  source: &Source,
+  typography: &Typography,
+) {
+  let mut reverts = Vec::new();
+  if !typography.quotes {
+    reverts.extend([
+      ('\u{201c}', "\""),
+      ('\u{201d}', "\""),
+      ('\u{2018}', "'"),
+      ('\u{2019}', "'"),
+    ]);
+  }
+  if !typography.dashes {
+    reverts.extend([
+      ('\u{2013}', "--"),
+      ('\u{2014}', "---"),
+    ]);
+  }
+  if !typography.ellipses {
+    reverts.push(('\u{2026}', "..."));
+  }
+  if reverts.is_empty() {
+    return;
+  }
+  let revert = |c: char| reverts.iter().find(|&&(r, _)| r == c);
+  for node in collect(root, |v| matches!(v, NodeValue::Text(_))) {
+    let mut data = node.data.borrow_mut();
+    let original = source.text(data.sourcepos);
+    let NodeValue::Text(ref mut literal) = data.value else {
+      continue;
+    };
+    if !literal.chars().any(|c| revert(c).is_some()) {
+      continue;
+    }
+    let mut out = String::with_capacity(literal.len());
+    for c in literal.chars() {
+      match revert(c) {
+        | Some(&(_, s)) if !original.is_some_and(|o| o.contains(c)) => {
+          out.push_str(s)
+        }
+        | _ => out.push(c),
+      }
+    }
+    *literal = out.into();
+  }
+}
+
+/// Replaces the English quotation marks produced by the `smart` option with
+/// those of `locale`. A right single quote is only taken as a closing quote
+/// after an opening one in the same block, and when it isn't followed by a
+/// letter or digit, since Comrak turns apostrophes into the same character.
+fn localize_quotes<'a>(root: &'a AstNode<'a>, locale: SmartLocale) {
+  let [
+    open_double,
+    close_double,
+    open_single,
+    close_single,
+  ] = locale.quotes();
+  let mut in_single = false;
+  for node in root.descendants() {
+    let mut data = node.data.borrow_mut();
+    let literal = match data.value {
+      | NodeValue::Text(ref mut literal) => literal,
+      | ref value => {
+        in_single &= !value.block();
+        continue;
+      }
+    };
+    if !literal.contains([
+      '\u{201c}', '\u{201d}', '\u{2018}', '\u{2019}',
+    ]) {
+      continue;
+    }
+    let mut out = String::with_capacity(literal.len());
+    let mut chars = literal.chars().peekable();
+    while let Some(c) = chars.next() {
+      match c {
+        | '\u{201c}' => out.push_str(open_double),
+        | '\u{201d}' => out.push_str(close_double),
+        | '\u{2018}' => {
+          in_single = true;
+          out.push_str(open_single);
+        }
+        | '\u{2019}'
+          if in_single
+            && !chars.peek().is_some_and(|c| c.is_alphanumeric()) =>
+        {
+          in_single = false;
+          out.push_str(close_single);
+        }
+        | c => out.push(c),
+      }
+    }
+    *literal = out.into();
+  }
+}
+
+/// Turns the text of images using the image size syntax, such as
+/// `![alt](img.png =100x200)`, into image nodes. CommonMark doesn't allow
+/// spaces in a link destination unless it's enclosed in `<>`, so Comrak leaves
+/// these as plain text. Only images with plain text for their alt text (with
+/// no `[` or `]`) are recognized.
+///
+/// Only text that reads exactly as it was written in the source is searched,
+/// so an escaped `\![alt](img.png =100x200)` stays text, and the new nodes are
+/// given their positions in the source. Text containing any backslash escape
+/// or entity is left alone.
+///
+/// The size is kept at the end of the image's URL, just as if the destination
+/// was written as `<img.png =100x200>`, and is rendered by the formatter.
+fn image_size_syntax<'a>(
+  arena: &'a Arena<'a>,
+  root: &'a AstNode<'a>,
+  source: &Source,
+) {
+  let has_image = |v: &NodeValue| match v {
+    | NodeValue::Text(literal) => literal.contains("!["),
+    | _ => false,
+  };
+  for node in collect(root, has_image) {
+    let (literal, start) = {
+      let data = node.data.borrow();
+      let NodeValue::Text(ref literal) = data.value else {
+        continue;
+      };
+      if source.text(data.sourcepos) != Some(&**literal) {
+        continue;
+      }
+      (literal.to_string(), data.sourcepos.start)
+    };
+    // text never spans lines, so its offsets map onto columns
+    let alloc = |value: NodeValue, range: Range<usize>| {
+      let node = arena.alloc(value.into());
+      let sourcepos = &mut node.data.borrow_mut().sourcepos;
+      sourcepos.start =
+        LineColumn { column: start.column + range.start, ..start };
+      sourcepos.end =
+        LineColumn { column: start.column + range.end - 1, ..start };
+      node
+    };
+    let text = |range: Range<usize>| {
+      let value = NodeValue::Text(literal[range.clone()].to_string().into());
+      alloc(value, range)
+    };
+    let mut at = 0;
+    while let Some((image, alt, url)) = find_sized_image(&literal, at) {
+      if image.start > at {
+        node.insert_before(text(at..image.start));
+      }
+      let link = NodeLink { url: url.to_string(), title: String::new() };
+      let image_node = alloc(NodeValue::Image(link.into()), image.clone());
+      if !alt.is_empty() {
+        image_node.append(text(alt));
+      }
+      node.insert_before(image_node);
+      at = image.end;
+    }
+    if at == literal.len() {
+      node.detach();
+    } else if at > 0 {
+      let mut data = node.data.borrow_mut();
+      data.value = NodeValue::Text(literal[at..].to_string().into());
+      data.sourcepos.start.column += at;
+    }
+  }
+}
+
+/// Finds the first image with a valid size in `text` from the offset `from`,
+/// returning the range it spans, the range of its alt text, and its URL
+/// (including the size).
+fn find_sized_image(
+  text: &str,
+  mut from: usize,
+) -> Option<(Range<usize>, Range<usize>, &str)> {
+  loop {
+    let start = from + text[from..].find("![")?;
+    from = start + 2;
+    let rest = &text[from..];
+    let alt_end = rest.find(['[', ']'])?;
+    let Some(dest) = rest[alt_end..].strip_prefix("](") else {
+      continue;
+    };
+    let dest_end = dest.find(')')?;
+    let url = dest[..dest_end].trim();
+    if url.contains(char::is_whitespace) && split_image_size(url).is_some() {
+      let end = from + alt_end + 2 + dest_end + 1;
+      return Some((start..end, from..from + alt_end, url));
+    }
+  }
+}
+
+/// Returns `true` if the given `LineBreak` node was produced by a backslash,
+/// rather than by two or more trailing spaces.
+pub(crate) fn is_backslash_break<'a>(
+  node: &'a AstNode<'a>,
+  source: &Source,
+) -> bool {
+  let start = node.data.borrow().sourcepos.start;
+  source.byte_at(start) == Some(b'\\')
+}
+
+/// Collects all descendants of `root` matching the predicate, so the tree can
+/// be safely mutated while iterating over the results.
+pub(crate) fn collect<'a>(
+  root: &'a AstNode<'a>,
+  predicate: impl Fn(&NodeValue) -> bool,
+) -> Vec<&'a AstNode<'a>> {
+  root
+    .descendants()
+    .filter(|node| predicate(&node.data.borrow().value))
+    .collect()
+}
+
+/// Replaces the language of each fenced code block, which is the first word of
+/// its info string, with the one it's aliased to, if any.
+fn language_aliases<'a>(
+  root: &'a AstNode<'a>,
+  aliases: &HashMap<String, String>,
+) {
+  for node in collect(root, |v| matches!(v, NodeValue::CodeBlock(_))) {
+    if let NodeValue::CodeBlock(ref mut ncb) = node.data.borrow_mut().value
+      && ncb.fenced
+    {
+      let end = ncb.info.find(char::is_whitespace).unwrap_or(ncb.info.len());
+      if let Some(alias) = aliases.get(&ncb.info[..end]) {
+        ncb.info.replace_range(..end, alias);
+      }
+    }
+  }
+}
+
+/// Expands the hard tabs in the content of every code block to spaces.
+fn code_tabs_to_spaces<'a>(root: &'a AstNode<'a>, width: usize) {
+  let has_tabs = |v: &NodeValue| match v {
+    | NodeValue::CodeBlock(ncb) => ncb.literal.contains('\t'),
+    | _ => false,
+  };
+  for node in collect(root, has_tabs) {
+    if let NodeValue::CodeBlock(ref mut ncb) = node.data.borrow_mut().value {
+      ncb.literal = expand_tabs(&ncb.literal, width).into();
+    }
+  }
+}
+
+/// Replaces each tab with enough spaces to reach the next tab stop, where tab
+/// stops are every `width` columns from the start of each line.
+fn expand_tabs(text: &str, width: usize) -> String {
+  let mut out = String::with_capacity(text.len());
+  let mut column = 0;
+  for c in text.chars() {
+    match c {
+      | '\t' => {
+        let spaces = width - column % width;
+        out.extend(std::iter::repeat_n(' ', spaces));
+        column += spaces;
+      }
+      | '\n' => {
+        out.push(c);
+        column = 0;
+      }
+      | _ => {
+        out.push(c);
+        column += 1;
+      }
+    }
+  }
+  out
+}
+
+/// Renumbers every ordered list to start at 1.
+fn force_start_one<'a>(root: &'a AstNode<'a>) {
+  let is_list =
+    |v: &NodeValue| matches!(v, NodeValue::List(_) | NodeValue::Item(_));
+  for node in collect(root, is_list) {
+    match node.data.borrow_mut().value {
+      | NodeValue::List(ref mut nl) | NodeValue::Item(ref mut nl) => {
+        nl.start = 1;
+      }
+      | _ => {}
+    }
+  }
+}
+
+/// Replaces the URL of each wikilink, along with its label if one is given,
+/// with those returned by the JS `resolver` for its title. Wikilinks for which
+/// it returns `null` or `undefined` are left as they are.
+fn resolve_wikilinks<'a>(
+  arena: &'a Arena<'a>,
+  root: &'a AstNode<'a>,
+  resolver: &Function,
+) -> Result<(), JsValue> {
+  for node in collect(root, |v| matches!(v, NodeValue::WikiLink(_))) {
+    let title = match node.data.borrow().value {
+      | NodeValue::WikiLink(ref nwl) => nwl.url.clone(),
+      | _ => continue,
+    };
+    let Some(resolved) = resolve_wikilink(resolver, &title)? else {
+      continue;
+    };
+    if let NodeValue::WikiLink(ref mut nwl) = node.data.borrow_mut().value {
+      nwl.url = resolved.url;
+    }
+    if let Some(label) = resolved.label {
+      while let Some(child) = node.first_child() {
+        child.detach();
+      }
+      node.append(arena.alloc(NodeValue::Text(label.into()).into()));
+    }
+  }
+  Ok(())
+}
+
+/// Removes every HTML block and inline HTML node that consists solely of
+/// comments (and whitespace). Conditional comments are only removed when
+/// `conditional` is set, and keep the node they appear in otherwise.
+fn strip_comments<'a>(root: &'a AstNode<'a>, conditional: bool) {
+  let is_comments = |v: &NodeValue| match v {
+    | NodeValue::HtmlBlock(nhb) => only_comments(&nhb.literal, conditional),
+    | NodeValue::HtmlInline(html) => only_comments(html, conditional),
+    | _ => false,
+  };
+  for node in collect(root, is_comments) {
+    node.detach();
+  }
+}
+
+/// Returns `true` if `html` is made up of one or more complete comments, none
+/// of which are conditional comments unless `conditional` is set.
+fn only_comments(html: &str, conditional: bool) -> bool {
+  let mut rest = html.trim_start();
+  if rest.is_empty() {
+    return false;
+  }
+  while !rest.is_empty() {
+    let Some(comment) = rest.strip_prefix("<!--") else {
+      return false;
+    };
+    let Some((body, after)) = comment.split_once("-->") else {
+      return false;
+    };
+    // e.g. `<!--[if IE]>`, `<!--[if !IE]><!-->`, and `<!--<![endif]-->`
+    let is_conditional = body.starts_with("[if") || body.starts_with("<![");
+    if is_conditional && !conditional {
+      return false;
+    }
+    rest = after.trim_start();
+  }
+  true
+}
+
+/// Turns the raw HTML made up solely of the `allowed` tags into raw output
+/// nodes, which Comrak renders verbatim, regardless of its `unsafe_`, `escape`,
+/// and `tagfilter` options.
+fn allow_raw_html<'a>(root: &'a AstNode<'a>, allowed: &BTreeSet<HtmlName>) {
+  let is_allowed = |v: &NodeValue| match v {
+    | NodeValue::HtmlBlock(nhb) => only_tags(&nhb.literal, allowed),
+    | NodeValue::HtmlInline(html) => only_tags(html, allowed),
+    | _ => false,
+  };
+  for node in collect(root, is_allowed) {
+    let mut ast = node.data.borrow_mut();
+    let html = match ast.value {
+      | NodeValue::HtmlBlock(ref nhb) => nhb.literal.to_string(),
+      | NodeValue::HtmlInline(ref html) => html.to_string(),
+      | _ => continue,
+    };
+    ast.value = NodeValue::Raw(html.into());
+  }
+}
+
+/// Returns `true` if `html` has at least one tag, and all of its tags are
+/// opening or closing tags of the `allowed` elements, without any attribute
+/// that could run script (see [`is_safe_attr`]). Any other use of `<`, such as
+/// in a comment or an attribute value, disqualifies it.
+fn only_tags(html: &str, allowed: &BTreeSet<HtmlName>) -> bool {
+  let mut found = false;
+  for token in tokenize(html) {
+    match token {
+      | Token::Text(text) if !text.contains('<') => {}
+      | Token::Tag(ref tag, raw) if !raw[1..].contains('<') => {
+        let is_allowed =
+          |name: &HtmlName| name.as_str().eq_ignore_ascii_case(&tag.name);
+        let is_safe =
+          |attr: &Attr| is_safe_attr(&attr.name, attr.value.as_deref());
+        if !allowed.iter().any(is_allowed) || !tag.attrs.iter().all(is_safe) {
+          return false;
+        }
+        found = true;
+      }
+      | _ => return false,
+    }
+  }
+  found
+}
+
+/// Clears the source position of every inline node, which Comrak's formatters
+/// take to mean the node has no position, and render no `data-sourcepos` for.
+fn sourcepos_blocks_only<'a>(root: &'a AstNode<'a>) {
+  for node in root.descendants() {
+    let mut ast = node.data.borrow_mut();
+    if !ast.value.block() {
+      ast.sourcepos = (0, 0, 0, 0).into();
+    }
+  }
+}
+
+/// Replaces each footnote reference with the content of its definition, in a
+/// `<span class="footnote">` whose `title` holds the plain text of the
+/// footnote, and removes the definitions. The paragraphs of a definition are
+/// joined by spaces, and its other blocks are reduced to their text, since a
+/// `<span>` can only hold inline content. References within the definitions
+/// themselves are dropped.
+fn inline_footnotes<'a>(arena: &'a Arena<'a>, root: &'a AstNode<'a>) {
+  // detached first, so that the references within them are left alone
+  let mut definitions = HashMap::new();
+  let is_definition =
+    |v: &NodeValue| matches!(v, NodeValue::FootnoteDefinition(_));
+  for node in collect(root, is_definition) {
+    node.detach();
+    if let NodeValue::FootnoteDefinition(ref nfd) = node.data.borrow().value {
+      definitions.insert(nfd.name.to_string(), node);
+    }
+  }
+  let text = |s: String| arena.alloc(NodeValue::Text(s.into()).into());
+  let raw = |s: String| arena.alloc(NodeValue::Raw(s.into()).into());
+  let is_reference =
+    |v: &NodeValue| matches!(v, NodeValue::FootnoteReference(_));
+  for reference in collect(root, is_reference) {
+    let definition = match reference.data.borrow().value {
+      | NodeValue::FootnoteReference(ref nfr) => definitions.get(&*nfr.name),
+      | _ => None,
+    };
+    let Some(&definition) = definition else {
+      continue;
+    };
+    let title = text_content(definition);
+    let title = title.split_whitespace().collect::<Vec<_>>().join(" ");
+    let title = escape_attr(&title);
+    let span = format!("<span class=\"footnote\" title=\"{title}\">");
+    reference.insert_before(raw(span));
+    for (i, block) in definition.children().enumerate() {
+      if i > 0 {
+        reference.insert_before(text(" ".to_string()));
+      }
+      if matches!(block.data.borrow().value, NodeValue::Paragraph) {
+        for child in block.children() {
+          reference.insert_before(deep_clone(arena, child));
+        }
+      } else {
+        reference.insert_before(text(text_content(block)));
+      }
+    }
+    reference.insert_before(raw("</span>".to_string()));
+    reference.detach();
+  }
+}
+
+/// Allocates a copy of `node` and its descendants, without their source
+/// positions, leaving out any footnote references.
+fn deep_clone<'a>(
+  arena: &'a Arena<'a>,
+  node: &'a AstNode<'a>,
+) -> &'a AstNode<'a> {
+  let copy = arena.alloc(node.data.borrow().value.clone().into());
+  for child in node.children() {
+    if !matches!(child.data.borrow().value, NodeValue::FootnoteReference(_)) {
+      copy.append(deep_clone(arena, child));
+    }
+  }
+  copy
+}
+
+/// Inserts a caption paragraph before each task list, with the number of its
+/// task items that are checked (e.g. `3/5 done`). Nested lists are counted on
+/// their own, unless `include_nested` is set, in which case only the outermost
+/// task lists get a caption, counting every task item within them.
+fn task_list_progress<'a>(
+  arena: &'a Arena<'a>,
+  root: &'a AstNode<'a>,
+  include_nested: bool,
+) {
+  let progress = |list: &'a AstNode<'a>| {
+    if include_nested {
+      task_counts(list.descendants())
+    } else {
+      task_counts(list.children())
+    }
+  };
+  for list in collect(root, |v| matches!(v, NodeValue::List(_))) {
+    let (checked, total) = progress(list);
+    if total == 0 {
+      continue;
+    }
+    if include_nested
+      && list.ancestors().skip(1).any(|n| {
+        matches!(n.data.borrow().value, NodeValue::List(_)) && progress(n).1 > 0
+      })
+    {
+      continue;
+    }
+    let text = format!("{checked}/{total} done");
+    let caption = arena.alloc(NodeValue::Paragraph.into());
+    caption.append(arena.alloc(NodeValue::Text(text.into()).into()));
+    list.insert_before(caption);
+  }
+}
+
+/// Returns the number of checked task items among `nodes`, and the total.
+fn task_counts<'a>(
+  nodes: impl Iterator<Item = &'a AstNode<'a>>,
+) -> (usize, usize) {
+  nodes.fold((0, 0), |(checked, total), node| {
+    match node.data.borrow().value {
+      | NodeValue::TaskItem(Some(_)) => (checked + 1, total + 1),
+      | NodeValue::TaskItem(None) => (checked, total + 1),
+      | _ => (checked, total),
+    }
+  })
+}
+
+/// Returns the fragments (without the leading `#`) of all links pointing
+/// within the document, in document order.
+pub(crate) fn link_fragments<'a>(
+  root: &'a AstNode<'a>,
+) -> impl Iterator<Item = String> + 'a {
+  root
+    .descendants()
+    .filter_map(|node| match node.data.borrow().value {
+      | NodeValue::Link(ref nl) => nl.url.strip_prefix('#').map(str::to_string),
+      | _ => None,
+    })
+}
+
+/// Reverts autolinks whose URL matches any of the given patterns back into
+/// plain text, by replacing each link node with its children. This runs on
+/// ASTs without their source too, so `<url>` autolinks and explicit links
+/// whose text is their URL are included, as they can't be told apart.
+fn exclude_autolinks<'a>(root: &'a AstNode<'a>, patterns: &[Pattern]) {
+  for link in collect(root, |v| matches!(v, NodeValue::Link(_))) {
+    let url = match link.data.borrow().value {
+      | NodeValue::Link(ref nl) if is_autolink(link, &nl.url) => nl.url.clone(),
+      | _ => continue,
+    };
+    if !patterns.iter().any(|p| p.matches(&url)) {
+      continue;
+    }
+    while let Some(child) = link.first_child() {
+      link.insert_before(child);
+    }
+    link.detach();
+  }
+}
+
+/// Reverts the links created by the `autolink` extension whose kind isn't
+/// enabled back into plain text. Autolinks written between angle brackets are
+/// part of CommonMark itself, so they're always kept.
+fn filter_autolinks<'a>(
+  root: &'a AstNode<'a>,
+  source: &Source,
+  kinds: AutolinkKinds,
+) {
+  for link in collect(root, |v| matches!(v, NodeValue::Link(_))) {
+    let enabled = {
+      let data = link.data.borrow();
+      let NodeValue::Link(ref nl) = data.value else {
+        continue;
+      };
+      if !is_autolink(link, &nl.url)
+        || source.byte_at(data.sourcepos.start) == Some(b'<')
+      {
+        continue;
+      }
+      if nl.url.starts_with("mailto:") {
+        kinds.email
+      } else if nl.url != text_content(link) {
+        // `www.` links are the only others that get a scheme added
+        kinds.www
+      } else {
+        kinds.urls
+      }
+    };
+    if !enabled {
+      while let Some(child) = link.first_child() {
+        link.insert_before(child);
+      }
+      link.detach();
+    }
+  }
+}
+
+/// Moves the given characters from the end of the links created by the
+/// `autolink` extension into the text after them, such as the `).` that ends a
+/// sentence, on top of the trailing punctuation Comrak already leaves out.
+/// Autolinks written between angle brackets are delimited explicitly, so
+/// they're left alone.
+fn trim_autolinks<'a>(
+  arena: &'a Arena<'a>,
+  root: &'a AstNode<'a>,
+  source: &Source,
+  chars: &str,
+) {
+  for link in collect(root, |v| matches!(v, NodeValue::Link(_))) {
+    let trailing = {
+      let data = link.data.borrow();
+      let NodeValue::Link(ref nl) = data.value else {
+        continue;
+      };
+      if !is_autolink(link, &nl.url)
+        || source.byte_at(data.sourcepos.start) == Some(b'<')
+      {
+        continue;
+      }
+      let text = text_content(link);
+      let kept = text.trim_end_matches(|c| chars.contains(c));
+      if kept.is_empty() || kept.len() == text.len() {
+        continue;
+      }
+      text[kept.len()..].to_string()
+    };
+    let len = trailing.len();
+    let Some(child) = link.first_child() else {
+      continue;
+    };
+    for node in [link, child] {
+      let mut data = node.data.borrow_mut();
+      data.sourcepos.end.column -= len;
+      match data.value {
+        | NodeValue::Link(ref mut nl) => nl.url.truncate(nl.url.len() - len),
+        | NodeValue::Text(ref mut literal) => {
+          let kept = literal.len() - len;
+          *literal = literal[..kept].to_string().into();
+        }
+        | _ => {}
+      }
+    }
+    let next = link.next_sibling();
+    let merged = next.is_some_and(|next| {
+      let mut data = next.data.borrow_mut();
+      let NodeValue::Text(ref mut literal) = data.value else {
+        return false;
+      };
+      *literal = format!("{trailing}{literal}").into();
+      data.sourcepos.start.column -= len;
+      true
+    });
+    if !merged {
+      let end = link.data.borrow().sourcepos.end;
+      let text = arena.alloc(NodeValue::Text(trailing.into()).into());
+      let sourcepos = &mut text.data.borrow_mut().sourcepos;
+      sourcepos.start = LineColumn { line: end.line, column: end.column + 1 };
+      sourcepos.end = LineColumn { line: end.line, column: end.column + len };
+      link.insert_after(text);
+    }
+  }
+}
+
+/// Turns bare tokens using one of the given custom schemes, such as
+/// `ticket:1234`, into links. Each scheme maps to a URL template, in which
+/// `{rest}` is replaced with the part of the token after the scheme.
+///
+/// A token starts at a word boundary and ends at the next whitespace, without
+/// any trailing punctuation. Text that is already inside a link is skipped, so
+/// this composes with Comrak's own autolinking.
+fn autolink_schemes<'a>(
+  arena: &'a Arena<'a>,
+  root: &'a AstNode<'a>,
+  schemes: &HashMap<String, String>,
+) {
+  let text =
+    |s: &str| arena.alloc(NodeValue::Text(s.to_string().into()).into());
+  let has_colon = |v: &NodeValue| match v {
+    | NodeValue::Text(literal) => literal.contains(':'),
+    | _ => false,
+  };
+  for node in collect(root, has_colon) {
+    let in_link = node.ancestors().skip(1).any(|n| {
+      matches!(
+        n.data.borrow().value,
+        NodeValue::Link(_) | NodeValue::Image(_) | NodeValue::WikiLink(_)
+      )
+    });
+    if in_link {
+      continue;
+    }
+    let literal = match node.data.borrow().value {
+      | NodeValue::Text(ref literal) => literal.to_string(),
+      | _ => continue,
+    };
+    let mut rest = literal.as_str();
+    while let Some((before, token, url, after)) = find_scheme(rest, schemes) {
+      if !before.is_empty() {
+        node.insert_before(text(before));
+      }
+      let link = NodeLink { url, title: String::new() };
+      let link = arena.alloc(NodeValue::Link(link.into()).into());
+      link.append(text(token));
+      node.insert_before(link);
+      rest = after;
+    }
+    if rest.is_empty() {
+      node.detach();
+    } else if rest.len() < literal.len() {
+      node.data.borrow_mut().value = NodeValue::Text(rest.to_string().into());
+    }
+  }
+}
+
+/// Finds the first token in `text` using one of the given schemes, returning
+/// the text before it, the token itself, its URL, and the text after it.
+fn find_scheme<'t>(
+  text: &'t str,
+  schemes: &HashMap<String, String>,
+) -> Option<(&'t str, &'t str, String, &'t str)> {
+  let is_scheme_char =
+    |c: char| c.is_ascii_alphanumeric() || c == '+' || c == '-';
+  let mut from = 0;
+  loop {
+    let colon = from + text[from..].find(':')?;
+    from = colon + 1;
+    let start = text[..colon]
+      .char_indices()
+      .rev()
+      .take_while(|&(_, c)| is_scheme_char(c))
+      .last()
+      .map_or(colon, |(i, _)| i);
+    let scheme = &text[start..colon];
+    if !scheme.starts_with(|c: char| c.is_ascii_alphabetic()) {
+      continue;
+    }
+    let template = schemes.iter().find_map(|(name, template)| {
+      let name = name.strip_suffix(':').unwrap_or(name);
+      name.eq_ignore_ascii_case(scheme).then_some(template)
+    });
+    let Some(template) = template else {
+      continue;
+    };
+    let after = &text[from..];
+    let len = after
+      .find(|c: char| c.is_whitespace() || c == '<')
+      .unwrap_or(after.len());
+    let value = after[..len].trim_end_matches([
+      '.', ',', ':', ';', '!', '?', '\'', '"', ')', ']',
+    ]);
+    if value.is_empty() {
+      continue;
+    }
+    let end = from + value.len();
+    let url = template.replace("{rest}", value);
+    return Some((&text[..start], &text[start..end], url, &text[end..]));
+  }
+}
+
+/// Turns `@user` mentions and `#tag` hashtags into links, using the URL
+/// templates of the `mentions` extension.
+///
+/// A mention starts after whitespace or an opening bracket or quote, so that
+/// email addresses and URL fragments are left alone, and its name is a run of
+/// letters, digits, `_`, and `-`. A hashtag's name must have a letter in it,
+/// so that numbers such as `#1` aren't linked. Text in code spans or already
+/// inside a link is skipped.
+fn link_mentions<'a>(
+  arena: &'a Arena<'a>,
+  root: &'a AstNode<'a>,
+  mentions: &Mentions,
+) {
+  let text =
+    |s: &str| arena.alloc(NodeValue::Text(s.to_string().into()).into());
+  let has_sigil = |v: &NodeValue| match v {
+    | NodeValue::Text(literal) => literal.contains(['@', '#']),
+    | _ => false,
+  };
+  for node in collect(root, has_sigil) {
+    let in_link = node.ancestors().skip(1).any(|n| {
+      matches!(
+        n.data.borrow().value,
+        NodeValue::Link(_) | NodeValue::Image(_) | NodeValue::WikiLink(_)
+      )
+    });
+    if in_link {
+      continue;
+    }
+    let literal = match node.data.borrow().value {
+      | NodeValue::Text(ref literal) => literal.to_string(),
+      | _ => continue,
+    };
+    let mut rest = literal.as_str();
+    let mut at_start = true;
+    while let Some((before, token, url, after)) =
+      find_mention(rest, at_start, mentions)
+    {
+      if !before.is_empty() {
+        node.insert_before(text(before));
+      }
+      let link = NodeLink { url, title: String::new() };
+      let link = arena.alloc(NodeValue::Link(link.into()).into());
+      link.append(text(token));
+      node.insert_before(link);
+      rest = after;
+      at_start = false;
+    }
+    if rest.is_empty() {
+      node.detach();
+    } else if rest.len() < literal.len() {
+      node.data.borrow_mut().value = NodeValue::Text(rest.to_string().into());
+    }
+  }
+}
+
+/// Finds the first mention or hashtag in `text` that has a URL template,
+/// returning the text before it, the mention itself, its URL, and the text
+/// after it. `at_start` tells whether `text` starts at a word boundary.
+fn find_mention<'t>(
+  text: &'t str,
+  at_start: bool,
+  mentions: &Mentions,
+) -> Option<(&'t str, &'t str, String, &'t str)> {
+  let is_name_char = |c: char| c.is_alphanumeric() || c == '_' || c == '-';
+  let mut prev = None;
+  for (i, c) in text.char_indices() {
+    let boundary = match prev {
+      | None => at_start,
+      | Some(p) => p.is_whitespace() || "([{\"'".contains(p),
+    };
+    prev = Some(c);
+    let template = match c {
+      | '@' => mentions.user_url.as_ref(),
+      | '#' => mentions.tag_url.as_ref(),
+      | _ => continue,
+    };
+    let Some(template) = template.filter(|_| boundary) else {
+      continue;
+    };
+    let after = &text[i + 1..];
+    let len = after.find(|c| !is_name_char(c)).unwrap_or(after.len());
+    let name = after[..len].trim_end_matches('-');
+    if name.is_empty() || (c == '#' && !name.contains(char::is_alphabetic)) {
+      continue;
+    }
+    let end = i + 1 + name.len();
+    let url = template.replace("{name}", name);
+    return Some((&text[..i], &text[i..end], url, &text[end..]));
+  }
+  None
+}
+
+/// Returns `true` if a link's text is just its URL, as is the case for both
+/// `<...>` autolinks and those created by the `autolink` extension (which adds
+/// an `http://` or `mailto:` scheme to `www.` links and bare email addresses).
+pub(crate) fn is_autolink<'a>(link: &'a AstNode<'a>, url: &str) -> bool {
+  let only_text = link.children().count() == 1
+    && link
+      .first_child()
+      .is_some_and(|c| matches!(c.data.borrow().value, NodeValue::Text(_)));
+  if !only_text {
+    return false;
+  }
+  let text = text_content(link);
+  url == text
+    || url.strip_prefix("http://") == Some(text.as_str())
+    || url.strip_prefix("mailto:") == Some(text.as_str())
+}
+
+/// Returns the plain text content of a node, the same way Comrak does when
+/// generating heading IDs.
+pub(crate) fn text_content<'a>(node: &'a AstNode<'a>) -> String {
+  let mut out = String::new();
+  for n in node.descendants() {
+    match n.data.borrow().value {
+      | NodeValue::Text(ref literal) => out.push_str(literal),
+      | NodeValue::Code(ref code) => out.push_str(&code.literal),
+      | NodeValue::Math(ref math) => out.push_str(&math.literal),
+      | NodeValue::LineBreak | NodeValue::SoftBreak => out.push(' '),
+      | _ => {}
+    }
+  }
+  out
+}
+
+/// Rewrites heading levels so that no level is ever skipped. Each heading is
+/// nested one level below the nearest preceding heading with a lower original
+/// level; headings with no such parent keep their original level.
+fn fix_heading_levels<'a>(root: &'a AstNode<'a>) {
+  // (original level, new level) of the current chain of parent headings
+  let mut parents: Vec<(u8, u8)> = Vec::new();
+  for heading in collect(root, |v| matches!(v, NodeValue::Heading(_))) {
+    let mut data = heading.data.borrow_mut();
+    let NodeValue::Heading(ref mut nh) = data.value else {
+      continue;
+    };
+    while parents.last().is_some_and(|&(level, _)| level >= nh.level) {
+      parents.pop();
+    }
+    let level = match parents.last() {
+      | Some(&(_, parent)) => parent + 1,
+      | None => nh.level,
+    };
+    parents.push((nh.level, level));
+    nh.level = level;
+  }
+}
+
+/// Replaces every paragraph consisting solely of the configured marker with a
+/// nested list of links to the document's headings.
+fn table_of_contents<'a>(
+  arena: &'a Arena<'a>,
+  root: &'a AstNode<'a>,
+  toc: &TableOfContents,
+  prefix: &str,
+  slugger: &mut Slugger,
+) -> Result<(), JsValue> {
+  let markers: Vec<_> = collect(root, |v| matches!(v, NodeValue::Paragraph))
+    .into_iter()
+    .filter(|node| text_content(node).trim() == toc.marker)
+    .collect();
+  if markers.is_empty() {
+    return Ok(());
+  }
+
+  let mut entries = Vec::new();
+  for heading in collect(root, |v| matches!(v, NodeValue::Heading(_))) {
+    let NodeValue::Heading(ref nh) = heading.data.borrow().value else {
+      continue;
+    };
+    let text = text_content(heading);
+    // every heading is slugged (even those we skip) so that duplicates are
+    // numbered exactly as they are in the rendered heading IDs
+    let slug = slugger.slug(&text, nh.level)?;
+    if nh.level <= toc.max_level {
+      entries.push((nh.level, text, format!("#{prefix}{slug}")));
+    }
+  }
+
+  for marker in markers {
+    if !entries.is_empty() {
+      marker.insert_before(toc_list(arena, &entries));
+    }
+    marker.detach();
+  }
+  Ok(())
+}
+
+fn toc_list<'a>(
+  arena: &'a Arena<'a>,
+  entries: &[(u8, String, String)],
+) -> &'a AstNode<'a> {
+  let root = bullet_list(arena);
+  let mut stack: Vec<(u8, &'a AstNode<'a>)> = Vec::new();
+  for (level, text, url) in entries {
+    while stack.len() > 1 && stack.last().is_some_and(|&(l, _)| *level < l) {
+      stack.pop();
+    }
+    match stack.last() {
+      | None => stack.push((*level, root)),
+      | Some(&(top, list)) if *level > top => {
+        if let Some(item) = list.last_child() {
+          let nested = match item.last_child() {
+            | Some(n) if matches!(n.data.borrow().value, NodeValue::List(_)) => {
+              n
+            }
+            | _ => {
+              let n = bullet_list(arena);
+              item.append(n);
+              n
+            }
+          };
+          stack.push((*level, nested));
+        }
+      }
+      | Some(_) => {}
+    }
+    let (_, list) = stack[stack.len() - 1];
+    let link = NodeLink { url: url.clone(), title: String::new() };
+    let link = arena.alloc(NodeValue::Link(link.into()).into());
+    link.append(arena.alloc(NodeValue::Text(text.clone().into()).into()));
+    let paragraph = arena.alloc(NodeValue::Paragraph.into());
+    paragraph.append(link);
+    let item = arena.alloc(NodeValue::Item(bullet()).into());
+    item.append(paragraph);
+    list.append(item);
+  }
+  root
+}
+
+fn bullet_list<'a>(arena: &'a Arena<'a>) -> &'a AstNode<'a> {
+  arena.alloc(NodeValue::List(bullet()).into())
+}
+
+fn bullet() -> NodeList {
+  NodeList {
+    list_type: ListType::Bullet,
+    bullet_char: b'-',
+    padding: 2,
+    tight: true,
+    ..NodeList::default()
+  }
+}
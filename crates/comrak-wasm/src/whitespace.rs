@@ -0,0 +1,289 @@
+//! Reformats the whitespace of rendered HTML, for the `minify` and
+//! `pretty_print` render options.
+//!
+//! Only whitespace between tags is changed, and only next to block-level
+//! elements, since whitespace between inline elements (such as
+//! `<em>a</em> <em>b</em>`) is rendered as a space. Text content is left as
+//! it is, and the content of elements where whitespace is significant (such
+//! as `<pre>`) is copied through verbatim.
+
+use wasm_bindgen::JsValue;
+
+use crate::map_err;
+use crate::options::RenderOptions;
+
+/// Elements whose surrounding whitespace is never rendered.
+const BLOCK_ELEMENTS: &[&str] = &[
+  "address", "article", "aside", "blockquote", "body", "br", "caption", "col",
+  "colgroup", "dd", "details", "div", "dl", "dt", "figcaption", "figure",
+  "footer", "form", "h1", "h2", "h3", "h4", "h5", "h6", "head", "header", "hr",
+  "html", "li", "main", "nav", "ol", "p", "pre", "section", "summary", "table",
+  "tbody", "td", "tfoot", "th", "thead", "tr", "ul",
+];
+
+/// Block-level elements that have no content or closing tag.
+const VOID_ELEMENTS: &[&str] = &[
+  "br", "col", "hr",
+];
+
+/// Elements whose content is copied through verbatim.
+const PRESERVED_ELEMENTS: &[&str] = &[
+  "pre", "script", "style", "textarea",
+];
+
+/// The indentation added for each level of nesting by [`pretty_print`].
+const INDENT: &str = "  ";
+
+/// How the whitespace of rendered HTML is reformatted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Layout {
+  /// Left exactly as rendered.
+  AsIs,
+  /// Reformatted with [`minify`].
+  Minified,
+  /// Reformatted with [`pretty_print`].
+  Pretty,
+}
+
+impl Layout {
+  /// Returns the layout selected by the `minify` and `pretty_print` render
+  /// options, which are mutually exclusive.
+  pub fn from_options(options: &RenderOptions) -> Result<Self, JsValue> {
+    match (options.minify, options.pretty_print) {
+      | (true, true) => {
+        Err(map_err("minify and prettyPrint are mutually exclusive"))
+      }
+      | (true, false) => Ok(Self::Minified),
+      | (false, true) => Ok(Self::Pretty),
+      | (false, false) => Ok(Self::AsIs),
+    }
+  }
+
+  pub fn apply(self, html: String) -> String {
+    match self {
+      | Self::AsIs => html,
+      | Self::Minified => minify(&html),
+      | Self::Pretty => pretty_print(&html),
+    }
+  }
+}
+
+/// Removes the whitespace between tags in `html` wherever it isn't rendered,
+/// along with any leading and trailing whitespace.
+pub fn minify(html: &str) -> String {
+  let mut out = String::with_capacity(html.len());
+  let mut tokens = Tokens::new(html).peekable();
+  // the start of the document behaves like a block boundary
+  let mut after_block = true;
+  while let Some(token) = tokens.next() {
+    if let Token::Text(text) = token
+      && text.bytes().all(|b| b.is_ascii_whitespace())
+    {
+      let before_block = tokens.peek().is_none_or(Token::is_block);
+      if after_block || before_block {
+        continue;
+      }
+    }
+    out.push_str(token.raw());
+    after_block = token.is_block();
+  }
+  out
+}
+
+/// Indents `html` by putting each block-level element on its own line, nested
+/// according to its depth, while keeping inline content on the line of the
+/// block it belongs to.
+pub fn pretty_print(html: &str) -> String {
+  #[derive(PartialEq)]
+  enum Last {
+    Open,
+    Close,
+    Inline,
+  }
+
+  let newline = |out: &mut String, depth: usize| {
+    if !out.is_empty() {
+      out.push('\n');
+      out.push_str(&INDENT.repeat(depth));
+    }
+  };
+  let html = minify(html);
+  let mut out = String::with_capacity(html.len() * 2);
+  let mut depth = 0;
+  // the start of the document behaves like the end of a block
+  let mut last = Last::Close;
+  for token in Tokens::new(&html) {
+    match token {
+      | Token::Markup { raw, name: Some(name), closing: false }
+        if is_block(name) =>
+      {
+        let void = raw.ends_with("/>") || contains(VOID_ELEMENTS, name);
+        // a line break ends the line it's on, rather than being on its own
+        if !name.eq_ignore_ascii_case("br") {
+          newline(&mut out, depth);
+        }
+        out.push_str(raw);
+        if void {
+          last = Last::Close;
+        } else {
+          depth += 1;
+          last = Last::Open;
+        }
+      }
+      | Token::Markup { raw, name: Some(name), closing: true }
+        if is_block(name) =>
+      {
+        depth = depth.saturating_sub(1);
+        if last == Last::Close {
+          newline(&mut out, depth);
+        }
+        out.push_str(raw);
+        last = Last::Close;
+      }
+      | token => {
+        if last == Last::Close {
+          newline(&mut out, depth);
+        }
+        out.push_str(token.raw());
+        last = Last::Inline;
+      }
+    }
+  }
+  if !out.is_empty() {
+    out.push('\n');
+  }
+  out
+}
+
+/// A piece of HTML, as split up by [`Tokens`].
+#[derive(Debug, Clone, Copy)]
+enum Token<'h> {
+  /// Text between two pieces of markup.
+  Text(&'h str),
+  /// The content of a preserved element, which must be kept as it is.
+  Verbatim(&'h str),
+  /// A tag, comment, or declaration, with the name of the tag (if it is one)
+  /// and whether it's a closing tag.
+  Markup {
+    raw:     &'h str,
+    name:    Option<&'h str>,
+    closing: bool,
+  },
+}
+
+impl<'h> Token<'h> {
+  fn raw(&self) -> &'h str {
+    match *self {
+      | Self::Text(raw) | Self::Verbatim(raw) | Self::Markup { raw, .. } => raw,
+    }
+  }
+
+  fn is_block(&self) -> bool {
+    match *self {
+      | Self::Markup { name: Some(name), .. } => is_block(name),
+      | _ => false,
+    }
+  }
+}
+
+/// Splits HTML into text and markup.
+struct Tokens<'h> {
+  rest:      &'h str,
+  /// The name of the preserved element whose content comes next, if any.
+  preserved: Option<&'h str>,
+}
+
+impl<'h> Tokens<'h> {
+  fn new(html: &'h str) -> Self {
+    Self { rest: html, preserved: None }
+  }
+
+  fn take(&mut self, len: usize) -> &'h str {
+    let (taken, rest) = self.rest.split_at(len);
+    self.rest = rest;
+    taken
+  }
+}
+
+impl<'h> Iterator for Tokens<'h> {
+  type Item = Token<'h>;
+
+  fn next(&mut self) -> Option<Token<'h>> {
+    if let Some(name) = self.preserved.take() {
+      let len = find_closing_tag(self.rest, name);
+      if len > 0 {
+        return Some(Token::Verbatim(self.take(len)));
+      }
+    }
+    if self.rest.is_empty() {
+      return None;
+    }
+    if !self.rest.starts_with('<') {
+      let len = self.rest.find('<').unwrap_or(self.rest.len());
+      return Some(Token::Text(self.take(len)));
+    }
+    let raw = self.take(markup_len(self.rest));
+    let name = tag_name(raw);
+    let closing = raw.starts_with("</");
+    if let Some(name) = name
+      && !closing
+      && contains(PRESERVED_ELEMENTS, name)
+    {
+      self.preserved = Some(name);
+    }
+    Some(Token::Markup { raw, name, closing })
+  }
+}
+
+fn contains(names: &[&str], name: &str) -> bool {
+  names.iter().any(|n| n.eq_ignore_ascii_case(name))
+}
+
+fn is_block(name: &str) -> bool {
+  contains(BLOCK_ELEMENTS, name)
+}
+
+/// Returns the name of the tag at the start of `markup`, or `None` if it isn't
+/// a tag (e.g. it is a comment, or a stray `<`).
+fn tag_name(markup: &str) -> Option<&str> {
+  let rest = markup.strip_prefix('<')?;
+  let rest = rest.strip_prefix('/').unwrap_or(rest);
+  let len = rest
+    .find(|c: char| !c.is_ascii_alphanumeric())
+    .unwrap_or(rest.len());
+  let name = &rest[..len];
+  name
+    .starts_with(|c: char| c.is_ascii_alphabetic())
+    .then_some(name)
+}
+
+/// Returns the length of the tag, comment, or declaration at the start of
+/// `markup`, which must begin with a `<`. A stray `<` has a length of 1.
+fn markup_len(markup: &str) -> usize {
+  if let Some(comment) = markup.strip_prefix("<!--") {
+    return comment.find("-->").map_or(markup.len(), |n| 4 + n + 3);
+  }
+  if tag_name(markup).is_none() && !markup.starts_with("<!") {
+    return 1;
+  }
+  let mut quote = None;
+  for (i, b) in markup.bytes().enumerate() {
+    match (quote, b) {
+      | (None, b'"' | b'\'') => quote = Some(b),
+      | (Some(q), _) if b == q => quote = None,
+      | (None, b'>') => return i + 1,
+      | _ => {}
+    }
+  }
+  markup.len()
+}
+
+/// Returns the offset of the closing tag of the named element in `html`, or
+/// the length of `html` if it isn't closed.
+fn find_closing_tag(html: &str, name: &str) -> usize {
+  let needle = format!("</{}", name.to_ascii_lowercase());
+  html
+    .to_ascii_lowercase()
+    .find(&needle)
+    .unwrap_or(html.len())
+}
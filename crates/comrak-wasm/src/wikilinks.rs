@@ -0,0 +1,33 @@
+//! Helpers for resolving wikilinks through a user-provided callback.
+
+use js_sys::Function;
+use serde::Deserialize;
+use serde_wasm_bindgen::from_value;
+use wasm_bindgen::JsValue;
+
+/// The URL and optional label of a wikilink, as returned by the
+/// `wikilinkResolver` callback.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct ResolvedWikilink {
+  pub url:   String,
+  #[serde(default)]
+  pub label: Option<String>,
+}
+
+/// Invokes a `wikilinkResolver` callback for the given wikilink title.
+///
+/// Returns `Ok(None)` if the callback returned `null` or `undefined`, and an
+/// error if it threw or returned a value that isn't a valid resolution.
+pub fn resolve_wikilink(
+  resolver: &Function,
+  title: &str,
+) -> Result<Option<ResolvedWikilink>, JsValue> {
+  let title_js = JsValue::from_str(title);
+  let result = resolver.call1(&JsValue::NULL, &title_js)?;
+  if result.is_null() || result.is_undefined() {
+    return Ok(None);
+  }
+  from_value(result).map(Some).map_err(|e| {
+    crate::map_err(format!("invalid wikilink resolution for {title:?}: {e}"))
+  })
+}
@@ -139,11 +139,20 @@ import type {
 } from "./src/options.ts";
 
 export * from "./src/adapters.ts";
+export * from "./src/amp.ts";
 export * from "./src/nodes.ts";
 export * from "./src/cm.ts";
+export * from "./src/convert.ts";
+export * from "./src/document.ts";
+export * from "./src/elements.ts";
+export * from "./src/front_matter.ts";
+export * from "./src/from_html.ts";
 export * from "./src/html.ts";
+export * from "./src/memory.ts";
 export * from "./src/options.ts";
+export * from "./src/paginate.ts";
 export * from "./src/parse.ts";
+export * from "./src/version.ts";
 export * from "./src/xml.ts";
 
 // legacy aliases
@@ -55,6 +55,18 @@ export function collectOptions(options?: Options): [
     },
   } satisfies Options;
 
+  // Comrak only understands the boolean form of `autolink`, so the kinds of
+  // links to keep are passed separately, to be filtered after parsing
+  const { autolink } = opts.extension;
+  if (autolink && typeof autolink === "object") {
+    const { urls = true, www = true, email = true } = autolink;
+    opts.extension = {
+      ...opts.extension,
+      autolink: urls || www || email,
+      autolinkKinds: { urls, www, email },
+    } as typeof opts.extension;
+  }
+
   let { brokenLinkCallback = null } = opts.parse;
   if (brokenLinkCallback && typeof brokenLinkCallback !== "function") {
     if (
@@ -79,7 +91,7 @@ export function collectOptions(options?: Options): [
       typeof imageURLRewriter.toHTML === "function"
     ) {
       const rewriter = imageURLRewriter;
-      imageURLRewriter = (url) => rewriter.toHTML(url);
+      imageURLRewriter = (url, context) => rewriter.toHTML(url, context);
     } else {
       imageURLRewriter = null;
     }
@@ -91,7 +103,7 @@ export function collectOptions(options?: Options): [
       typeof linkURLRewriter.toHTML === "function"
     ) {
       const rewriter = linkURLRewriter;
-      linkURLRewriter = (url) => rewriter.toHTML(url);
+      linkURLRewriter = (url, context) => rewriter.toHTML(url, context);
     } else {
       linkURLRewriter = null;
     }
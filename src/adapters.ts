@@ -168,11 +168,17 @@ export interface SyntaxHighlighterAdapter {
    * Invoked with the raw `code` content and `lang` string (if one was given).
    * Should return the highlighted HTML to be inserted inside the `<code>` tag.
    *
+   * For code blocks, the rest of the info string following the language is
+   * passed as `meta`, such as `title="main.ts" {1,3}` for a code fence opened
+   * with ` ```ts title="main.ts" {1,3} `. This is useful for rendering file
+   * titles or highlighting specific lines. It is `null` if there is no meta.
+   *
    * @param code The raw code content.
    * @param [lang] The optional language identifier.
+   * @param [meta] The optional meta string following the language.
    * @returns The highlighted HTML string.
    */
-  highlight(code: string, lang?: string | null): string;
+  highlight(code: string, lang?: string | null, meta?: string | null): string;
 
   /**
    * Called to render the `<pre>` element that wraps the code block.
@@ -0,0 +1,74 @@
+import { describe, it, type TestContext } from "node:test";
+
+import { SyntaxHighlighterAdapter } from "./_wasm.ts";
+import { markdownToAMP } from "./amp.ts";
+import { hasFeature } from "./version.ts";
+
+describe("markdownToAMP", () => {
+  it("should convert images to <amp-img>", (t: TestContext) => {
+    const urls: string[] = [];
+    const html = markdownToAMP("![a cat](cat.png?w=1&h=2)", {
+      render: {
+        imageDimensions(url) {
+          urls.push(url);
+          return { width: 320, height: 240 };
+        },
+      },
+    });
+    t.assert.deepStrictEqual(urls, ["cat.png?w=1&h=2"]);
+    t.assert.strictEqual(
+      html,
+      '<p><amp-img src="cat.png?w=1&amp;h=2" alt="a cat" width="320" ' +
+        'height="240" layout="responsive"></amp-img></p>\n',
+    );
+  });
+
+  it("should fall back to a fill layout", (t: TestContext) => {
+    const html = markdownToAMP("![a cat](cat.png)");
+    t.assert.strictEqual(
+      html,
+      '<p><amp-img src="cat.png" alt="a cat" layout="fill"></amp-img></p>\n',
+    );
+  });
+
+  it("should strip disallowed elements and attributes", (t: TestContext) => {
+    const md = '<p style="color:red" onclick="x()">Hi</p>\n\n' +
+      "<script>alert(1)</script>\n\n[link](javascript:alert(1))\n";
+    const html = markdownToAMP(md, { render: { unsafe: true } });
+    t.assert.strictEqual(html, "<p>Hi</p>\n\n<p><a>link</a></p>\n");
+  });
+
+  it("should highlight code with classes", (t: TestContext) => {
+    if (!hasFeature("syntect")) {
+      return t.skip("requires the syntect feature");
+    }
+    // the constructor takes a theme with the syntect feature
+    // deno-lint-ignore no-explicit-any
+    const Adapter = SyntaxHighlighterAdapter as any;
+    const html = markdownToAMP("```rs\nfn main() {}\n```\n", {
+      plugins: {
+        render: {
+          codefenceSyntaxHighlighter: new Adapter("base16-ocean.dark"),
+        },
+      },
+    });
+    t.assert.ok(!html.includes("style="));
+    t.assert.match(html, /<span class="[^"]*rust/);
+  });
+
+  it("should strip the styles of custom highlighters", (t: TestContext) => {
+    if (hasFeature("syntect")) {
+      return t.skip("requires custom syntax highlighters");
+    }
+    const html = markdownToAMP("```\nx\n```\n", {
+      plugins: {
+        render: {
+          codefenceSyntaxHighlighter: {
+            highlight: (code) => `<span style="color:red">${code}</span>`,
+          },
+        },
+      },
+    });
+    t.assert.strictEqual(html, "<pre><code><span>x\n</span></code></pre>\n");
+  });
+});
@@ -0,0 +1,59 @@
+/**
+ * This module provides the {@linkcode markdownToAMP} function, which renders
+ * Markdown documents into HTML that is compatible with the [AMP] validator.
+ *
+ * [AMP]: https://amp.dev "AMP: A web component framework"
+ *
+ * @module amp
+ */
+import wasm from "./_wasm.ts";
+import { collectOptions } from "./_internal.ts";
+import type { Options } from "./options.ts";
+
+/**
+ * Render Markdown to AMP-compatible HTML.
+ *
+ * The document is rendered exactly like {@linkcode markdownToHTML}, and the
+ * output is then rewritten to satisfy the AMP validator:
+ *
+ * - Images are emitted as `<amp-img>` elements. Their `width` and `height`
+ *   are resolved with the {@linkcode RenderOptions.imageDimensions} callback;
+ *   images of unknown size fall back to `layout="fill"`.
+ * - Disallowed elements such as `<script>`, `<style>`, and `<iframe>` are
+ *   removed, along with their content.
+ * - Inline `style` attributes, `on*` event handlers, and `javascript:` URLs
+ *   are stripped from all elements.
+ *
+ * Since AMP does not permit inline styles, the built-in syntax highlighter
+ * (with the `"syntect"` feature) always emits class-based markup here, even
+ * when it was created with a theme, as if it were created with
+ * `SyntaxHighlighterAdapter.classed()`. The page then has to include the
+ * theme's stylesheet, from `css_for_theme`, for the code to be colored. A
+ * custom syntax highlighter is not converted: any inline styles in its
+ * markup are stripped like any other, so it should emit class-based markup
+ * itself.
+ *
+ * @param markdown The Markdown string to be converted.
+ * @param [options] Options to customize the conversion.
+ * @returns The generated AMP-compatible HTML string.
+ * @example
+ * ```ts
+ * import assert from "node:assert";
+ * import { markdownToAMP } from "@nick/comrak";
+ *
+ * const html = markdownToAMP("![logo](logo.png)", {
+ *   render: { imageDimensions: () => ({ width: 64, height: 64 }) },
+ * });
+ * assert.strictEqual(
+ *   html,
+ *   '<p><amp-img src="logo.png" alt="logo" width="64" height="64" ' +
+ *     'layout="responsive"></amp-img></p>\n',
+ * );
+ * ```
+ * @category Conversion
+ * @tags amp
+ */
+export function markdownToAMP(markdown: string, options?: Options): string {
+  const args = collectOptions(options);
+  return wasm.markdown_to_amp(markdown, ...args);
+}
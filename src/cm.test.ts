@@ -35,6 +35,49 @@ describe("markdownToCommonMark", () => {
         );
       }
     });
+
+    it("should preserve soft breaks when set", (t: TestContext) => {
+      const md = "a b c d e f g h\ni j\n";
+      const wrapped = markdownToCommonMark(md, { render: { width: 10 } });
+      t.assert.strictEqual(wrapped, "a b c d e\nf g h i j\n");
+      const cm = markdownToCommonMark(md, {
+        render: { width: 10, preserveSoftBreaks: true },
+      });
+      t.assert.strictEqual(cm, md);
+    });
+
+    it("should align table columns", (t: TestContext) => {
+      const md = "> | a | b |\n> | :-: | - |\n> | long | c |\n\n" +
+        "```\n| not | a |\n| - | - |\n```\n";
+      const cm = markdownToCommonMark(md, {
+        extension: { table: true },
+        render: { alignTableColumns: true },
+      });
+      t.assert.strictEqual(
+        cm,
+        "> |  a   | b   |\n" +
+          "> | :--: | --- |\n" +
+          "> | long | c   |\n\n" +
+          "```\n| not | a |\n| - | - |\n```\n",
+      );
+    });
+
+    it("should write line endings per lineEnding", (t: TestContext) => {
+      const md = "# a\r\n\r\n- b\r\n- c\r\n";
+      const cm = "# a\n\n- b\n- c\n";
+      t.assert.strictEqual(markdownToCommonMark(md), cm);
+      const crlf = cm.replaceAll("\n", "\r\n");
+      const render = (lineEnding: "lf" | "crlf" | "preserve") =>
+        markdownToCommonMark(md, { render: { lineEnding } });
+      t.assert.strictEqual(render("preserve"), crlf);
+      t.assert.strictEqual(render("crlf"), crlf);
+      t.assert.strictEqual(
+        markdownToCommonMark("a\nb\r\nc\n", {
+          render: { lineEnding: "preserve" },
+        }),
+        "a\nb\nc\n",
+      );
+    });
   });
 });
 
@@ -0,0 +1,38 @@
+import { describe, it, type TestContext } from "node:test";
+
+import { markdownToCommonMark } from "./cm.ts";
+import { markdownTo } from "./convert.ts";
+import { markdownToHTML } from "./html.ts";
+import { markdownToXML } from "./xml.ts";
+
+describe("markdownTo", () => {
+  const md = "# Title\n\n- one\n- *two*\n\n| a | b |\n|---|---|\n| 1 | 2 |\n";
+  const options = { extension: { table: true } };
+
+  it("should match the format-specific functions", (t: TestContext) => {
+    t.assert.strictEqual(
+      markdownTo(md, "html", options),
+      markdownToHTML(md, options),
+    );
+    t.assert.strictEqual(
+      markdownTo(md, "xml", options),
+      markdownToXML(md, options),
+    );
+    t.assert.strictEqual(
+      markdownTo(md, "commonmark", options),
+      markdownToCommonMark(md, options),
+    );
+  });
+
+  it("should render plain text", (t: TestContext) => {
+    t.assert.strictEqual(
+      markdownTo(md, "plaintext", options),
+      "Title\none\ntwo\na\tb\n1\t2\n",
+    );
+  });
+
+  it("should throw for unknown formats", (t: TestContext) => {
+    // deno-lint-ignore no-explicit-any
+    t.assert.throws(() => markdownTo(md, "pdf" as any), TypeError);
+  });
+});
@@ -0,0 +1,62 @@
+/**
+ * This module provides the {@linkcode markdownTo} function, which converts a
+ * Markdown document into an output format chosen at runtime, for callers that
+ * would otherwise have to branch over the format-specific functions.
+ *
+ * @module convert
+ */
+import wasm from "./_wasm.ts";
+import { collectOptions } from "./_internal.ts";
+import type { Options } from "./options.ts";
+
+/**
+ * The output formats supported by {@linkcode markdownTo}.
+ *
+ * - `"html"`: HTML, as rendered by {@linkcode markdownToHTML}.
+ * - `"xml"`: CommonMark XML, as rendered by {@linkcode markdownToXML}.
+ * - `"commonmark"`: CommonMark, as rendered by
+ *   {@linkcode markdownToCommonMark}.
+ * - `"plaintext"`: the text content of the document alone, with each
+ *   paragraph, heading, code block, and table row on its own line, and the
+ *   cells of table rows separated by tabs. Raw HTML is omitted.
+ *
+ * @category Conversion
+ */
+export type OutputFormat = "html" | "xml" | "commonmark" | "plaintext";
+
+/**
+ * Render Markdown to the given output format.
+ *
+ * This is equivalent to calling the conversion function for the format, such
+ * as {@linkcode markdownToHTML} for `"html"`, with the same options.
+ *
+ * @param markdown The Markdown string to be converted.
+ * @param format The format to render the Markdown to.
+ * @param [options] Options to customize the conversion.
+ * @returns The rendered output.
+ * @throws {TypeError} If the format is not supported.
+ * @example
+ * ```ts
+ * import { markdownTo } from "@nick/comrak";
+ * import assert from "node:assert";
+ *
+ * const md = "# Hello\n\nSome *emphasized* text.";
+ * assert.strictEqual(
+ *   markdownTo(md, "html"),
+ *   "<h1>Hello</h1>\n<p>Some <em>emphasized</em> text.</p>\n",
+ * );
+ * assert.strictEqual(
+ *   markdownTo(md, "plaintext"),
+ *   "Hello\nSome emphasized text.\n",
+ * );
+ * ```
+ * @category Conversion
+ */
+export function markdownTo(
+  markdown: string,
+  format: OutputFormat,
+  options?: Options,
+): string {
+  const args = collectOptions(options);
+  return wasm.markdown_to(markdown, format, ...args);
+}
@@ -0,0 +1,41 @@
+import { describe, it, type TestContext } from "node:test";
+
+import { markdownToCommonMark } from "./cm.ts";
+import { Document } from "./document.ts";
+import { markdownToHTML } from "./html.ts";
+import { markdownToXML } from "./xml.ts";
+
+describe("Document", () => {
+  const md = "# Title\n\n## Usage\n\nSee [the docs][docs].\n\n[docs]: /docs\n";
+
+  it("should match the format-specific functions", (t: TestContext) => {
+    using doc = new Document(md);
+    t.assert.strictEqual(doc.toHTML(), markdownToHTML(md));
+    t.assert.strictEqual(doc.toXML(), markdownToXML(md));
+    t.assert.strictEqual(doc.toCommonMark(), markdownToCommonMark(md));
+  });
+
+  it("should render with different options each time", (t: TestContext) => {
+    using doc = new Document(md);
+    const options = { extension: { headerIDs: "" } };
+    t.assert.strictEqual(doc.toHTML(options), markdownToHTML(md, options));
+    t.assert.strictEqual(doc.toHTML(), markdownToHTML(md));
+  });
+
+  it("should apply parse options once", (t: TestContext) => {
+    const text = "Visit https://example.com";
+    using doc = new Document(text, { extension: { autolink: true } });
+    t.assert.strictEqual(
+      doc.toHTML(),
+      markdownToHTML(text, { extension: { autolink: true } }),
+    );
+  });
+
+  it("should return the headings", (t: TestContext) => {
+    using doc = new Document(md);
+    t.assert.deepStrictEqual(doc.headings(), [
+      { level: 1, content: "Title" },
+      { level: 2, content: "Usage" },
+    ]);
+  });
+});
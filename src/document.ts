@@ -0,0 +1,117 @@
+/**
+ * This module provides the {@linkcode Document} class, a handle to a parsed
+ * Markdown document that can be rendered several times without reparsing it.
+ *
+ * @module document
+ */
+import { Document as WasmDocument } from "./_wasm.ts";
+import { collectOptions } from "./_internal.ts";
+import type { HeadingMeta } from "./adapters.ts";
+import type { Options } from "./options.ts";
+
+/**
+ * A parsed Markdown document, which keeps its AST in WebAssembly memory so it
+ * can be rendered to several formats, or with different render options,
+ * without being parsed again. This avoids both the reparsing done by the
+ * `markdownTo*` functions and the serialization of the AST done by
+ * {@linkcode parseMarkdown} and the `render*` functions.
+ *
+ * The parse options, `brokenLinkCallback`, and URL rewriters are applied once,
+ * when the document is constructed. The options passed to each render method
+ * only affect rendering.
+ *
+ * **The memory held by a document is not garbage collected.** Call
+ * {@linkcode Document.free} once the document is no longer needed, or declare
+ * it with `using` to have it freed automatically at the end of its scope.
+ *
+ * @example
+ * ```ts
+ * import { Document } from "@nick/comrak";
+ * import assert from "node:assert";
+ *
+ * using doc = new Document("# Hello\n\nSome *emphasized* text.");
+ * assert.strictEqual(
+ *   doc.toHTML(),
+ *   "<h1>Hello</h1>\n<p>Some <em>emphasized</em> text.</p>\n",
+ * );
+ * assert.strictEqual(
+ *   doc.toCommonMark(),
+ *   "# Hello\n\nSome *emphasized* text.\n",
+ * );
+ * assert.deepStrictEqual(doc.headings(), [{ level: 1, content: "Hello" }]);
+ * ```
+ * @category Parsing
+ */
+export class Document implements Disposable {
+  #inner: WasmDocument;
+
+  /**
+   * Parses the given Markdown into a new document.
+   *
+   * @param markdown The Markdown string to be parsed.
+   * @param [options] Options to customize parsing.
+   */
+  constructor(markdown: string, options?: Options) {
+    const [opts, , , brokenLinkCallback, imageURLRewriter, linkURLRewriter] =
+      collectOptions(options);
+    this.#inner = new WasmDocument(
+      markdown,
+      opts,
+      brokenLinkCallback,
+      imageURLRewriter,
+      linkURLRewriter,
+    );
+  }
+
+  /**
+   * Render the document to HTML.
+   *
+   * @param [options] Options to customize rendering.
+   * @returns The generated HTML string.
+   */
+  toHTML(options?: Options): string {
+    const [opts, syntaxAdapter, headingAdapter] = collectOptions(options);
+    return this.#inner.toHtml(opts, syntaxAdapter, headingAdapter);
+  }
+
+  /**
+   * Render the document to XML.
+   *
+   * @param [options] Options to customize rendering.
+   * @returns The generated XML string.
+   */
+  toXML(options?: Options): string {
+    const [opts, syntaxAdapter, headingAdapter] = collectOptions(options);
+    return this.#inner.toXml(opts, syntaxAdapter, headingAdapter);
+  }
+
+  /**
+   * Render the document to CommonMark.
+   *
+   * @param [options] Options to customize rendering.
+   * @returns The generated CommonMark string.
+   */
+  toCommonMark(options?: Options): string {
+    const [opts, syntaxAdapter, headingAdapter] = collectOptions(options);
+    return this.#inner.toCommonmark(opts, syntaxAdapter, headingAdapter);
+  }
+
+  /**
+   * Returns the level and text content of every heading in the document, in
+   * document order.
+   */
+  headings(): HeadingMeta[] {
+    return this.#inner.headings();
+  }
+
+  /**
+   * Frees the memory held by the document. It must not be used afterwards.
+   */
+  free(): void {
+    this.#inner.free();
+  }
+
+  [Symbol.dispose](): void {
+    this.free();
+  }
+}
@@ -0,0 +1,83 @@
+import { describe, it, type TestContext } from "node:test";
+
+import { type ElementNode, markdownToElements } from "./elements.ts";
+
+const el = (
+  type: string,
+  props: ElementNode["props"],
+  ...children: ElementNode["children"]
+): ElementNode => ({ type, props, children });
+
+describe("markdownToElements", () => {
+  it("should mirror the structure of the HTML", (t: TestContext) => {
+    const md = "# Hi\n\n- a\n- *b*\n\n```ts\nlet x;\n```\n";
+    t.assert.deepStrictEqual(markdownToElements(md), [
+      el("h1", {}, "Hi"),
+      el("ul", {}, el("li", {}, "a"), el("li", {}, el("em", {}, "b"))),
+      el("pre", {}, el("code", { class: "language-ts" }, "let x;\n")),
+    ]);
+  });
+
+  it("should apply heading IDs and table alignments", (t: TestContext) => {
+    const md = "# One\n\n| a | b |\n| :- | -: |\n| 1 | 2 |\n";
+    const options = { extension: { headerIDs: "h-", table: true } };
+    t.assert.deepStrictEqual(markdownToElements(md, options), [
+      el("h1", { id: "h-one" }, "One"),
+      el(
+        "table",
+        {},
+        el(
+          "thead",
+          {},
+          el(
+            "tr",
+            {},
+            el("th", { align: "left" }, "a"),
+            el("th", { align: "right" }, "b"),
+          ),
+        ),
+        el(
+          "tbody",
+          {},
+          el(
+            "tr",
+            {},
+            el("td", { align: "left" }, "1"),
+            el("td", { align: "right" }, "2"),
+          ),
+        ),
+      ),
+    ]);
+  });
+
+  it("should only include raw HTML when unsafe", (t: TestContext) => {
+    const md = "<div>hi</div>\n";
+    t.assert.deepStrictEqual(markdownToElements(md), []);
+    t.assert.deepStrictEqual(
+      markdownToElements(md, { render: { unsafe: true } }),
+      [el("#html", { html: "<div>hi</div>\n" })],
+    );
+  });
+
+  it("should filter and sanitize raw HTML", (t: TestContext) => {
+    const md = '<div onclick="x()">hi</div>\n\nA <script>x()</script>\n';
+    t.assert.deepStrictEqual(
+      markdownToElements(md, { render: { rawHtml: "filter" } }),
+      [
+        el("#html", { html: '<div onclick="x()">hi</div>\n' }),
+        el(
+          "p",
+          {},
+          "A ",
+          el("#html", { html: "&lt;script>" }),
+          "x()",
+          el("#html", { html: "&lt;/script>" }),
+        ),
+      ],
+    );
+    t.assert.deepStrictEqual(
+      markdownToElements(md, { render: { unsafe: true, sanitizeHtml: true } }),
+      [el("#html", { html: "<div>hi</div>\n" }), el("p", {}, "A x()")],
+    );
+  });
+});
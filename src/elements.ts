@@ -0,0 +1,83 @@
+/**
+ * This module provides the {@linkcode markdownToElements} function, which
+ * renders Markdown into a tree of HTML elements rather than an HTML string,
+ * for front-end frameworks that create their elements themselves.
+ *
+ * @module elements
+ */
+import { markdown_to_elements } from "./_wasm.ts";
+import { collectOptions } from "./_internal.ts";
+import type { Options } from "./options.ts";
+
+/**
+ * An HTML element in the tree returned by {@linkcode markdownToElements}.
+ *
+ * Props are named after the HTML attributes they represent, such as `class`
+ * and `data-footnote-ref`, so they may need to be renamed for frameworks that
+ * use different names (e.g. `className` in React).
+ *
+ * Raw HTML is represented by an element with a `type` of `"#html"`, whose
+ * `html` prop holds the markup and which never has any children.
+ *
+ * @category Conversion
+ */
+export interface ElementNode {
+  /** The tag name of the element, such as `"p"` or `"a"`. */
+  type: string;
+  /** The attributes of the element. */
+  props: Record<string, string | number | boolean>;
+  /** The child elements and text of the element. */
+  children: ElementChild[];
+}
+
+/**
+ * A child of an {@linkcode ElementNode}: either a nested element, or text.
+ *
+ * @category Conversion
+ */
+export type ElementChild = ElementNode | string;
+
+/**
+ * Render Markdown to a tree of HTML elements, in the `{ type, props,
+ * children }` shape used by front-end frameworks, instead of an HTML string.
+ *
+ * The tree mirrors the HTML rendered by {@linkcode markdownToHTML}, which
+ * saves clients from handling every type of node in the {@linkcode AST}
+ * themselves. Text is represented by strings in the `children` of each
+ * element. Raw HTML is only included when the `render.unsafe` option is set,
+ * as an `#html` element (see {@linkcode ElementNode}). Its markup goes through
+ * the `tagfilter` extension and the `render.sanitizeHtml` option just like the
+ * HTML output, with each `#html` element sanitized on its own.
+ *
+ * @param markdown The Markdown string to be converted.
+ * @param [options] Options to customize the conversion.
+ * @returns The top-level elements of the document.
+ * @example
+ * ```ts
+ * import { markdownToElements } from "@nick/comrak";
+ * import assert from "node:assert";
+ *
+ * const elements = markdownToElements("Hello, [*world*](/world)!");
+ * assert.deepStrictEqual(elements, [{
+ *   type: "p",
+ *   props: {},
+ *   children: [
+ *     "Hello, ",
+ *     {
+ *       type: "a",
+ *       props: { href: "/world" },
+ *       children: [{ type: "em", props: {}, children: ["world"] }],
+ *     },
+ *     "!",
+ *   ],
+ * }]);
+ * ```
+ * @category Conversion
+ */
+export function markdownToElements(
+  markdown: string,
+  options?: Options,
+): ElementChild[] {
+  const [opts, , , ...fns] = collectOptions(options);
+  return markdown_to_elements(markdown, opts, ...fns);
+}
@@ -0,0 +1,37 @@
+import { describe, it, type TestContext } from "node:test";
+
+import { htmlToMarkdown } from "./from_html.ts";
+
+describe("htmlToMarkdown", () => {
+  it("should convert blocks and inlines", (t: TestContext) => {
+    const html = "<h2>Title</h2>\n<p>Some <em>emphasized</em> and " +
+      "<strong>strong</strong> text,<br>with <code>code</code>.</p>\n" +
+      "<ul><li>one<li>two</ul>\n<blockquote><p>quoted</p></blockquote>";
+    t.assert.strictEqual(
+      htmlToMarkdown(html),
+      "## Title\n\nSome *emphasized* and **strong** text,\\\n" +
+        "with `code`.\n\n- one\n- two\n\n> quoted\n",
+    );
+  });
+
+  it("should convert tables with a header row", (t: TestContext) => {
+    const html = "<table><thead><tr><th>a</th><th align=\"right\">b</th>" +
+      "</tr></thead><tbody><tr><td>1</td><td>2</td></tr></tbody></table>";
+    t.assert.strictEqual(
+      htmlToMarkdown(html),
+      "| a | b |\n| --- | --: |\n| 1 | 2 |\n",
+    );
+  });
+
+  it("should keep or drop unsupported elements", (t: TestContext) => {
+    const html = "<p>a <span>b</span><!-- c --></p>";
+    t.assert.strictEqual(
+      htmlToMarkdown(html),
+      "a <span>b</span><!-- c -->\n",
+    );
+    t.assert.strictEqual(
+      htmlToMarkdown(html, {}, { unsupported: "drop" }),
+      "a b\n",
+    );
+  });
+});
@@ -0,0 +1,70 @@
+/**
+ * This module provides the {@linkcode htmlToMarkdown} function, which converts
+ * an HTML document back into Markdown, e.g. to import content written in a
+ * rich text editor or scraped from a web page.
+ *
+ * @module from-html
+ */
+import wasm from "./_wasm.ts";
+import { collectOptions } from "./_internal.ts";
+import type { Options } from "./options.ts";
+
+/**
+ * Options for converting HTML to Markdown with {@linkcode htmlToMarkdown}.
+ *
+ * @category Options
+ * @tags html
+ */
+export interface HtmlToMarkdownOptions {
+  /**
+   * What to do with the elements that have no Markdown equivalent, such as
+   * `<span>` or `<details>`, and with HTML comments.
+   *
+   * - `"raw"`: keep their tags as raw HTML in the Markdown output.
+   * - `"drop"`: drop their tags, keeping their content as text. Comments and
+   *   the content of `<script>` and `<style>` elements are dropped entirely.
+   *
+   * @default {"raw"}
+   */
+  unsupported?: "raw" | "drop";
+}
+
+/**
+ * Convert HTML to Markdown.
+ *
+ * The HTML is converted to an {@linkcode AST}, which is then formatted as
+ * CommonMark exactly like {@linkcode renderCommonMark} would, with the same
+ * render options. Headings, paragraphs, block quotes, lists, code blocks and
+ * inline code, links, images, emphasis, strikethrough, line breaks, thematic
+ * breaks, and tables are converted. The first row of a table is always used
+ * as its header, since Markdown tables require one.
+ *
+ * This uses a lightweight tokenizer rather than a full HTML parser. Missing
+ * end tags are implied wherever the next element can't be nested in the open
+ * one, but malformed HTML is otherwise converted on a best-effort basis.
+ *
+ * @param html The HTML string to be converted.
+ * @param [options] Options to customize the formatting of the Markdown.
+ * @param [conversion] Options to customize the conversion itself.
+ * @returns The generated Markdown string.
+ * @example
+ * ```ts
+ * import { htmlToMarkdown } from "@nick/comrak";
+ * import assert from "node:assert";
+ *
+ * const html = '<h1>Hello</h1><p>See <a href="/docs">the docs</a>.</p>';
+ * assert.strictEqual(
+ *   htmlToMarkdown(html),
+ *   "# Hello\n\nSee [the docs](/docs).\n",
+ * );
+ * ```
+ * @category Conversion
+ */
+export function htmlToMarkdown(
+  html: string,
+  options?: Options,
+  conversion?: HtmlToMarkdownOptions,
+): string {
+  const [opts] = collectOptions(options);
+  return wasm.html_to_markdown(html, opts, conversion);
+}
@@ -0,0 +1,29 @@
+import { describe, it, type TestContext } from "node:test";
+
+import { splitFrontMatter } from "./front_matter.ts";
+import { markdownToHTML } from "./html.ts";
+
+describe("splitFrontMatter", () => {
+  it("should split front matter from the body", (t: TestContext) => {
+    const md = "---\nlayout: post\ntitle: Hi\n---\n# Hello\n";
+    const { frontMatter, body } = splitFrontMatter(md, "---");
+    t.assert.strictEqual(frontMatter, "layout: post\ntitle: Hi\n");
+    t.assert.strictEqual(body, "# Hello\n");
+  });
+
+  it("should match what the renderer skips", (t: TestContext) => {
+    const md = "+++\ndraft = true\n+++\n\n\nText\n";
+    const { body } = splitFrontMatter(md, "+++");
+    t.assert.strictEqual(
+      markdownToHTML(md, { extension: { frontMatterDelimiter: "+++" } }),
+      markdownToHTML(body),
+    );
+  });
+
+  it("should return null when there is no front matter", (t: TestContext) => {
+    const md = "# Hello\n\n---\nnot: front matter\n---\n";
+    const { frontMatter, body } = splitFrontMatter(md, "---");
+    t.assert.strictEqual(frontMatter, null);
+    t.assert.strictEqual(body, md);
+  });
+});
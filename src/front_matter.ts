@@ -0,0 +1,60 @@
+/**
+ * This module provides the {@linkcode splitFrontMatter} function, which can be
+ * used to separate a Markdown document's front matter block from its body,
+ * using the exact same detection logic that Comrak applies when the
+ * {@linkcode ExtensionOptions.frontMatterDelimiter} option is set.
+ *
+ * @module front-matter
+ */
+import { split_front_matter } from "./_wasm.ts";
+
+/**
+ * The result of splitting a Markdown document into its front matter and body.
+ *
+ * @category Parsing
+ * @tags front-matter
+ */
+export interface FrontMatterSplit {
+  /**
+   * The raw contents of the front matter block, **excluding** the opening and
+   * closing delimiter lines, or `null` if no front matter was found.
+   */
+  frontMatter: string | null;
+  /**
+   * The remainder of the document following the front matter block. If no
+   * front matter was found, this is the original document, unchanged.
+   */
+  body: string;
+}
+
+/**
+ * Splits a Markdown document into its front matter block and its body. This
+ * reuses Comrak's own front matter detection, so the split always matches what
+ * the renderer would skip when `frontMatterDelimiter` is set to the same value.
+ *
+ * The returned `frontMatter` string does **not** include the delimiter lines,
+ * making it suitable to pass directly into a YAML or TOML parser.
+ *
+ * @param markdown The Markdown document to split.
+ * @param [delimiter="---"] The front matter delimiter to look for.
+ * @returns An object containing the front matter (or `null`) and the body.
+ * @example
+ * ```ts
+ * import { splitFrontMatter } from "@nick/comrak";
+ * import assert from "node:assert";
+ *
+ * const md = "---\ntitle: Hello\n---\n# Hello, world!\n";
+ * const { frontMatter, body } = splitFrontMatter(md, "---");
+ *
+ * assert.strictEqual(frontMatter, "title: Hello\n");
+ * assert.strictEqual(body, "# Hello, world!\n");
+ * ```
+ * @category Parsing
+ * @tags front-matter
+ */
+export function splitFrontMatter(
+  markdown: string,
+  delimiter = "---",
+): FrontMatterSplit {
+  return split_front_matter(markdown, delimiter);
+}
@@ -146,3 +146,60 @@ for (const { name: group, input, warmup, n, options } of benchmarks) {
     });
   }
 }
+
+{
+  const { compileOptions, markdownToHTML, markdownToHTMLWith } = await import(
+    "../mod.ts"
+  );
+  const group = `Basic Markdown (${basic_md.length} B): compiled options`;
+  const handle = compileOptions(options);
+
+  Deno.bench({
+    name: "markdownToHTML",
+    group,
+    baseline: true,
+    fn() {
+      markdownToHTML(basic_md, options);
+    },
+  });
+
+  Deno.bench({
+    name: "markdownToHTMLWith",
+    group,
+    fn() {
+      markdownToHTMLWith(handle, basic_md);
+    },
+  });
+}
+
+{
+  const { hasFeature, releaseMemory } = await import("../mod.ts");
+  const { SyntaxHighlighterAdapter } = await import("./_wasm.ts");
+  const group = "SyntaxHighlighterAdapter construction";
+  const ignore = !hasFeature("syntect");
+  // the constructor takes a theme with the syntect feature
+  // deno-lint-ignore no-explicit-any
+  const Adapter = SyntaxHighlighterAdapter as any;
+
+  // loads Syntect's default syntaxes and themes every time
+  Deno.bench({
+    name: "uncached",
+    group,
+    baseline: true,
+    ignore,
+    fn() {
+      releaseMemory();
+      new Adapter("base16-ocean.dark").free();
+    },
+  });
+
+  // shares the syntaxes and themes loaded by an earlier adapter
+  Deno.bench({
+    name: "cached",
+    group,
+    ignore,
+    fn() {
+      new Adapter("base16-ocean.dark").free();
+    },
+  });
+}
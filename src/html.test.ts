@@ -1,7 +1,14 @@
 import { describe, it, type TestContext } from "node:test";
 
 import { legacy } from "../scripts/parse_comrak_version.ts";
-import { markdownToHTML } from "./html.ts";
+import {
+  compileOptions,
+  markdownToHTML,
+  markdownToHTMLStreaming,
+  markdownToHTMLWith,
+  renderHTML,
+} from "./html.ts";
+import { parseMarkdown } from "./parse.ts";
 
 describe("markdownToHTML", () => {
   describe("basic functionality and sanity checks", () => {
@@ -65,6 +72,23 @@ describe("markdownToHTML", () => {
       );
     });
 
+    it("should only autolink the enabled kinds of links", (t: TestContext) => {
+      const md = "www.a.dev, https://b.dev, c@d.dev, and <https://e.dev>";
+      t.assert.strictEqual(
+        markdownToHTML(md, { extension: { autolink: { urls: false } } }),
+        '<p><a href="http://www.a.dev">www.a.dev</a>, https://b.dev, ' +
+          '<a href="mailto:c@d.dev">c@d.dev</a>, and ' +
+          '<a href="https://e.dev">https://e.dev</a></p>\n',
+      );
+      t.assert.strictEqual(
+        markdownToHTML(md, {
+          extension: { autolink: { www: false, email: false } },
+        }),
+        '<p>www.a.dev, <a href="https://b.dev">https://b.dev</a>, ' +
+          'c@d.dev, and <a href="https://e.dev">https://e.dev</a></p>\n',
+      );
+    });
+
     it("should support descriptionLists extension", (t: TestContext) => {
       const html = markdownToHTML("Term\n\n: Definition", {
         extension: { descriptionLists: true },
@@ -104,6 +128,25 @@ describe("markdownToHTML", () => {
       );
     });
 
+    it("should use a custom slugify function", (t: TestContext) => {
+      const calls: [string, number][] = [];
+      const html = markdownToHTML("# Ünï\n\n## Ünï\n", {
+        extension: {
+          headerIDs: "h-",
+          slugify(text, level) {
+            calls.push([text, level]);
+            return text.normalize("NFD").replace(/\p{M}/gu, "").toLowerCase();
+          },
+        },
+      });
+      t.assert.deepStrictEqual(calls, [["Ünï", 1], ["Ünï", 2]]);
+      t.assert.strictEqual(
+        html,
+        `<h1><a href="#uni" aria-hidden="true" class="anchor" id="h-uni"></a>Ünï</h1>\n` +
+          `<h2><a href="#uni-1" aria-hidden="true" class="anchor" id="h-uni-1"></a>Ünï</h2>\n`,
+      );
+    });
+
     it("should support strikethrough extension", (t: TestContext) => {
       const html = markdownToHTML("Hello ~world~ there.\n", {
         extension: { strikethrough: true },
@@ -150,6 +193,88 @@ describe("markdownToHTML", () => {
           `</ul>\n`,
       );
     });
+
+    it("should support the image size syntax", (t: TestContext) => {
+      const html = markdownToHTML("Logo: ![logo](logo.png =100x200)", {
+        extension: { imageSizeSyntax: true },
+      });
+      t.assert.strictEqual(
+        html,
+        '<p>Logo: <img src="logo.png" alt="logo" width="100" ' +
+          'height="200" /></p>\n',
+      );
+    });
+
+    it("should not size escaped images", (t: TestContext) => {
+      const md = "\\![logo](logo.png =100x200)";
+      const html = markdownToHTML(md, { extension: { imageSizeSyntax: true } });
+      t.assert.strictEqual(html, "<p>![logo](logo.png =100x200)</p>\n");
+    });
+
+    it("should give sized images source positions", (t: TestContext) => {
+      const html = markdownToHTML("Logo: ![logo](logo.png =100x200)!", {
+        extension: { imageSizeSyntax: true },
+        render: { sourcepos: true },
+      });
+      t.assert.strictEqual(
+        html,
+        '<p data-sourcepos="1:1-1:33">Logo: <img data-sourcepos="1:7-1:32" ' +
+          'src="logo.png" alt="logo" width="100" height="200" />!</p>\n',
+      );
+    });
+
+    it("should resolve wikilinks with wikilinkResolver", (t: TestContext) => {
+      const titles: string[] = [];
+      const html = markdownToHTML("[[Home Page|home]] [[Label|missing]]", {
+        extension: {
+          wikilinksTitleBeforePipe: true,
+          wikilinkResolver: (title) => {
+            titles.push(title);
+            if (title !== "home") return null;
+            return { url: "/wiki/home", label: "Home" };
+          },
+        },
+      });
+      t.assert.deepStrictEqual(titles, ["home", "missing"]);
+      t.assert.strictEqual(
+        html,
+        '<p><a href="/wiki/home" data-wikilink="true">Home</a> ' +
+          '<a href="missing" data-wikilink="true">Label</a></p>\n',
+      );
+    });
+
+    it("should autolink custom schemes", (t: TestContext) => {
+      const md = "See TICKET:12, [ticket:3](/t), `ticket:4`, and x:1 " +
+        "at https://a.dev";
+      const html = markdownToHTML(md, {
+        extension: {
+          autolink: true,
+          autolinkSchemes: { ticket: "/t/{rest}" },
+        },
+      });
+      t.assert.strictEqual(
+        html,
+        '<p>See <a href="/t/12">TICKET:12</a>, <a href="/t">ticket:3</a>, ' +
+          "<code>ticket:4</code>, and x:1 at " +
+          '<a href="https://a.dev">https://a.dev</a></p>\n',
+      );
+    });
+
+    it("should link mentions and hashtags", (t: TestContext) => {
+      const md = "Hi @ann and @bob-, a@b.dev, `@c`, [@d](/d), #1 #rust";
+      const user = { mentions: { userUrl: "/u/{name}" } };
+      t.assert.strictEqual(
+        markdownToHTML(md, { extension: user }),
+        '<p>Hi <a href="/u/ann">@ann</a> and <a href="/u/bob">@bob</a>-, ' +
+          'a@b.dev, <code>@c</code>, <a href="/d">@d</a>, #1 #rust</p>\n',
+      );
+      const tag = { mentions: { tagUrl: "/t/{name}" } };
+      t.assert.strictEqual(
+        markdownToHTML(md, { extension: tag }),
+        "<p>Hi @ann and @bob-, a@b.dev, <code>@c</code>, " +
+          '<a href="/d">@d</a>, #1 <a href="/t/rust">#rust</a></p>\n',
+      );
+    });
   });
 
   describe("ParseOptions", () => {
@@ -169,6 +294,26 @@ describe("markdownToHTML", () => {
       );
     });
 
+    it("should highlight fences with defaultInfoString", (t: TestContext) => {
+      const md = "```\nlet a;\n```\n";
+      const calls: unknown[][] = [];
+      const html = markdownToHTML(md, {
+        parse: { defaultInfoString: "ts" },
+        plugins: {
+          render: {
+            codefenceSyntaxHighlighter: {
+              highlight: (code, lang, meta) => {
+                calls.push([code, lang, meta]);
+                return `<b>${code}</b>`;
+              },
+            },
+          },
+        },
+      });
+      t.assert.deepStrictEqual(calls, [["let a;\n", "ts", null]]);
+      t.assert.match(html, /^<pre><code class="language-ts"><b>let a;/);
+    });
+
     it("should support smart punctuation", (t: TestContext) => {
       const input = `'Hello,' "world" ...`;
       const plain = markdownToHTML(input);
@@ -216,6 +361,201 @@ describe("markdownToHTML", () => {
           `</ul>\n`,
       );
     });
+
+    it("should reject documents exceeding maxNodes", (t: TestContext) => {
+      // document, paragraph, emph, and text
+      const md = "*hi*";
+      t.assert.throws(
+        () => markdownToHTML(md, { parse: { maxNodes: 3 } }),
+        TypeError,
+      );
+      const html = markdownToHTML(md, { parse: { maxNodes: 4 } });
+      t.assert.strictEqual(html, "<p><em>hi</em></p>\n");
+    });
+
+    it("should reject documents exceeding maxInputBytes", (t: TestContext) => {
+      // 4 bytes of UTF-8, but 2 UTF-16 code units
+      const md = "\u{1F600}";
+      t.assert.throws(
+        () => markdownToHTML(md, { parse: { maxInputBytes: 3 } }),
+        TypeError,
+      );
+      const html = markdownToHTML(md, { parse: { maxInputBytes: 4 } });
+      t.assert.strictEqual(html, "<p>\u{1F600}</p>\n");
+    });
+
+    it("should localize smart quotes", (t: TestContext) => {
+      const md = `"Oui", dit-il, 'c'est l'heure'.`;
+      const html = markdownToHTML(md, {
+        parse: { smart: true, smartLocale: "fr" },
+      });
+      t.assert.strictEqual(
+        html,
+        "<p>\u00ab\u00a0Oui\u00a0\u00bb, dit-il, " +
+          "\u2039\u00a0c\u2019est l\u2019heure\u00a0\u203a.</p>\n",
+      );
+      t.assert.strictEqual(
+        markdownToHTML(md, { parse: { smartLocale: "fr" } }),
+        `<p>&quot;Oui&quot;, dit-il, 'c'est l'heure'.</p>\n`,
+      );
+    });
+
+    it("should render front matter per frontMatterMode", (t: TestContext) => {
+      const md = "---\ntitle: 'A & B'\ntags:\n  - x\n---\n\nbody\n";
+      const extension = { frontMatterDelimiter: "---" };
+      const html = markdownToHTML(md, {
+        extension,
+        parse: { frontMatterMode: "render" },
+      });
+      t.assert.strictEqual(
+        html,
+        "<dl>\n<dt>title</dt>\n<dd>A &amp; B</dd>\n</dl>\n<p>body</p>\n",
+      );
+      t.assert.strictEqual(
+        markdownToHTML(md, { extension }),
+        "<p>body</p>\n",
+      );
+    });
+
+    it("should indent tabbed lines per tabWidth", (t: TestContext) => {
+      const md = "- a\n\n\tcode\n";
+      t.assert.strictEqual(
+        markdownToHTML(md),
+        "<ul>\n<li>\n<p>a</p>\n<p>code</p>\n</li>\n</ul>\n",
+      );
+      t.assert.strictEqual(
+        markdownToHTML(md, { parse: { tabWidth: 8 } }),
+        "<ul>\n<li>\n<p>a</p>\n<pre><code>  code\n</code></pre>\n" +
+          "</li>\n</ul>\n",
+      );
+      t.assert.strictEqual(
+        markdownToHTML("\tx\n", {
+          parse: { tabWidth: 2 },
+          render: { sourcepos: true },
+        }),
+        '<p data-sourcepos="1:2-1:2">x</p>\n',
+      );
+    });
+
+    it("should support relaxedAutolinks", (t: TestContext) => {
+      const extension = { autolink: true };
+      t.assert.strictEqual(
+        markdownToHTML("[https://a.dev]", {
+          extension,
+          parse: { relaxedAutolinks: true },
+        }),
+        '<p>[<a href="https://a.dev">https://a.dev</a>]</p>\n',
+      );
+      const md = "(see https://a.dev/x_(y)).";
+      for (const relaxedAutolinks of [false, true]) {
+        t.assert.strictEqual(
+          markdownToHTML(md, { extension, parse: { relaxedAutolinks } }),
+          '<p>(see <a href="https://a.dev/x_(y)">https://a.dev/x_(y)</a>)' +
+            ".</p>\n",
+        );
+      }
+    });
+
+    it("should trim autolinkTrimChars from autolinks", (t: TestContext) => {
+      const md = "\u00abhttps://a.dev/x\u00bb. <https://b.dev/y\u00bb>";
+      t.assert.strictEqual(
+        markdownToHTML(md, {
+          extension: { autolink: true },
+          parse: { autolinkTrimChars: "\u00bb" },
+        }),
+        '<p>\u00ab<a href="https://a.dev/x">https://a.dev/x</a>\u00bb. ' +
+          '<a href="https://b.dev/y%C2%BB">https://b.dev/y\u00bb</a></p>\n',
+      );
+    });
+
+    it("should report the path of invalid options", (t: TestContext) => {
+      // deno-lint-ignore no-explicit-any
+      const extension = { frontMatterDelimiter: 1 as any };
+      t.assert.throws(() => markdownToHTML("a", { extension }), {
+        name: "TypeError",
+        message: /^extension\.frontMatterDelimiter: /,
+      });
+    });
+  });
+
+  describe("ParseOptions.backslashLineBreaks", () => {
+    it("should treat backslashes as hard breaks", (t: TestContext) => {
+      const html = markdownToHTML("a\\\nb\n");
+      t.assert.strictEqual(html, "<p>a<br />\nb</p>\n");
+    });
+
+    it("should keep backslashes when disabled", (t: TestContext) => {
+      const html = markdownToHTML("a\\\nb\n", {
+        parse: { backslashLineBreaks: false },
+      });
+      t.assert.strictEqual(html, "<p>a\\\nb</p>\n");
+    });
+
+    it("should not affect trailing-space hard breaks", (t: TestContext) => {
+      const html = markdownToHTML("a  \nb\n", {
+        parse: { backslashLineBreaks: false },
+      });
+      t.assert.strictEqual(html, "<p>a<br />\nb</p>\n");
+    });
+  });
+
+  describe("ParseOptions.brokenLinkCallback", () => {
+    it("should resolve broken links", (t: TestContext) => {
+      const html = markdownToHTML("[foo]", {
+        parse: {
+          brokenLinkCallback: (ref) => ({
+            url: `/${ref.normalized}`,
+            title: "",
+          }),
+        },
+      });
+      t.assert.strictEqual(html, '<p><a href="/foo">foo</a></p>\n');
+    });
+
+    it("should propagate exceptions from the callback", (t: TestContext) => {
+      const error = new Error("boom");
+      t.assert.throws(
+        () =>
+          markdownToHTML("[foo] and [bar]", {
+            parse: {
+              brokenLinkCallback() {
+                throw error;
+              },
+            },
+          }),
+        (e) => e === error,
+      );
+    });
+
+    it("should leave links broken on malformed results", (t: TestContext) => {
+      const html = markdownToHTML("[foo]", {
+        parse: {
+          // deno-lint-ignore no-explicit-any
+          brokenLinkCallback: () => ({ url: 42 }) as any,
+        },
+      });
+      t.assert.strictEqual(html, "<p>[foo]</p>\n");
+    });
+
+    it("should prefer referenceMap over the callback", (t: TestContext) => {
+      const labels: string[] = [];
+      const md = "[Known  Link] [other] [own]\n\n[own]: /own";
+      const html = markdownToHTML(md, {
+        parse: {
+          referenceMap: { "known link": { url: "/known" }, own: { url: "/x" } },
+          brokenLinkCallback: (ref) => {
+            labels.push(ref.original);
+            return { url: "/fallback", title: "" };
+          },
+        },
+      });
+      t.assert.deepStrictEqual(labels, ["other"]);
+      t.assert.strictEqual(
+        html,
+        '<p><a href="/known">Known  Link</a> <a href="/fallback">other</a> ' +
+          '<a href="/own">own</a></p>\n',
+      );
+    });
   });
 
   describe("RenderOptions", () => {
@@ -275,5 +615,1113 @@ describe("markdownToHTML", () => {
       t.assert.ok(unsafeOutput.includes("<script>"), "Script tag missing");
       t.assert.ok(unsafeOutput.includes("<marquee>"), "Marquee tag missing");
     });
+
+    it("should preserve bullet styles when requested", (t: TestContext) => {
+      const html = markdownToHTML("* one\n* two\n\n- three\n", {
+        render: { preserveBulletStyleInHtml: true },
+      });
+      t.assert.strictEqual(
+        html,
+        `<ul data-bullet="*">\n` +
+          `<li data-bullet="*">one</li>\n` +
+          `<li data-bullet="*">two</li>\n` +
+          `</ul>\n` +
+          `<ul data-bullet="-">\n` +
+          `<li data-bullet="-">three</li>\n` +
+          `</ul>\n`,
+      );
+    });
+
+    it("should replace the marker with a TOC", (t: TestContext) => {
+      const md = "# A\n\n[[TOC]]\n\n## B\n\n### C\n\n## B\n";
+      const html = markdownToHTML(md, {
+        extension: { headerIDs: "h-" },
+        render: { tableOfContents: { maxLevel: 2 } },
+      });
+      const anchor = (id: string) =>
+        `<a href="#${id}" aria-hidden="true" class="anchor" id="h-${id}"></a>`;
+      t.assert.strictEqual(
+        html,
+        `<h1>${anchor("a")}A</h1>\n` +
+          `<ul>\n` +
+          `<li><a href="#h-a">A</a>\n` +
+          `<ul>\n` +
+          `<li><a href="#h-b">B</a></li>\n` +
+          `<li><a href="#h-b-1">B</a></li>\n` +
+          `</ul>\n` +
+          `</li>\n` +
+          `</ul>\n` +
+          `<h2>${anchor("b")}B</h2>\n` +
+          `<h3>${anchor("c")}C</h3>\n` +
+          `<h2>${anchor("b-1")}B</h2>\n`,
+      );
+    });
+
+    it("should do nothing without a marker", (t: TestContext) => {
+      const md = "# A\n\nText\n";
+      t.assert.strictEqual(
+        markdownToHTML(md, { render: { tableOfContents: {} } }),
+        markdownToHTML(md),
+      );
+    });
+
+    it("should wrap headings in their anchor link", (t: TestContext) => {
+      const html = markdownToHTML("# Hello\n\n## Hello\n", {
+        extension: { headerIDs: "h-", slugify: (s) => s.toUpperCase() },
+        render: { headingAnchorWrapHeading: true },
+      });
+      t.assert.strictEqual(
+        html,
+        `<h1><a href="#HELLO" class="anchor" id="h-HELLO">Hello</a></h1>\n` +
+          `<h2><a href="#HELLO-1" class="anchor" id="h-HELLO-1">Hello</a></h2>\n`,
+      );
+    });
+
+    it("should add classes to headings by level", (t: TestContext) => {
+      const md = "# A\n\n## B\n\n### C\n";
+      const headingClasses = { 1: "title", 2: "heading-lg" };
+      t.assert.strictEqual(
+        markdownToHTML(md, { render: { headingClasses } }),
+        '<h1 class="title">A</h1>\n<h2 class="heading-lg">B</h2>\n' +
+          "<h3>C</h3>\n",
+      );
+      t.assert.strictEqual(
+        markdownToHTML("## Foo", {
+          extension: { headerIDs: "" },
+          render: { headingClasses, headingAnchorWrapHeading: true },
+        }),
+        '<h2 class="heading-lg"><a href="#foo" class="anchor" id="foo">' +
+          "Foo</a></h2>\n",
+      );
+      t.assert.strictEqual(
+        markdownToHTML("## Foo", {
+          extension: { headerIDs: "h-", slugify: (s: string) => s },
+          render: { headingClasses },
+        }),
+        '<h2 class="heading-lg"><a href="#Foo" aria-hidden="true" ' +
+          'class="anchor" id="h-Foo"></a>Foo</h2>\n',
+      );
+    });
+
+    it("should fix skipped heading levels", (t: TestContext) => {
+      const md = "[[TOC]]\n\n# A\n\n### B\n\n#### C\n\n## D\n";
+      const html = markdownToHTML(md, {
+        render: { fixHeadingLevels: true, tableOfContents: { maxLevel: 2 } },
+      });
+      t.assert.strictEqual(
+        html,
+        `<ul>\n` +
+          `<li><a href="#a">A</a>\n` +
+          `<ul>\n` +
+          `<li><a href="#b">B</a></li>\n` +
+          `<li><a href="#d">D</a></li>\n` +
+          `</ul>\n` +
+          `</li>\n` +
+          `</ul>\n` +
+          `<h1>A</h1>\n<h2>B</h2>\n<h3>C</h3>\n<h2>D</h2>\n`,
+      );
+    });
+
+    it("should highlight prefixed inline code", (t: TestContext) => {
+      const html = markdownToHTML("`rust:let x = 5` and `std::io`", {
+        render: { highlightInlineCode: true },
+        plugins: {
+          render: {
+            codefenceSyntaxHighlighter: {
+              highlight: (code, lang) => `<span class="${lang}">${code}</span>`,
+            },
+          },
+        },
+      });
+      t.assert.strictEqual(
+        html,
+        '<p><code class="language-rust"><span class="rust">let x = 5</span>' +
+          "</code> and <code>std::io</code></p>\n",
+      );
+    });
+
+    it("should only add IDs to referenced headings", (t: TestContext) => {
+      const md = "# Intro\n\n## Usage\n\nSee [usage](#h-usage).\n";
+      const html = markdownToHTML(md, {
+        extension: { headerIDs: "h-" },
+        render: { headerIdsOnlyWhenReferenced: true },
+      });
+      t.assert.strictEqual(
+        html,
+        "<h1>Intro</h1>\n" +
+          '<h2><a href="#usage" aria-hidden="true" class="anchor" ' +
+          'id="h-usage"></a>Usage</h2>\n' +
+          '<p>See <a href="#h-usage">usage</a>.</p>\n',
+      );
+    });
+
+    it("should escape Vue interpolation in text", (t: TestContext) => {
+      const html = markdownToHTML("{{ msg }} and `{{ code }}`, but {not}", {
+        render: { frameworkEscaping: "vue" },
+      });
+      t.assert.strictEqual(
+        html,
+        "<p>&#123;&#123; msg &#125;&#125; and " +
+          "<code>&#123;&#123; code &#125;&#125;</code>, but {not}</p>\n",
+      );
+    });
+
+    it("should render code blocks via codeBlockRenderer", (t: TestContext) => {
+      const md = "```js {1,3} title=a.js\nlet a;\n```\n\n```\nplain\n```\n";
+      const calls: unknown[][] = [];
+      const html = markdownToHTML(md, {
+        render: {
+          codeBlockRenderer: (code, lang, meta) => {
+            calls.push([code, lang, meta]);
+            return lang ? `<pre data-meta="${meta}">${code}</pre>` : null;
+          },
+        },
+      });
+      t.assert.deepStrictEqual(calls, [
+        ["let a;\n", "js", "{1,3} title=a.js"],
+        ["plain\n", null, null],
+      ]);
+      t.assert.strictEqual(
+        html,
+        '<pre data-meta="{1,3} title=a.js">let a;\n</pre>\n' +
+          "<pre><code>plain\n</code></pre>\n",
+      );
+    });
+
+    it("should expand tabs in code blocks", (t: TestContext) => {
+      const md = "Text\ttab\n\n```\n\tx\nab\tc\n```\n";
+      const html = markdownToHTML(md, { render: { codeTabsToSpaces: 4 } });
+      t.assert.strictEqual(
+        html,
+        "<p>Text\ttab</p>\n" +
+          "<pre><code>    x\nab  c\n</code></pre>\n",
+      );
+    });
+
+    it("should pass code block meta to the highlighter", (t: TestContext) => {
+      const md = '```ts title="a.ts" {1}\nlet a;\n```\n\n' +
+        "```ts\nlet b;\n```\n";
+      const calls: unknown[][] = [];
+      markdownToHTML(md, {
+        plugins: {
+          render: {
+            codefenceSyntaxHighlighter: {
+              highlight: (code, lang, meta) => {
+                calls.push([code, lang, meta]);
+                return code;
+              },
+            },
+          },
+        },
+      });
+      t.assert.deepStrictEqual(calls, [
+        ["let a;\n", "ts", 'title="a.ts" {1}'],
+        ["let b;\n", "ts", null],
+      ]);
+    });
+
+    it("should sanitize with the default allowlist", (t: TestContext) => {
+      const md = '<div onclick="x()" title="t"><script>x()</script>' +
+        '<a href="javascript:x()">a</a> <a href="/b">b</a></div>\n';
+      const html = markdownToHTML(md, {
+        render: { unsafe: true, sanitizeHtml: true },
+      });
+      t.assert.strictEqual(
+        html,
+        '<div title="t"><a>a</a> <a href="/b">b</a></div>\n',
+      );
+    });
+
+    it("should sanitize with a custom allowlist", (t: TestContext) => {
+      const html = markdownToHTML('Hi <b class="x">there</b> <i>you</i>', {
+        render: {
+          unsafe: true,
+          sanitizeHtml: { allowedTags: ["p", "b"], allowedAttributes: {} },
+        },
+      });
+      t.assert.strictEqual(html, "<p>Hi <b>there</b> you</p>\n");
+    });
+
+    it("should sanitize after abruptly closed comments", (t: TestContext) => {
+      const img = "<img src=x onerror=alert(1)>-->";
+      const md = `<!-->${img}\n\n<!--->${img}\n\n<!-- --!>${img}\n`;
+      const html = markdownToHTML(md, {
+        render: { unsafe: true, sanitizeHtml: true },
+      });
+      t.assert.strictEqual(
+        html,
+        '<!--><img src="x">-->\n<!---><img src="x">-->\n' +
+          '<!-- --!><img src="x">-->\n',
+      );
+    });
+
+    it("should drop a cut-off tag when sanitizing", (t: TestContext) => {
+      const html = markdownToHTML("Hi 1 < 2\n\n<div onmouseover=alert(1)", {
+        render: { unsafe: true, sanitizeHtml: true, document: {} },
+      });
+      t.assert.ok(!html.includes("onmouseover"));
+      t.assert.match(html, /<body>\n<p>Hi 1 &lt; 2<\/p>\n<\/body>\n/);
+    });
+
+    it("should render a custom backref at the start", (t: TestContext) => {
+      const html = markdownToHTML("Hi[^x]\n\n[^x]: A greeting.", {
+        extension: { footnotes: true },
+        render: { footnoteBackref: { symbol: "^", position: "start" } },
+      });
+      t.assert.strictEqual(
+        html,
+        '<p>Hi<sup class="footnote-ref"><a href="#fn-x" id="fnref-x" ' +
+          "data-footnote-ref>1</a></sup></p>\n" +
+          '<section class="footnotes" data-footnotes>\n<ol>\n' +
+          '<li id="fn-x">\n<p><a href="#fnref-x" class="footnote-backref" ' +
+          'data-footnote-backref data-footnote-backref-idx="1" ' +
+          'aria-label="Back to reference 1">^</a> A greeting.</p>\n' +
+          "</li>\n</ol>\n</section>\n",
+      );
+    });
+
+    it("should abort when the output exceeds the limit", (t: TestContext) => {
+      const md = "[a]".repeat(1000) + "\n\n[a]: https://example.com";
+      t.assert.throws(
+        () => markdownToHTML(md, { render: { maxOutputBytes: 1024 } }),
+        TypeError,
+      );
+      const html = markdownToHTML("*hi*", {
+        render: { maxOutputBytes: 1024 },
+      });
+      t.assert.strictEqual(html, "<p><em>hi</em></p>\n");
+    });
+
+    it("should caption task lists with their progress", (t: TestContext) => {
+      const html = markdownToHTML(
+        "- [x] a\n- [x] b\n- [ ] c\n- [x] d\n- [ ] e\n",
+        {
+          extension: { tasklist: true },
+          render: { taskListProgress: true },
+        },
+      );
+      t.assert.strictEqual(
+        html,
+        "<p>3/5 done</p>\n<ul>\n" +
+          '<li><input type="checkbox" checked="" disabled="" /> a</li>\n' +
+          '<li><input type="checkbox" checked="" disabled="" /> b</li>\n' +
+          '<li><input type="checkbox" disabled="" /> c</li>\n' +
+          '<li><input type="checkbox" checked="" disabled="" /> d</li>\n' +
+          '<li><input type="checkbox" disabled="" /> e</li>\n' +
+          "</ul>\n",
+      );
+    });
+
+    it("should control the start of ordered lists", (t: TestContext) => {
+      const md = "5. Five\n6. Six\n";
+      t.assert.strictEqual(
+        markdownToHTML(md),
+        '<ol start="5">\n<li>Five</li>\n<li>Six</li>\n</ol>\n',
+      );
+      t.assert.strictEqual(
+        markdownToHTML(md, { render: { respectOrderedListStart: false } }),
+        "<ol>\n<li>Five</li>\n<li>Six</li>\n</ol>\n",
+      );
+      t.assert.strictEqual(
+        markdownToHTML(md, { render: { forceStartOne: true } }),
+        "<ol>\n<li>Five</li>\n<li>Six</li>\n</ol>\n",
+      );
+    });
+
+    it("should render task items as named inputs", (t: TestContext) => {
+      const md = "- [x] a\n- [ ] b\n";
+      const html = markdownToHTML(md, {
+        extension: { tasklist: true },
+        render: { taskListInputs: { interactive: true, namePrefix: "q" } },
+      });
+      t.assert.strictEqual(
+        html,
+        "<ul>\n" +
+          '<li><input type="checkbox" name="q-0" checked="" /> a</li>\n' +
+          '<li><input type="checkbox" name="q-1" /> b</li>\n' +
+          "</ul>\n",
+      );
+      const disabled = markdownToHTML(md, {
+        extension: { tasklist: true },
+        render: { taskListInputs: { interactive: false } },
+      });
+      t.assert.match(disabled, /name="task-1" disabled="" \/> b/);
+    });
+
+    it("should use custom footnote IDs and labels", (t: TestContext) => {
+      const html = markdownToHTML("Hi[^x]\n\n[^x]: Note.", {
+        extension: { footnotes: true },
+        render: {
+          footnotes: {
+            backrefLabel: "Retour",
+            refPrefix: "d1-ref-",
+            defPrefix: "d1-fn-",
+          },
+        },
+      });
+      t.assert.strictEqual(
+        html,
+        '<p>Hi<sup class="footnote-ref"><a href="#d1-fn-x" id="d1-ref-x" ' +
+          "data-footnote-ref>1</a></sup></p>\n" +
+          '<section class="footnotes" data-footnotes>\n<ol>\n' +
+          '<li id="d1-fn-x">\n<p>Note. <a href="#d1-ref-x" ' +
+          'class="footnote-backref" data-footnote-backref ' +
+          'data-footnote-backref-idx="1" aria-label="Retour 1">↩</a></p>\n' +
+          "</li>\n</ol>\n</section>\n",
+      );
+    });
+
+    it("should omit sourcepos from inline nodes", (t: TestContext) => {
+      const html = markdownToHTML("# Title\n\n- a *b* [c](/d)\n", {
+        render: { sourcepos: true, sourceposBlocksOnly: true },
+      });
+      t.assert.strictEqual(
+        html,
+        '<h1 data-sourcepos="1:1-1:7">Title</h1>\n' +
+          '<ul data-sourcepos="3:1-3:15">\n' +
+          '<li data-sourcepos="3:1-3:15">a <em>b</em> ' +
+          '<a href="/d">c</a></li>\n' +
+          "</ul>\n",
+      );
+    });
+
+    it("should toggle sourcepos of inline nodes", (t: TestContext) => {
+      const md = "a *b*\n";
+      const html = (inlineSourcepos: boolean) =>
+        markdownToHTML(md, { render: { sourcepos: true, inlineSourcepos } });
+      t.assert.match(html(true), /<em data-sourcepos="1:3-1:5">b<\/em>/);
+      t.assert.equal(
+        html(false),
+        '<p data-sourcepos="1:1-1:5">a <em>b</em></p>\n',
+      );
+    });
+
+    it("should render soft line breaks per lineBreaks", (t: TestContext) => {
+      const md = "one\ntwo  \nthree\\\nfour";
+      const render = (lineBreaks: "soft" | "hard" | "explicit") =>
+        markdownToHTML(md, { render: { hardbreaks: true, lineBreaks } });
+      t.assert.strictEqual(
+        render("soft"),
+        "<p>one\ntwo<br />\nthree<br />\nfour</p>\n",
+      );
+      t.assert.strictEqual(
+        render("hard"),
+        "<p>one<br />\ntwo<br />\nthree<br />\nfour</p>\n",
+      );
+      t.assert.strictEqual(
+        render("explicit"),
+        "<p>one two<br />\nthree<br />\nfour</p>\n",
+      );
+    });
+
+    it("should apply the same options to an AST", (t: TestContext) => {
+      const md = '![alt](a.png "Caption")\n\n[link](/b)';
+      const options = {
+        extension: {
+          imageURLRewriter: (url: string) => `https://cdn.test/${url}`,
+          linkURLRewriter: (url: string) => `https://site.test${url}`,
+        },
+        render: { figureWithCaption: true },
+      };
+      const html = markdownToHTML(md, options);
+      t.assert.strictEqual(
+        html,
+        '<p><figure><img src="https://cdn.test/a.png" alt="alt" ' +
+          'title="Caption" /><figcaption>Caption</figcaption></figure></p>\n' +
+          '<p><a href="https://site.test/b">link</a></p>\n',
+      );
+      const ast = parseMarkdown(md, options);
+      t.assert.strictEqual(renderHTML(ast, options), html);
+    });
+
+    it("should override raw HTML options with rawHtml", (t: TestContext) => {
+      const md = "<b>x</b><iframe></iframe>";
+      const render = (rawHtml: "escape" | "filter" | "allow") =>
+        markdownToHTML(md, {
+          extension: { tagfilter: true },
+          render: { rawHtml, unsafe: false, escape: true },
+        });
+      t.assert.strictEqual(
+        render("escape"),
+        "<p>&lt;b&gt;x&lt;/b&gt;&lt;iframe&gt;&lt;/iframe&gt;</p>\n",
+      );
+      t.assert.strictEqual(
+        render("filter"),
+        "<p><b>x</b>&lt;iframe>&lt;/iframe></p>\n",
+      );
+      t.assert.strictEqual(render("allow"), `<p>${md}</p>\n`);
+    });
+
+    it("should render math with mathRenderer", (t: TestContext) => {
+      const calls: [string, boolean][] = [];
+      const html = markdownToHTML("$a$ $$b$$\n\n```math\nc\n```\n", {
+        extension: { mathDollars: true },
+        render: {
+          mathRenderer: (latex, displayMode) => {
+            calls.push([latex, displayMode]);
+            return `<math display="${displayMode ? "block" : "inline"}">` +
+              `<mi>${latex.trim()}</mi></math>`;
+          },
+        },
+      });
+      t.assert.deepStrictEqual(calls, [
+        ["a", false],
+        ["b", true],
+        ["c\n", true],
+      ]);
+      t.assert.strictEqual(
+        html,
+        '<p><math display="inline"><mi>a</mi></math> ' +
+          '<math display="block"><mi>b</mi></math></p>\n' +
+          '<math display="block"><mi>c</mi></math>\n',
+      );
+    });
+
+    it("should strip comments with stripComments", (t: TestContext) => {
+      const md = "<!-- a -->\n\n<!--[if IE]><p>IE</p><![endif]-->\n\n" +
+        "x <!-- b --><i>y</i>\n\n<!-- c --><p>z</p>\n";
+      const render = (stripConditionalComments: boolean) =>
+        markdownToHTML(md, {
+          render: {
+            unsafe: true,
+            stripComments: true,
+            stripConditionalComments,
+          },
+        });
+      t.assert.strictEqual(
+        render(false),
+        "<!--[if IE]><p>IE</p><![endif]-->\n<p>x <i>y</i></p>\n" +
+          "<!-- c --><p>z</p>\n",
+      );
+      t.assert.strictEqual(
+        render(true),
+        "<p>x <i>y</i></p>\n<!-- c --><p>z</p>\n",
+      );
+    });
+
+    it("should add loading attributes to images", (t: TestContext) => {
+      const md = "![a](a.png \"A\") [![b](b.png =10x)](/b)";
+      const html = markdownToHTML(md, {
+        extension: { imageSizeSyntax: true },
+        render: { images: { lazy: true, decoding: "async" } },
+      });
+      t.assert.strictEqual(
+        html,
+        '<p><img src="a.png" alt="a" title="A" loading="lazy" ' +
+          'decoding="async" /> <a href="/b"><img src="b.png" alt="b" ' +
+          'width="10" loading="lazy" decoding="async" /></a></p>\n',
+      );
+    });
+
+    it("should add resolved image dimensions", (t: TestContext) => {
+      const urls: string[] = [];
+      const html = markdownToHTML("![a](a.png) ![b](b.png)", {
+        extension: { imageURLRewriter: (url) => `/img/${url}` },
+        render: {
+          imageDimensions: (url) => {
+            urls.push(url);
+            return url.endsWith("a.png") ? { width: 2, height: 1 } : null;
+          },
+        },
+      });
+      t.assert.strictEqual(
+        html,
+        '<p><img src="/img/a.png" alt="a" width="2" height="1" /> ' +
+          '<img src="/img/b.png" alt="b" /></p>\n',
+      );
+      t.assert.deepStrictEqual(urls, ["/img/a.png", "/img/b.png"]);
+      const sized = markdownToHTML("![c](c.png =5x)", {
+        extension: { imageSizeSyntax: true },
+        render: { imageDimensions: () => ({ width: 2, height: 1 }) },
+      });
+      t.assert.strictEqual(
+        sized,
+        '<p><img src="c.png" alt="c" width="5" /></p>\n',
+      );
+    });
+
+    it("should clamp image dimensions", (t: TestContext) => {
+      const md = "![a](a.png) ![b](b.png) ![c](c.png =1000x)";
+      const html = markdownToHTML(md, {
+        extension: { imageSizeSyntax: true },
+        render: {
+          imageDimensions: (url) =>
+            url === "a.png" ? { width: 1000, height: 500 } : null,
+          maxImageWidth: 400,
+          maxImageHeight: 100,
+        },
+      });
+      t.assert.strictEqual(
+        html,
+        '<p><img src="a.png" alt="a" width="200" height="100" /> ' +
+          '<img src="b.png" alt="b" /> ' +
+          '<img src="c.png" alt="c" width="400" /></p>\n',
+      );
+    });
+
+    it("should render table alignment as classes", (t: TestContext) => {
+      const md = "| a | b | c | d |\n|:--|:-:|--:|---|\n| 1 | 2 | 3 | 4 |\n";
+      const html = markdownToHTML(md, {
+        extension: { table: true },
+        render: { tableAlignClasses: true, sourcepos: true },
+      });
+      t.assert.ok(!html.includes("align"));
+      t.assert.match(html, /<th [^>]* class="text-left">a<\/th>/);
+      t.assert.match(html, /<th [^>]* class="text-center">b<\/th>/);
+      t.assert.match(html, /<td [^>]* class="text-right">3<\/td>/);
+      t.assert.match(html, /<td data-sourcepos="[^"]+">4<\/td>/);
+    });
+
+    it("should add a class to description lists", (t: TestContext) => {
+      const md = "Term\n\n: Definition\n\nOther\n\n: *More*\n";
+      const html = markdownToHTML(md, {
+        extension: { descriptionLists: true },
+        render: { descriptionLists: { className: "a&b" }, sourcepos: true },
+      });
+      t.assert.match(html, /^<dl data-sourcepos="[^"]+" class="a&amp;b">\n/);
+      t.assert.ok(html.endsWith("</dd>\n</dl>\n"));
+      t.assert.ok(html.includes("<dt"));
+      t.assert.ok(html.includes("<em>More</em>"));
+    });
+
+    it("should minify without altering code", (t: TestContext) => {
+      const md = "Before\n\n```\n  a\n\n    b\n```\nAfter `x  y`\n";
+      const html = markdownToHTML(md, { render: { minify: true } });
+      t.assert.strictEqual(
+        html,
+        "<p>Before</p><pre><code>  a\n\n    b\n</code></pre>" +
+          "<p>After <code>x  y</code></p>",
+      );
+    });
+
+    it("should minify each chunk when streaming", (t: TestContext) => {
+      const md = "# A\n\n```\n1\n```\n\n- b\n";
+      const options = { render: { minify: true } };
+      const chunks: string[] = [];
+      markdownToHTMLStreaming(md, (chunk) => chunks.push(chunk), options);
+      t.assert.strictEqual(chunks.join(""), markdownToHTML(md, options));
+    });
+
+    it("should pretty-print without altering code", (t: TestContext) => {
+      const md = "> - a\n>\n>   ```\n>     b\n>   ```\n";
+      const html = markdownToHTML(md, { render: { prettyPrint: true } });
+      t.assert.strictEqual(
+        html,
+        [
+          "<blockquote>",
+          "  <ul>",
+          "    <li>",
+          "      <p>a</p>",
+          "      <pre><code>  b\n</code></pre>",
+          "    </li>",
+          "  </ul>",
+          "</blockquote>",
+          "",
+        ].join("\n"),
+      );
+    });
+
+    it("should not pretty-print and minify at once", (t: TestContext) => {
+      const render = { minify: true, prettyPrint: true };
+      t.assert.throws(() => markdownToHTML("a", { render }), TypeError);
+    });
+
+    it("should localize alert titles", (t: TestContext) => {
+      const md = "> [!NOTE]\n> a\n\n> [!TIP] Hint\n> b\n";
+      const html = markdownToHTML(md, {
+        extension: { alerts: true },
+        render: { alerts: { titles: { Note: "Hinweis" }, includeIcon: true } },
+      });
+      const icon = '<span class="markdown-alert-icon" aria-hidden="true">';
+      t.assert.match(html, new RegExp(`title">${icon}</span>Hinweis</p>`));
+      t.assert.match(html, new RegExp(`title">${icon}</span>Hint</p>`));
+    });
+
+    it("should render strikethrough with the given tag", (t: TestContext) => {
+      const extension = { strikethrough: true };
+      const md = "~~a~~";
+      const html = markdownToHTML(md, { extension });
+      t.assert.equal(html, "<p><del>a</del></p>\n");
+      const render = { strikethroughTag: "strike" } as const;
+      t.assert.equal(
+        markdownToHTML(md, { extension, render }),
+        "<p><strike>a</strike></p>\n",
+      );
+    });
+
+    it("should render scripts with custom tags", (t: TestContext) => {
+      const md = "x^a~b~^ and y~c^d^~";
+      const extension = { superscript: true, subscript: true };
+      t.assert.equal(
+        markdownToHTML(md, { extension }),
+        "<p>x<sup>a<sub>b</sub></sup> and y<sub>c<sup>d</sup></sub></p>\n",
+      );
+      const html = markdownToHTML(md, {
+        extension,
+        render: { supTag: "span", supClass: "up", subClass: "down" },
+      });
+      t.assert.equal(
+        html,
+        '<p>x<span class="up">a<sub class="down">b</sub></span> and ' +
+          'y<sub class="down">c<span class="up">d</span></sub></p>\n',
+      );
+    });
+
+    it("should reject invalid script tags", (t: TestContext) => {
+      const render = { supTag: "<b>" };
+      t.assert.throws(() => markdownToHTML("x^2^", { render }), {
+        name: "TypeError",
+        message: /^render\.supTag: invalid HTML name/,
+      });
+    });
+
+    it("should render spoilers with custom elements", (t: TestContext) => {
+      const extension = { spoiler: true };
+      const spoiler = {
+        tag: "span",
+        attributes: { role: "button", "aria-label": "<spoiler>" },
+      };
+      t.assert.equal(
+        markdownToHTML("||a||", { extension, render: { spoiler } }),
+        '<p><span class="spoiler" aria-label="&lt;spoiler&gt;" ' +
+          'role="button">a</span></p>\n',
+      );
+      t.assert.equal(
+        markdownToHTML("||a||", { extension, render: { spoiler: {} } }),
+        markdownToHTML("||a||", { extension }),
+      );
+    });
+
+    it("should report headings while rendering", (t: TestContext) => {
+      const md = "# A\n\n## *B*\n\n# A\n";
+      const seen: unknown[] = [];
+      const html = markdownToHTML(md, {
+        extension: { headerIDs: "h-" },
+        render: {
+          headingAnchorWrapHeading: true,
+          onHeading: ({ level, content }, id, { start }) =>
+            void seen.push([level, content, id, start.line]),
+        },
+      });
+      t.assert.match(html, /<h1><a href="#a-1" class="anchor" id="h-a-1">/);
+      t.assert.deepStrictEqual(seen, [
+        [1, "A", "h-a", 1],
+        [2, "B", "h-b", 3],
+        [1, "A", "h-a-1", 5],
+      ]);
+    });
+
+    it("should ignore plugins when disablePlugins is set", (t: TestContext) => {
+      const md = "# Hi\n\n```js\nx\n```\n";
+      const plugins = {
+        render: {
+          codefenceSyntaxHighlighter: { highlight: () => "highlighted" },
+          headingAdapter: { enter: () => "<h1 class=x>", exit: () => "</h1>" },
+        },
+      };
+      t.assert.match(markdownToHTML(md, { plugins }), /^<h1 class=x>.*highl/s);
+      t.assert.strictEqual(
+        markdownToHTML(md, { plugins, render: { disablePlugins: true } }),
+        markdownToHTML(md),
+      );
+    });
+
+    it("should wrap the output in a document", (t: TestContext) => {
+      const md = "Intro\n\n# A <b>&</b> B\n";
+      const html = markdownToHTML(md, { render: { document: {} } });
+      t.assert.match(html, /^<!DOCTYPE html>\n<html>\n<head>\n/);
+      t.assert.match(html, /<title>A &amp; B<\/title>\n<\/head>/);
+      t.assert.match(html, /<body>\n<p>Intro<\/p>\n.*<\/body>\n<\/html>\n$/s);
+      const titled = markdownToHTML("text", {
+        render: { document: { title: "Custom" } },
+      });
+      t.assert.match(titled, /<title>Custom<\/title>/);
+    });
+
+    it("should scope heading IDs per render", (t: TestContext) => {
+      const md = "# Intro\n\n# Intro\n";
+      const ids = (idScope: string) =>
+        [...markdownToHTML(md, {
+          extension: { headerIDs: "h-" },
+          render: { idScope },
+        }).matchAll(/id="([^"]+)"/g)].map((m) => m[1]);
+      t.assert.deepStrictEqual(ids("a-"), ["a-h-intro", "a-h-intro-1"]);
+      t.assert.deepStrictEqual(ids("b-"), ["b-h-intro", "b-h-intro-1"]);
+    });
+
+    it("should scope the links of heading anchors", (t: TestContext) => {
+      const html = markdownToHTML("# Intro\n", {
+        extension: { headerIDs: "" },
+        render: { idScope: "a-" },
+      });
+      t.assert.strictEqual(
+        html,
+        '<h1><a href="#a-intro" aria-hidden="true" class="anchor" ' +
+          'id="a-intro"></a>Intro</h1>\n',
+      );
+    });
+
+    it("should render allowed raw HTML tags as-is", (t: TestContext) => {
+      const md = 'Embed: <iframe src="/e"></iframe> and <b>b</b> <i>i</i>\n';
+      t.assert.strictEqual(
+        markdownToHTML(md, { render: { rawHtmlAllowTags: ["IFRAME", "b"] } }),
+        '<p>Embed: <iframe src="/e"></iframe> and <b>b</b> ' +
+          "&lt;i&gt;i&lt;/i&gt;</p>\n",
+      );
+      t.assert.strictEqual(
+        markdownToHTML(md, {
+          render: { rawHtml: "filter", rawHtmlAllowTags: ["iframe"] },
+        }),
+        '<p>Embed: <iframe src="/e"></iframe> and <b>b</b> <i>i</i></p>\n',
+      );
+      t.assert.throws(
+        () => markdownToHTML(md, { render: { rawHtmlAllowTags: ["<b>"] } }),
+        { name: "TypeError", message: /invalid HTML name/ },
+      );
+    });
+
+    it("should not allow tags with unsafe attributes", (t: TestContext) => {
+      const md = '<b onmouseover="alert(1)">b</b> <b title="t">t</b>\n\n' +
+        '<iframe src="javascript:alert(1)"></iframe>\n\n' +
+        '<iframe srcdoc="&lt;script&gt;alert(1)&lt;/script&gt;"></iframe>\n';
+      t.assert.strictEqual(
+        markdownToHTML(md, { render: { rawHtmlAllowTags: ["b", "iframe"] } }),
+        '<p>&lt;b onmouseover=&quot;alert(1)&quot;&gt;b</b> ' +
+          '<b title="t">t</b></p>\n' +
+          "&lt;iframe src=&quot;javascript:alert(1)&quot;&gt;" +
+          "&lt;/iframe&gt;\n" +
+          "&lt;iframe srcdoc=&quot;&amp;lt;script&amp;gt;alert(1)&amp;lt;" +
+          "/script&amp;gt;&quot;&gt;&lt;/iframe&gt;\n",
+      );
+    });
+
+    it("should render footnotes inline per footnoteStyle", (t: TestContext) => {
+      const md = "A[^1] and B[^n][^1].\n\n" +
+        '[^1]: One "x".\n\n    More.\n\n[^n]: Two.\n';
+      const html = markdownToHTML(md, {
+        extension: { footnotes: true },
+        render: { footnoteStyle: "inline" },
+      });
+      const one = '<span class="footnote" title="One &quot;x&quot;. More.">' +
+        "One &quot;x&quot;. More.</span>";
+      t.assert.strictEqual(
+        html,
+        `<p>A${one} and B<span class="footnote" title="Two.">Two.</span>` +
+          `${one}.</p>\n`,
+      );
+    });
+
+    it("should fill in link titles per linkTitleFallback", (t: TestContext) => {
+      const md = "[a](/a) [b](/b 'B')";
+      const html = markdownToHTML(md, {
+        extension: { linkURLRewriter: (url: string) => `https://x.dev${url}` },
+        render: { linkTitleFallback: "url" },
+      });
+      t.assert.strictEqual(
+        html,
+        '<p><a href="https://x.dev/a" title="https://x.dev/a">a</a> ' +
+          '<a href="https://x.dev/b" title="B">b</a></p>\n',
+      );
+    });
+
+    it("should rewrite a link's URL once for its title", (t: TestContext) => {
+      const urls: string[] = [];
+      const html = markdownToHTML("[a](/a) [b](/b)", {
+        extension: {
+          linkURLRewriter: (url) => `${url}?n=${urls.push(url)}`,
+        },
+        render: { linkTitleFallback: "url" },
+      });
+      t.assert.strictEqual(
+        html,
+        '<p><a href="/a?n=1" title="/a?n=1">a</a> ' +
+          '<a href="/b?n=2" title="/b?n=2">b</a></p>\n',
+      );
+      t.assert.deepStrictEqual(urls, ["/a", "/b"]);
+    });
+
+    it("should take figure captions from figureCaption", (t: TestContext) => {
+      const md = '![A *cat*](/a.png "Photo") ![](/b.png)';
+      const render = { figureWithCaption: true };
+      t.assert.strictEqual(
+        markdownToHTML(md, { render: { ...render, figureCaption: "alt" } }),
+        '<p><figure><img src="/a.png" alt="A cat" title="Photo" />' +
+          "<figcaption>A cat</figcaption></figure> " +
+          '<figure><img src="/b.png" alt="" /></figure></p>\n',
+      );
+      t.assert.strictEqual(
+        markdownToHTML(md, { render: { ...render, figureCaption: "none" } }),
+        '<p><figure><img src="/a.png" alt="A cat" title="Photo" /></figure> ' +
+          '<figure><img src="/b.png" alt="" /></figure></p>\n',
+      );
+    });
+
+    it("should replace aliased code block languages", (t: TestContext) => {
+      const md = "```js\na\n```\n\n```node title=x\nb\n```\n\n" +
+        "```py\nc\n```\n";
+      const languageAliases = { js: "javascript", node: "javascript" };
+      const seen: string[] = [];
+      const html = markdownToHTML(md, {
+        render: { languageAliases },
+        plugins: {
+          render: {
+            codefenceSyntaxHighlighter: {
+              highlight: (code, lang) => (seen.push(lang), code),
+            },
+          },
+        },
+      });
+      t.assert.deepStrictEqual(seen, ["javascript", "javascript", "py"]);
+      t.assert.match(html, /class="language-javascript"/);
+    });
+
+    it("should number headings", (t: TestContext) => {
+      const md = "# A\n\n### B\n\n## C\n\n# D\n\n## E\n";
+      const html = markdownToHTML(md, {
+        render: { numberHeadings: { enabled: true, separator: " " } },
+      });
+      t.assert.strictEqual(
+        html,
+        "<h1>1 A</h1>\n<h3>1.0.1 B</h3>\n<h2>1.1 C</h2>\n" +
+          "<h1>2 D</h1>\n<h2>2.1 E</h2>\n",
+      );
+    });
+
+    it("should start numbering headings at startLevel", (t: TestContext) => {
+      const md = "# A\n\n## B\n\n## C\n\n# D\n\n## E\n";
+      const html = markdownToHTML(md, {
+        extension: { headerIDs: "" },
+        render: { numberHeadings: { enabled: true, startLevel: 2 } },
+      });
+      t.assert.ok(html.includes('id="b"></a>1. B</h2>'));
+      t.assert.ok(html.includes('id="c"></a>2. C</h2>'));
+      t.assert.ok(html.includes('id="e"></a>1. E</h2>'));
+      t.assert.ok(html.includes('id="a"></a>A</h1>'));
+    });
+
+    it("should choose typographic substitutions", (t: TestContext) => {
+      const md = "\"Don't\" -- wait...\n";
+      t.assert.strictEqual(
+        markdownToHTML(md, { render: { typography: { dashes: false } } }),
+        "<p>\u201cDon\u2019t\u201d -- wait\u2026</p>\n",
+      );
+      t.assert.strictEqual(
+        markdownToHTML(md, {
+          parse: { smart: true },
+          render: { typography: { quotes: false } },
+        }),
+        "<p>&quot;Don't&quot; \u2013 wait\u2026</p>\n",
+      );
+    });
+
+    it("should encode non-ASCII output as entities", (t: TestContext) => {
+      const md = "\u201cCaf\u00e9\u201d \u{1F389}\n\n```\n\u00e9\n```\n";
+      t.assert.strictEqual(
+        markdownToHTML(md, { render: { encodeEntities: "numeric" } }),
+        "<p>&#8220;Caf&#233;&#8221; &#127881;</p>\n" +
+          "<pre><code>\u00e9\n</code></pre>\n",
+      );
+      t.assert.strictEqual(
+        markdownToHTML(md, { render: { encodeEntities: "named" } }),
+        "<p>&ldquo;Caf&#233;&rdquo; &#127881;</p>\n" +
+          "<pre><code>\u00e9\n</code></pre>\n",
+      );
+    });
+
+    it("should apply the limit after encoding entities", (t: TestContext) => {
+      const md = "é".repeat(200);
+      const render = (encodeEntities: "utf8" | "numeric") =>
+        markdownToHTML(md, {
+          render: { maxOutputBytes: 1024, encodeEntities },
+        });
+      t.assert.strictEqual(render("utf8"), `<p>${md}</p>\n`);
+      t.assert.throws(() => render("numeric"), {
+        name: "TypeError",
+        message: /exceeds the maximum of 1024 bytes/,
+      });
+    });
+
+    it("should filter attribute values", (t: TestContext) => {
+      const calls: string[][] = [];
+      const md = '<p style="color: red" title="a">x</p>\n\n![i](i.png "t")\n';
+      const html = markdownToHTML(md, {
+        render: {
+          unsafe: true,
+          attributeFilter: (tag, name, value) => {
+            calls.push([tag, name, value]);
+            if (name === "style") return null;
+            return name === "title" ? value.toUpperCase() : value;
+          },
+        },
+      });
+      t.assert.strictEqual(
+        html,
+        '<p title="A">x</p>\n' +
+          '<p><img src="i.png" alt="i" title="T" /></p>\n',
+      );
+      t.assert.deepStrictEqual(calls, [
+        ["p", "style", "color: red"],
+        ["p", "title", "a"],
+        ["img", "src", "i.png"],
+        ["img", "alt", "i"],
+        ["img", "title", "t"],
+      ]);
+    });
+
+    it("should number the lines of code blocks", (t: TestContext) => {
+      const md = "```js\n/* a\nb */ x\n```\n\n`y`\n";
+      const html = markdownToHTML(md, {
+        render: {
+          codeLineNumbers: { enabled: true, start: 3 },
+          codeBlockRenderer: (code) =>
+            `<pre><code><i>${code.replace(" x", "</i> x")}</code></pre>`,
+        },
+      });
+      t.assert.strictEqual(
+        html,
+        '<pre><code><span class="line" data-line="3"><i>/* a</i></span>\n' +
+          '<span class="line" data-line="4"><i>b */</i> x</span>\n' +
+          "</code></pre>\n<p><code>y</code></p>\n",
+      );
+    });
+
+    it("should limit hard breaks to hardbreaksScope", (t: TestContext) => {
+      const md = "> a\n> *b\n> c*\n\n- d\n  e\n";
+      const render = (hardbreaksScope: "all" | "paragraphs" | "none") =>
+        markdownToHTML(md, { render: { hardbreaks: true, hardbreaksScope } });
+      t.assert.strictEqual(
+        render("paragraphs"),
+        "<blockquote>\n<p>a<br />\n<em>b<br />\nc</em></p>\n" +
+          "</blockquote>\n<ul>\n<li>d\ne</li>\n</ul>\n",
+      );
+      t.assert.strictEqual(
+        render("all"),
+        "<blockquote>\n<p>a<br />\n<em>b<br />\nc</em></p>\n" +
+          "</blockquote>\n<ul>\n<li>d<br />\ne</li>\n</ul>\n",
+      );
+      t.assert.strictEqual(
+        render("none"),
+        "<blockquote>\n<p>a\n<em>b\nc</em></p>\n" +
+          "</blockquote>\n<ul>\n<li>d\ne</li>\n</ul>\n",
+      );
+    });
+
+    it("should pass the node context to URL rewriters", (t: TestContext) => {
+      const contexts: unknown[] = [];
+      const html = markdownToHTML("# [Top](/top)\n\n![A *cat*](cat.png)", {
+        extension: {
+          linkURLRewriter: (url, context) => (contexts.push(context), url),
+          imageURLRewriter: {
+            toHTML: (url, context) =>
+              context?.context === "image" ? `/cdn/${url}` : url,
+          },
+        },
+      });
+      t.assert.strictEqual(
+        html,
+        '<h1><a href="/top">Top</a></h1>\n' +
+          '<p><img src="/cdn/cat.png" alt="A cat" /></p>\n',
+      );
+      t.assert.deepStrictEqual(contexts, [
+        { context: "link", parentType: "Heading", text: "Top" },
+      ]);
+    });
+
+    it("should not autolink excluded URLs", (t: TestContext) => {
+      const html = markdownToHTML(
+        "Visit www.example.com, <https://example.org/a>, or https://b.dev",
+        {
+          extension: { autolink: true },
+          render: {
+            autolinkExcludePatterns: ["http://*.example.com", "/\\.org\\//"],
+          },
+        },
+      );
+      t.assert.strictEqual(
+        html,
+        "<p>Visit www.example.com, https://example.org/a, or " +
+          '<a href="https://b.dev">https://b.dev</a></p>\n',
+      );
+    });
+
+    it("should exclude explicit links written as URLs", (t: TestContext) => {
+      const md = "[https://a.dev](https://a.dev) [a](https://a.dev)";
+      const html = markdownToHTML(md, {
+        render: { autolinkExcludePatterns: ["https://a.dev"] },
+      });
+      t.assert.strictEqual(
+        html,
+        '<p>https://a.dev <a href="https://a.dev">a</a></p>\n',
+      );
+    });
+  });
+});
+
+describe("markdownToHTMLStreaming", () => {
+  it("should pass one chunk per top-level block", (t: TestContext) => {
+    const md = "# Title\n\n- a\n- b\n\nText[^1]\n\n[^1]: Note.\n";
+    const options = { extension: { footnotes: true } };
+    const chunks: string[] = [];
+    markdownToHTMLStreaming(md, (chunk) => chunks.push(chunk), options);
+    t.assert.deepStrictEqual(chunks.slice(0, 3), [
+      "<h1>Title</h1>\n",
+      "<ul>\n<li>a</li>\n<li>b</li>\n</ul>\n",
+      '<p>Text<sup class="footnote-ref"><a href="#fn-1" id="fnref-1" ' +
+        'data-footnote-ref>1</a></sup></p>\n',
+    ]);
+    t.assert.strictEqual(chunks.join(""), markdownToHTML(md, options));
+  });
+
+  it("should rethrow errors thrown by the sink", (t: TestContext) => {
+    const error = new Error("sink closed");
+    const chunks: string[] = [];
+    t.assert.throws(
+      () =>
+        markdownToHTMLStreaming("one\n\ntwo\n\nthree", (chunk) => {
+          if (chunks.push(chunk) === 2) throw error;
+        }),
+      error,
+    );
+    t.assert.deepStrictEqual(chunks, ["<p>one</p>\n", "<p>two</p>\n"]);
+  });
+
+  it("should sanitize raw HTML spanning blocks", (t: TestContext) => {
+    const md = "<iframe>\n\n<b>hidden</b>\n\n</iframe>\n\nshown\n";
+    const options = { render: { unsafe: true, sanitizeHtml: true } };
+    const chunks: string[] = [];
+    markdownToHTMLStreaming(md, (chunk) => chunks.push(chunk), options);
+    t.assert.strictEqual(chunks.join(""), "\n<p>shown</p>\n");
+    t.assert.strictEqual(chunks.join(""), markdownToHTML(md, options));
+  });
+});
+
+describe("markdownToHTMLWith", () => {
+  it("should match markdownToHTML with the same options", (t: TestContext) => {
+    const options = {
+      extension: { headerIDs: "", strikethrough: true },
+      render: { unsafe: true },
+    };
+    using handle = compileOptions(options);
+    for (const md of ["# A\n\n# A\n", "~~x~~ <b>y</b>", "[a](/b)"]) {
+      t.assert.strictEqual(
+        markdownToHTMLWith(handle, md),
+        markdownToHTML(md, options),
+      );
+    }
+  });
+
+  it("should keep calling the captured callbacks", (t: TestContext) => {
+    const urls: string[] = [];
+    using handle = compileOptions({
+      extension: {
+        linkURLRewriter: (url: string) => (urls.push(url), `/x${url}`),
+      },
+    });
+    t.assert.strictEqual(
+      markdownToHTMLWith(handle, "[a](/a)"),
+      '<p><a href="/x/a">a</a></p>\n',
+    );
+    markdownToHTMLWith(handle, "[b](/b)");
+    t.assert.deepStrictEqual(urls, ["/a", "/b"]);
   });
 });
@@ -4,12 +4,16 @@
  * rendering an existing abstract syntax tree ({@linkcode AST}) into HTML.
  *
  * @see {@linkcode markdownToHTML} to convert Markdown to HTML.
+ * @see {@linkcode markdownToHTMLStreaming} to convert Markdown to HTML in
+ * chunks.
+ * @see {@linkcode compileOptions} and {@linkcode markdownToHTMLWith} to
+ * convert many documents with the same options.
  * @see {@linkcode renderHTML} to render an existing {@linkcode AST} into HTML.
  * @see {@linkcode parseMarkdown} to parse Markdown into an {@linkcode AST}.
  *
  * @module html
  */
-import wasm from "./_wasm.ts";
+import wasm, { OptionsHandle as WasmOptionsHandle } from "./_wasm.ts";
 import { collectOptions } from "./_internal.ts";
 import type { AST } from "./nodes.ts";
 import type { Options } from "./options.ts";
@@ -35,6 +39,136 @@ export function markdownToHTML(markdown: string, options?: Options): string {
   return wasm.markdown_to_html(markdown, ...args);
 }
 
+/**
+ * Render Markdown to HTML, passing the output to a `sink` function in chunks
+ * as it is rendered, rather than returning it as a single string. A chunk is
+ * passed to the sink after each top-level block, which keeps the peak memory
+ * use low when rendering very large documents, e.g. into a response stream.
+ *
+ * Joined together, the chunks are identical to the output of
+ * {@linkcode markdownToHTML}. When the `sanitizeHtml` option is set, a block
+ * whose raw HTML leaves a tag or a removed element open is held back until
+ * it's closed, so that it's sanitized as a whole. An exception thrown by the
+ * sink aborts rendering, and is rethrown to the caller.
+ *
+ * @param markdown The Markdown string to be converted.
+ * @param sink The function to pass each chunk of HTML to.
+ * @param [options] Options to customize the conversion.
+ * @example
+ * ```ts
+ * import assert from "node:assert";
+ * import { markdownToHTMLStreaming } from "@nick/comrak";
+ *
+ * const chunks: string[] = [];
+ * markdownToHTMLStreaming("# Hello\n\nWorld", (chunk) => chunks.push(chunk));
+ * assert.deepStrictEqual(chunks, ["<h1>Hello</h1>\n", "<p>World</p>\n"]);
+ * ```
+ * @category Conversion
+ */
+export function markdownToHTMLStreaming(
+  markdown: string,
+  sink: (chunk: string) => void,
+  options?: Options,
+): void {
+  const args = collectOptions(options);
+  wasm.markdown_to_html_streaming(markdown, ...args, sink);
+}
+
+// gives the functions below access to the private field of a handle
+let unwrap: (handle: OptionsHandle) => WasmOptionsHandle;
+
+/**
+ * A set of options compiled by {@linkcode compileOptions}, which can be passed
+ * to {@linkcode markdownToHTMLWith} any number of times.
+ *
+ * **The memory held by a handle is not garbage collected.** Call
+ * {@linkcode OptionsHandle.free} once the handle is no longer needed, or
+ * declare it with `using` to have it freed automatically at the end of its
+ * scope.
+ *
+ * @category Conversion
+ */
+export class OptionsHandle implements Disposable {
+  static {
+    unwrap = (handle) => handle.#inner;
+  }
+
+  #inner: WasmOptionsHandle;
+
+  /** @internal */
+  constructor(inner: WasmOptionsHandle) {
+    this.#inner = inner;
+  }
+
+  /**
+   * Frees the memory held by the handle. It must not be used afterwards.
+   */
+  free(): void {
+    this.#inner.free();
+  }
+
+  [Symbol.dispose](): void {
+    this.free();
+  }
+}
+
+/**
+ * Deserializes and validates a set of options once, returning a handle that
+ * can be passed to {@linkcode markdownToHTMLWith} in their place. This saves
+ * the cost of converting the options on every call when rendering many
+ * documents the same way, e.g. in a static site generator.
+ *
+ * Plugins and callbacks are captured by the handle, and are used by every
+ * render. Heading IDs are still deduplicated per document.
+ *
+ * @param [options] Options to customize the conversion.
+ * @returns A handle to the compiled options.
+ * @example
+ * ```ts
+ * import assert from "node:assert";
+ * import { compileOptions, markdownToHTMLWith } from "@nick/comrak";
+ *
+ * using handle = compileOptions({ extension: { strikethrough: true } });
+ * assert.strictEqual(
+ *   markdownToHTMLWith(handle, "~~Hello~~, **Nick**!"),
+ *   "<p><del>Hello</del>, <strong>Nick</strong>!</p>\n",
+ * );
+ * ```
+ * @category Conversion
+ */
+export function compileOptions(options?: Options): OptionsHandle {
+  const args = collectOptions(options);
+  return new OptionsHandle(wasm.compile_options(...args));
+}
+
+/**
+ * Render Markdown to HTML with options compiled by {@linkcode compileOptions}.
+ * The output is identical to that of {@linkcode markdownToHTML} with the same
+ * options.
+ *
+ * @param handle The compiled options to render with.
+ * @param markdown The Markdown string to be converted.
+ * @returns The generated HTML string.
+ * @example
+ * ```ts
+ * import assert from "node:assert";
+ * import { compileOptions, markdownToHTMLWith } from "@nick/comrak";
+ *
+ * using handle = compileOptions();
+ * for (const name of ["Nick", "World"]) {
+ *   const html = markdownToHTMLWith(handle, `Hello, **${name}**!`);
+ *   assert.strictEqual(html, `<p>Hello, <strong>${name}</strong>!</p>\n`);
+ * }
+ * ```
+ * @category Conversion
+ */
+export function markdownToHTMLWith(
+  handle: OptionsHandle,
+  markdown: string,
+): string {
+  return wasm.markdown_to_html_with(unwrap(handle), markdown);
+}
+
 /**
  * Formats an abstract syntax tree (AST), produced by parsing a Markdown
  * document with the {@linkcode parseMarkdown} function, into HTML text.
@@ -0,0 +1,31 @@
+import { describe, it, type TestContext } from "node:test";
+
+import { markdownToHTML } from "./html.ts";
+import { memoryUsage, releaseMemory } from "./memory.ts";
+import { hasFeature } from "./version.ts";
+
+describe("memoryUsage", () => {
+  it("should report whole pages of reserved memory", (t: TestContext) => {
+    const { reserved } = memoryUsage();
+    t.assert.ok(reserved > 0);
+    t.assert.strictEqual(reserved % 65536, 0);
+  });
+
+  it("should count allocations with the alloc feature", (t: TestContext) => {
+    const { allocated, reserved } = memoryUsage();
+    if (hasFeature("alloc")) {
+      t.assert.strictEqual(typeof allocated, "number");
+      t.assert.ok(allocated! <= reserved);
+    } else {
+      t.assert.strictEqual(allocated, null);
+    }
+  });
+});
+
+describe("releaseMemory", () => {
+  it("should not affect later renders", (t: TestContext) => {
+    const html = markdownToHTML("# Hello");
+    releaseMemory();
+    t.assert.strictEqual(markdownToHTML("# Hello"), html);
+  });
+});
@@ -0,0 +1,74 @@
+/**
+ * This module provides functions for inspecting and reclaiming the memory
+ * used by the WebAssembly module, which is useful in long-running processes
+ * (such as servers and workers) after rendering unusually large documents.
+ *
+ * Note that WebAssembly memory can only ever grow. Memory that is freed (or
+ * released with {@linkcode releaseMemory}) is reused by later renders, but it
+ * is never returned to the host, so the memory reserved by the module never
+ * decreases. The only way to reclaim it entirely is to load a new instance of
+ * the module, such as by restarting the worker that loaded it.
+ *
+ * @module memory
+ */
+import { memory_usage, release_memory } from "./_wasm.ts";
+
+/**
+ * The memory used by the WebAssembly module, in bytes.
+ *
+ * @category Utilities
+ */
+export interface MemoryUsage {
+  /**
+   * The number of bytes currently allocated, or `null` if the module was not
+   * built with the `alloc` feature, which provides the allocator that keeps
+   * track of it.
+   */
+  allocated: number | null;
+  /**
+   * The size of the module's linear memory, which includes both allocated
+   * memory and free memory that is available for reuse.
+   */
+  reserved: number;
+}
+
+/**
+ * Returns the amount of memory used by the WebAssembly module.
+ *
+ * @returns The number of bytes allocated and reserved by the module.
+ * @example
+ * ```ts
+ * import { memoryUsage } from "@nick/comrak";
+ * import assert from "node:assert";
+ *
+ * const { allocated, reserved } = memoryUsage();
+ * assert.ok(allocated === null || allocated <= reserved);
+ * ```
+ * @category Utilities
+ */
+export function memoryUsage(): MemoryUsage {
+  return memory_usage();
+}
+
+/**
+ * Releases the memory held by internal caches, such as the syntaxes and
+ * themes loaded by the built-in syntax highlighter, which are reloaded the
+ * next time they're needed.
+ *
+ * The released memory is kept by the module for reuse, rather than returned
+ * to the host, so this doesn't reduce {@linkcode MemoryUsage.reserved}. It
+ * does keep the module from reserving any more memory until the released
+ * memory has been reused.
+ *
+ * @example
+ * ```ts
+ * import { markdownToHTML, releaseMemory } from "@nick/comrak";
+ *
+ * markdownToHTML("# A very large document");
+ * releaseMemory();
+ * ```
+ * @category Utilities
+ */
+export function releaseMemory(): void {
+  release_memory();
+}
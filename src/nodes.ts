@@ -76,7 +76,9 @@ export interface AST {
 export declare namespace AST {
   export {}; // only expose explicitly exported types
 
-  type Resolve<T extends string, V> = V extends never ? T : { [K in T]: V };
+  // wrapped in a tuple so `never` doesn't distribute into an empty union
+  type Resolve<T extends string, V> = [V] extends [never] ? T
+    : { [K in T]: V };
 
   /**
    * Represents the data associated with a specific node in an abstract syntax
@@ -5,7 +5,13 @@
  *
  * @module options
  */
-import type { HeadingAdapter, SyntaxHighlighterAdapter } from "./adapters.ts";
+import type {
+  HeadingAdapter,
+  HeadingMeta,
+  SyntaxHighlighterAdapter,
+} from "./adapters.ts";
+import type { Sourcepos } from "./nodes.ts";
+import type { Diagnostic } from "./parse.ts";
 import { cloneDeep, type RequiredDeep } from "./_internal.ts";
 
 /**
@@ -46,6 +52,20 @@ export interface ResolvedReference {
   url: string;
 }
 
+/**
+ * A link reference definition, as used by the
+ * {@linkcode ParseOptions.referenceMap} option.
+ *
+ * @category Options
+ * @tags parse
+ */
+export interface ReferenceDefinition {
+  /** The destination URL of the reference. */
+  url: string;
+  /** The title of the reference. */
+  title?: string;
+}
+
 /**
  * When the Comrak parser encounters an image or link reference that does not
  * have a corresponding definition, the {@linkcode BrokenLinkCallback} will be
@@ -92,1027 +112,3461 @@ export type BrokenLinkCallback =
   | BrokenLinkCallbackImpl;
 
 /**
- * A function that rewrites URLs for images or links during rendering.
+ * A function that generates the slug for a heading's ID from its text content
+ * and level (`1` through `6`).
  *
  * @category Options
  * @tags extension
  */
-export interface URLRewriterFunction {
-  (url: string): string;
+export interface SlugifyFunction {
+  (text: string, level: number): string;
 }
 
 /**
- * An object that rewrites URLs for images or links during rendering.
+ * The URL and optional label of a wikilink, as returned by a
+ * {@linkcode WikilinkResolverFunction}.
  *
  * @category Options
  * @tags extension
  */
-export interface URLRewriterImpl {
-  toHTML(url: string): string;
+export interface ResolvedWikilink {
+  /** The URL the wikilink points to. */
+  url: string;
+  /** The text of the link, replacing its original label. */
+  label?: string | null;
 }
 
 /**
- * A type that represents either a function or an object for rewriting URLs.
+ * A function that resolves the URL and label of a wikilink from its title,
+ * returning `null` or `undefined` to keep the wikilink as it is.
  *
- * @see {@linkcode URLRewriterFunction} for function-based URL rewriting.
- * @see {@linkcode URLRewriterImpl} for object-based URL rewriting.
  * @category Options
  * @tags extension
  */
-export type URLRewriter = URLRewriterFunction | URLRewriterImpl;
+export interface WikilinkResolverFunction {
+  (title: string): Maybe<ResolvedWikilink>;
+}
 
 /**
- * Comrak's capabilities really shine through its highly configurable APIs. The
- * `Options` interface allows you to fine-tune each stage of the Markdown
- * processing pipeline, from parsing to rendering.
- *
- * Comrak also supports numerous extensions to the CommonMark specification,
- * enabling features like tables, footnotes, autolinks, task lists, description
- * lists, HTML tag filtering, and much more. You can also customize Comrak's
- * behavior further through its extensible {@linkcode Plugins} API, allowing
- * for integration with external syntax highlighters, heading ID slugifiers,
- * and other capabilities.
- *
- * These options can be passed to any of Comrak's main functions:
- *
- * - {@link markdownToHTML}
- * - {@link markdownToXML}
- * - {@link markdownToCommonMark}
- * - {@link parseMarkdown}
- * - {@link renderHTML}
- * - {@link renderXML}
- * - {@link renderCommonMark}
- *
- * See the documentation for each individual option for detailed information on
- * their behavior and usage, as well as functional code examples.
+ * Options for the table of contents generated by the
+ * {@linkcode RenderOptions.tableOfContents} option.
  *
  * @category Options
+ * @tags render
  */
-export interface Options {
-  /** Configure extensions to the CommonMark specification. */
-  extension?: ExtensionOptions;
-  /** Configure parse-time options. */
-  parse?: ParseOptions;
-  /** Configure render-time options. */
-  render?: RenderOptions;
-  /** Configure {@linkcode Plugins} to customize Comrak's behavior. */
-  plugins?: Plugins;
+export interface TableOfContentsOptions {
+  /**
+   * The text of the paragraph to replace with the table of contents.
+   *
+   * @default {"[[TOC]]"}
+   */
+  marker?: string;
+  /**
+   * The deepest heading level to include in the table of contents.
+   *
+   * @default {6}
+   */
+  maxLevel?: number;
 }
 
 /**
- * Options to select extensions.
+ * The typographic substitutions made by the
+ * {@linkcode RenderOptions.typography} option. Omitted substitutions are made.
  *
  * @category Options
- * @tags extension
+ * @tags render
  */
-export interface ExtensionOptions {
+export interface TypographyOptions {
   /**
-   * Enables the [autolink extension] from the GFM spec.
+   * Replace straight quotes and apostrophes with curly ones.
    *
-   * [autolink extension]: https://github.github.com/gfm/#autolinks-extension-
+   * @default {true}
+   */
+  quotes?: boolean;
+  /**
+   * Replace `--` with an en dash, and `---` with an em dash.
    *
-   * @example
-   * ```ts
-   * import { markdownToHTML } from "@nick/comrak";
-   * import assert from "node:assert";
+   * @default {true}
+   */
+  dashes?: boolean;
+  /**
+   * Replace `...` with an ellipsis.
    *
-   * const html = markdownToHTML("Hello www.github.com.\n", {
-   *   extension: { autolink: true },
-   * });
-   * assert.equal(html, '<p>Hello <a href="http://www.github.com">www.github.com</a>.</p>\n');
-   * ```
-   * @default {false}
+   * @default {true}
    */
-  autolink?: boolean;
+  ellipses?: boolean;
+}
 
+/**
+ * Options for the heading numbers added by the
+ * {@linkcode RenderOptions.numberHeadings} option.
+ *
+ * @category Options
+ * @tags render
+ */
+export interface NumberHeadingsOptions {
+  /** Whether to number headings. */
+  enabled: boolean;
   /**
-   * Enables the description lists extension.
+   * The level of the headings numbered `1.`, `2.`, and so on. Headings of a
+   * higher level (e.g. `h1` for a `startLevel` of `2`) aren't numbered.
    *
-   * Each term must be defined in one paragraph, followed by a blank line, and
-   * then by the details. Details begins with a colon.
-   *
-   * @example
-   * ```ts
-   * import { markdownToHTML } from "@nick/comrak";
-   * import assert from "node:assert";
+   * @default {1}
+   */
+  startLevel?: number;
+  /**
+   * The text written between the number and the text of a heading.
    *
-   * const html = markdownToHTML("Term\n\n: Definition", {
-   *   extension: { descriptionLists: true },
-   * });
-   * assert.equal(html, "<dl>\n<dt>Term</dt>\n<dd>\n<p>Definition</p>\n</dd>\n</dl>\n");
-   * ```
-   * @default {false}
+   * @default {". "}
    */
-  descriptionLists?: boolean;
+  separator?: string;
+}
 
+/**
+ * Options for the line numbers added to code blocks by the
+ * {@linkcode RenderOptions.codeLineNumbers} option.
+ *
+ * @category Options
+ * @tags render
+ */
+export interface CodeLineNumbersOptions {
+  /** Whether to number the lines of code blocks. */
+  enabled: boolean;
   /**
-   * Enables the footnotes extension per cmark-gfm.
-   *
-   * The extension is modelled after [Kramdown].
-   *
-   * [Kramdown]: https://kramdown.gettalong.org/syntax.html#footnotes
-   *
-   * @example
-   * ```ts
-   * import { markdownToHTML } from "@nick/comrak";
-   * import assert from "node:assert";
+   * The number of the first line of each code block.
    *
-   * const html = markdownToHTML("Hi[^x].\n\n[^x]: A greeting.\n", {
-   *   extension: { footnotes: true },
-   * });
-   * assert.equal(html, '<p>Hi<sup class="footnote-ref"><a href="#fn-x" id="fnref-x" data-footnote-ref>1</a></sup>.</p>\n<section class="footnotes" data-footnotes>\n<ol>\n<li id="fn-x">\n<p>A greeting. <a href="#fnref-x" class="footnote-backref" data-footnote-backref data-footnote-backref-idx="1" aria-label="Back to reference 1">↩</a></p>\n</li>\n</ol>\n</section>\n');
-   * ```
-   * @default {false}
+   * @default {1}
    */
-  footnotes?: boolean;
+  start?: number;
+}
 
+/**
+ * The kinds of links created by the {@linkcode ExtensionOptions.autolink}
+ * extension, for its object form. Omitted kinds are enabled.
+ *
+ * @category Options
+ * @tags extension
+ */
+export interface AutolinkOptions {
   /**
-   * Enables the inline footnotes extension.
-   *
-   * Allows inline footnote syntax `^[content]` where the content can include
-   * inline markup. Inline footnotes are automatically converted to regular
-   * footnotes with auto-generated names and share the same numbering sequence.
-   *
-   * Requires `footnotes` to be enabled as well.
+   * Whether to autolink URLs with a scheme, such as `https://example.com`.
    *
-   * @example
-   * ```ts
-   * import { markdownToHTML, Options } from "@nick/comrak";
-   * import assert from "node:assert";
+   * @default {true}
+   */
+  urls?: boolean;
+  /**
+   * Whether to autolink URLs starting with `www.`, such as `www.example.com`,
+   * which are linked with an `http://` scheme.
    *
-   * const options = Options.default();
-   * options.extension.footnotes = true;
-   * options.extension.inlineFootnotes = true;
+   * @default {true}
+   */
+  www?: boolean;
+  /**
+   * Whether to autolink email addresses, such as `nick@example.com`, which
+   * are linked with a `mailto:` scheme.
    *
-   * const md = "Hi^[An inline note].\n";
-   * const html = markdownToHTML(md, options);
-   * assert.equal(html, "<p>Hi<sup class=\"footnote-ref\"><a href=\"#fn-__inline_1\" id=\"fnref-__inline_1\" data-footnote-ref>1</a></sup>.</p>\n<section class=\"footnotes\" data-footnotes>\n<ol>\n<li id=\"fn-__inline_1\">\n<p>An inline note <a href=\"#fnref-__inline_1\" class=\"footnote-backref\" data-footnote-backref data-footnote-backref-idx=\"1\" aria-label=\"Back to reference 1\">↩</a></p>\n</li>\n</ol>\n</section>\n");
-   * ```
+   * @default {true}
    */
-  inlineFootnotes?: boolean;
+  email?: boolean;
+}
 
+/**
+ * The URL templates of the {@linkcode ExtensionOptions.mentions} extension,
+ * in which `{name}` is replaced with the name after the `@` or `#`. A kind
+ * without a template is left as text.
+ *
+ * @category Options
+ * @tags extension
+ */
+export interface MentionsOptions {
   /**
-   * Enables the front matter extension.
+   * The URL that `@user` mentions link to, such as `/users/{name}`.
    *
-   * Front matter, which begins with the delimiter string at the beginning of
-   * the file and ends at the end of the next line that contains only the
-   * delimiter, is passed through unchanged in markdown output and omitted
-   * from HTML output.
+   * @default {undefined}
+   */
+  userUrl?: string;
+  /**
+   * The URL that `#tag` hashtags link to, such as `/tags/{name}`.
    *
-   * @example
-   * ```ts
-   * import { markdownToHTML } from "@nick/comrak";
-   * import assert from "node:assert";
+   * @default {undefined}
+   */
+  tagUrl?: string;
+}
+
+/**
+ * The intrinsic dimensions of an image, in CSS pixels.
+ *
+ * @category Options
+ * @tags render
+ */
+export interface ImageDimensions {
+  /** The width of the image, in pixels. */
+  width: number;
+  /** The height of the image, in pixels. */
+  height: number;
+}
+
+/**
+ * A function that resolves the intrinsic dimensions of an image from its URL,
+ * returning `null` or `undefined` if the dimensions are unknown.
+ *
+ * @category Options
+ * @tags render
+ */
+export interface ImageDimensionsFunction {
+  (url: string): Maybe<ImageDimensions>;
+}
+
+/**
+ * The frontend framework whose template syntax is escaped in text by the
+ * {@linkcode RenderOptions.frameworkEscaping} option.
+ *
+ * @category Options
+ * @tags render
+ */
+export type FrameworkEscaping = "none" | "vue" | "angular";
+
+/**
+ * A function that renders a code block to HTML, used by the
+ * {@linkcode RenderOptions.codeBlockRenderer} option. Returning `null` or
+ * `undefined` falls back to the default rendering.
+ *
+ * @param code The raw content of the code block.
+ * @param lang The language of the code block, if any.
+ * @param meta The rest of the info string after the language, if any.
+ * @category Options
+ * @tags render
+ */
+export interface CodeBlockRenderer {
+  (code: string, lang: string | null, meta: string | null): Maybe<string>;
+}
+
+/**
+ * A function that renders a math span or block to HTML (such as MathML), used
+ * by the {@linkcode RenderOptions.mathRenderer} option. Returning `null` or
+ * `undefined` falls back to the default rendering.
+ *
+ * @param latex The LaTeX source of the math.
+ * @param displayMode Whether the math is displayed as a block (`$$...$$` or a
+ * `math` code block), rather than inline with the surrounding text.
+ * @category Options
+ * @tags render
+ */
+export interface MathRenderer {
+  (latex: string, displayMode: boolean): Maybe<string>;
+}
+
+/**
+ * A function that is called for each heading as it is rendered, used by the
+ * {@linkcode RenderOptions.onHeading} option. Its return value is ignored.
+ *
+ * @param heading The level and text content of the heading.
+ * @param id The `id` of the heading, including the `headerIDs` prefix, or an
+ * empty string if the `headerIDs` extension is disabled.
+ * @param sourcepos The position of the heading in the source.
+ * @category Options
+ * @tags render
+ */
+export interface HeadingCallback {
+  (heading: HeadingMeta, id: string, sourcepos: Sourcepos): void;
+}
+
+/**
+ * A custom allowlist for the {@linkcode RenderOptions.sanitizeHtml} option.
+ * Omitted fields fall back to the default allowlist.
+ *
+ * @category Options
+ * @tags render
+ */
+export interface SanitizeHtmlOptions {
+  /** The names of the allowed tags, such as `["p", "a", "em"]`. */
+  allowedTags?: string[];
+  /**
+   * The allowed attributes, keyed by tag name. Attributes listed under the
+   * `"*"` key are allowed on every tag.
    *
-   * const html = markdownToHTML("---\nlayout: post\n---\nText\n", {
-   *   extension: { frontMatterDelimiter: "---" },
-   * });
-   * assert.equal(html, "<p>Text</p>\n");
-   * ```
-   * @default {null}
+   * @example { "*": ["title"], "a": ["href"] }
    */
-  frontMatterDelimiter?: string | null;
+  allowedAttributes?: Record<string, string[]>;
+}
 
+/**
+ * Options for the {@linkcode RenderOptions.footnoteBackref} option.
+ *
+ * @category Options
+ * @tags render
+ */
+export interface FootnoteBackrefOptions {
   /**
-   * Enables the header IDs Comrak extension.
+   * The text of each backreference link. It is escaped before rendering.
    *
-   * @example
-   * ```ts
-   * import { markdownToHTML } from "@nick/comrak";
-   * import assert from "node:assert";
+   * @default {"↩"}
+   */
+  symbol?: string;
+  /**
+   * Whether the backreference links are placed at the start or the end of
+   * the footnote definition.
    *
-   * const html = markdownToHTML("# README\n", {
-   *   extension: { headerIDs: "user-content-" },
-   * });
-   * assert.equal(html, "<h1><a href=\"#readme\" aria-hidden=\"true\" class=\"anchor\" id=\"user-content-readme\"></a>README</h1>\n");
-   * ```
-   * @default {null}
+   * @default {"end"}
    */
-  headerIDs?: string | null;
+  position?: "start" | "end";
+}
 
+/**
+ * Options for the {@linkcode RenderOptions.footnotes} option.
+ *
+ * @category Options
+ * @tags render
+ */
+export interface FootnotesOptions {
   /**
-   * Enables the [table extension] from the GFM spec.
+   * The `aria-label` of each backreference link, which is followed by a space
+   * and the number of the reference it links back to.
    *
-   * [table extension]: https://github.github.com/gfm/#tables-extension-
+   * @default {"Back to reference"}
+   */
+  backrefLabel?: string;
+  /**
+   * The prefix of the `id` of each footnote reference, which is followed by
+   * the name of the footnote.
+   *
+   * @default {"fnref-"}
+   */
+  refPrefix?: string;
+  /**
+   * The prefix of the `id` of each footnote definition, which is followed by
+   * the name of the footnote.
+   *
+   * @default {"fn-"}
+   */
+  defPrefix?: string;
+}
+
+/**
+ * Options for the {@linkcode RenderOptions.taskListInputs} option.
+ *
+ * @category Options
+ * @tags render
+ */
+export interface TaskListInputsOptions {
+  /**
+   * Whether the checkboxes can be toggled. When `false`, they keep the
+   * `disabled` attribute, exactly like the default rendering.
+   *
+   * @default {false}
+   */
+  interactive?: boolean;
+  /**
+   * The prefix of each checkbox's `name` attribute. It is followed by a `-`
+   * and the zero-based index of the task item within the document, such as
+   * `task-0`, `task-1`, and so on.
+   *
+   * @default {"task"}
+   */
+  namePrefix?: string;
+}
+
+/**
+ * Options for the {@linkcode RenderOptions.descriptionLists} option.
+ *
+ * @category Options
+ * @tags render
+ */
+export interface DescriptionListOptions {
+  /**
+   * The `class` attribute of each `<dl>` element. Omitted when `null`.
+   *
+   * @default {null}
+   */
+  className?: string | null;
+}
+
+/**
+ * Options for the {@linkcode RenderOptions.document} option.
+ *
+ * @category Options
+ * @tags render
+ */
+export interface HtmlDocumentOptions {
+  /**
+   * The `<title>` of the document. Defaults to the text of the first level 1
+   * heading, and is omitted if there is none.
+   *
+   * @default {null}
+   */
+  title?: string | null;
+  /**
+   * The `lang` attribute of the `<html>` element, such as `"en"`. Omitted
+   * when `null`.
+   *
+   * @default {null}
+   */
+  lang?: string | null;
+  /**
+   * The URL of a stylesheet to link to from the `<head>` of the document.
+   *
+   * @default {null}
+   */
+  stylesheet?: string | null;
+}
+
+/**
+ * Options for the {@linkcode RenderOptions.spoiler} option.
+ *
+ * @category Options
+ * @tags render
+ */
+export interface SpoilerOptions {
+  /**
+   * The tag of the element that spoilers are rendered with, such as
+   * `details` or `mark`. It must be a valid element name.
+   *
+   * @default {"span"}
+   */
+  tag?: string;
+  /**
+   * The `class` attribute of the element. Omitted when empty.
+   *
+   * @default {"spoiler"}
+   */
+  className?: string;
+  /**
+   * Extra attributes of the element, such as `role` or `tabindex`. Names must
+   * be valid attribute names, and values are escaped.
+   *
+   * @default {{}}
+   */
+  attributes?: Record<string, string>;
+}
+
+/**
+ * Options for the {@linkcode RenderOptions.alerts} option.
+ *
+ * @category Options
+ * @tags render
+ */
+export interface AlertOptions {
+  /**
+   * The titles of alerts that don't have one of their own, keyed by the type
+   * of alert (`note`, `tip`, `important`, `warning`, or `caution`). Keys are
+   * matched case-insensitively, and types without a title fall back to the
+   * default, capitalized title (such as `Note`).
+   *
+   * @default {{}}
+   */
+  titles?: Record<string, string>;
+  /**
+   * Whether to add an empty `<span class="markdown-alert-icon">` before the
+   * title of each alert, for an icon to be added with CSS.
+   *
+   * @default {false}
+   */
+  includeIcon?: boolean;
+}
+
+/**
+ * Options for the {@linkcode RenderOptions.images} option.
+ *
+ * @category Options
+ * @tags render
+ */
+export interface ImageOptions {
+  /**
+   * Whether to add `loading="lazy"`, so that browsers defer loading images
+   * until they are close to the viewport.
+   *
+   * @default {false}
+   */
+  lazy?: boolean;
+  /**
+   * The value of the `decoding` attribute, which hints whether images should
+   * be decoded asynchronously. Omitted when `null`.
+   *
+   * @default {null}
+   */
+  decoding?: "async" | "sync" | "auto" | null;
+}
+
+/**
+ * The link or image whose URL is passed to a {@linkcode URLRewriter}.
+ *
+ * @category Options
+ * @tags extension
+ */
+export interface URLRewriterContext {
+  /** Whether the URL is that of a link (or wikilink) or of an image. */
+  context: "link" | "image";
+  /**
+   * The type of the node containing the link or image, as named in the AST
+   * (e.g., `"Paragraph"`, `"Heading"`, or `"TableCell"`).
+   */
+  parentType: string;
+  /** The text of the link, or the alt text of the image. */
+  text?: string;
+}
+
+/**
+ * A function that rewrites URLs for images or links during rendering.
+ *
+ * When rendering HTML, the rewriter also receives the context of the link or
+ * image as its second argument, for rewriting URLs differently depending on
+ * where they appear. The context isn't available when rendering other
+ * formats, such as CommonMark.
+ *
+ * @category Options
+ * @tags extension
+ */
+export interface URLRewriterFunction {
+  (url: string, context?: URLRewriterContext): string;
+}
+
+/**
+ * An object that rewrites URLs for images or links during rendering.
+ *
+ * @category Options
+ * @tags extension
+ */
+export interface URLRewriterImpl {
+  toHTML(url: string, context?: URLRewriterContext): string;
+}
+
+/**
+ * A type that represents either a function or an object for rewriting URLs.
+ *
+ * @see {@linkcode URLRewriterFunction} for function-based URL rewriting.
+ * @see {@linkcode URLRewriterImpl} for object-based URL rewriting.
+ * @category Options
+ * @tags extension
+ */
+export type URLRewriter = URLRewriterFunction | URLRewriterImpl;
+
+/**
+ * Comrak's capabilities really shine through its highly configurable APIs. The
+ * `Options` interface allows you to fine-tune each stage of the Markdown
+ * processing pipeline, from parsing to rendering.
+ *
+ * Comrak also supports numerous extensions to the CommonMark specification,
+ * enabling features like tables, footnotes, autolinks, task lists, description
+ * lists, HTML tag filtering, and much more. You can also customize Comrak's
+ * behavior further through its extensible {@linkcode Plugins} API, allowing
+ * for integration with external syntax highlighters, heading ID slugifiers,
+ * and other capabilities.
+ *
+ * These options can be passed to any of Comrak's main functions:
+ *
+ * - {@link markdownToHTML}
+ * - {@link markdownToXML}
+ * - {@link markdownToCommonMark}
+ * - {@link parseMarkdown}
+ * - {@link renderHTML}
+ * - {@link renderXML}
+ * - {@link renderCommonMark}
+ *
+ * See the documentation for each individual option for detailed information on
+ * their behavior and usage, as well as functional code examples.
+ *
+ * @category Options
+ */
+export interface Options {
+  /** Configure extensions to the CommonMark specification. */
+  extension?: ExtensionOptions;
+  /** Configure parse-time options. */
+  parse?: ParseOptions;
+  /** Configure render-time options. */
+  render?: RenderOptions;
+  /** Configure {@linkcode Plugins} to customize Comrak's behavior. */
+  plugins?: Plugins;
+}
+
+/**
+ * Options to select extensions.
+ *
+ * @category Options
+ * @tags extension
+ */
+export interface ExtensionOptions {
+  /**
+   * Enables the [autolink extension] from the GFM spec.
+   *
+   * Pass an {@linkcode AutolinkOptions} object instead of `true` to only
+   * autolink some kinds of links, such as `www.` URLs but not those with a
+   * scheme. Links written between angle brackets (e.g. `<https://a.dev>`)
+   * are part of CommonMark itself, and are always linked.
+   *
+   * [autolink extension]: https://github.github.com/gfm/#autolinks-extension-
+   *
+   * @example
+   * ```ts
+   * import { markdownToHTML } from "@nick/comrak";
+   * import assert from "node:assert";
+   *
+   * const html = markdownToHTML("Hello www.github.com.\n", {
+   *   extension: { autolink: true },
+   * });
+   * assert.equal(html, '<p>Hello <a href="http://www.github.com">www.github.com</a>.</p>\n');
+   * ```
+   * @example
+   * ```ts
+   * import { markdownToHTML } from "@nick/comrak";
+   * import assert from "node:assert";
+   *
+   * const html = markdownToHTML("www.a.dev or https://b.dev", {
+   *   extension: { autolink: { urls: false } },
+   * });
+   * assert.equal(
+   *   html,
+   *   '<p><a href="http://www.a.dev">www.a.dev</a> or https://b.dev</p>\n',
+   * );
+   * ```
+   * @default {false}
+   */
+  autolink?: boolean | AutolinkOptions;
+
+  /**
+   * Enables the description lists extension.
+   *
+   * Each term must be defined in one paragraph, followed by a blank line, and
+   * then by the details. Details begins with a colon.
+   *
+   * @example
+   * ```ts
+   * import { markdownToHTML } from "@nick/comrak";
+   * import assert from "node:assert";
+   *
+   * const html = markdownToHTML("Term\n\n: Definition", {
+   *   extension: { descriptionLists: true },
+   * });
+   * assert.equal(html, "<dl>\n<dt>Term</dt>\n<dd>\n<p>Definition</p>\n</dd>\n</dl>\n");
+   * ```
+   * @default {false}
+   */
+  descriptionLists?: boolean;
+
+  /**
+   * Enables the footnotes extension per cmark-gfm.
+   *
+   * The extension is modelled after [Kramdown].
+   *
+   * [Kramdown]: https://kramdown.gettalong.org/syntax.html#footnotes
+   *
+   * @example
+   * ```ts
+   * import { markdownToHTML } from "@nick/comrak";
+   * import assert from "node:assert";
+   *
+   * const html = markdownToHTML("Hi[^x].\n\n[^x]: A greeting.\n", {
+   *   extension: { footnotes: true },
+   * });
+   * assert.equal(html, '<p>Hi<sup class="footnote-ref"><a href="#fn-x" id="fnref-x" data-footnote-ref>1</a></sup>.</p>\n<section class="footnotes" data-footnotes>\n<ol>\n<li id="fn-x">\n<p>A greeting. <a href="#fnref-x" class="footnote-backref" data-footnote-backref data-footnote-backref-idx="1" aria-label="Back to reference 1">↩</a></p>\n</li>\n</ol>\n</section>\n');
+   * ```
+   * @default {false}
+   */
+  footnotes?: boolean;
+
+  /**
+   * Enables the inline footnotes extension.
+   *
+   * Allows inline footnote syntax `^[content]` where the content can include
+   * inline markup. Inline footnotes are automatically converted to regular
+   * footnotes with auto-generated names and share the same numbering sequence.
+   *
+   * Requires `footnotes` to be enabled as well.
+   *
+   * @example
+   * ```ts
+   * import { markdownToHTML, Options } from "@nick/comrak";
+   * import assert from "node:assert";
+   *
+   * const options = Options.default();
+   * options.extension.footnotes = true;
+   * options.extension.inlineFootnotes = true;
+   *
+   * const md = "Hi^[An inline note].\n";
+   * const html = markdownToHTML(md, options);
+   * assert.equal(html, "<p>Hi<sup class=\"footnote-ref\"><a href=\"#fn-__inline_1\" id=\"fnref-__inline_1\" data-footnote-ref>1</a></sup>.</p>\n<section class=\"footnotes\" data-footnotes>\n<ol>\n<li id=\"fn-__inline_1\">\n<p>An inline note <a href=\"#fnref-__inline_1\" class=\"footnote-backref\" data-footnote-backref data-footnote-backref-idx=\"1\" aria-label=\"Back to reference 1\">↩</a></p>\n</li>\n</ol>\n</section>\n");
+   * ```
+   */
+  inlineFootnotes?: boolean;
+
+  /**
+   * Enables the front matter extension.
+   *
+   * Front matter, which begins with the delimiter string at the beginning of
+   * the file and ends at the end of the next line that contains only the
+   * delimiter, is passed through unchanged in markdown output and omitted
+   * from HTML output.
+   *
+   * @example
+   * ```ts
+   * import { markdownToHTML } from "@nick/comrak";
+   * import assert from "node:assert";
+   *
+   * const html = markdownToHTML("---\nlayout: post\n---\nText\n", {
+   *   extension: { frontMatterDelimiter: "---" },
+   * });
+   * assert.equal(html, "<p>Text</p>\n");
+   * ```
+   * @default {null}
+   */
+  frontMatterDelimiter?: string | null;
+
+  /**
+   * Enables the header IDs Comrak extension.
+   *
+   * @example
+   * ```ts
+   * import { markdownToHTML } from "@nick/comrak";
+   * import assert from "node:assert";
+   *
+   * const html = markdownToHTML("# README\n", {
+   *   extension: { headerIDs: "user-content-" },
+   * });
+   * assert.equal(html, "<h1><a href=\"#readme\" aria-hidden=\"true\" class=\"anchor\" id=\"user-content-readme\"></a>README</h1>\n");
+   * ```
+   * @default {null}
+   */
+  headerIDs?: string | null;
+
+  /**
+   * A custom function for generating the slugs used by {@linkcode headerIDs},
+   * replacing Comrak's default anchorization. It receives the heading's text
+   * content and level, and should return the slug to use for the heading.
+   *
+   * The returned slug is combined with the `headerIDs` prefix in the same way
+   * as the default slugs, and duplicate slugs are made unique by appending a
+   * numeric suffix (`-1`, `-2`, etc.). It has no effect unless `headerIDs` is
+   * also set, and is ignored when a custom `headingAdapter` plugin is used.
+   *
+   * @example
+   * ```ts
+   * import { markdownToHTML } from "@nick/comrak";
+   * import assert from "node:assert";
+   *
+   * const html = markdownToHTML("# Ünïcödé\n", {
+   *   extension: {
+   *     headerIDs: "h-",
+   *     slugify: (text) => text.normalize("NFD").replace(/\p{M}/gu, "").toLowerCase(),
+   *   },
+   * });
+   * assert.equal(html, "<h1><a href=\"#unicode\" aria-hidden=\"true\" class=\"anchor\" id=\"h-unicode\"></a>Ünïcödé</h1>\n");
+   * ```
+   * @default {null}
+   */
+  slugify?: SlugifyFunction | null;
+
+  /**
+   * Enables the inline image size syntax popularized by editors such as
+   * Obsidian and Typora, where a `=WxH` suffix in the destination of an image
+   * is rendered as its `width` and `height` attributes. Either dimension can
+   * be omitted, as in `=100x` (width only) or `=x200` (height only).
+   *
+   * Only images with plain text for their alt text are recognized.
+   *
+   * @example
+   * ```ts
+   * import { markdownToHTML } from "@nick/comrak";
+   * import assert from "node:assert";
+   *
+   * const html = markdownToHTML("![logo](logo.png =64x)", {
+   *   extension: { imageSizeSyntax: true },
+   * });
+   * assert.equal(html, '<p><img src="logo.png" alt="logo" width="64" /></p>\n');
+   * ```
+   * @default {false}
+   */
+  imageSizeSyntax?: boolean;
+
+  /**
+   * A custom function for resolving wikilinks, such as mapping page titles to
+   * URLs through a lookup table. It is called with the target page of each
+   * wikilink, which is the part that would otherwise be used as its URL (the
+   * side of the pipe opposite the label, per `wikilinksTitleBeforePipe` and
+   * `wikilinksTitleAfterPipe`). It returns the `url` of the link, along with
+   * an optional `label` to display in place of the original one. Returning
+   * `null` or `undefined` keeps the default URL and label.
+   *
+   * @example
+   * ```ts
+   * import { markdownToHTML } from "@nick/comrak";
+   * import assert from "node:assert";
+   *
+   * const pages: Record<string, string> = { "Getting Started": "intro" };
+   * const html = markdownToHTML("[[Getting Started]] [[Missing]]", {
+   *   extension: {
+   *     wikilinksTitleAfterPipe: true,
+   *     wikilinkResolver: (title) =>
+   *       title in pages ? { url: `/wiki/${pages[title]}` } : null,
+   *   },
+   * });
+   * assert.equal(html, '<p><a href="/wiki/intro" data-wikilink="true">Getting Started</a> <a href="Missing" data-wikilink="true">Missing</a></p>\n');
+   * ```
+   * @default {null}
+   */
+  wikilinkResolver?: WikilinkResolverFunction | null;
+
+  /**
+   * Custom URL schemes to autolink, mapped to the URL template to link them
+   * to. Bare tokens using one of these schemes, such as `ticket:1234`, are
+   * turned into links, with `{rest}` in the template replaced by the part of
+   * the token after the scheme. Schemes are matched case-insensitively.
+   *
+   * A token ends at the next whitespace, not including any trailing
+   * punctuation. Text inside existing links is left alone, so this can be
+   * combined with the {@linkcode autolink} extension.
+   *
+   * @example
+   * ```ts
+   * import { markdownToHTML } from "@nick/comrak";
+   * import assert from "node:assert";
+   *
+   * const html = markdownToHTML("Fixed in ticket:1234.", {
+   *   extension: {
+   *     autolinkSchemes: { ticket: "https://tracker.example/{rest}" },
+   *   },
+   * });
+   * assert.equal(
+   *   html,
+   *   '<p>Fixed in <a href="https://tracker.example/1234">ticket:1234</a>.</p>\n',
+   * );
+   * ```
+   * @default {{}}
+   */
+  autolinkSchemes?: Record<string, string>;
+
+  /**
+   * Turns `@user` mentions and `#tag` hashtags into links, with the URL
+   * templates given for each. A mention must start after whitespace or an
+   * opening bracket or quote, so email addresses and URL fragments are left
+   * alone, and a hashtag needs a letter in its name, so `#1` isn't linked.
+   * Text in code spans and inside existing links is left alone.
+   *
+   * @example
+   * ```ts
+   * import { markdownToHTML } from "@nick/comrak";
+   * import assert from "node:assert";
+   *
+   * const html = markdownToHTML("Thanks @nick! #release", {
+   *   extension: {
+   *     mentions: { userUrl: "/u/{name}", tagUrl: "/t/{name}" },
+   *   },
+   * });
+   * assert.equal(
+   *   html,
+   *   '<p>Thanks <a href="/u/nick">@nick</a>! <a href="/t/release">#release</a></p>\n',
+   * );
+   * ```
+   * @default {null}
+   */
+  mentions?: MentionsOptions | null;
+
+  /**
+   * Enables the [table extension] from the GFM spec.
+   *
+   * [table extension]: https://github.github.com/gfm/#tables-extension-
+   *
+   * @example
+   * ```ts
+   * import { markdownToHTML } from "@nick/comrak";
+   * import assert from "node:assert";
+   *
+   * const html = markdownToHTML("| a | b |\n|---|---|\n| c | d |\n", {
+   *   extension: { table: true },
+   * });
+   * assert.equal(html, "<table>\n<thead>\n<tr>\n<th>a</th>\n<th>b</th>\n</tr>\n</thead>\n<tbody>\n<tr>\n<td>c</td>\n<td>d</td>\n</tr>\n</tbody>\n</table>\n");
+   * ```
+   * @default {false}
+   */
+  table?: boolean;
+
+  /**
+   * Enables the [tagfilter extension] per the GFM spec.
+   *
+   * [tagfilter extension]: https://github.github.com/gfm/#disallowed-raw-html-extension-
+   *
+   * @example
+   * ```ts
+   * import { markdownToHTML } from "@nick/comrak";
+   * import assert from "node:assert";
+   *
+   * const html = markdownToHTML("Hello <xmp>.\n\n<xmp>", {
+   *   extension: { tagfilter: true },
+   *   render: { unsafe: true },
+   * });
+   * assert.equal(html, "<p>Hello &lt;xmp>.</p>\n&lt;xmp>\n");
+   * ```
+   * @default {false}
+   */
+  tagfilter?: boolean;
+
+  /**
+   * Enables the [task list items extension] from the GFM spec.
+   *
+   * Note that the spec does not define the precise output, so only the bare
+   * essentials are rendered.
+   *
+   * [task list items extension]: https://github.github.com/gfm/#task-list-items-extension-
+   *
+   * @example
+   * ```ts
+   * import { markdownToHTML } from "@nick/comrak";
+   * import assert from "node:assert";
+   *
+   * const html = markdownToHTML("* [x] Done\n* [ ] Not done\n", { extension: { tasklist: true } });
+   * assert.equal(html, "<ul>\n<li><input type=\"checkbox\" checked=\"\" disabled=\"\" /> Done</li>\n<li><input type=\"checkbox\" disabled=\"\" /> Not done</li>\n</ul>\n");
+   * ```
+   * @default {false}
+   */
+  tasklist?: boolean;
+
+  /**
+   * Enables the multiline block quote extension.
+   *
+   * Place `>>>` before and after text to make it into a block quote.
+   *
+   * @example
+   * ```ts
+   * import { markdownToHTML } from "@nick/comrak";
+   * import assert from "node:assert";
+   *
+   * const html = markdownToHTML(">>>\nparagraph\n>>>", {
+   *   extension: { multilineBlockQuotes: true },
+   * });
+   * assert.equal(html, "<blockquote>\n<p>paragraph</p>\n</blockquote>\n");
+   * ```
+   * @default {false}
+   */
+  multilineBlockQuotes?: boolean;
+
+  /**
+   * Enables GitHub-style alerts.
+   *
+   * @example
+   * ```ts
+   * import { markdownToHTML } from "@nick/comrak";
+   * import assert from "node:assert";
+   *
+   * const html = markdownToHTML("> [!note]\n> Something of note", {
+   *   extension: { alerts: true },
+   * });
+   * assert.equal(html, "<div class=\"markdown-alert markdown-alert-note\">\n<p class=\"markdown-alert-title\">Note</p>\n<p>Something of note</p>\n</div>\n");
+   * ```
+   *
+   * @default {false}
+   */
+  alerts?: boolean;
+
+  /**
+   * Enables math using dollar syntax.
+   *
+   * @example
+   * ```ts
+   * import { markdownToHTML } from "@nick/comrak";
+   * import assert from "node:assert";
+   *
+   * const html = markdownToHTML("$1 + 2$ and $$x = y$$", {
+   *   extension: { mathDollars: true },
+   * });
+   * assert.equal(html, "<p><span data-math-style=\"inline\">1 + 2</span> and <span data-math-style=\"display\">x = y</span></p>\n");
+   * ```
+   * @default {false}
+   */
+  mathDollars?: boolean;
+
+  /**
+   * Enables math using code syntax.
+   *
+   * @example
+   * ```ts
+   * import { markdownToHTML } from "@nick/comrak";
+   * import assert from "node:assert";
+   *
+   * const html = markdownToHTML("$`1 + 2`$", {
+   *   extension: { mathCode: true },
+   * });
+   * assert.equal(html, "<p><code data-math-style=\"inline\">1 + 2</code></p>\n");
+   * ```
+   * @default {false}
+   */
+  mathCode?: boolean;
+
+  /**
+   * Enables wikilinks extension.
+   *
+   * @example
+   * ```ts
+   * import { markdownToHTML } from "@nick/comrak";
+   * import assert from "node:assert";
+   *
+   * // Title before pipe: [[title|url]]
+   * const html = markdownToHTML("[[link label|url]]", {
+   *   extension: { wikilinksTitleBeforePipe: true },
+   * });
+   * assert.equal(html, "<p><a href=\"url\" data-wikilink=\"true\">link label</a></p>\n");
+   * ```
+   * @default {false}
+   */
+  wikilinksTitleBeforePipe?: boolean;
+
+  /**
+   * Enables wikilinks extension.
+   *
+   * @example
+   * ```ts
+   * import { markdownToHTML } from "@nick/comrak";
+   * import assert from "node:assert";
+   *
+   * // Title after pipe: [[url|title]]
+   * const html = markdownToHTML("[[url|link label]]", {
+   *   extension: { wikilinksTitleAfterPipe: true },
+   * });
+   * assert.equal(html, "<p><a href=\"url\" data-wikilink=\"true\">link label</a></p>\n");
+   * ```
+   * @default {false}
+   */
+  wikilinksTitleAfterPipe?: boolean;
+
+  /**
+   * Enables underlines using double underscores.
+   *
+   * @example
+   * ```ts
+   * import { markdownToHTML } from "@nick/comrak";
+   * import assert from "node:assert";
+   *
+   * const html = markdownToHTML("__underlined text__", {
+   *   extension: { underline: true },
+   * });
+   * assert.equal(html, "<p><u>underlined text</u></p>\n");
+   * ```
+   * @default {false}
+   */
+  underline?: boolean;
+
+  /**
+   * Enables the [strikethrough extension] from the GFM spec.
+   *
+   * [strikethrough extension]: https://github.github.com/gfm/#strikethrough-extension-
+   *
+   * @example
+   * ```ts
+   * import { markdownToHTML } from "@nick/comrak";
+   * import assert from "node:assert";
+   *
+   * const html = markdownToHTML("Hello ~world~ there.\n", {
+   *   extension: { strikethrough: true },
+   * });
+   * assert.equal(html, "<p>Hello <del>world</del> there.</p>\n");
+   * ```
+   * @default {false}
+   */
+  strikethrough?: boolean;
+
+  /**
+   * Enables the superscript Comrak extension.
+   *
+   * @example
+   * ```ts
+   * import { markdownToHTML } from "@nick/comrak";
+   * import assert from "node:assert";
+   *
+   * const html = markdownToHTML("e = mc^2^.\n", { extension: { superscript: true } });
+   * assert.equal(html, "<p>e = mc<sup>2</sup>.</p>\n");
+   * ```
+   * @default {false}
+   */
+  superscript?: boolean;
+
+  /**
+   * Enables subscript text using single tildes.
+   *
+   * @example
+   * ```ts
+   * import { markdownToHTML } from "@nick/comrak";
+   * import assert from "node:assert";
+   *
+   * const html = markdownToHTML("H~2~O", {
+   *   extension: { subscript: true },
+   * });
+   * assert.equal(html, "<p>H<sub>2</sub>O</p>\n");
+   * ```
+   * @default {false}
+   */
+  subscript?: boolean;
+
+  /**
+   * Enables spoilers using double vertical bars.
+   *
+   * @example
+   * ```ts
+   * import { markdownToHTML } from "@nick/comrak";
+   * import assert from "node:assert";
+   *
+   * const html = markdownToHTML("Darth Vader is ||Luke's father||", {
+   *   extension: { spoiler: true },
+   * });
+   * assert.equal(html, "<p>Darth Vader is <span class=\"spoiler\">Luke's father</span></p>\n");
+   * ```
+   * @default {false}
+   */
+  spoiler?: boolean;
+
+  /**
+   * Requires at least one space after a `>` character to generate a blockquote.
+   *
+   * @example
+   * ```ts
+   * import { markdownToHTML } from "@nick/comrak";
+   * import assert from "node:assert";
+   *
+   * const html = markdownToHTML(">implying implications", {
+   *   extension: { greentext: true },
+   * });
+   * assert.equal(html, "<p>&gt;implying implications</p>\n");
+   * ```
+   * @default {false}
+   */
+  greentext?: boolean;
+
+  /**
+   * Phrases wrapped inside of ':' blocks will be replaced with emojis.
+   *
+   * @example
+   * ```ts
+   * import { markdownToHTML } from "@nick/comrak";
+   * import assert from "node:assert";
+   *
+   * const html = markdownToHTML("Happy Friday! :smile:", {
+   *   extension: { shortcodes: true },
+   * });
+   * assert.equal(html, "<p>Happy Friday! 😄</p>\n");
+   * ```
+   * @default {false}
+   */
+  shortcodes?: boolean;
+
+  /**
+   * Wraps image URLs using a custom {@linkcode URLRewriter}.
+   *
+   * It's called once for each image rendered as HTML. A rewritten URL that's
+   * unsafe, such as a `javascript:` URL, is removed unless `render.unsafe` is
+   * set.
+   *
+   * @example
+   * ```ts
+   * import { markdownToHTML, Options } from "@nick/comrak";
+   * import assert from "node:assert";
+   *
+   * const options = Options.default();
+   * options.extension.imageURLRewriter = (url: string) =>
+   *   `https://cdn.example.com/images/${encodeURIComponent(url)}`;
+   *
+   * const html = markdownToHTML("![alt text](image.png)", options);
+   * assert.equal(html, '<p><img src="https://cdn.example.com/images/image.png" alt="alt text" /></p>\n');
+   * ```
+   * @default {null}
+   */
+  imageURLRewriter?: URLRewriter | null;
+
+  /**
+   * Wraps link URLs using a function or custom trait object.
+   *
+   * It's called once for each link rendered as HTML. A rewritten URL that's
+   * unsafe, such as a `javascript:` URL, is removed unless `render.unsafe` is
+   * set.
+   *
+   * @example
+   * ```ts
+   * import { markdownToHTML, Options } from "@nick/comrak";
+   * import assert from "node:assert";
+   *
+   * const options = Options.default();
+   * options.extension.linkURLRewriter = (url: string) =>
+   *   `https://safe.example.com/norefer?url=${encodeURIComponent(url)}`;
+   *
+   * const html = markdownToHTML("[my link](http://unsafe.example.com/bad)", options);
+   * assert.equal(html,
+   *   '<p><a href="https://safe.example.com/norefer?url=http%3A%2F%2Funsafe.example.com%2Fbad">my link</a></p>\n'
+   * );
+   * ```
+   *
+   * @example
+   * ```ts
+   * import { markdownToHTML } from "@nick/comrak";
+   * import assert from "node:assert";
+   *
+   * const html = markdownToHTML("# [Top](/top)\n\n[Down](/down)", {
+   *   extension: {
+   *     linkURLRewriter: (url, context) =>
+   *       context?.parentType === "Heading" ? `#${url.slice(1)}` : url,
+   *   },
+   * });
+   * assert.equal(
+   *   html,
+   *   '<h1><a href="#top">Top</a></h1>\n<p><a href="/down">Down</a></p>\n',
+   * );
+   * ```
+   * @default {null}
+   */
+  linkURLRewriter?: URLRewriter | null;
+
+  /**
+   * Recognizes many emphasis that appear in CJK contexts.
+   *
+   * @example
+   * ```ts
+   * import { markdownToHTML } from "@nick/comrak";
+   * import assert from "node:assert";
+   *
+   * const html = markdownToHTML("**この文は重要です。**但这句话并不重要。", {
+   *   extension: { cjkFriendlyEmphasis: true },
+   * });
+   * assert.equal(html, "<p><strong>この文は重要です。</strong>但这句话并不重要。</p>\n");
+   * ```
+   * @default {false}
+   */
+  cjkFriendlyEmphasis?: boolean;
+
+  /**
+   * Enables block scoped subscript that acts similar to a header.
+   *
+   * ```md
+   * -# subtext
+   * ```
+   *
+   * @example
+   * ```ts
+   * import { markdownToHTML, Options } from "@nick/comrak";
+   * import assert from "node:assert";
+   *
+   * const options = Options.default();
+   * options.extension.subtext = true;
+   *
+   * assert.equal(markdownToHTML("-# subtext", options),
+   *           "<p><sub>subtext</sub></p>\n");
+   * ```
+   * @default {false}
+   */
+  subtext?: boolean;
+
+  /**
+   * Enables highlighting (mark) using `==`.
+   *
+   * ```md
+   * Hey, ==this is important==!
+   * ```
+   * @example
+   * ```ts
+   * import { markdownToHTML, Options } from "@nick/comrak";
+   * import assert from "node:assert";
+   *
+   * const options = Options.default();
+   * options.extension.highlight = true;
+   *
+   * const html = markdownToHTML("Hey, ==this is important==!", options);
+   * assert.equal(html, "<p>Hey, <mark>this is important</mark>!</p>\n");
+   * ```
+   */
+  highlight?: boolean;
+}
+
+/**
+ * Options for parser functions.
+ *
+ * @category Options
+ * @tags parse
+ */
+export interface ParseOptions {
+  /**
+   * The default info string for fenced code blocks that don't have one, such
+   * as a bare ```` ``` ```` fence. It is applied while parsing, so the block
+   * is treated exactly as if it were written with this info string: it gets a
+   * `language-*` class, and its language is passed to the
+   * {@linkcode RenderPlugins.codefenceSyntaxHighlighter | syntax highlighter}
+   * and the {@linkcode RenderOptions.codeBlockRenderer | codeBlockRenderer}.
+   * Indented code blocks are left without a language.
+   *
+   * Since it's applied while parsing, it has no effect on functions that
+   * render an existing AST, such as {@linkcode renderHTML}.
+   *
+   * @example
+   * ````ts
+   * import { markdownToHTML } from "@nick/comrak";
+   * import assert from "node:assert";
+   *
+   * let html = markdownToHTML("```\nfn hello();\n```\n");
+   * assert.equal(html, "<pre><code>fn hello();\n</code></pre>\n");
+   *
+   * html = markdownToHTML("```\nfn hello();\n```\n", {
+   *   parse: { defaultInfoString: "rust" },
+   * });
+   * assert.equal(html, "<pre><code class=\"language-rust\">fn hello();\n</code></pre>\n");
+   * ````
+   * @default {null}
+   */
+  defaultInfoString?: string | null;
+
+  /**
+   * When enabled, converts all quotes, full-stops, hyphens, ellipsis, and
+   * other punctuation into their ‘smart’ equivalents. Also commonly known as
+   * "smartypants" conversion.
+   *
+   * @example
+   * ```ts
+   * import { markdownToHTML } from "@nick/comrak";
+   * import assert from "node:assert";
+   *
+   * let html = markdownToHTML("'Hello,' \"world\" ...");
+   * assert.equal(html, "<p>'Hello,' &quot;world&quot; ...</p>\n");
+   *
+   * html = markdownToHTML("'Hello,' \"world\" ...", {
+   *   parse: { smart: true },
+   * });
+   * assert.equal(html, "<p>‘Hello,’ “world” …</p>\n");
+   * ```
+   *
+   * @default {false}
+   */
+  smart?: boolean;
+
+  /**
+   * Whether or not a simple `x` or `X` is used for tasklist or any other
+   * symbol is allowed.
+   *
+   * @default {false}
+   */
+  relaxedTasklistMatching?: boolean;
+
+  /**
+   * Whether tasklist items can be parsed in table cells. At present, the
+   * tasklist item must be the only content in the cell. Both tables and
+   * tasklists much be enabled for this to work.
+   *
+   * @example
+   * ```ts
+   * import { markdownToHTML, Options } from "@nick/comrak";
+   * import assert from "node:assert";
+   *
+   * const options = Options.default();
+   * options.extension.table = true;
+   * options.extension.tasklist = true;
+   * assert.equal(markdownToHTML("| val |\n| - |\n| [ ] |\n", options),
+   *            "<table>\n<thead>\n<tr>\n<th>val</th>\n</tr>\n</thead>\n<tbody>\n<tr>\n<td>[ ]</td>\n</tr>\n</tbody>\n</table>\n");
+   *
+   * options.parse.tasklistInTable = true;
+   * assert.equal(markdownToHTML("| val |\n| - |\n| [ ] |\n", options),
+   *            "<table>\n<thead>\n<tr>\n<th>val</th>\n</tr>\n</thead>\n<tbody>\n<tr>\n<td>\n<input type=\"checkbox\" disabled=\"\" /> </td>\n</tr>\n</tbody>\n</table>\n");
+   * ```
+   * @default {false}
+   */
+  tasklistInTable?: boolean;
+
+  /**
+   * Relax parsing of autolinks, allow links to be detected inside brackets
+   * and allow all url schemes.
+   *
+   * @example
+   * ```ts
+   * import { markdownToHTML } from "@nick/comrak";
+   * import assert from "node:assert";
+   *
+   * const html = markdownToHTML("[https://foo.com]", {
+   *   extension: { autolink: true },
+   *   parse: { relaxedAutolinks: true },
+   * });
+   * assert.equal(html, "<p>[<a href=\"https://foo.com\">https://foo.com</a>]</p>\n");
+   * ```
+   * @default {false}
+   */
+  relaxedAutolinks?: boolean;
+
+  /**
+   * Ignore setext headings in input.
+   *
+   * @example
+   * ```ts
+   * import { markdownToHTML } from "@nick/comrak";
+   * import assert from "node:assert";
+   *
+   * const html = markdownToHTML("setext heading\n---", {
+   *   parse: { ignoreSetext: true },
+   * });
+   * assert.equal(html, "<p>setext heading</p>\n<hr />\n");
+   * ```
+   * @default {false}
+   */
+  ignoreSetext?: boolean;
+
+  /**
+   * Callback for broken link resolution. When the parser encounters a link or
+   * image reference that does not have a corresponding definition, this option
+   * determines how to handle it.
+   *
+   * You can specify the callback either as a function, or an object that
+   * implements the {@linkcode BrokenLinkCallbackImpl} interface.
+   *
+   * In either case, the callback will be invoked with a
+   * {@linkcode BrokenLinkReference} object containing the normalized and
+   * original URLs of the broken link. The callback should return a
+   * {@linkcode ResolvedReference} object, which contains a `url` and `title`
+   * to replace the broken link with, or `null` or `undefined` to leave the
+   * link unchanged.
+   *
+   * @example
+   * ```ts
+   * import { markdownToHTML, Options } from "@nick/comrak";
+   * import assert from "node:assert";
+   *
+   * const options = Options.default();
+   *
+   * options.parse.brokenLinkCallback = (ref) => {
+   *   if (ref.normalized.includes("badge")) {
+   *     return {
+   *       url: "https://img.shields.io/badge/placeholder-lightgrey.svg",
+   *       title: `Placeholder Badge (original: ${ref.original})`,
+   *     };
+   *   }
+   *   return null;
+   * };
+   *
+   * const html = markdownToHTML("![Build Status][undefined-badge]\n", options);
+   * assert.equal(html, '<p><img src="https://img.shields.io/badge/placeholder-lightgrey.svg" alt="Build Status" title="Placeholder Badge (original: undefined-badge)" /></p>\n');
+   * ```
+   * @default {null}
+   */
+  brokenLinkCallback?: BrokenLinkCallback | null;
+
+  /**
+   * Leave footnote definitions in place in the document tree, rather than
+   * reordering them to the end.  This will also cause unreferenced footnote
+   * definitions to remain in the tree, rather than being removed.
+   *
+   * Comrak's default formatters expect this option to be turned **off**, so
+   * use this with care if you use the default formatters.
+   *
+   * @default {false}
+   */
+  // TODO: add example with AST manipulation
+  leaveFootnoteDefinitions?: boolean;
+
+  /**
+   * Leave escaped characters in an `Escaped` node in the document tree.
+   *
+   * **Note**: enabling the {@linkcode RenderOptions.escapedCharSpans} will
+   * cause this option to be enabled automatically.
+   *
+   * @default {false}
+   */
+  escapedCharSpans?: boolean;
+
+  /**
+   * Treat a backslash (`\`) at the end of a line as a hard line break, as
+   * required by the CommonMark spec. When disabled, the backslash is kept as
+   * literal text and the line ending is treated as a regular soft break.
+   *
+   * Hard breaks made with two or more trailing spaces are not affected.
+   *
+   * @example
+   * ```ts
+   * import { markdownToHTML } from "@nick/comrak";
+   * import assert from "node:assert";
+   *
+   * let html = markdownToHTML("a\\\nb\n");
+   * assert.equal(html, "<p>a<br />\nb</p>\n");
+   *
+   * html = markdownToHTML("a\\\nb\n", {
+   *   parse: { backslashLineBreaks: false },
+   * });
+   * assert.equal(html, "<p>a\\\nb</p>\n");
+   * ```
+   * @default {true}
+   */
+  backslashLineBreaks?: boolean;
+
+  /**
+   * The maximum size of the input, in bytes of UTF-8. Larger documents are
+   * rejected with a `TypeError` before they are parsed.
+   *
+   * This is the only limit on the work done while parsing: Comrak parses in
+   * time roughly linear in the size of the input, but has no hook to stop a
+   * parse that is underway, so {@linkcode ParseOptions.maxNodes} is checked
+   * only once the whole AST has been built.
+   *
+   * @example
+   * ```ts
+   * import { markdownToHTML } from "@nick/comrak";
+   * import assert from "node:assert";
+   *
+   * const options = { parse: { maxInputBytes: 1000 } };
+   * assert.throws(() => markdownToHTML("a".repeat(1001), options), TypeError);
+   * ```
+   * @default {null}
+   */
+  maxInputBytes?: number | null;
+
+  /**
+   * The maximum number of nodes in the parsed AST, counting the document
+   * node itself. Documents exceeding it are rejected with a `TypeError`
+   * before any transformations or rendering take place.
+   *
+   * Since WebAssembly has no threads by default, a wall-clock timeout can't
+   * interrupt a render that is already underway. A node budget is portable
+   * and deterministic instead: the same input is always accepted or always
+   * rejected, regardless of the speed of the host.
+   *
+   * The nodes are counted after Comrak has parsed the whole document, since
+   * it has no hook to count them as they are created, so this bounds the
+   * work done on the AST afterwards, but **not** the work of parsing it. Use
+   * {@linkcode ParseOptions.maxInputBytes} to bound that.
+   *
+   * This is one of several layered defenses for rendering untrusted input:
+   * `maxInputBytes` bounds the work of parsing, `maxNodes` bounds the size of
+   * the tree, and therefore the work done when transforming and rendering
+   * it, and {@linkcode RenderOptions.maxOutputBytes} bounds the size of the
+   * output, which a small tree can still expand into (e.g. through
+   * references). There is no `maxNestingDepth` option, as the version of
+   * Comrak used here has no way to limit how deeply blocks or inlines nest;
+   * a deeply nested document is still bounded by the other limits, since
+   * each level of nesting takes at least one node and one byte of input.
+   *
+   * @example
+   * ```ts
+   * import { markdownToHTML } from "@nick/comrak";
+   * import assert from "node:assert";
+   *
+   * const md = "- a\n".repeat(1000);
+   * assert.throws(
+   *   () => markdownToHTML(md, { parse: { maxNodes: 1000 } }),
+   *   TypeError,
+   * );
+   * ```
+   * @default {null}
+   */
+  maxNodes?: number | null;
+
+  /**
+   * A map of link reference definitions, keyed by their label, which are used
+   * to resolve reference-style links and images as if they were defined in
+   * the document itself. Labels are matched the same way as in Markdown,
+   * ignoring case and collapsing whitespace.
+   *
+   * Definitions in the document take precedence over the map. The
+   * {@linkcode brokenLinkCallback} is still called for labels that are in
+   * neither, so a known set of links can be preloaded without a callback
+   * round-trip for each one.
+   *
+   * @example
+   * ```ts
+   * import { markdownToHTML } from "@nick/comrak";
+   * import assert from "node:assert";
+   *
+   * const html = markdownToHTML("See [the docs] and [Home][home].", {
+   *   parse: {
+   *     referenceMap: {
+   *       "The Docs": { url: "https://docs.example", title: "Docs" },
+   *       home: { url: "/" },
+   *     },
+   *   },
+   * });
+   * assert.equal(html, '<p>See <a href="https://docs.example" title="Docs">the docs</a> and <a href="/">Home</a>.</p>\n');
+   * ```
+   * @default {{}}
+   */
+  referenceMap?: Record<string, ReferenceDefinition>;
+
+  /**
+   * The quotation marks produced by the {@linkcode smart} option, which only
+   * knows English conventions on its own. Has no effect unless `smart` is
+   * enabled.
+   *
+   * - `"en"`: `“double”` and `‘single’` quotes.
+   * - `"fr"`: `« double »` and `‹ single ›` guillemets, with no-break spaces
+   *   inside.
+   * - `"de"`: `„double“` and `‚single‘` low-high quotes.
+   *
+   * Apostrophes are left as `’` in every locale.
+   *
+   * @example
+   * ```ts
+   * import { markdownToHTML } from "@nick/comrak";
+   * import assert from "node:assert";
+   *
+   * const html = markdownToHTML(`Er sagte: "Hallo" und 'tschüss'.`, {
+   *   parse: { smart: true, smartLocale: "de" },
+   * });
+   * assert.equal(html, "<p>Er sagte: „Hallo“ und ‚tschüss‘.</p>\n");
+   * ```
+   * @default {"en"}
+   */
+  smartLocale?: "en" | "fr" | "de";
+
+  /**
+   * How the front matter matched by the {@linkcode
+   * ExtensionOptions.frontMatterDelimiter} option is rendered.
+   *
+   * - `"strip"`: omitted from the output.
+   * - `"render"`: rendered as a description list of its top-level `key:
+   *   value` pairs (or `key = value` pairs in TOML). Nested values and
+   *   comments are skipped, and quotes around values are removed.
+   * - `"codeblock"`: rendered as a fenced code block, with a language of
+   *   `toml` for the `+++` delimiter and `yaml` for any other.
+   *
+   * @example
+   * ```ts
+   * import { markdownToHTML } from "@nick/comrak";
+   * import assert from "node:assert";
+   *
+   * const md = "---\ntitle: Hello\n---\n\n# Hi\n";
+   * const html = markdownToHTML(md, {
+   *   extension: { frontMatterDelimiter: "---" },
+   *   parse: { frontMatterMode: "codeblock" },
+   * });
+   * assert.equal(
+   *   html,
+   *   '<pre><code class="language-yaml">title: Hello\n</code></pre>\n' +
+   *     "<h1>Hi</h1>\n",
+   * );
+   * ```
+   * @default {"strip"}
+   */
+  frontMatterMode?: "strip" | "render" | "codeblock";
+
+  /**
+   * The width of the tab stops used when a tab indents a line, which decides
+   * how far the line is indented, such as whether it starts an indented code
+   * block or continues a list item. CommonMark specifies tab stops every 4
+   * columns, but content written with wider (or narrower) tabs in mind can be
+   * parsed as it was intended by setting this to match.
+   *
+   * Comrak's tab stops are fixed, so other widths are emulated by expanding
+   * the tabs in the indentation of each line (including those inside code
+   * blocks) to spaces before parsing. Source positions still refer to the
+   * original input. Must be at least 1.
+   *
+   * @example
+   * ```ts
+   * import { markdownToHTML } from "@nick/comrak";
+   * import assert from "node:assert";
+   *
+   * const md = "\tnot code\n";
+   * assert.equal(markdownToHTML(md), "<pre><code>not code\n</code></pre>\n");
+   * assert.equal(
+   *   markdownToHTML(md, { parse: { tabWidth: 2 } }),
+   *   "<p>not code</p>\n",
+   * );
+   * ```
+   * @default {4}
+   */
+  tabWidth?: number;
+
+  /**
+   * Report the syntax errors that the parser recovers from silently to the
+   * {@linkcode ParseOptions.onDiagnostic} callback, with their source
+   * position: code fences that are never closed, runs of `*` or `_` that
+   * don't match any other and are kept as text, and inline links whose
+   * destination is never closed. The output is unchanged.
+   *
+   * @example
+   * ```ts
+   * import { type Diagnostic, markdownToHTML } from "@nick/comrak";
+   * import assert from "node:assert";
+   *
+   * const found: Diagnostic[] = [];
+   * markdownToHTML("Some **bold text.\n", {
+   *   parse: { reportRecoveries: true, onDiagnostic: (d) => found.push(d) },
+   * });
+   * assert.deepStrictEqual(found.map((d) => d.message), [
+   *   '"**" is not matched',
+   * ]);
+   * ```
+   * @default {false}
+   */
+  reportRecoveries?: boolean;
+
+  /**
+   * Called with each {@linkcode Diagnostic} reported while parsing, such as
+   * those enabled by the {@linkcode ParseOptions.reportRecoveries} option.
+   * An exception thrown by the callback aborts parsing, and is rethrown.
+   *
+   * @default {null}
+   */
+  onDiagnostic?: ((diagnostic: Diagnostic) => void) | null;
+
+  /**
+   * Characters that are moved from the end of the links created by the
+   * `autolink` extension into the text after them. Comrak already leaves out
+   * trailing punctuation such as `.`, `,`, `:`, and unbalanced closing
+   * parentheses, as GitHub does; this trims more, such as the closing
+   * guillemet of French quotes. Links written between angle brackets are
+   * left as they are.
+   *
+   * @example
+   * ```ts
+   * import { markdownToHTML } from "@nick/comrak";
+   * import assert from "node:assert";
+   *
+   * const html = markdownToHTML("\u00abhttps://example.com\u00bb", {
+   *   extension: { autolink: true },
+   *   parse: { autolinkTrimChars: "\u00bb" },
+   * });
+   * assert.equal(
+   *   html,
+   *   '<p>\u00ab<a href="https://example.com">https://example.com</a>' +
+   *     "\u00bb</p>\n",
+   * );
+   * ```
+   * @default {null}
+   */
+  autolinkTrimChars?: string | null;
+}
+
+/**
+ * Options for the renderer functions.
+ *
+ * @category Options
+ * @tags render
+ */
+export interface RenderOptions {
+  /**
+   * Escape raw HTML instead of clobbering it.
+   *
+   * @example
+   * ```ts
+   * import { markdownToHTML } from "@nick/comrak";
+   * import assert from "node:assert";
+   *
+   * let html = markdownToHTML("<i>italic text</i>");
+   * assert.equal(html, "<p><!-- raw HTML omitted -->italic text<!-- raw HTML omitted --></p>\n");
+   *
+   * html = markdownToHTML("<i>italic text</i>", {
+   *   render: { escape: true },
+   * });
+   * assert.equal(html, "<p>&lt;i&gt;italic text&lt;/i&gt;</p>\n");
+   * ```
+   * @default {false}
+   */
+  escape?: boolean;
+
+  /**
+   * GitHub-style `<pre lang="xyz">` is used for fenced code blocks with info
+   * tags, instead of `<pre><code class="language-xyz">`.
+   *
+   * @example
+   * ````ts
+   * import { markdownToHTML } from "@nick/comrak";
+   * import assert from "node:assert";
+   *
+   * let html = markdownToHTML("```rust\nfn hello();\n```\n");
+   * assert.equal(html, "<pre><code class=\"language-rust\">fn hello();\n</code></pre>\n");
+   *
+   * html = markdownToHTML("```rust\nfn hello();\n```\n", {
+   *   render: { githubPreLang: true },
+   * });
+   * assert.equal(html, "<pre lang=\"rust\"><code>fn hello();\n</code></pre>\n");
+   * ````
+   * @default {false}
+   */
+  githubPreLang?: boolean;
+
+  /**
+   * [Soft line breaks](https://spec.commonmark.org/0.27/#soft-line-breaks) in
+   * the input translate into hard line breaks in the output.
+   *
+   * Overridden by the {@linkcode RenderOptions.lineBreaks} option, if set.
+   *
+   * @example
+   * ```ts
+   * import { markdownToHTML } from "@nick/comrak";
+   * import assert from "node:assert";
+   *
+   * let html = markdownToHTML("Hello.\nWorld.\n");
+   * assert.equal(html, "<p>Hello.\nWorld.</p>\n");
+   *
+   * html = markdownToHTML("Hello.\nWorld.\n", { render: { hardbreaks: true } });
+   * assert.equal(html, "<p>Hello.<br />\nWorld.</p>\n");
+   * ```
+   * @default {false}
+   */
+  hardbreaks?: boolean;
+
+  /**
+   * Allow rendering of raw HTML and potentially dangerous links.
+   *
+   * @example
+   * ```ts
+   * import { markdownToHTML, Options } from "@nick/comrak";
+   * import assert from "node:assert";
+   *
+   * const options = Options.default();
+   *
+   * const dangerousMarkdown = "<script>\nalert('xyz');\n</script>\n\nPossibly <marquee>annoying</marquee>.\n\n[Dangerous](javascript:alert(document.cookie)).\n\n[Safe](http://commonmark.org).\n";
+   *
+   * const safeHTML = markdownToHTML(dangerousMarkdown, options);
+   * assert.equal(safeHTML, "<!-- raw HTML omitted -->\n<p>Possibly <!-- raw HTML omitted -->annoying<!-- raw HTML omitted -->.</p>\n<p><a href=\"\">Dangerous</a>.</p>\n<p><a href=\"http://commonmark.org\">Safe</a>.</p>\n");
+   *
+   * options.render.unsafe = true;
+   * const unsafeHTML = markdownToHTML(dangerousMarkdown, options);
+   * assert.equal(unsafeHTML, "<script>\nalert(\'xyz\');\n</script>\n<p>Possibly <marquee>annoying</marquee>.</p>\n<p><a href=\"javascript:alert(document.cookie)\">Dangerous</a>.</p>\n<p><a href=\"http://commonmark.org\">Safe</a>.</p>\n");
+   * ```
+   * @default {false}
+   */
+  unsafe?: boolean;
+
+  /**
+   * The wrap column when outputting CommonMark.
+   *
+   * @default {0}
+   */
+  width?: number;
+
+  /**
+   * Whether to use the full info string for fenced code blocks.
+   *
+   * @example
+   * ````ts
+   * import { markdownToHTML } from "@nick/comrak";
+   * import assert from "node:assert";
+   *
+   * let html = markdownToHTML("```rust extra info\nfn hello();\n```\n");
+   * assert.equal(html, '<pre><code class="language-rust">fn hello();\n</code></pre>\n');
+   *
+   * html = markdownToHTML("```rust extra info\nfn hello();\n```\n", {
+   *   render: { fullInfoString: true },
+   * });
+   * assert.equal(html, '<pre><code class="language-rust" data-meta="extra info">fn hello();\n</code></pre>\n');
+   * ````
+   *
+   * @default {false}
+   */
+  fullInfoString?: boolean;
+
+  /**
+   * The style for list items.
+   *
+   * @example
+   * ```ts
+   * import { markdownToHTML } from "@nick/comrak";
+   * import assert from "node:assert";
+   *
+   * const html = markdownToHTML("* Item\n* Item\n", { render: { listStyle: "star" } });
+   * assert.equal(html, "<ul>\n<li>Item</li>\n<li>Item</li>\n</ul>\n");
+   * ```
+   * @default {"dash"}
+   */
+  listStyle?: "dash" | "plus" | "star";
+
+  /**
+   * Include source position attributes in HTML and XML output.
+   *
+   * @example
+   * ```ts
+   * import { markdownToHTML } from "@nick/comrak";
+   * import assert from "node:assert";
+   *
+   * const html = markdownToHTML("Hello *world*!", {
+   *   render: { sourcepos: true },
+   * });
+   * assert.equal(html, "<p data-sourcepos=\"1:1-1:14\">Hello <em data-sourcepos=\"1:7-1:13\">world</em>!</p>\n");
+   * ```
+   *
+   * @default {false}
+   */
+  sourcepos?: boolean;
+
+  /**
+   * Wrap escaped characters in a `<span>` to allow any post-processing to recognize them.
+   *
+   * **Note**: enabling this will also enable {@linkcode ParseOptions.escapedCharSpans}.
+   *
+   * @example
+   * ```ts
+   * import { markdownToHTML } from "@nick/comrak";
+   * import assert from "node:assert";
+   *
+   * const html = markdownToHTML("Notify user \\@example", {
+   *   render: { escapedCharSpans: true },
+   * });
+   * assert.equal(html, "<p>Notify user <span data-escaped-char>@</span>example</p>\n");
+   * ```
+   * @default {false}
+   */
+  escapedCharSpans?: boolean;
+
+  /**
+   * Ignore empty links in input.
    *
    * @example
    * ```ts
    * import { markdownToHTML } from "@nick/comrak";
    * import assert from "node:assert";
    *
-   * const html = markdownToHTML("| a | b |\n|---|---|\n| c | d |\n", {
-   *   extension: { table: true },
+   * const html = markdownToHTML("[]()", {
+   *   render: { ignoreEmptyLinks: true },
    * });
-   * assert.equal(html, "<table>\n<thead>\n<tr>\n<th>a</th>\n<th>b</th>\n</tr>\n</thead>\n<tbody>\n<tr>\n<td>c</td>\n<td>d</td>\n</tr>\n</tbody>\n</table>\n");
+   * assert.equal(html, "<p>[]()</p>\n");
    * ```
    * @default {false}
    */
-  table?: boolean;
+  ignoreEmptyLinks?: boolean;
 
   /**
-   * Enables the [tagfilter extension] per the GFM spec.
+   * Enables GFM quirks in HTML output which break CommonMark compatibility.
    *
-   * [tagfilter extension]: https://github.github.com/gfm/#disallowed-raw-html-extension-
+   * Currently this covers a single behavior: directly nested `<strong>`
+   * elements are collapsed into one, as on GitHub. The other parts of GitHub's
+   * rendering are separate options, such as `extension.tagfilter` for filtering
+   * raw HTML tags and `extension.autolink` for bare URL autolinks.
    *
    * @example
    * ```ts
    * import { markdownToHTML } from "@nick/comrak";
    * import assert from "node:assert";
    *
-   * const html = markdownToHTML("Hello <xmp>.\n\n<xmp>", {
-   *   extension: { tagfilter: true },
-   *   render: { unsafe: true },
+   * const html = markdownToHTML("****abcd****", {
+   *   render: { gfmQuirks: true },
    * });
-   * assert.equal(html, "<p>Hello &lt;xmp>.</p>\n&lt;xmp>\n");
+   * assert.equal(html, "<p><strong>abcd</strong></p>\n");
+   * ```
+   * @default {false}
+   */
+  gfmQuirks?: boolean;
+
+  /**
+   * Prefer fenced code blocks when outputting CommonMark.
+   *
+   * @default {false}
+   */
+  preferFenced?: boolean;
+
+  /**
+   * Render the image as a figure element with the title as its caption.
+   *
+   * @example
+   * ```ts
+   * import { markdownToHTML } from "@nick/comrak";
+   * import assert from "node:assert";
+   *
+   * const html = markdownToHTML("![image](https://example.com/image.png \"this is an image\")", { render: { figureWithCaption: true } });
+   * assert.equal(html, "<p><figure><img src=\"https://example.com/image.png\" alt=\"image\" title=\"this is an image\" /><figcaption>this is an image</figcaption></figure></p>\n");
+   * ```
+   * @default {false}
+   */
+  figureWithCaption?: boolean;
+
+  /**
+   * Where the `<figcaption>` of images rendered by
+   * {@linkcode RenderOptions.figureWithCaption} is taken from:
+   *
+   * - `"title"`: the title of the image, as Comrak does.
+   * - `"alt"`: the alt text of the image.
+   * - `"none"`: nowhere, so figures are rendered without a caption.
+   *
+   * Images without the chosen source are rendered without a caption.
+   *
+   * @example
+   * ```ts
+   * import { markdownToHTML } from "@nick/comrak";
+   * import assert from "node:assert";
+   *
+   * const html = markdownToHTML('![A cat](/cat.png "Photo")', {
+   *   render: { figureWithCaption: true, figureCaption: "alt" },
+   * });
+   * assert.equal(
+   *   html,
+   *   '<p><figure><img src="/cat.png" alt="A cat" title="Photo" />' +
+   *     "<figcaption>A cat</figcaption></figure></p>\n",
+   * );
+   * ```
+   * @default {"title"}
+   */
+  figureCaption?: "title" | "alt" | "none";
+
+  /**
+   * Add classes to the output of the tasklist extension.
+   *
+   * @example
+   * ```ts
+   * import { markdownToHTML } from "@nick/comrak";
+   * import assert from "node:assert";
+   *
+   * const html = markdownToHTML("- [ ] Foo", {
+   *   extension: { tasklist: true },
+   *   render: { tasklistClasses: true },
+   * });
+   * assert.equal(html, "<ul class=\"contains-task-list\">\n<li class=\"task-list-item\"><input type=\"checkbox\" class=\"task-list-item-checkbox\" disabled=\"\" /> Foo</li>\n</ul>\n");
+   * ```
+   * @default {false}
+   */
+  tasklistClasses?: boolean;
+
+  /**
+   * Render ordered list with a minimum marker width.
+   *
+   * @default {0}
+   */
+  olWidth?: number;
+
+  /**
+   * Minimise escapes used in CommonMark output.
+   *
+   * @default {false}
+   */
+  experimentalMinimizeCommonmark?: boolean;
+
+  /**
+   * Resolves the intrinsic `width` and `height` of an image from its URL.
+   *
+   * The function is called for each image as it is rendered to HTML, with its
+   * URL after any rewriting by the `imageURLRewriter`. When it returns the
+   * dimensions, they are added to the `<img>` tag as `width` and `height`
+   * attributes, which prevents layout shift as the image loads. Returning
+   * `null` leaves the image untouched. A size given with the image size
+   * syntax takes precedence, and the function isn't called for it.
+   *
+   * This is also used by `markdownToAMP` to populate the dimensions that
+   * are required on every `<amp-img>` element. Images whose dimensions cannot
+   * be resolved are rendered with `layout="fill"` instead.
+   *
+   * @example
+   * ```ts
+   * import { markdownToHTML } from "@nick/comrak";
+   * import assert from "node:assert";
+   *
+   * const html = markdownToHTML("![cat](cat.png) ![dog](dog.png)", {
+   *   render: {
+   *     imageDimensions: (url) =>
+   *       url === "cat.png" ? { width: 640, height: 480 } : null,
+   *   },
+   * });
+   * assert.equal(html, "<p><img src=\"cat.png\" alt=\"cat\" width=\"640\" height=\"480\" /> <img src=\"dog.png\" alt=\"dog\" /></p>\n");
+   * ```
+   * @example
+   * ```ts
+   * import { markdownToAMP } from "@nick/comrak";
+   * import assert from "node:assert";
+   *
+   * const html = markdownToAMP("![cat](cat.png)", {
+   *   render: { imageDimensions: () => ({ width: 640, height: 480 }) },
+   * });
+   * assert.equal(html, "<p><amp-img src=\"cat.png\" alt=\"cat\" width=\"640\" height=\"480\" layout=\"responsive\"></amp-img></p>\n");
+   * ```
+   * @default {null}
+   */
+  imageDimensions?: ImageDimensionsFunction | null;
+
+  /**
+   * The largest `width` attribute rendered for an image, whether its size
+   * comes from the {@linkcode RenderOptions.imageDimensions} function or the
+   * image size syntax. Wider images are scaled down to fit, along with their
+   * `height` to keep their aspect ratio. Images without a size are left as
+   * they are.
+   *
+   * @example
+   * ```ts
+   * import { markdownToHTML } from "@nick/comrak";
+   * import assert from "node:assert";
+   *
+   * const html = markdownToHTML("![cat](cat.png)", {
+   *   render: {
+   *     imageDimensions: () => ({ width: 1600, height: 1200 }),
+   *     maxImageWidth: 800,
+   *   },
+   * });
+   * assert.equal(html, "<p><img src=\"cat.png\" alt=\"cat\" width=\"800\" height=\"600\" /></p>\n");
+   * ```
+   * @default {null}
+   */
+  maxImageWidth?: number | null;
+
+  /**
+   * The largest `height` attribute rendered for an image, which is scaled
+   * down to fit along with its `width`, like
+   * {@linkcode RenderOptions.maxImageWidth}.
+   *
+   * @example
+   * ```ts
+   * import { markdownToHTML } from "@nick/comrak";
+   * import assert from "node:assert";
+   *
+   * const html = markdownToHTML("![cat](cat.png)", {
+   *   render: {
+   *     imageDimensions: () => ({ width: 1600, height: 1200 }),
+   *     maxImageHeight: 300,
+   *   },
+   * });
+   * assert.equal(html, "<p><img src=\"cat.png\" alt=\"cat\" width=\"400\" height=\"300\" /></p>\n");
+   * ```
+   * @default {null}
+   */
+  maxImageHeight?: number | null;
+
+  /**
+   * Add a `data-bullet` attribute to the `<ul>` and `<li>` elements of bullet
+   * lists in HTML output, containing the marker (`-`, `*`, or `+`) used in the
+   * source. This allows stylesheets to vary the bullet style to match.
+   *
+   * @example
+   * ```ts
+   * import { markdownToHTML } from "@nick/comrak";
+   * import assert from "node:assert";
+   *
+   * const html = markdownToHTML("* Foo", {
+   *   render: { preserveBulletStyleInHtml: true },
+   * });
+   * assert.equal(html, "<ul data-bullet=\"*\">\n<li data-bullet=\"*\">Foo</li>\n</ul>\n");
+   * ```
+   * @default {false}
+   */
+  preserveBulletStyleInHtml?: boolean;
+
+  /**
+   * Generate a table of contents in place of a marker paragraph. Before the
+   * document is rendered, every paragraph consisting solely of the `marker`
+   * text is replaced with a nested list of links to the document's headings.
+   * If the marker is not present, the document is left unchanged.
+   *
+   * The links use the same slugs as the {@linkcode ExtensionOptions.headerIDs}
+   * extension (including any custom `slugify` function), prefixed with the
+   * `headerIDs` prefix, so they should be used together.
+   *
+   * @example
+   * ```ts
+   * import { markdownToHTML } from "@nick/comrak";
+   * import assert from "node:assert";
+   *
+   * const html = markdownToHTML("[[TOC]]\n\n# Intro\n\n## Usage\n", {
+   *   extension: { headerIDs: "" },
+   *   render: { tableOfContents: { maxLevel: 1 } },
+   * });
+   * assert.equal(html, "<ul>\n<li><a href=\"#intro\">Intro</a></li>\n</ul>\n" +
+   *   "<h1><a href=\"#intro\" aria-hidden=\"true\" class=\"anchor\" id=\"intro\"></a>Intro</h1>\n" +
+   *   "<h2><a href=\"#usage\" aria-hidden=\"true\" class=\"anchor\" id=\"usage\"></a>Usage</h2>\n");
+   * ```
+   * @default {null}
+   */
+  tableOfContents?: TableOfContentsOptions | null;
+
+  /**
+   * Prevent URLs matching any of these patterns from being autolinked; they
+   * are rendered as plain text instead. This applies to both `<...>` autolinks
+   * and those created by the {@linkcode ExtensionOptions.autolink} extension.
+   *
+   * The patterns are applied when rendering, to the AST alone, where an
+   * autolink is only told apart from other links by its text being its URL.
+   * So an explicit link written the same way, such as
+   * `[https://example.com](https://example.com)`, is also reverted to plain
+   * text when its URL matches, while `[example](https://example.com)` is
+   * always kept.
+   *
+   * Patterns are globs by default, matched against the entire URL, where `*`
+   * matches any sequence of characters and `?` matches a single character. A
+   * pattern wrapped in slashes (e.g. `/^https?:\/\/localhost\b/i`) is used as a
+   * regular expression instead, and may match any part of the URL.
+   *
+   * @example
+   * ```ts
+   * import { markdownToHTML } from "@nick/comrak";
+   * import assert from "node:assert";
+   *
+   * const html = markdownToHTML("See http://localhost:8080 or https://example.com", {
+   *   extension: { autolink: true },
+   *   render: { autolinkExcludePatterns: ["*://localhost*"] },
+   * });
+   * assert.equal(html, "<p>See http://localhost:8080 or <a href=\"https://example.com\">https://example.com</a></p>\n");
+   * ```
+   * @default {[]}
+   */
+  autolinkExcludePatterns?: string[];
+
+  /**
+   * Wrap the entire content of each heading in its anchor link, making the
+   * whole heading clickable, rather than rendering an empty anchor before the
+   * heading content. This only applies when the
+   * {@linkcode ExtensionOptions.headerIDs} extension is enabled, and composes
+   * with its prefix and any custom `slugify` function. It has no effect when
+   * a custom `headingAdapter` plugin is used.
+   *
+   * @example
+   * ```ts
+   * import { markdownToHTML } from "@nick/comrak";
+   * import assert from "node:assert";
+   *
+   * const html = markdownToHTML("# Hello *world*\n", {
+   *   extension: { headerIDs: "" },
+   *   render: { headingAnchorWrapHeading: true },
+   * });
+   * assert.equal(html, "<h1><a href=\"#hello-world\" class=\"anchor\" id=\"hello-world\">Hello <em>world</em></a></h1>\n");
+   * ```
+   * @default {false}
+   */
+  headingAnchorWrapHeading?: boolean;
+
+  /**
+   * The `class` attribute to add to the headings of each level, keyed by the
+   * heading level (1 to 6). Levels not present in the map are rendered
+   * without a class. This composes with the
+   * {@linkcode ExtensionOptions.headerIDs} extension, a custom `slugify`
+   * function, and the other heading options, but has no effect when a custom
+   * `headingAdapter` plugin is used.
+   *
+   * @example
+   * ```ts
+   * import { markdownToHTML } from "@nick/comrak";
+   * import assert from "node:assert";
+   *
+   * const html = markdownToHTML("# Title\n\n## Foo\n", {
+   *   render: { headingClasses: { 2: "heading-lg" } },
+   * });
+   * assert.equal(html, '<h1>Title</h1>\n<h2 class="heading-lg">Foo</h2>\n');
+   * ```
+   * @default {{}}
+   */
+  headingClasses?: Record<number, string>;
+
+  /**
+   * Rewrite heading levels so that they never skip a level, while preserving
+   * the relative structure of the document. For example, an `h1` followed by
+   * an `h3` becomes an `h1` followed by an `h2`. This improves accessibility
+   * for documents whose authors skip heading levels for visual effect.
+   *
+   * Headings without a preceding parent heading keep their original level.
+   * The adjusted levels are also reflected by the
+   * {@linkcode RenderOptions.tableOfContents} option.
+   *
+   * @example
+   * ```ts
+   * import { markdownToHTML } from "@nick/comrak";
+   * import assert from "node:assert";
+   *
+   * const html = markdownToHTML("# Title\n\n### Section\n", {
+   *   render: { fixHeadingLevels: true },
+   * });
+   * assert.equal(html, "<h1>Title</h1>\n<h2>Section</h2>\n");
+   * ```
+   * @default {false}
+   */
+  fixHeadingLevels?: boolean;
+
+  /**
+   * Prepend hierarchical section numbers to the headings of the rendered
+   * HTML, such as `1.`, `1.1.`, and `1.2.`, for technical documents.
+   *
+   * Headings are numbered in document order, starting from `startLevel`:
+   * headings of a higher level than that aren't numbered, but restart the
+   * numbering of the headings below them. A skipped level is numbered `0`, so
+   * an `h3` directly after an `h1` numbered `1` is numbered `1.0.1`. The
+   * `separator` is written between the number and the text of the heading.
+   *
+   * The numbers are written right after the opening tag of each heading, and
+   * after the output of any {@linkcode HeadingAdapter}. They're not part of
+   * the heading's text, so they don't affect its ID.
+   *
+   * @example
+   * ```ts
+   * import { markdownToHTML } from "@nick/comrak";
+   * import assert from "node:assert";
+   *
+   * const html = markdownToHTML("# Intro\n\n## Usage\n\n# API\n", {
+   *   render: { numberHeadings: { enabled: true } },
+   * });
+   * assert.equal(
+   *   html,
+   *   "<h1>1. Intro</h1>\n<h2>1.1. Usage</h2>\n<h1>2. API</h1>\n",
+   * );
+   * ```
+   * @default {null}
+   */
+  numberHeadings?: NumberHeadingsOptions | null;
+
+  /**
+   * Highlight inline code spans that begin with a `lang:` prefix, such as
+   * `` `rust:let x = 5` ``, using the configured
+   * {@linkcode RenderPlugins.codefenceSyntaxHighlighter}. The prefix is
+   * removed from the output, and the `<code>` element is given a
+   * `language-{lang}` class. Without a highlighter, the code is escaped as
+   * usual. Code spans without a valid prefix are rendered unchanged.
+   *
+   * @example
+   * ```ts
+   * import { markdownToHTML } from "@nick/comrak";
+   * import assert from "node:assert";
+   *
+   * const html = markdownToHTML("`rust:let x = 5`", {
+   *   render: { highlightInlineCode: true },
+   * });
+   * assert.equal(
+   *   html,
+   *   '<p><code class="language-rust">let x = 5</code></p>\n',
+   * );
+   * ```
+   * @default {false}
+   */
+  highlightInlineCode?: boolean;
+
+  /**
+   * Only generate heading IDs (see {@linkcode ExtensionOptions.headerIDs})
+   * for headings that are the target of an in-document link, such as
+   * `[Usage](#usage)`. All other headings are rendered without an anchor,
+   * keeping the markup clean. Duplicate headings are still numbered as
+   * usual, so the IDs of linked headings are unaffected by this option.
+   *
+   * @example
+   * ```ts
+   * import { markdownToHTML } from "@nick/comrak";
+   * import assert from "node:assert";
+   *
+   * const html = markdownToHTML("# Intro\n\n## Usage\n\nSee [usage](#usage).", {
+   *   extension: { headerIDs: "" },
+   *   render: { headerIdsOnlyWhenReferenced: true },
+   * });
+   * assert.equal(
+   *   html,
+   *   '<h1>Intro</h1>\n<h2><a href="#usage" aria-hidden="true" class="anchor" id="usage"></a>Usage</h2>\n<p>See <a href="#usage">usage</a>.</p>\n',
+   * );
    * ```
    * @default {false}
    */
-  tagfilter?: boolean;
+  headerIdsOnlyWhenReferenced?: boolean;
 
   /**
-   * Enables the [task list items extension] from the GFM spec.
+   * Escape the template syntax of a frontend framework in text and inline
+   * code, so the rendered HTML can be embedded in one of its templates
+   * without being interpreted. Special characters are replaced with numeric
+   * character references, which render identically in the browser:
    *
-   * Note that the spec does not define the precise output, so only the bare
-   * essentials are rendered.
+   * - `"vue"`: the `{{` and `}}` interpolation delimiters.
+   * - `"angular"`: every `{` and `}` (covering both interpolation and ICU
+   *   expressions), the `[(` and `)]` of two-way bindings, and every `*` (as
+   *   used by structural directives such as `*ngIf`).
    *
-   * [task list items extension]: https://github.github.com/gfm/#task-list-items-extension-
+   * Code blocks and raw HTML are not escaped.
    *
    * @example
    * ```ts
    * import { markdownToHTML } from "@nick/comrak";
    * import assert from "node:assert";
    *
-   * const html = markdownToHTML("* [x] Done\n* [ ] Not done\n", { extension: { tasklist: true } });
-   * assert.equal(html, "<ul>\n<li><input type=\"checkbox\" checked=\"\" disabled=\"\" /> Done</li>\n<li><input type=\"checkbox\" disabled=\"\" /> Not done</li>\n</ul>\n");
+   * const html = markdownToHTML("Hello, {{ name }}!", {
+   *   render: { frameworkEscaping: "vue" },
+   * });
+   * assert.equal(html, "<p>Hello, &#123;&#123; name &#125;&#125;!</p>\n");
    * ```
-   * @default {false}
+   * @default {"none"}
    */
-  tasklist?: boolean;
+  frameworkEscaping?: FrameworkEscaping;
 
   /**
-   * Enables the multiline block quote extension.
+   * Render code blocks in their entirety with a custom function, which is
+   * given the raw code, the language (the first word of the info string), and
+   * the rest of the info string (the "meta"). This allows delegating code
+   * blocks to a JavaScript highlighter such as Shiki, with full control over
+   * the `<pre>` and `<code>` markup.
    *
-   * Place `>>>` before and after text to make it into a block quote.
+   * If the function returns `null` or `undefined`, the code block is rendered
+   * as usual (including by {@linkcode RenderPlugins.codefenceSyntaxHighlighter}).
    *
    * @example
    * ```ts
    * import { markdownToHTML } from "@nick/comrak";
    * import assert from "node:assert";
    *
-   * const html = markdownToHTML(">>>\nparagraph\n>>>", {
-   *   extension: { multilineBlockQuotes: true },
+   * const md = "```ts title=\"main.ts\"\nlet x = 1;\n```\n";
+   * const html = markdownToHTML(md, {
+   *   render: {
+   *     codeBlockRenderer: (code, lang, meta) =>
+   *       `<figure data-lang="${lang}" data-meta='${meta}'>${code}</figure>`,
+   *   },
    * });
-   * assert.equal(html, "<blockquote>\n<p>paragraph</p>\n</blockquote>\n");
+   * assert.equal(
+   *   html,
+   *   `<figure data-lang="ts" data-meta='title="main.ts"'>let x = 1;\n</figure>\n`,
+   * );
    * ```
-   * @default {false}
+   * @default {null}
    */
-  multilineBlockQuotes?: boolean;
+  codeBlockRenderer?: CodeBlockRenderer | null;
 
   /**
-   * Enables GitHub-style alerts.
+   * Render math with a custom function, which is given the LaTeX source of
+   * each math span or block, and whether it is in display mode. This allows
+   * rendering math into MathML (or HTML) on the server, with a converter such
+   * as Temml or KaTeX, rather than leaving it to a client-side library.
+   *
+   * This applies to the math parsed by the `mathDollars` and `mathCode`
+   * extensions, as well as to `math` code blocks, which are always in display
+   * mode. If the function returns `null` or `undefined`, the math is rendered
+   * as usual, with its source wrapped in an element.
    *
    * @example
    * ```ts
    * import { markdownToHTML } from "@nick/comrak";
    * import assert from "node:assert";
    *
-   * const html = markdownToHTML("> [!note]\n> Something of note", {
-   *   extension: { alerts: true },
+   * const html = markdownToHTML("$x^2$ and $$y$$", {
+   *   extension: { mathDollars: true },
+   *   render: {
+   *     mathRenderer: (latex, displayMode) =>
+   *       displayMode ? null : `<math><mi>${latex}</mi></math>`,
+   *   },
    * });
-   * assert.equal(html, "<div class=\"markdown-alert markdown-alert-note\">\n<p class=\"markdown-alert-title\">Note</p>\n<p>Something of note</p>\n</div>\n");
+   * assert.equal(
+   *   html,
+   *   '<p><math><mi>x^2</mi></math> and <span data-math-style="display">y</span></p>\n',
+   * );
    * ```
-   *
-   * @default {false}
+   * @default {null}
    */
-  alerts?: boolean;
+  mathRenderer?: MathRenderer | null;
 
   /**
-   * Enables math using dollar syntax.
+   * Calls a function with the metadata of each heading as it is rendered, so
+   * that a table of contents can be collected in the same pass that renders
+   * the HTML, rather than by parsing the document again. The output isn't
+   * affected by the function.
+   *
+   * The `id` matches the one rendered by the `headerIDs` extension (including
+   * any custom `slugify` function), unless headings are rendered by a custom
+   * {@linkcode HeadingAdapter}.
    *
    * @example
    * ```ts
    * import { markdownToHTML } from "@nick/comrak";
    * import assert from "node:assert";
    *
-   * const html = markdownToHTML("$1 + 2$ and $$x = y$$", {
-   *   extension: { mathDollars: true },
+   * const toc: string[] = [];
+   * markdownToHTML("# Intro\n\n## Usage\n", {
+   *   extension: { headerIDs: "" },
+   *   render: {
+   *     onHeading: ({ level, content }, id) =>
+   *       void toc.push(`${"  ".repeat(level - 1)}- [${content}](#${id})`),
+   *   },
    * });
-   * assert.equal(html, "<p><span data-math-style=\"inline\">1 + 2</span> and <span data-math-style=\"display\">x = y</span></p>\n");
+   * assert.deepStrictEqual(toc, ["- [Intro](#intro)", "  - [Usage](#usage)"]);
    * ```
-   * @default {false}
+   * @default {null}
    */
-  mathDollars?: boolean;
+  onHeading?: HeadingCallback | null;
 
   /**
-   * Enables math using code syntax.
+   * Expand hard tabs in the content of code blocks to the given number of
+   * spaces, respecting tab stops (so a tab advances to the next column that
+   * is a multiple of this value). Tabs outside of code blocks are untouched.
+   * Set to `null` (or `0`) to keep tabs as-is.
    *
    * @example
    * ```ts
    * import { markdownToHTML } from "@nick/comrak";
    * import assert from "node:assert";
    *
-   * const html = markdownToHTML("$`1 + 2`$", {
-   *   extension: { mathCode: true },
+   * const html = markdownToHTML("```\nab\tc\n```\n", {
+   *   render: { codeTabsToSpaces: 4 },
    * });
-   * assert.equal(html, "<p><code data-math-style=\"inline\">1 + 2</code></p>\n");
+   * assert.equal(html, "<pre><code>ab  c\n</code></pre>\n");
    * ```
-   * @default {false}
+   * @default {null}
    */
-  mathCode?: boolean;
+  codeTabsToSpaces?: number | null;
 
   /**
-   * Enables wikilinks extension.
+   * Numbers the lines of code blocks, wrapping each line in a
+   * `<span class="line" data-line="N">` that a stylesheet can show the
+   * number of in a gutter (e.g. with `content: attr(data-line)`). Markup
+   * from the syntax highlighter or `codeBlockRenderer` that spans several
+   * lines is closed and reopened around each line span, so highlighting is
+   * kept. Inline code is unaffected.
    *
    * @example
    * ```ts
    * import { markdownToHTML } from "@nick/comrak";
    * import assert from "node:assert";
    *
-   * // Title before pipe: [[title|url]]
-   * const html = markdownToHTML("[[link label|url]]", {
-   *   extension: { wikilinksTitleBeforePipe: true },
+   * const html = markdownToHTML("```\na\nb\n```\n", {
+   *   render: { codeLineNumbers: { enabled: true } },
    * });
-   * assert.equal(html, "<p><a href=\"url\" data-wikilink=\"true\">link label</a></p>\n");
+   * assert.equal(
+   *   html,
+   *   '<pre><code><span class="line" data-line="1">a</span>\n' +
+   *     '<span class="line" data-line="2">b</span>\n</code></pre>\n',
+   * );
    * ```
-   * @default {false}
+   * @default {null}
    */
-  wikilinksTitleBeforePipe?: boolean;
+  codeLineNumbers?: CodeLineNumbersOptions | null;
 
   /**
-   * Enables wikilinks extension.
+   * Aliases for the languages of fenced code blocks, mapping the language an
+   * author wrote (the first word of the info string) to the one it should be
+   * rendered as. The language is replaced before it reaches the
+   * {@linkcode RenderPlugins.codefenceSyntaxHighlighter} and the
+   * `language-…` class of the output, so highlighters only need to know the
+   * canonical names. Unmapped languages are left unchanged.
    *
    * @example
    * ```ts
    * import { markdownToHTML } from "@nick/comrak";
    * import assert from "node:assert";
    *
-   * // Title after pipe: [[url|title]]
-   * const html = markdownToHTML("[[url|link label]]", {
-   *   extension: { wikilinksTitleAfterPipe: true },
+   * const html = markdownToHTML("```js\nlet x;\n```\n", {
+   *   render: { languageAliases: { js: "javascript", node: "javascript" } },
    * });
-   * assert.equal(html, "<p><a href=\"url\" data-wikilink=\"true\">link label</a></p>\n");
+   * assert.equal(
+   *   html,
+   *   '<pre><code class="language-javascript">let x;\n</code></pre>\n',
+   * );
    * ```
-   * @default {false}
+   * @default {{}}
    */
-  wikilinksTitleAfterPipe?: boolean;
+  languageAliases?: Record<string, string>;
 
   /**
-   * Enables underlines using double underscores.
+   * Sanitize the rendered HTML with an allowlist of tags and attributes, in
+   * the spirit of the [ammonia](https://docs.rs/ammonia) crate. This is meant
+   * to be paired with {@linkcode RenderOptions.unsafe} when rendering
+   * untrusted content, and runs inside of the WebAssembly module so it can't
+   * be skipped by accident.
+   *
+   * - Tags that aren't allowed are removed, but their content is kept.
+   * - Elements whose content is never safe, such as `<script>`, `<style>`,
+   *   `<iframe>`, and `<svg>`, are removed along with their content.
+   * - Attributes that aren't allowed are removed, including all `on*` event
+   *   handlers and `style` attributes with the default allowlist.
+   * - URLs in `href`, `src`, and `cite` attributes must be relative or use
+   *   the `http`, `https`, `mailto`, or `tel` schemes.
+   * - A tag cut off by the end of the output is removed, and any other `<`
+   *   that doesn't begin a tag, comment, or declaration is escaped.
+   *
+   * Set to `true` to use the default allowlist, which covers the common
+   * formatting tags and all of the markup Comrak renders itself, or pass an
+   * object to replace the allowed tags and/or attributes.
    *
    * @example
    * ```ts
    * import { markdownToHTML } from "@nick/comrak";
    * import assert from "node:assert";
    *
-   * const html = markdownToHTML("__underlined text__", {
-   *   extension: { underline: true },
+   * const md = '<p onclick="steal()">Hi<script>steal()</script></p>';
+   * const html = markdownToHTML(md, {
+   *   render: { unsafe: true, sanitizeHtml: true },
    * });
-   * assert.equal(html, "<p><u>underlined text</u></p>\n");
+   * assert.equal(html, "<p>Hi</p>\n");
    * ```
    * @default {false}
    */
-  underline?: boolean;
+  sanitizeHtml?: boolean | SanitizeHtmlOptions | null;
 
   /**
-   * Enables the [strikethrough extension] from the GFM spec.
+   * Customizes the backreference links rendered in footnote definitions,
+   * which link back to the footnote's references in the document. Requires
+   * the `footnotes` extension.
    *
-   * [strikethrough extension]: https://github.github.com/gfm/#strikethrough-extension-
+   * By default, Comrak renders a `↩` link at the end of each definition.
    *
    * @example
    * ```ts
    * import { markdownToHTML } from "@nick/comrak";
    * import assert from "node:assert";
    *
-   * const html = markdownToHTML("Hello ~world~ there.\n", {
-   *   extension: { strikethrough: true },
+   * const html = markdownToHTML("Hi[^x]\n\n[^x]: A greeting.", {
+   *   extension: { footnotes: true },
+   *   render: { footnoteBackref: { symbol: "^", position: "start" } },
    * });
-   * assert.equal(html, "<p>Hello <del>world</del> there.</p>\n");
+   * assert.equal(html, '<p>Hi<sup class="footnote-ref"><a href="#fn-x" id="fnref-x" data-footnote-ref>1</a></sup></p>\n<section class="footnotes" data-footnotes>\n<ol>\n<li id="fn-x">\n<p><a href="#fnref-x" class="footnote-backref" data-footnote-backref data-footnote-backref-idx="1" aria-label="Back to reference 1">^</a> A greeting.</p>\n</li>\n</ol>\n</section>\n');
    * ```
-   * @default {false}
+   * @default {null}
    */
-  strikethrough?: boolean;
+  footnoteBackref?: FootnoteBackrefOptions | null;
 
   /**
-   * Enables the superscript Comrak extension.
+   * The maximum size of the rendered output, in bytes. If rendering would
+   * exceed it, rendering is aborted and a `TypeError` is thrown instead.
+   *
+   * Some Markdown constructs (such as deeply nested emphasis or many uses of
+   * the same reference) can expand to far more output than input, so this is
+   * recommended when rendering untrusted input on a server. The limit is
+   * checked while writing, so memory usage stays bounded. It applies to all
+   * of the output formats.
+   *
+   * See {@linkcode ParseOptions.maxInputBytes} and
+   * {@linkcode ParseOptions.maxNodes} for bounding the size of the input and
+   * of the AST.
    *
    * @example
    * ```ts
    * import { markdownToHTML } from "@nick/comrak";
    * import assert from "node:assert";
    *
-   * const html = markdownToHTML("e = mc^2^.\n", { extension: { superscript: true } });
-   * assert.equal(html, "<p>e = mc<sup>2</sup>.</p>\n");
+   * const md = "[a]".repeat(1000) + "\n\n[a]: https://example.com";
+   * assert.throws(
+   *   () => markdownToHTML(md, { render: { maxOutputBytes: 1024 } }),
+   *   TypeError,
+   * );
    * ```
-   * @default {false}
+   * @default {null}
    */
-  superscript?: boolean;
+  maxOutputBytes?: number | null;
 
   /**
-   * Enables subscript text using single tildes.
+   * Inserts a caption paragraph before each task list, with the number of
+   * its items that are checked out of the total, such as `3/5 done`. Requires
+   * the `tasklist` extension.
+   *
+   * Nested task lists get their own caption, counting only their own items,
+   * unless {@linkcode RenderOptions.taskListProgressIncludeNested} is set.
    *
    * @example
    * ```ts
    * import { markdownToHTML } from "@nick/comrak";
    * import assert from "node:assert";
    *
-   * const html = markdownToHTML("H~2~O", {
-   *   extension: { subscript: true },
+   * const html = markdownToHTML("- [x] Done\n- [ ] Not done\n", {
+   *   extension: { tasklist: true },
+   *   render: { taskListProgress: true },
    * });
-   * assert.equal(html, "<p>H<sub>2</sub>O</p>\n");
+   * assert.equal(html, "<p>1/2 done</p>\n<ul>\n<li><input type=\"checkbox\" checked=\"\" disabled=\"\" /> Done</li>\n<li><input type=\"checkbox\" disabled=\"\" /> Not done</li>\n</ul>\n");
    * ```
    * @default {false}
    */
-  subscript?: boolean;
+  taskListProgress?: boolean;
 
   /**
-   * Enables spoilers using double vertical bars.
+   * When {@linkcode RenderOptions.taskListProgress} is enabled, counts the
+   * items of nested task lists towards the caption of their outermost task
+   * list, instead of giving each nested list a caption of its own.
+   *
+   * @default {false}
+   */
+  taskListProgressIncludeNested?: boolean;
+
+  /**
+   * Whether to render the `start` attribute of ordered lists that don't start
+   * at 1, such as a list beginning with `5.`. When disabled, the attribute is
+   * omitted, so every ordered list starts at 1 in the browser.
+   *
+   * This only affects the HTML output. To renumber the lists themselves, for
+   * every output format, use {@linkcode RenderOptions.forceStartOne}.
    *
    * @example
    * ```ts
    * import { markdownToHTML } from "@nick/comrak";
    * import assert from "node:assert";
    *
-   * const html = markdownToHTML("Darth Vader is ||Luke's father||", {
-   *   extension: { spoiler: true },
+   * const html = markdownToHTML("5. Five\n6. Six\n", {
+   *   render: { respectOrderedListStart: false },
    * });
-   * assert.equal(html, "<p>Darth Vader is <span class=\"spoiler\">Luke's father</span></p>\n");
+   * assert.equal(html, "<ol>\n<li>Five</li>\n<li>Six</li>\n</ol>\n");
    * ```
-   * @default {false}
+   * @default {true}
    */
-  spoiler?: boolean;
+  respectOrderedListStart?: boolean;
 
   /**
-   * Requires at least one space after a `>` character to generate a blockquote.
+   * Renumbers every ordered list in the document to start at 1, before it is
+   * rendered. Unlike {@linkcode RenderOptions.respectOrderedListStart}, this
+   * applies to every output format, including CommonMark.
    *
    * @example
    * ```ts
-   * import { markdownToHTML } from "@nick/comrak";
+   * import { markdownToCommonMark } from "@nick/comrak";
    * import assert from "node:assert";
    *
-   * const html = markdownToHTML(">implying implications", {
-   *   extension: { greentext: true },
+   * const md = markdownToCommonMark("5. Five\n6. Six\n", {
+   *   render: { forceStartOne: true },
    * });
-   * assert.equal(html, "<p>&gt;implying implications</p>\n");
+   * assert.equal(md, "1. Five\n2. Six\n");
    * ```
    * @default {false}
    */
-  greentext?: boolean;
+  forceStartOne?: boolean;
 
   /**
-   * Phrases wrapped inside of ':' blocks will be replaced with emojis.
+   * Renders the checkboxes of task list items with stable `name` attributes,
+   * based on the index of each item within the document, so that their state
+   * can be submitted with a form. Requires the `tasklist` extension.
+   *
+   * Set `interactive` to `true` to omit the `disabled` attribute, allowing
+   * the checkboxes to be toggled by the user.
    *
    * @example
    * ```ts
    * import { markdownToHTML } from "@nick/comrak";
    * import assert from "node:assert";
    *
-   * const html = markdownToHTML("Happy Friday! :smile:", {
-   *   extension: { shortcodes: true },
+   * const html = markdownToHTML("- [x] Done\n- [ ] Not done\n", {
+   *   extension: { tasklist: true },
+   *   render: { taskListInputs: { interactive: true, namePrefix: "todo" } },
    * });
-   * assert.equal(html, "<p>Happy Friday! 😄</p>\n");
+   * assert.equal(html, '<ul>\n<li><input type="checkbox" name="todo-0" checked="" /> Done</li>\n<li><input type="checkbox" name="todo-1" /> Not done</li>\n</ul>\n');
    * ```
-   * @default {false}
+   * @default {null}
    */
-  shortcodes?: boolean;
+  taskListInputs?: TaskListInputsOptions | null;
 
   /**
-   * Wraps image URLs using a custom {@linkcode URLRewriter}.
+   * Customizes the IDs and labels used when rendering footnotes, which are
+   * otherwise fixed by Comrak. Requires the `footnotes` extension.
+   *
+   * The `aria-label` of backreference links can be localized with
+   * `backrefLabel`. When rendering multiple documents on the same page, give
+   * each a distinct `refPrefix` and `defPrefix` to avoid `id` collisions.
    *
    * @example
    * ```ts
-   * import { markdownToHTML, Options } from "@nick/comrak";
+   * import { markdownToHTML } from "@nick/comrak";
    * import assert from "node:assert";
    *
-   * const options = Options.default();
-   * options.extension.imageURLRewriter = (url: string) =>
-   *   `https://cdn.example.com/images/${encodeURIComponent(url)}`;
-   *
-   * const html = markdownToHTML("![alt text](image.png)", options);
-   * assert.equal(html, '<p><img src="https://cdn.example.com/images/image.png" alt="alt text" /></p>\n');
+   * const html = markdownToHTML("Hi[^x]\n\n[^x]: Hallo.", {
+   *   extension: { footnotes: true },
+   *   render: {
+   *     footnotes: {
+   *       backrefLabel: "Zurück zu Verweis",
+   *       refPrefix: "a-ref-",
+   *       defPrefix: "a-fn-",
+   *     },
+   *   },
+   * });
+   * assert.equal(html, '<p>Hi<sup class="footnote-ref"><a href="#a-fn-x" id="a-ref-x" data-footnote-ref>1</a></sup></p>\n<section class="footnotes" data-footnotes>\n<ol>\n<li id="a-fn-x">\n<p>Hallo. <a href="#a-ref-x" class="footnote-backref" data-footnote-backref data-footnote-backref-idx="1" aria-label="Zurück zu Verweis 1">↩</a></p>\n</li>\n</ol>\n</section>\n');
    * ```
    * @default {null}
    */
-  imageURLRewriter?: URLRewriter | null;
+  footnotes?: FootnotesOptions | null;
 
   /**
-   * Wraps link URLs using a function or custom trait object.
+   * Where footnotes are rendered. Requires the `footnotes` extension.
+   *
+   * - `"section"`: in a section at the end of the document, linked to from
+   *   each of their references, as Comrak renders them.
+   * - `"inline"`: in place of each of their references, for print or other
+   *   output where jumping to the end isn't an option. The content goes in a
+   *   `<span class="footnote">`, to be styled as needed, whose `title` holds
+   *   the plain text of the footnote (shown as a tooltip). Footnotes are made
+   *   of blocks, but a `<span>` can only hold inline content, so paragraphs
+   *   are joined with spaces and other blocks are reduced to their text.
    *
    * @example
    * ```ts
-   * import { markdownToHTML, Options } from "@nick/comrak";
+   * import { markdownToHTML } from "@nick/comrak";
    * import assert from "node:assert";
    *
-   * const options = Options.default();
-   * options.extension.linkURLRewriter = (url: string) =>
-   *   `https://safe.example.com/norefer?url=${encodeURIComponent(url)}`;
-   *
-   * const html = markdownToHTML("[my link](http://unsafe.example.com/bad)", options);
-   * assert.equal(html,
-   *   '<p><a href="https://safe.example.com/norefer?url=http%3A%2F%2Funsafe.example.com%2Fbad">my link</a></p>\n'
+   * const html = markdownToHTML("Hi[^x].\n\n[^x]: A *note*.\n", {
+   *   extension: { footnotes: true },
+   *   render: { footnoteStyle: "inline" },
+   * });
+   * assert.equal(
+   *   html,
+   *   '<p>Hi<span class="footnote" title="A note.">A <em>note</em>.</span>.' +
+   *     "</p>\n",
    * );
    * ```
-   * @default {null}
+   * @default {"section"}
    */
-  linkURLRewriter?: URLRewriter | null;
+  footnoteStyle?: "section" | "inline";
 
   /**
-   * Recognizes many emphasis that appear in CJK contexts.
+   * Restricts the `data-sourcepos` attributes added by the `sourcepos` option
+   * to block-level nodes, such as headings, paragraphs, list items, and code
+   * blocks, omitting them from inline nodes like emphasis and links. This is
+   * usually all an editor needs to sync its scroll position with a preview,
+   * and considerably reduces the size of the output for large documents.
+   *
+   * Has no effect unless `sourcepos` is enabled.
    *
    * @example
    * ```ts
    * import { markdownToHTML } from "@nick/comrak";
    * import assert from "node:assert";
    *
-   * const html = markdownToHTML("**この文は重要です。**但这句话并不重要。", {
-   *   extension: { cjkFriendlyEmphasis: true },
+   * const html = markdownToHTML("Hello *world*!", {
+   *   render: { sourcepos: true, sourceposBlocksOnly: true },
    * });
-   * assert.equal(html, "<p><strong>この文は重要です。</strong>但这句话并不重要。</p>\n");
+   * assert.equal(html, '<p data-sourcepos="1:1-1:14">Hello <em>world</em>!</p>\n');
    * ```
    * @default {false}
    */
-  cjkFriendlyEmphasis?: boolean;
+  sourceposBlocksOnly?: boolean;
 
   /**
-   * Enables block scoped subscript that acts similar to a header.
+   * Whether the `data-sourcepos` attributes added by the `sourcepos` option
+   * also map inline nodes, such as emphasis, links, and code spans, to their
+   * position in the source. Live-preview editors need these to map a cursor
+   * or selection within a paragraph, but they roughly double the size of the
+   * output for prose-heavy documents, so set this to `false` (equivalent to
+   * `sourceposBlocksOnly`) when only block positions are needed.
    *
-   * ```md
-   * -# subtext
-   * ```
+   * Inline source positions are always rendered by the pinned version of
+   * Comrak, so this option is enabled by default.
    *
    * @example
    * ```ts
-   * import { markdownToHTML, Options } from "@nick/comrak";
+   * import { markdownToHTML } from "@nick/comrak";
    * import assert from "node:assert";
    *
-   * const options = Options.default();
-   * options.extension.subtext = true;
-   *
-   * assert.equal(markdownToHTML("-# subtext", options),
-   *           "<p><sub>subtext</sub></p>\n");
+   * const html = markdownToHTML("Hello `world`!", {
+   *   render: { sourcepos: true, inlineSourcepos: true },
+   * });
+   * assert.equal(
+   *   html,
+   *   '<p data-sourcepos="1:1-1:14">Hello ' +
+   *     '<code data-sourcepos="1:7-1:13">world</code>!</p>\n',
+   * );
    * ```
-   * @default {false}
+   * @default {true}
    */
-  subtext?: boolean;
+  inlineSourcepos?: boolean;
 
   /**
-   * Enables highlighting (mark) using `==`.
+   * Controls how soft line breaks (ordinary line endings within a paragraph)
+   * are rendered, overriding the `hardbreaks` option when set. Explicit line
+   * breaks, written with a trailing backslash or two trailing spaces, are
+   * always rendered as `<br />`.
+   *
+   * - `"soft"`: soft line breaks are kept as line endings, like `hardbreaks`
+   *   set to `false`.
+   * - `"hard"`: soft line breaks are rendered as `<br />`, like `hardbreaks`
+   *   set to `true`.
+   * - `"explicit"`: soft line breaks are rendered as spaces, so only explicit
+   *   line breaks end a line. This suits wiki-style content, where the source
+   *   is wrapped independently of the rendered text.
    *
-   * ```md
-   * Hey, ==this is important==!
-   * ```
    * @example
    * ```ts
-   * import { markdownToHTML, Options } from "@nick/comrak";
+   * import { markdownToHTML } from "@nick/comrak";
    * import assert from "node:assert";
    *
-   * const options = Options.default();
-   * options.extension.highlight = true;
-   *
-   * const html = markdownToHTML("Hey, ==this is important==!", options);
-   * assert.equal(html, "<p>Hey, <mark>this is important</mark>!</p>\n");
+   * const html = markdownToHTML("Hello\nwrapped\\\nWorld", {
+   *   render: { lineBreaks: "explicit" },
+   * });
+   * assert.equal(html, "<p>Hello wrapped<br />\nWorld</p>\n");
    * ```
+   * @default {null}
    */
-  highlight?: boolean;
-}
+  lineBreaks?: "soft" | "hard" | "explicit" | null;
 
-/**
- * Options for parser functions.
- *
- * @category Options
- * @tags parse
- */
-export interface ParseOptions {
   /**
-   * The default info string for fenced code blocks.
+   * Where soft line breaks (ordinary line endings within a paragraph) are
+   * rendered as `<br />`, for finer control than the `hardbreaks` option,
+   * which this overrides along with {@linkcode RenderOptions.lineBreaks}.
+   * Soft line breaks outside of the scope are kept as line endings.
+   *
+   * - `"all"`: everywhere, like `hardbreaks: true`.
+   * - `"paragraphs"`: in paragraphs, except those inside list items, as in
+   *   chat apps where each line of a message is kept, but list items wrap.
+   * - `"none"`: nowhere, like `hardbreaks: false`.
+   *
+   * When `null`, the `hardbreaks` option applies.
    *
    * @example
-   * ````ts
+   * ```ts
    * import { markdownToHTML } from "@nick/comrak";
    * import assert from "node:assert";
    *
-   * let html = markdownToHTML("```\nfn hello();\n```\n");
-   * assert.equal(html, "<pre><code>fn hello();\n</code></pre>\n");
-   *
-   * html = markdownToHTML("```\nfn hello();\n```\n", {
-   *   parse: { defaultInfoString: "rust" },
+   * const html = markdownToHTML("a\nb\n\n- c\n  d\n", {
+   *   render: { hardbreaksScope: "paragraphs" },
    * });
-   * assert.equal(html, "<pre><code class=\"language-rust\">fn hello();\n</code></pre>\n");
-   * ````
+   * assert.equal(
+   *   html,
+   *   "<p>a<br />\nb</p>\n<ul>\n<li>c\nd</li>\n</ul>\n",
+   * );
+   * ```
    * @default {null}
    */
-  defaultInfoString?: string | null;
+  hardbreaksScope?: "all" | "paragraphs" | "none" | null;
 
   /**
-   * When enabled, converts all quotes, full-stops, hyphens, ellipsis, and
-   * other punctuation into their ‘smart’ equivalents. Also commonly known as
-   * "smartypants" conversion.
+   * Controls how raw HTML in the document is rendered, in place of the
+   * combination of the `escape` and `unsafe` render options and the
+   * `tagfilter` extension, which it overrides when set.
+   *
+   * - `"escape"`: raw HTML is escaped, so that it is displayed as text.
+   * - `"filter"`: raw HTML is rendered as-is, except for the tags disallowed
+   *   by the GFM [tag filter] (such as `<script>` and `<iframe>`), which are
+   *   escaped. Note that this is **not** a sanitizer; see `sanitizeHtml`.
+   * - `"allow"`: raw HTML is rendered as-is, like `unsafe` on its own.
+   *
+   * [tag filter]: https://github.github.com/gfm/#disallowed-raw-html-extension-
    *
    * @example
    * ```ts
    * import { markdownToHTML } from "@nick/comrak";
    * import assert from "node:assert";
    *
-   * let html = markdownToHTML("'Hello,' \"world\" ...");
-   * assert.equal(html, "<p>'Hello,' &quot;world&quot; ...</p>\n");
+   * const md = "<b>bold</b> <script>alert(1)</script>";
+   * const render = (rawHtml: "escape" | "filter" | "allow") =>
+   *   markdownToHTML(md, { render: { rawHtml } });
    *
-   * html = markdownToHTML("'Hello,' \"world\" ...", {
-   *   parse: { smart: true },
-   * });
-   * assert.equal(html, "<p>‘Hello,’ “world” …</p>\n");
+   * assert.equal(render("escape"), "<p>&lt;b&gt;bold&lt;/b&gt; &lt;script&gt;alert(1)&lt;/script&gt;</p>\n");
+   * assert.equal(render("filter"), "<p><b>bold</b> &lt;script>alert(1)&lt;/script></p>\n");
+   * assert.equal(render("allow"), "<p><b>bold</b> <script>alert(1)</script></p>\n");
    * ```
-   *
-   * @default {false}
+   * @default {null}
    */
-  smart?: boolean;
+  rawHtml?: "escape" | "filter" | "allow" | null;
 
   /**
-   * Whether or not a simple `x` or `X` is used for tasklist or any other
-   * symbol is allowed.
+   * The names of the tags whose raw HTML is rendered as-is, without turning
+   * on the {@linkcode unsafe} option for every tag, such as to allow the
+   * `<iframe>` embeds from a trusted source. With {@linkcode unsafe} off, other
+   * raw HTML is escaped; otherwise, it's rendered as it would be without this
+   * option (as-is, or filtered by the `tagfilter` extension).
+   *
+   * An HTML block or inline tag is only let through if all of the tags in it
+   * are listed, which also exempts them from the `tagfilter` extension (and
+   * the {@linkcode rawHtml} option). Tag names are matched case-insensitively.
+   * A tag with an attribute that could run script, such as an event handler
+   * (`onload`), `srcdoc`, or a URL with a scheme other than `http`, `https`,
+   * `mailto`, or `tel` (such as `javascript:`), isn't let through. Other
+   * attributes are kept as they are, so combine this with
+   * {@linkcode sanitizeHtml} if they can't be trusted.
    *
-   * @default {false}
+   * @example
+   * ```ts
+   * import { markdownToHTML } from "@nick/comrak";
+   * import assert from "node:assert";
+   *
+   * const md = '<iframe src="/embed"></iframe>\n\n<b>hi</b>\n';
+   * const html = markdownToHTML(md, {
+   *   render: { rawHtmlAllowTags: ["iframe"] },
+   * });
+   * assert.equal(
+   *   html,
+   *   '<iframe src="/embed"></iframe>\n&lt;b&gt;hi&lt;/b&gt;\n',
+   * );
+   * ```
+   * @default {[]}
    */
-  relaxedTasklistMatching?: boolean;
+  rawHtmlAllowTags?: string[];
 
   /**
-   * Whether tasklist items can be parsed in table cells. At present, the
-   * tasklist item must be the only content in the cell. Both tables and
-   * tasklists much be enabled for this to work.
+   * Removes HTML comments from the output, even when raw HTML is otherwise
+   * rendered as-is (e.g. with `unsafe` enabled). This applies to HTML blocks
+   * and inline HTML that consist solely of comments; comments mixed with
+   * other HTML in the same block are kept.
+   *
+   * Conditional comments (such as `<!--[if IE]>...<![endif]-->`) are kept,
+   * unless {@linkcode RenderOptions.stripConditionalComments} is also set.
    *
    * @example
    * ```ts
-   * import { markdownToHTML, Options } from "@nick/comrak";
+   * import { markdownToHTML } from "@nick/comrak";
    * import assert from "node:assert";
    *
-   * const options = Options.default();
-   * options.extension.table = true;
-   * options.extension.tasklist = true;
-   * assert.equal(markdownToHTML("| val |\n| - |\n| [ ] |\n", options),
-   *            "<table>\n<thead>\n<tr>\n<th>val</th>\n</tr>\n</thead>\n<tbody>\n<tr>\n<td>[ ]</td>\n</tr>\n</tbody>\n</table>\n");
-   *
-   * options.parse.tasklistInTable = true;
-   * assert.equal(markdownToHTML("| val |\n| - |\n| [ ] |\n", options),
-   *            "<table>\n<thead>\n<tr>\n<th>val</th>\n</tr>\n</thead>\n<tbody>\n<tr>\n<td>\n<input type=\"checkbox\" disabled=\"\" /> </td>\n</tr>\n</tbody>\n</table>\n");
+   * const md = "<!-- TODO: rewrite -->\n\nHello <!-- note -->world";
+   * const html = markdownToHTML(md, {
+   *   render: { unsafe: true, stripComments: true },
+   * });
+   * assert.equal(html, "<p>Hello world</p>\n");
    * ```
    * @default {false}
    */
-  tasklistInTable?: boolean;
+  stripComments?: boolean;
 
   /**
-   * Relax parsing of autolinks, allow links to be detected inside brackets
-   * and allow all url schemes.
+   * When {@linkcode RenderOptions.stripComments} is enabled, also removes
+   * conditional comments, rather than keeping them.
+   *
+   * @default {false}
+   */
+  stripConditionalComments?: boolean;
+
+  /**
+   * Adds loading attributes to every rendered `<img>` tag, including images
+   * within links, such as `loading="lazy"` and `decoding="async"`.
    *
    * @example
    * ```ts
    * import { markdownToHTML } from "@nick/comrak";
    * import assert from "node:assert";
    *
-   * const html = markdownToHTML("[https://foo.com]", {
-   *   extension: { autolink: true },
-   *   parse: { relaxedAutolinks: true },
+   * const html = markdownToHTML("![cat](cat.png)", {
+   *   render: { images: { lazy: true, decoding: "async" } },
    * });
-   * assert.equal(html, "<p>[<a href=\"https://foo.com\">https://foo.com</a>]</p>\n");
+   * assert.equal(
+   *   html,
+   *   '<p><img src="cat.png" alt="cat" loading="lazy" decoding="async" /></p>\n',
+   * );
    * ```
-   * @default {false}
+   * @default {null}
    */
-  relaxedAutolinks?: boolean;
+  images?: ImageOptions | null;
 
   /**
-   * Ignore setext headings in input.
+   * Renders the alignment of table cells as a `text-left`, `text-center`, or
+   * `text-right` class on each `<th>` and `<td>` element, in place of the
+   * alignment Comrak renders by default. This suits setups whose Content
+   * Security Policy rejects inline styles, with the classes defined in a
+   * stylesheet instead. Requires the `table` extension.
    *
    * @example
    * ```ts
    * import { markdownToHTML } from "@nick/comrak";
    * import assert from "node:assert";
    *
-   * const html = markdownToHTML("setext heading\n---", {
-   *   parse: { ignoreSetext: true },
+   * const html = markdownToHTML("| a | b |\n|:-:|---|\n| 1 | 2 |\n", {
+   *   extension: { table: true },
+   *   render: { tableAlignClasses: true },
    * });
-   * assert.equal(html, "<p>setext heading</p>\n<hr />\n");
+   * assert.equal(html, "<table>\n<thead>\n<tr>\n<th class=\"text-center\">a</th>\n<th>b</th>\n</tr>\n</thead>\n<tbody>\n<tr>\n<td class=\"text-center\">1</td>\n<td>2</td>\n</tr>\n</tbody>\n</table>\n");
    * ```
    * @default {false}
    */
-  ignoreSetext?: boolean;
+  tableAlignClasses?: boolean;
 
   /**
-   * Callback for broken link resolution. When the parser encounters a link or
-   * image reference that does not have a corresponding definition, this option
-   * determines how to handle it.
-   *
-   * You can specify the callback either as a function, or an object that
-   * implements the {@linkcode BrokenLinkCallbackImpl} interface.
-   *
-   * In either case, the callback will be invoked with a
-   * {@linkcode BrokenLinkReference} object containing the normalized and
-   * original URLs of the broken link. The callback should return a
-   * {@linkcode ResolvedReference} object, which contains a `url` and `title`
-   * to replace the broken link with, or `null` or `undefined` to leave the
-   * link unchanged.
+   * Customizes the `<dl>` elements rendered for the `descriptionLists`
+   * extension, such as by giving them a class to style them with.
    *
    * @example
    * ```ts
-   * import { markdownToHTML, Options } from "@nick/comrak";
+   * import { markdownToHTML } from "@nick/comrak";
    * import assert from "node:assert";
    *
-   * const options = Options.default();
-   *
-   * options.parse.brokenLinkCallback = (ref) => {
-   *   if (ref.normalized.includes("badge")) {
-   *     return {
-   *       url: "https://img.shields.io/badge/placeholder-lightgrey.svg",
-   *       title: `Placeholder Badge (original: ${ref.original})`,
-   *     };
-   *   }
-   *   return null;
-   * };
-   *
-   * const html = markdownToHTML("![Build Status][undefined-badge]\n", options);
-   * assert.equal(html, '<p><img src="https://img.shields.io/badge/placeholder-lightgrey.svg" alt="Build Status" title="Placeholder Badge (original: undefined-badge)" /></p>\n');
+   * const html = markdownToHTML("Term\n\n: Definition", {
+   *   extension: { descriptionLists: true },
+   *   render: { descriptionLists: { className: "glossary" } },
+   * });
+   * assert.equal(html, "<dl class=\"glossary\">\n<dt>Term</dt>\n<dd>\n<p>Definition</p>\n</dd>\n</dl>\n");
    * ```
    * @default {null}
    */
-  brokenLinkCallback?: BrokenLinkCallback | null;
+  descriptionLists?: DescriptionListOptions | null;
 
   /**
-   * Leave footnote definitions in place in the document tree, rather than
-   * reordering them to the end.  This will also cause unreferenced footnote
-   * definitions to remain in the tree, rather than being removed.
+   * Minifies the HTML output by removing insignificant whitespace, such as
+   * the line breaks between block-level tags and the trailing newline.
    *
-   * Comrak's default formatters expect this option to be turned **off**, so
-   * use this with care if you use the default formatters.
+   * Only whitespace between tags is removed, and only next to block-level
+   * elements, since the whitespace between inline elements is rendered as a
+   * space. The content of `<pre>` elements (including code blocks) is always
+   * preserved exactly.
+   *
+   * @example
+   * ```ts
+   * import { markdownToHTML } from "@nick/comrak";
+   * import assert from "node:assert";
    *
+   * const html = markdownToHTML("# Hi\n\n- *a* **b**\n- c\n", {
+   *   render: { minify: true },
+   * });
+   * assert.equal(
+   *   html,
+   *   "<h1>Hi</h1><ul><li><em>a</em> <strong>b</strong></li><li>c</li></ul>",
+   * );
+   * ```
    * @default {false}
    */
-  // TODO: add example with AST manipulation
-  leaveFootnoteDefinitions?: boolean;
+  minify?: boolean;
 
   /**
-   * Leave escaped characters in an `Escaped` node in the document tree.
+   * Pretty-prints the HTML output, putting each block-level element on its own
+   * line and indenting it by two spaces for each level of nesting. Inline
+   * content stays on the line of the block it belongs to, and the content of
+   * `<pre>` elements (including code blocks) is preserved exactly.
    *
-   * **Note**: enabling the {@linkcode RenderOptions.escapedCharSpans} will
-   * cause this option to be enabled automatically.
+   * This cannot be combined with the {@linkcode minify} option; setting both
+   * throws a `TypeError`.
+   *
+   * @example
+   * ```ts
+   * import { markdownToHTML } from "@nick/comrak";
+   * import assert from "node:assert";
    *
+   * const html = markdownToHTML("- a\n- b\n\n> *c*\n", {
+   *   render: { prettyPrint: true },
+   * });
+   * assert.equal(
+   *   html,
+   *   [
+   *     "<ul>",
+   *     "  <li>a</li>",
+   *     "  <li>b</li>",
+   *     "</ul>",
+   *     "<blockquote>",
+   *     "  <p><em>c</em></p>",
+   *     "</blockquote>",
+   *     "",
+   *   ].join("\n"),
+   * );
+   * ```
    * @default {false}
    */
-  escapedCharSpans?: boolean;
-}
+  prettyPrint?: boolean;
 
-/**
- * Options for the renderer functions.
- *
- * @category Options
- * @tags render
- */
-export interface RenderOptions {
   /**
-   * Escape raw HTML instead of clobbering it.
+   * Aligns the columns of tables in CommonMark output, by padding each cell
+   * to the width of the widest cell in its column (and widening the delimiter
+   * row to match). Cells are padded according to the alignment of their
+   * column. This only affects the CommonMark formatter.
    *
    * @example
    * ```ts
-   * import { markdownToHTML } from "@nick/comrak";
+   * import { markdownToCommonMark } from "@nick/comrak";
    * import assert from "node:assert";
    *
-   * let html = markdownToHTML("<i>italic text</i>");
-   * assert.equal(html, "<p><!-- raw HTML omitted -->italic text<!-- raw HTML omitted --></p>\n");
-   *
-   * html = markdownToHTML("<i>italic text</i>", {
-   *   render: { escape: true },
+   * const md = "| a | longer |\n| -: | :- |\n| 100 | b |\n";
+   * const cm = markdownToCommonMark(md, {
+   *   extension: { table: true },
+   *   render: { alignTableColumns: true },
    * });
-   * assert.equal(html, "<p>&lt;i&gt;italic text&lt;/i&gt;</p>\n");
+   * assert.equal(
+   *   cm,
+   *   "|   a | longer |\n| --: | :----- |\n| 100 | b      |\n",
+   * );
    * ```
    * @default {false}
    */
-  escape?: boolean;
+  alignTableColumns?: boolean;
 
   /**
-   * GitHub-style `<pre lang="xyz">` is used for fenced code blocks with info
-   * tags, instead of `<pre><code class="language-xyz">`.
+   * Keeps the soft line breaks of paragraphs in CommonMark output where they
+   * are in the source, instead of reflowing the text to the {@linkcode width}
+   * option. Other syntax is still normalized, which keeps line-based diffs of
+   * formatted Markdown small. This only affects the CommonMark formatter.
    *
    * @example
-   * ````ts
-   * import { markdownToHTML } from "@nick/comrak";
+   * ```ts
+   * import { markdownToCommonMark } from "@nick/comrak";
    * import assert from "node:assert";
    *
-   * let html = markdownToHTML("```rust\nfn hello();\n```\n");
-   * assert.equal(html, "<pre><code class=\"language-rust\">fn hello();\n</code></pre>\n");
-   *
-   * html = markdownToHTML("```rust\nfn hello();\n```\n", {
-   *   render: { githubPreLang: true },
+   * const md = "One *line*\ntwo __lines__ and a few more words\n";
+   * const cm = markdownToCommonMark(md, {
+   *   render: { width: 20, preserveSoftBreaks: true },
    * });
-   * assert.equal(html, "<pre lang=\"rust\"><code>fn hello();\n</code></pre>\n");
-   * ````
+   * assert.equal(cm, "One *line*\ntwo **lines** and a few more words\n");
+   * ```
    * @default {false}
    */
-  githubPreLang?: boolean;
+  preserveSoftBreaks?: boolean;
 
   /**
-   * [Soft line breaks](https://spec.commonmark.org/0.27/#soft-line-breaks) in
-   * the input translate into hard line breaks in the output.
+   * The line endings of CommonMark output. Comrak always writes `"lf"` (`\n`)
+   * line endings, which turns every line of a document authored with `"crlf"`
+   * (`\r\n`) into a change when it's formatted. `"preserve"` matches whichever
+   * of the two ends most lines of the input, for teams that mix both.
+   *
+   * An AST has no input to go by, so `"preserve"` writes `"lf"` line endings
+   * when formatting one with {@linkcode renderCommonMark}. This only affects
+   * the CommonMark formatter.
    *
    * @example
    * ```ts
-   * import { markdownToHTML } from "@nick/comrak";
+   * import { markdownToCommonMark } from "@nick/comrak";
    * import assert from "node:assert";
    *
-   * let html = markdownToHTML("Hello.\nWorld.\n");
-   * assert.equal(html, "<p>Hello.\nWorld.</p>\n");
-   *
-   * html = markdownToHTML("Hello.\nWorld.\n", { render: { hardbreaks: true } });
-   * assert.equal(html, "<p>Hello.<br />\nWorld.</p>\n");
+   * const md = "# Title\r\n\r\nSome *text*\r\n";
+   * const options = { render: { lineEnding: "preserve" as const } };
+   * const cm = markdownToCommonMark(md, options);
+   * assert.equal(cm, "# Title\r\n\r\nSome *text*\r\n");
    * ```
-   * @default {false}
+   * @default {"lf"}
    */
-  hardbreaks?: boolean;
+  lineEnding?: "lf" | "crlf" | "preserve";
 
   /**
-   * Allow rendering of raw HTML and potentially dangerous links.
+   * Renders without the {@linkcode RenderPlugins | render plugins},
+   * as if no syntax highlighter or heading adapter had been given. This is
+   * handy for comparing the output with and without them, without having to
+   * remove each one from the options. The `slugify` extension option still
+   * applies.
    *
    * @example
    * ```ts
-   * import { markdownToHTML, Options } from "@nick/comrak";
+   * import { markdownToHTML } from "@nick/comrak";
    * import assert from "node:assert";
    *
-   * const options = Options.default();
-   *
-   * const dangerousMarkdown = "<script>\nalert('xyz');\n</script>\n\nPossibly <marquee>annoying</marquee>.\n\n[Dangerous](javascript:alert(document.cookie)).\n\n[Safe](http://commonmark.org).\n";
-   *
-   * const safeHTML = markdownToHTML(dangerousMarkdown, options);
-   * assert.equal(safeHTML, "<!-- raw HTML omitted -->\n<p>Possibly <!-- raw HTML omitted -->annoying<!-- raw HTML omitted -->.</p>\n<p><a href=\"\">Dangerous</a>.</p>\n<p><a href=\"http://commonmark.org\">Safe</a>.</p>\n");
-   *
-   * options.render.unsafe = true;
-   * const unsafeHTML = markdownToHTML(dangerousMarkdown, options);
-   * assert.equal(unsafeHTML, "<script>\nalert(\'xyz\');\n</script>\n<p>Possibly <marquee>annoying</marquee>.</p>\n<p><a href=\"javascript:alert(document.cookie)\">Dangerous</a>.</p>\n<p><a href=\"http://commonmark.org\">Safe</a>.</p>\n");
+   * const html = markdownToHTML("# Hi", {
+   *   render: { disablePlugins: true },
+   *   plugins: {
+   *     render: {
+   *       headingAdapter: { enter: () => "<h1 class=x>", exit: () => "</h1>" },
+   *     },
+   *   },
+   * });
+   * assert.equal(html, "<h1>Hi</h1>\n");
    * ```
    * @default {false}
    */
-  unsafe?: boolean;
+  disablePlugins?: boolean;
 
   /**
-   * The wrap column when outputting CommonMark.
+   * Wraps the rendered HTML in a minimal, standalone HTML5 document, with a
+   * `<!DOCTYPE html>`, a `<head>` with a title and optional stylesheet link,
+   * and a `<body>`. By default, the output is an HTML fragment.
    *
-   * @default {0}
+   * @example
+   * ```ts
+   * import { markdownToHTML } from "@nick/comrak";
+   * import assert from "node:assert";
+   *
+   * const html = markdownToHTML("# Hello\n", {
+   *   render: { document: { lang: "en", stylesheet: "style.css" } },
+   * });
+   * assert.equal(
+   *   html,
+   *   '<!DOCTYPE html>\n<html lang="en">\n<head>\n<meta charset="utf-8">\n' +
+   *     '<meta name="viewport" content="width=device-width, ' +
+   *     'initial-scale=1">\n<title>Hello</title>\n' +
+   *     '<link rel="stylesheet" href="style.css">\n</head>\n<body>\n' +
+   *     "<h1>Hello</h1>\n</body>\n</html>\n",
+   * );
+   * ```
+   * @default {null}
    */
-  width?: number;
+  document?: HtmlDocumentOptions | null;
 
   /**
-   * Whether to use the full info string for fenced code blocks.
+   * A scope prepended to the {@linkcode ExtensionOptions.headerIDs} prefix of
+   * every heading ID. Each render numbers duplicate headings on its own, so
+   * two documents rendered for the same page would otherwise both have an
+   * `introduction` heading, for instance. Giving each render a distinct scope
+   * (such as `"post-1-"` and `"post-2-"`) keeps IDs unique across the page.
+   *
+   * The scope is also prepended to the `href` of each heading's own anchor
+   * link, in front of the slug (without the `headerIDs` prefix, as Comrak
+   * writes it), and applies to the links of the table of contents and to the
+   * IDs reported by {@linkcode onHeading}. It has no effect unless the
+   * `headerIDs` extension is enabled.
+   *
+   * Only heading IDs are scoped. The IDs of footnotes are not; give each
+   * render distinct {@linkcode RenderOptions.footnotes} prefixes instead.
    *
    * @example
-   * ````ts
+   * ```ts
    * import { markdownToHTML } from "@nick/comrak";
    * import assert from "node:assert";
    *
-   * let html = markdownToHTML("```rust extra info\nfn hello();\n```\n");
-   * assert.equal(html, '<pre><code class="language-rust">fn hello();\n</code></pre>\n');
-   *
-   * html = markdownToHTML("```rust extra info\nfn hello();\n```\n", {
-   *   render: { fullInfoString: true },
+   * const html = markdownToHTML("# Intro\n", {
+   *   extension: { headerIDs: "" },
+   *   render: { idScope: "post-1-" },
    * });
-   * assert.equal(html, '<pre><code class="language-rust" data-meta="extra info">fn hello();\n</code></pre>\n');
-   * ````
-   *
-   * @default {false}
+   * assert.equal(
+   *   html,
+   *   '<h1><a href="#post-1-intro" aria-hidden="true" class="anchor" ' +
+   *     'id="post-1-intro"></a>Intro</h1>\n',
+   * );
+   * ```
+   * @default {null}
    */
-  fullInfoString?: boolean;
+  idScope?: string | null;
 
   /**
-   * The style for list items.
+   * Customizes the rendering of alerts (enabled by the `alerts` extension),
+   * with localized titles and an optional icon before each title. Alerts with
+   * an explicit title keep it.
    *
    * @example
    * ```ts
    * import { markdownToHTML } from "@nick/comrak";
    * import assert from "node:assert";
    *
-   * const html = markdownToHTML("* Item\n* Item\n", { render: { listStyle: "star" } });
-   * assert.equal(html, "<ul>\n<li>Item</li>\n<li>Item</li>\n</ul>\n");
+   * const html = markdownToHTML("> [!WARNING]\n> Careful!\n", {
+   *   extension: { alerts: true },
+   *   render: { alerts: { titles: { warning: "Achtung" } } },
+   * });
+   * assert.equal(
+   *   html,
+   *   '<div class="markdown-alert markdown-alert-warning">\n' +
+   *     '<p class="markdown-alert-title">Achtung</p>\n' +
+   *     "<p>Careful!</p>\n</div>\n",
+   * );
    * ```
-   * @default {"dash"}
+   * @default {null}
    */
-  listStyle?: "dash" | "plus" | "star";
+  alerts?: AlertOptions | null;
 
   /**
-   * Include source position attributes in HTML and XML output.
+   * The tag that strikethrough text (enabled by the `strikethrough`
+   * extension) is rendered with, such as `<s>` to match existing stylesheets
+   * or to mark text that is no longer accurate rather than deleted.
    *
    * @example
    * ```ts
    * import { markdownToHTML } from "@nick/comrak";
    * import assert from "node:assert";
    *
-   * const html = markdownToHTML("Hello *world*!", {
-   *   render: { sourcepos: true },
+   * const html = markdownToHTML("~~old~~ new", {
+   *   extension: { strikethrough: true },
+   *   render: { strikethroughTag: "s" },
    * });
-   * assert.equal(html, "<p data-sourcepos=\"1:1-1:14\">Hello <em data-sourcepos=\"1:7-1:13\">world</em>!</p>\n");
+   * assert.equal(html, "<p><s>old</s> new</p>\n");
    * ```
-   *
-   * @default {false}
+   * @default {"del"}
    */
-  sourcepos?: boolean;
+  strikethroughTag?: "del" | "s" | "strike";
 
   /**
-   * Wrap escaped characters in a `<span>` to allow any post-processing to recognize them.
+   * Where the `title` attribute of links without a title is taken from, to
+   * give every link a description (e.g. for assistive technologies):
    *
-   * **Note**: enabling this will also enable {@linkcode ParseOptions.escapedCharSpans}.
+   * - `"none"`: nowhere, so they're rendered without one.
+   * - `"text"`: the plain text of the link.
+   * - `"url"`: the URL of the link, as returned by the
+   *   {@linkcode ExtensionOptions.linkURLRewriter | linkURLRewriter}.
+   *
+   * Titles given in the document are never replaced.
    *
    * @example
    * ```ts
    * import { markdownToHTML } from "@nick/comrak";
    * import assert from "node:assert";
    *
-   * const html = markdownToHTML("Notify user \\@example", {
-   *   render: { escapedCharSpans: true },
-   * });
-   * assert.equal(html, "<p>Notify user <span data-escaped-char>@</span>example</p>\n");
+   * const md = '[*Docs*](/docs) and [home](/ "Home page")';
+   * assert.equal(
+   *   markdownToHTML(md, { render: { linkTitleFallback: "text" } }),
+   *   '<p><a href="/docs" title="Docs"><em>Docs</em></a> and ' +
+   *     '<a href="/" title="Home page">home</a></p>\n',
+   * );
    * ```
-   * @default {false}
+   * @default {"none"}
    */
-  escapedCharSpans?: boolean;
+  linkTitleFallback?: "none" | "text" | "url";
 
   /**
-   * Ignore empty links in input.
+   * The tag that superscript text (enabled by the `superscript` extension) is
+   * rendered with, in place of `<sup>`. It must be a valid element name, such
+   * as `span` or `my-sup`.
    *
    * @example
    * ```ts
    * import { markdownToHTML } from "@nick/comrak";
    * import assert from "node:assert";
    *
-   * const html = markdownToHTML("[]()", {
-   *   render: { ignoreEmptyLinks: true },
+   * const html = markdownToHTML("x^2^", {
+   *   extension: { superscript: true },
+   *   render: { supTag: "span", supClass: "sup" },
    * });
-   * assert.equal(html, "<p>[]()</p>\n");
+   * assert.equal(html, '<p>x<span class="sup">2</span></p>\n');
    * ```
-   * @default {false}
+   * @default {null}
    */
-  ignoreEmptyLinks?: boolean;
+  supTag?: string | null;
 
   /**
-   * Enables GFM quirks in HTML output which break CommonMark compatibility.
+   * The `class` attribute of superscript text. Omitted when `null`.
+   *
+   * @default {null}
+   */
+  supClass?: string | null;
+
+  /**
+   * The tag that subscript text (enabled by the `subscript` extension) is
+   * rendered with, in place of `<sub>`. It must be a valid element name, such
+   * as `span` or `my-sub`.
    *
    * @example
    * ```ts
    * import { markdownToHTML } from "@nick/comrak";
    * import assert from "node:assert";
    *
-   * const html = markdownToHTML("****abcd****", {
-   *   render: { gfmQuirks: true },
+   * const html = markdownToHTML("H~2~O", {
+   *   extension: { subscript: true },
+   *   render: { subTag: "span", subClass: "sub" },
    * });
-   * assert.equal(html, "<p><strong>abcd</strong></p>\n");
+   * assert.equal(html, '<p>H<span class="sub">2</span>O</p>\n');
    * ```
-   * @default {false}
+   * @default {null}
    */
-  gfmQuirks?: boolean;
+  subTag?: string | null;
 
   /**
-   * Prefer fenced code blocks when outputting CommonMark.
+   * The `class` attribute of subscript text. Omitted when `null`.
    *
-   * @default {false}
+   * @default {null}
    */
-  preferFenced?: boolean;
+  subClass?: string | null;
 
   /**
-   * Render the image as a figure element with the title as its caption.
+   * Customizes the element that spoilers (enabled by the `spoiler` extension)
+   * are rendered with, which is a `<span class="spoiler">` by default.
    *
    * @example
    * ```ts
    * import { markdownToHTML } from "@nick/comrak";
    * import assert from "node:assert";
    *
-   * const html = markdownToHTML("![image](https://example.com/image.png \"this is an image\")", { render: { figureWithCaption: true } });
-   * assert.equal(html, "<p><figure><img src=\"https://example.com/image.png\" alt=\"image\" title=\"this is an image\" /><figcaption>this is an image</figcaption></figure></p>\n");
+   * const html = markdownToHTML("Hi ||you||.", {
+   *   extension: { spoiler: true },
+   *   render: { spoiler: { tag: "mark", className: "hidden" } },
+   * });
+   * assert.equal(html, '<p>Hi <mark class="hidden">you</mark>.</p>\n');
    * ```
-   * @default {false}
+   * @default {null}
    */
-  figureWithCaption?: boolean;
+  spoiler?: SpoilerOptions | null;
 
   /**
-   * Add classes to the output of the tasklist extension.
+   * Choose each of the typographic substitutions made by the
+   * {@linkcode ParseOptions.smart} option on its own, such as curly quotes
+   * without en and em dashes. When set, this takes the place of the `smart`
+   * option, and every substitution not turned off is made, as with `smart`.
+   *
+   * The substitutions are made when parsing, so this has no effect on an
+   * existing {@linkcode AST}.
    *
    * @example
    * ```ts
    * import { markdownToHTML } from "@nick/comrak";
    * import assert from "node:assert";
    *
-   * const html = markdownToHTML("- [ ] Foo", {
-   *   extension: { tasklist: true },
-   *   render: { tasklistClasses: true },
+   * const html = markdownToHTML('"Wait" -- what...', {
+   *   render: { typography: { dashes: false } },
    * });
-   * assert.equal(html, "<ul class=\"contains-task-list\">\n<li class=\"task-list-item\"><input type=\"checkbox\" class=\"task-list-item-checkbox\" disabled=\"\" /> Foo</li>\n</ul>\n");
+   * assert.equal(html, "<p>\u201cWait\u201d -- what\u2026</p>\n");
    * ```
-   * @default {false}
+   * @default {null}
    */
-  tasklistClasses?: boolean;
+  typography?: TypographyOptions | null;
 
   /**
-   * Render ordered list with a minimum marker width.
+   * Writes the non-ASCII characters of the HTML output (such as smart quotes
+   * and emoji) as character references, for pipelines that only handle
+   * ASCII, like those of some email clients. ASCII characters, and the
+   * content of code blocks and inline code, are left as they are.
    *
-   * @default {0}
+   * - `"utf8"`: characters are written as they are.
+   * - `"named"`: named references such as `&rsquo;` where HTML has one for
+   *   the character, and numeric references otherwise.
+   * - `"numeric"`: numeric references such as `&#8217;`.
+   *
+   * @example
+   * ```ts
+   * import { markdownToHTML } from "@nick/comrak";
+   * import assert from "node:assert";
+   *
+   * const html = markdownToHTML("It's done \u{1F389}", {
+   *   parse: { smart: true },
+   *   render: { encodeEntities: "numeric" },
+   * });
+   * assert.equal(html, "<p>It&#8217;s done &#127881;</p>\n");
+   * ```
+   * @default {"utf8"}
    */
-  olWidth?: number;
+  encodeEntities?: "utf8" | "named" | "numeric";
 
   /**
-   * Minimise escapes used in CommonMark output.
+   * Rewrites or removes each attribute of the HTML output, such as to strip
+   * inline `style` attributes for a strict Content Security Policy, or to
+   * scrub tracking parameters from URLs. The function is called with the tag
+   * name, the attribute name, and its unescaped value (an empty string for an
+   * attribute without one), and returns the new value, or `null` to remove
+   * the attribute. It applies to every element, including raw HTML, and runs
+   * before {@linkcode RenderOptions.sanitizeHtml}.
    *
-   * @default {false}
+   * @example
+   * ```ts
+   * import { markdownToHTML } from "@nick/comrak";
+   * import assert from "node:assert";
+   *
+   * const html = markdownToHTML("[docs](https://a.dev/?utm_source=x)", {
+   *   render: {
+   *     attributeFilter: (_tag, name, value) =>
+   *       name === "href" ? value.replace(/\?utm_[^#]*/, "") : value,
+   *   },
+   * });
+   * assert.equal(html, '<p><a href="https://a.dev/">docs</a></p>\n');
+   * ```
+   * @default {null}
    */
-  experimentalMinimizeCommonmark?: boolean;
+  attributeFilter?:
+    | ((tag: string, name: string, value: string) => string | null)
+    | null;
 }
 
 /**
@@ -1155,6 +3609,11 @@ export const defaultOptions: DefaultOptions = {
     inlineFootnotes: false,
     frontMatterDelimiter: null,
     headerIDs: null,
+    slugify: null,
+    imageSizeSyntax: false,
+    wikilinkResolver: null,
+    autolinkSchemes: {},
+    mentions: null,
     strikethrough: false,
     superscript: false,
     table: false,
@@ -1187,6 +3646,16 @@ export const defaultOptions: DefaultOptions = {
     brokenLinkCallback: null,
     leaveFootnoteDefinitions: false,
     escapedCharSpans: false,
+    backslashLineBreaks: true,
+    maxInputBytes: null,
+    maxNodes: null,
+    referenceMap: {},
+    smartLocale: "en",
+    frontMatterMode: "strip",
+    tabWidth: 4,
+    reportRecoveries: false,
+    onDiagnostic: null,
+    autolinkTrimChars: null,
   },
   render: {
     escape: false,
@@ -1202,9 +3671,69 @@ export const defaultOptions: DefaultOptions = {
     gfmQuirks: false,
     preferFenced: false,
     figureWithCaption: false,
+    figureCaption: "title",
     tasklistClasses: false,
     olWidth: 0,
     experimentalMinimizeCommonmark: false,
+    imageDimensions: null,
+    maxImageWidth: null,
+    maxImageHeight: null,
+    preserveBulletStyleInHtml: false,
+    tableOfContents: null,
+    autolinkExcludePatterns: [],
+    headingAnchorWrapHeading: false,
+    headingClasses: {},
+    fixHeadingLevels: false,
+    numberHeadings: null,
+    highlightInlineCode: false,
+    headerIdsOnlyWhenReferenced: false,
+    frameworkEscaping: "none",
+    codeBlockRenderer: null,
+    mathRenderer: null,
+    onHeading: null,
+    codeTabsToSpaces: null,
+    codeLineNumbers: null,
+    languageAliases: {},
+    sanitizeHtml: false,
+    footnoteBackref: null,
+    maxOutputBytes: null,
+    taskListProgress: false,
+    taskListProgressIncludeNested: false,
+    respectOrderedListStart: true,
+    forceStartOne: false,
+    taskListInputs: null,
+    footnotes: null,
+    footnoteStyle: "section",
+    sourceposBlocksOnly: false,
+    inlineSourcepos: true,
+    lineBreaks: null,
+    hardbreaksScope: null,
+    rawHtml: null,
+    rawHtmlAllowTags: [],
+    stripComments: false,
+    stripConditionalComments: false,
+    images: null,
+    tableAlignClasses: false,
+    descriptionLists: null,
+    minify: false,
+    prettyPrint: false,
+    alignTableColumns: false,
+    preserveSoftBreaks: false,
+    lineEnding: "lf",
+    disablePlugins: false,
+    document: null,
+    idScope: null,
+    alerts: null,
+    strikethroughTag: "del",
+    linkTitleFallback: "none",
+    supTag: null,
+    supClass: null,
+    subTag: null,
+    subClass: null,
+    spoiler: null,
+    typography: null,
+    encodeEntities: "utf8",
+    attributeFilter: null,
   },
   plugins: {
     render: {
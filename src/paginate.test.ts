@@ -0,0 +1,23 @@
+import { describe, it, type TestContext } from "node:test";
+
+import { paginate } from "./paginate.ts";
+
+describe("paginate", () => {
+  it("should split pages at the configured block count", (t: TestContext) => {
+    const md = "# Title\n\nOne\n\n- a\n- b\n\nTwo\n\n> Three\n";
+    const pages = paginate(md, {}, { maxBlocksPerPage: 3 });
+    t.assert.deepStrictEqual(pages, [
+      '<div class="page">\n<h1>Title</h1>\n<p>One</p>\n' +
+        "<ul>\n<li>a</li>\n<li>b</li>\n</ul>\n</div>\n",
+      '<div class="page">\n<p>Two</p>\n' +
+        "<blockquote>\n<p>Three</p>\n</blockquote>\n</div>\n",
+    ]);
+  });
+
+  it("should reject a maxBlocksPerPage of zero", (t: TestContext) => {
+    t.assert.throws(
+      () => paginate("Hi", {}, { maxBlocksPerPage: 0 }),
+      TypeError,
+    );
+  });
+});
@@ -0,0 +1,67 @@
+/**
+ * This module provides the {@linkcode paginate} function, which renders a
+ * Markdown document into HTML split across multiple pages, for use in print
+ * layouts and e-readers.
+ *
+ * @module paginate
+ */
+import wasm from "./_wasm.ts";
+import { collectOptions } from "./_internal.ts";
+import type { Options } from "./options.ts";
+
+/**
+ * Options for splitting a document into pages with {@linkcode paginate}.
+ *
+ * @category Options
+ * @tags paginate
+ */
+export interface PaginateOptions {
+  /**
+   * The maximum number of top-level blocks (paragraphs, headings, lists,
+   * code blocks, etc.) on a single page. Must be greater than `0`.
+   *
+   * @default {10}
+   */
+  maxBlocksPerPage?: number;
+}
+
+/**
+ * Render Markdown to HTML, split into pages at block boundaries.
+ *
+ * Each page holds at most `maxBlocksPerPage` top-level blocks, and a block is
+ * never split across two pages. Every page is wrapped in a
+ * `<div class="page">` element, and is rendered with the same options as
+ * {@linkcode markdownToHTML}. Front matter does not count towards a page.
+ *
+ * This uses a simple block count as a heuristic for the height of a page,
+ * rather than estimating the rendered height of each block.
+ *
+ * @param markdown The Markdown string to be converted.
+ * @param [options] Options to customize the conversion.
+ * @param [pagination] Options to customize the pagination.
+ * @returns An array of the generated HTML pages, which is empty if the
+ * document has no blocks.
+ * @example
+ * ```ts
+ * import { paginate } from "@nick/comrak";
+ * import assert from "node:assert";
+ *
+ * const pages = paginate("# Title\n\nOne\n\nTwo\n", {}, {
+ *   maxBlocksPerPage: 2,
+ * });
+ * assert.deepStrictEqual(pages, [
+ *   '<div class="page">\n<h1>Title</h1>\n<p>One</p>\n</div>\n',
+ *   '<div class="page">\n<p>Two</p>\n</div>\n',
+ * ]);
+ * ```
+ * @category Conversion
+ * @tags paginate
+ */
+export function paginate(
+  markdown: string,
+  options?: Options,
+  pagination?: PaginateOptions,
+): string[] {
+  const args = collectOptions(options);
+  return wasm.paginate(markdown, ...args, pagination);
+}
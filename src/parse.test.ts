@@ -0,0 +1,319 @@
+import { describe, it, type TestContext } from "node:test";
+
+import { markdownToCommonMark, renderCommonMark } from "./cm.ts";
+import { markdownToHTML, renderHTML } from "./html.ts";
+import {
+  anchorTargets,
+  astFromJSON,
+  astToJSON,
+  type Diagnostic,
+  diagnostics,
+  extractCodeBlocks,
+  mapNodes,
+  parseMarkdown,
+  reparseRange,
+  resolvedReferences,
+  sourceposToOffsets,
+} from "./parse.ts";
+
+describe("anchorTargets", () => {
+  it("should return each in-document link fragment", (t: TestContext) => {
+    const md = "[One](#one), [two][2], [one](#one), and [x](https://x.dev/#x)" +
+      "\n\n[2]: #two\n";
+    t.assert.deepStrictEqual(anchorTargets(md), ["one", "two"]);
+  });
+
+  it("should return an empty array without anchor links", (t: TestContext) => {
+    t.assert.deepStrictEqual(anchorTargets("# Hello\n\n[x](/x)\n"), []);
+  });
+});
+
+describe("astToJSON", () => {
+  const md = "# Title\n\n| a | b |\n|---|---|\n| 1 | 2 |\n\n" +
+    "- [x] done[^1]\n\n[^1]: Note.\n";
+  const options = {
+    extension: { table: true, tasklist: true, footnotes: true },
+  };
+
+  it("should round-trip to identical HTML", (t: TestContext) => {
+    const ast = parseMarkdown(md, options);
+    const json = astToJSON(ast);
+    t.assert.strictEqual(JSON.parse(json).schemaVersion, 2);
+    t.assert.strictEqual(
+      renderHTML(astFromJSON(json), options),
+      markdownToHTML(md, options),
+    );
+  });
+
+  it("should write the schema's own node values", (t: TestContext) => {
+    const { root } = JSON.parse(astToJSON(parseMarkdown("# Hi\n")));
+    t.assert.deepStrictEqual(root.value, { type: "Document" });
+    const [heading] = root.children;
+    t.assert.deepStrictEqual(heading.sourcepos, [1, 1, 1, 4]);
+    t.assert.deepStrictEqual(heading.value, {
+      type: "Heading",
+      level: 1,
+      setext: false,
+    });
+    t.assert.deepStrictEqual(heading.children[0].value, {
+      type: "Text",
+      literal: "Hi",
+    });
+  });
+
+  it("should migrate version 1 of the schema", (t: TestContext) => {
+    const ast = parseMarkdown(md, options);
+    // version 1 held the values and positions of the AST's own nodes
+    const node = (i: number): unknown => {
+      const { data, first_child } = ast.nodes[i];
+      const children = [];
+      let child = first_child;
+      while (child !== undefined) {
+        children.push(node(child));
+        child = ast.nodes[child].next_sibling;
+      }
+      return { value: data.value, sourcepos: data.sourcepos, children };
+    };
+    const json = JSON.stringify({ schemaVersion: 1, root: node(0) });
+    t.assert.strictEqual(
+      renderHTML(astFromJSON(json), options),
+      renderHTML(ast, options),
+    );
+  });
+
+  it("should migrate an AST persisted as-is", (t: TestContext) => {
+    const ast = parseMarkdown(md, options);
+    t.assert.strictEqual(
+      renderHTML(astFromJSON(JSON.stringify(ast)), options),
+      renderHTML(ast, options),
+    );
+  });
+
+  it("should reject newer schema versions", (t: TestContext) => {
+    const json = JSON.stringify({ schemaVersion: 99, root: {} });
+    t.assert.throws(() => astFromJSON(json), TypeError);
+  });
+});
+
+describe("reparseRange", () => {
+  const options = { render: { sourcepos: true } };
+  const edit = (md: string, start: number, end: number, text: string) => {
+    const ast = parseMarkdown(md, options);
+    const edited = md.slice(0, start) + text + md.slice(end);
+    return [
+      renderHTML(reparseRange(md, ast, start, end, text, options), options),
+      markdownToHTML(edited, options),
+    ];
+  };
+
+  it("should match a full parse after an edit", (t: TestContext) => {
+    const md = "# A\n\nOne\n\nTwo\n\nThree\n\n- x\n- y\n";
+    const [actual, expected] = edit(md, 10, 13, "2\nlines\n\nand more");
+    t.assert.strictEqual(actual, expected);
+  });
+
+  it("should merge a block into its neighbour", (t: TestContext) => {
+    const md = "Intro\n\n- a\n- b\n\nText\n\nEnd\n";
+    const [actual, expected] = edit(md, 16, 20, "- c");
+    t.assert.strictEqual(actual, expected);
+  });
+
+  it("should handle edits that open a code fence", (t: TestContext) => {
+    const md = "One\n\nTwo\n\nThree\n\nFour\n";
+    const [actual, expected] = edit(md, 5, 8, "```");
+    t.assert.strictEqual(actual, expected);
+  });
+
+  it("should not read front matter mid-document", (t: TestContext) => {
+    const md = "Intro\n\nOne\n\n---\ntitle: x\n---\n\nEnd\n";
+    const opts = { ...options, extension: { frontMatterDelimiter: "---" } };
+    const ast = parseMarkdown(md, opts);
+    const reparsed = reparseRange(md, ast, 23, 24, "y", opts);
+    t.assert.strictEqual(
+      renderHTML(reparsed, opts),
+      markdownToHTML(md.replace("x", "y"), opts),
+    );
+  });
+
+  it("should reject ranges that split a character", (t: TestContext) => {
+    const ast = parseMarkdown("é");
+    t.assert.throws(() => reparseRange("é", ast, 1, 1, "x"), TypeError);
+  });
+});
+
+describe("diagnostics", () => {
+  const lint = (md: string) =>
+    diagnostics(md, { extension: { table: true } }).map((d) =>
+      [d.kind, d.sourcepos.start.line] as const
+    );
+
+  it("should report table rows with the wrong cell count", (t: TestContext) => {
+    const md = "| a | b |\n| - | - |\n| 1 |\n| 1 \\| 2 | 3 |\n1 | 2 | 3\n";
+    t.assert.deepStrictEqual(lint(md), [
+      ["table-columns", 3],
+      ["table-columns", 5],
+    ]);
+  });
+
+  it("should report unclosed code fences", (t: TestContext) => {
+    t.assert.deepStrictEqual(lint("```js\nok\n```\n"), []);
+    t.assert.deepStrictEqual(lint("> ````\n> x\n> ```\n\nafter\n"), [
+      ["unclosed-fence", 1],
+    ]);
+  });
+
+  it("should report each undefined label once", (t: TestContext) => {
+    const md = "[a][x], [x][], [y], and [b][z]\n\n[z]: /z\n\n[c][x]\n";
+    const found = diagnostics(md);
+    t.assert.deepStrictEqual(found.map((d) => d.message), [
+      'reference "x" is not defined',
+    ]);
+    t.assert.deepStrictEqual(found[0].sourcepos.start, { line: 1, column: 1 });
+  });
+
+  it("should report recoveries while parsing", (t: TestContext) => {
+    const found: Diagnostic[] = [];
+    const md = "a **b, 2 * 3, snake_case, \\*x\n\n[c](/d e\n\n```\ncode\n";
+    parseMarkdown(md, {
+      parse: { reportRecoveries: true, onDiagnostic: (d) => found.push(d) },
+    });
+    t.assert.deepStrictEqual(found.map((d) => [d.kind, d.sourcepos.start]), [
+      ["unmatched-emphasis", { line: 1, column: 3 }],
+      ["unclosed-link", { line: 3, column: 3 }],
+      ["unclosed-fence", { line: 5, column: 1 }],
+    ]);
+  });
+
+  it("should skip references resolved by the callback", (t: TestContext) => {
+    const brokenLinkCallback = () => ({ url: "/", title: "" });
+    const options = { parse: { brokenLinkCallback } };
+    t.assert.deepStrictEqual(diagnostics("[a][x]\n", options), []);
+  });
+});
+
+describe("parseMarkdown", () => {
+  it("should round-trip GFM documents", (t: TestContext) => {
+    const md = "# Tasks\n\n" +
+      "| a | b |\n| :- | -: |\n| `x` | ~~y~~ |\n\n" +
+      "- [x] done[^1]\n- [ ] todo\n\n" +
+      "See https://example.com[^note].\n\n" +
+      "[^1]: First.\n[^note]: Second, with **bold**.\n";
+    const options = {
+      extension: {
+        autolink: true,
+        footnotes: true,
+        strikethrough: true,
+        table: true,
+        tasklist: true,
+      },
+    };
+    const ast = parseMarkdown(md, options);
+    t.assert.strictEqual(
+      renderHTML(ast, options),
+      markdownToHTML(md, options),
+    );
+    t.assert.strictEqual(
+      renderCommonMark(ast, options),
+      markdownToCommonMark(md, options),
+    );
+  });
+});
+
+describe("extractCodeBlocks", () => {
+  it("should return fenced and indented code blocks", (t: TestContext) => {
+    const md = "# Doc\n\n~~~rust  no_run\nfn main() {}\n~~~\n\n" +
+      "- ```\n  nested\n  ```\n\n    indented\n";
+    const blocks = extractCodeBlocks(md);
+    t.assert.deepStrictEqual(
+      blocks.map(({ language, meta, code }) => ({ language, meta, code })),
+      [
+        { language: "rust", meta: "no_run", code: "fn main() {}\n" },
+        { language: null, meta: null, code: "nested\n" },
+        { language: null, meta: null, code: "indented\n" },
+      ],
+    );
+    t.assert.deepStrictEqual(blocks[0].sourcepos, {
+      start: { line: 3, column: 1 },
+      end: { line: 5, column: 3 },
+    });
+    t.assert.strictEqual(blocks[1].sourcepos.start.line, 7);
+  });
+
+  it("should return an empty array without code blocks", (t: TestContext) => {
+    t.assert.deepStrictEqual(extractCodeBlocks("Just `inline` code."), []);
+  });
+});
+
+describe("mapNodes", () => {
+  it("should delete, replace, and keep nodes", (t: TestContext) => {
+    const ast = parseMarkdown("Hi *secret* [x](/a) and [y](/b)");
+    const result = mapNodes(ast, (value) => {
+      if (value === "Emph") return null;
+      if (typeof value !== "object") return;
+      if ("Text" in value) return { Text: value.Text.toUpperCase() };
+      if ("Link" in value && value.Link.url === "/b") {
+        return { Link: { ...value.Link, url: "/c" } };
+      }
+    });
+    t.assert.strictEqual(
+      renderHTML(result),
+      '<p>HI  <a href="/a">X</a> AND <a href="/c">Y</a></p>\n',
+    );
+  });
+
+  it("should not visit the children of deleted nodes", (t: TestContext) => {
+    const seen: string[] = [];
+    mapNodes(parseMarkdown("> quoted\n\nplain"), (value) => {
+      if (value === "BlockQuote") return null;
+      if (typeof value === "object" && "Text" in value) seen.push(value.Text);
+    });
+    t.assert.deepStrictEqual(seen, ["plain"]);
+  });
+});
+
+describe("resolvedReferences", () => {
+  const md = "[a], [b](/b), and ![c][]\n\n[c]: /c.png\n";
+
+  it("should return only callback-resolved links", (t: TestContext) => {
+    const options = {
+      parse: {
+        brokenLinkCallback: ({ normalized }: { normalized: string }) => ({
+          url: `/x/${normalized}`,
+          title: "",
+        }),
+      },
+    };
+    const [link, ...rest] = resolvedReferences(md, options);
+    t.assert.deepStrictEqual(rest, []);
+    t.assert.strictEqual(link.label, "a");
+    t.assert.strictEqual(link.url, "/x/a");
+    const node = parseMarkdown(md, options).nodes.find((n) =>
+      typeof n.data.value === "object" && "Link" in n.data.value &&
+      n.data.value.Link.url === "/x/a"
+    );
+    t.assert.deepStrictEqual(link.sourcepos, node!.data.sourcepos);
+  });
+
+  it("should return an empty array without a callback", (t: TestContext) => {
+    t.assert.deepStrictEqual(resolvedReferences(md), []);
+  });
+});
+
+describe("sourceposToOffsets", () => {
+  it("should count multi-byte characters in bytes", (t: TestContext) => {
+    const md = "# Héllo\n\né *wörld*\n";
+    const emph = parseMarkdown(md).nodes.find((n) => n.data.value === "Emph");
+    const range = sourceposToOffsets(md, emph!.data.sourcepos!);
+    t.assert.deepStrictEqual(range, { start: 13, end: 21 });
+    const bytes = new TextEncoder().encode(md).slice(range.start, range.end);
+    t.assert.strictEqual(new TextDecoder().decode(bytes), "*wörld*");
+  });
+
+  it("should throw for positions outside of the document", (t: TestContext) => {
+    const sourcepos = {
+      start: { line: 5, column: 1 },
+      end: { line: 5, column: 2 },
+    };
+    t.assert.throws(() => sourceposToOffsets("text", sourcepos), TypeError);
+  });
+});
@@ -3,13 +3,34 @@
  * Markdown from text into an abstract syntax tree ({@linkcode AST}), which can
  * be further processed or manipulated and then rendered back into various
  * formats such as HTML, CommonMark, or CommonMark XML using the respective
- * rendering functions.
+ * rendering functions. It also provides {@linkcode anchorTargets} for listing
+ * the in-document anchors referenced by a document's links,
+ * {@linkcode resolvedReferences} for auditing the links resolved by the
+ * `brokenLinkCallback` option, {@linkcode diagnostics} for finding structural
+ * problems that are silently recovered from while parsing,
+ * {@linkcode extractCodeBlocks} for collecting the code blocks of a document,
+ * {@linkcode mapNodes} for transforming an AST
+ * before it is rendered, {@linkcode reparseRange} for updating an AST after
+ * an edit, {@linkcode astToJSON} and {@linkcode astFromJSON}
+ * for persisting an AST, and {@linkcode sourceposToOffsets} for mapping
+ * source positions to offsets.
  *
  * @module parse
  */
-import { parse_document } from "./_wasm.ts";
+import {
+  anchor_targets,
+  ast_from_json,
+  ast_to_json,
+  diagnostics as _diagnostics,
+  extract_code_blocks,
+  map_nodes,
+  parse_document,
+  reparse_range,
+  resolved_references,
+  sourcepos_to_offsets,
+} from "./_wasm.ts";
 import { collectOptions } from "./_internal.ts";
-import type { AST } from "./nodes.ts";
+import type { AST, Sourcepos } from "./nodes.ts";
 import type { Options } from "./options.ts";
 
 /**
@@ -40,3 +61,460 @@ export function parseMarkdown(markdown: string, options?: Options): AST {
   const [opts, , , ...fns] = collectOptions(options);
   return parse_document(markdown, opts, ...fns);
 }
+
+/**
+ * Returns the unique fragments referenced by in-document links in a Markdown
+ * document (such as `[usage](#usage)`), without the leading `#`, in the order
+ * they first appear. These can be compared against the heading IDs of the
+ * rendered document to detect broken anchor links.
+ *
+ * @param markdown The Markdown string to be scanned.
+ * @param [options] Options to customize the parsing.
+ * @returns The fragments referenced by the document's links.
+ * @example
+ * ```ts
+ * import { anchorTargets } from "@nick/comrak";
+ * import assert from "node:assert";
+ *
+ * const md = "See [usage](#usage) and [the API](#api).\n\n# Usage\n";
+ *
+ * assert.deepStrictEqual(anchorTargets(md), ["usage", "api"]);
+ * ```
+ * @category Parsing
+ */
+export function anchorTargets(markdown: string, options?: Options): string[] {
+  const [opts, , , ...fns] = collectOptions(options);
+  return anchor_targets(markdown, opts, ...fns);
+}
+
+/**
+ * A link or image whose reference was resolved by the `brokenLinkCallback` or
+ * `referenceMap` parse options, as returned by
+ * {@linkcode resolvedReferences}.
+ *
+ * @category Parsing
+ */
+export interface ResolvedLink {
+  /** The label of the reference, as written in the document. */
+  label: string;
+  /** The URL the reference was resolved to. */
+  url: string;
+  /** The title the reference was resolved to. */
+  title: string;
+  /**
+   * The source position of the link or image, which matches that of the
+   * corresponding node in the AST returned by {@linkcode parseMarkdown}.
+   */
+  sourcepos: Sourcepos;
+}
+
+/**
+ * Returns the links and images in a Markdown document whose references were
+ * resolved by the `brokenLinkCallback` or `referenceMap` parse options, rather
+ * than by a link reference definition in the document itself, in the order
+ * they appear.
+ *
+ * In the AST returned by {@linkcode parseMarkdown}, these links can't be told
+ * apart from the ones that were authored. Their source positions can be used
+ * to find them, such as for link-checking tools to treat fallback-resolved
+ * links differently from authored ones.
+ *
+ * @param markdown The Markdown string to be scanned.
+ * @param [options] Options to customize the parsing.
+ * @returns The links and images resolved by the callback.
+ * @example
+ * ```ts
+ * import { resolvedReferences } from "@nick/comrak";
+ * import assert from "node:assert";
+ *
+ * const md = "[docs] and [home][]\n\n[home]: /\n";
+ * const links = resolvedReferences(md, {
+ *   parse: { brokenLinkCallback: () => ({ url: "/docs", title: "" }) },
+ * });
+ *
+ * assert.deepStrictEqual(links.map((link) => link.label), ["docs"]);
+ * assert.strictEqual(links[0].url, "/docs");
+ * ```
+ * @category Parsing
+ */
+export function resolvedReferences(
+  markdown: string,
+  options?: Options,
+): ResolvedLink[] {
+  const [opts, , , ...fns] = collectOptions(options);
+  return resolved_references(markdown, opts, ...fns);
+}
+
+/**
+ * A structural problem in a Markdown document, as reported by
+ * {@linkcode diagnostics}, or to the `onDiagnostic` parse option.
+ *
+ * @category Parsing
+ */
+export interface Diagnostic {
+  /**
+   * The kind of problem:
+   *
+   * - `"table-columns"`: a table row with more or fewer cells than its header
+   * - `"unclosed-fence"`: a fenced code block that is never closed
+   * - `"unclosed-link"`: an inline link whose destination is invalid or never
+   *   closed, which is kept as text
+   * - `"undefined-reference"`: a full (`[text][label]`) or collapsed
+   *   (`[label][]`) reference link whose label isn't defined
+   * - `"unmatched-emphasis"`: a run of `*` or `_` that doesn't match any
+   *   other, which is kept as text
+   *
+   * Unclosed links and unmatched emphasis are only reported to the
+   * `onDiagnostic` parse option, along with unclosed fences, when its
+   * `reportRecoveries` option is enabled.
+   */
+  kind:
+    | "table-columns"
+    | "unclosed-fence"
+    | "unclosed-link"
+    | "undefined-reference"
+    | "unmatched-emphasis";
+  /** A description of the problem. */
+  message: string;
+  /**
+   * The source position of the problem: the table row, the code block, the
+   * text holding the unresolved link, or the delimiters left as text.
+   */
+  sourcepos: Sourcepos;
+}
+
+/**
+ * Lints a Markdown document, returning the structural problems that the
+ * parser recovers from without any warning, in order of their position.
+ *
+ * Markdown never fails to parse, so mistakes are rendered as best they can
+ * be: a table row with the wrong number of cells is padded or truncated, a
+ * code fence that is never closed swallows the rest of its container, and a
+ * link to an undefined reference is left as plain text. This reports each of
+ * them, for editors and CI checks to surface.
+ *
+ * References resolved by the `brokenLinkCallback` or `referenceMap` parse
+ * options are not reported. Shortcut references (`[label]`) are never
+ * reported either, since they can't be told apart from bracketed text.
+ *
+ * @param markdown The Markdown string to be checked.
+ * @param [options] Options to customize the parsing.
+ * @returns The problems found in the document.
+ * @example
+ * ```ts
+ * import { diagnostics } from "@nick/comrak";
+ * import assert from "node:assert";
+ *
+ * const md = "| a | b |\n| - | - |\n| 1 | 2 | 3 |\n\nSee [docs][api].\n";
+ * const found = diagnostics(md, { extension: { table: true } });
+ *
+ * assert.deepStrictEqual(found.map((d) => d.message), [
+ *   "table row has 3 cells, but the header has 2",
+ *   'reference "api" is not defined',
+ * ]);
+ * ```
+ * @category Parsing
+ */
+export function diagnostics(
+  markdown: string,
+  options?: Options,
+): Diagnostic[] {
+  const [opts, , , ...fns] = collectOptions(options);
+  return _diagnostics(markdown, opts, ...fns);
+}
+
+/**
+ * A code block in a Markdown document, as returned by
+ * {@linkcode extractCodeBlocks}.
+ *
+ * @category Parsing
+ */
+export interface ExtractedCodeBlock {
+  /**
+   * The language of the code block, which is the first word of its info
+   * string, or `null` if it has none (as for indented code blocks).
+   */
+  language: string | null;
+  /** The rest of the info string after the language, or `null` if empty. */
+  meta: string | null;
+  /** The code in the block, ending with a newline unless it's empty. */
+  code: string;
+  /**
+   * The source position of the code block, which matches that of the
+   * corresponding node in the AST returned by {@linkcode parseMarkdown}.
+   */
+  sourcepos: Sourcepos;
+}
+
+/**
+ * Returns every code block in a Markdown document, fenced or indented, in the
+ * order they appear, with the language and meta of its info string, its code,
+ * and its source position. This is handy for tools that run or check the code
+ * examples in documentation, without having to scan the source or the
+ * rendered HTML for them.
+ *
+ * @param markdown The Markdown string to be scanned.
+ * @param [options] Options to customize the parsing.
+ * @returns The code blocks in the document.
+ * @example
+ * ```ts
+ * import { extractCodeBlocks } from "@nick/comrak";
+ * import assert from "node:assert";
+ *
+ * const md = "```ts title=a.ts\nlet a = 1;\n```\n\n    indented\n";
+ * const blocks = extractCodeBlocks(md);
+ *
+ * assert.deepStrictEqual(
+ *   blocks.map(({ language, meta, code }) => ({ language, meta, code })),
+ *   [
+ *     { language: "ts", meta: "title=a.ts", code: "let a = 1;\n" },
+ *     { language: null, meta: null, code: "indented\n" },
+ *   ],
+ * );
+ * ```
+ * @category Parsing
+ */
+export function extractCodeBlocks(
+  markdown: string,
+  options?: Options,
+): ExtractedCodeBlock[] {
+  const [opts, , , ...fns] = collectOptions(options);
+  return extract_code_blocks(markdown, opts, ...fns);
+}
+
+/**
+ * The value of a node in an {@linkcode AST}, such as `{ Text: "hi" }` or
+ * `{ Link: { url: "/", title: "" } }`.
+ *
+ * @category Parsing
+ */
+export type NodeValue = AST.NodeType["data"]["value"];
+
+/**
+ * A function called by {@linkcode mapNodes} for each node of an AST. It can
+ * return a replacement value for the node, `null` to delete the node along
+ * with its children, or `undefined` to leave the node unchanged.
+ *
+ * @category Parsing
+ */
+export interface MapNodesCallback {
+  (value: NodeValue): NodeValue | null | undefined | void;
+}
+
+/**
+ * Transforms an abstract syntax tree (AST), produced by the
+ * {@linkcode parseMarkdown} function, by calling `callback` with the value of
+ * each node in document order. Returns a new AST that can be rendered by any
+ * of the formatter functions, without reparsing the document.
+ *
+ * The callback can delete a node by returning `null`, or replace its value by
+ * returning a new one, such as to change the content of text or the URL of a
+ * link. The children of a node are visited after the node itself, and the
+ * children of deleted nodes are not visited at all.
+ *
+ * @param ast The AST to be transformed.
+ * @param callback The function called for the value of each node.
+ * @returns The transformed AST.
+ * @example
+ * ```ts
+ * import { mapNodes, parseMarkdown, renderHTML } from "@nick/comrak";
+ * import assert from "node:assert";
+ *
+ * const ast = parseMarkdown("Call 555-0100 or see [the site](http://a.dev).");
+ * const result = mapNodes(ast, (value) => {
+ *   if (typeof value !== "object") return;
+ *   if ("Text" in value) {
+ *     return { Text: value.Text.replace(/\d{3}-\d{4}/g, "[redacted]") };
+ *   }
+ *   if ("Link" in value) {
+ *     const url = value.Link.url.replace(/^http:/, "https:");
+ *     return { Link: { ...value.Link, url } };
+ *   }
+ * });
+ *
+ * assert.strictEqual(
+ *   renderHTML(result),
+ *   '<p>Call [redacted] or see <a href="https://a.dev">the site</a>.</p>\n',
+ * );
+ * ```
+ * @category Parsing
+ */
+export function mapNodes(ast: AST, callback: MapNodesCallback): AST {
+  return map_nodes(ast, callback);
+}
+
+/**
+ * Applies an edit to a Markdown document and returns the AST of the edited
+ * document, updating the AST of the original one rather than parsing the
+ * whole document again. This is meant for live editors, which reparse the
+ * document on every keystroke.
+ *
+ * Only the top-level blocks touched by the edit are reparsed, along with the
+ * block on either side of them. When that might not give the same AST as
+ * {@linkcode parseMarkdown}, such as when the document contains link
+ * reference or footnote definitions (which apply to the whole document), or
+ * when the edit opens a code fence, the whole document is parsed instead.
+ *
+ * The edit replaces the range from `start` to `end` of the original document
+ * with `text`. Like the rest of the API, these are offsets in UTF-8 bytes,
+ * rather than in the UTF-16 code units of JS strings, which only differ for
+ * documents containing non-ASCII characters.
+ *
+ * @param markdown The original Markdown string.
+ * @param ast The AST of the original string, parsed with the same options.
+ * @param start The offset of the first byte replaced by the edit.
+ * @param end The offset immediately following the last byte replaced.
+ * @param text The text inserted by the edit.
+ * @param [options] Options to customize the parsing.
+ * @returns The AST of the edited document.
+ * @example
+ * ```ts
+ * import { parseMarkdown, renderHTML, reparseRange } from "@nick/comrak";
+ * import assert from "node:assert";
+ *
+ * const md = "# Title\n\nHello, world!\n\nGoodbye.\n";
+ * const ast = parseMarkdown(md);
+ * const edited = reparseRange(md, ast, 16, 21, "**Nick**");
+ * assert.strictEqual(
+ *   renderHTML(edited),
+ *   "<h1>Title</h1>\n<p>Hello, <strong>Nick</strong>!</p>\n<p>Goodbye.</p>\n",
+ * );
+ * ```
+ * @category Parsing
+ */
+export function reparseRange(
+  markdown: string,
+  ast: AST,
+  start: number,
+  end: number,
+  text: string,
+  options?: Options,
+): AST {
+  const [opts, , , ...fns] = collectOptions(options);
+  return reparse_range(markdown, ast, start, end, text, opts, ...fns);
+}
+
+/**
+ * Serializes an abstract syntax tree (AST), produced by the
+ * {@linkcode parseMarkdown} function, to JSON in a stable, versioned schema
+ * for persisting it, such as in a database. Unlike the AST itself, whose
+ * shape follows the internals of Comrak, the schema is kept stable across
+ * releases, and older versions are migrated by {@linkcode astFromJSON}.
+ *
+ * The JSON is an object with the version of the schema it was written with,
+ * and the root node of the tree:
+ *
+ * ```json
+ * {
+ *   "schemaVersion": 2,
+ *   "root": { "value": { "type": "Document" }, "sourcepos": [1, 1, 1, 19] }
+ * }
+ * ```
+ *
+ * Each node has a `value` with the `type` of the node and only the fields
+ * needed to render it (such as the `level` of a heading, or the `url` and
+ * `title` of a link), a `sourcepos` of `[startLine, startColumn, endLine,
+ * endColumn]`, and its child nodes in a `children` array, which is omitted
+ * when it has none. These are the schema's own types, versioned separately
+ * from the AST's `value` and `sourcepos`, which follow Comrak's and change
+ * along with it. The state of the parser held by the AST is not kept.
+ *
+ * @param ast The AST to be serialized.
+ * @returns The JSON string.
+ * @example
+ * ```ts
+ * import { astFromJSON, astToJSON } from "@nick/comrak";
+ * import { parseMarkdown, renderHTML } from "@nick/comrak";
+ * import assert from "node:assert";
+ *
+ * const ast = parseMarkdown("# Hello, **world**!");
+ * const json = astToJSON(ast);
+ * assert.strictEqual(JSON.parse(json).schemaVersion, 2);
+ * assert.strictEqual(renderHTML(astFromJSON(json)), renderHTML(ast));
+ * ```
+ * @category Parsing
+ */
+export function astToJSON(ast: AST): string {
+  return ast_to_json(ast);
+}
+
+/**
+ * Deserializes JSON written by {@linkcode astToJSON} back into an AST, which
+ * can be rendered by any of the formatter functions. JSON written by an older
+ * version of the schema is migrated to the current one, and an AST returned
+ * by {@linkcode parseMarkdown} that was persisted with `JSON.stringify` is
+ * read as well.
+ *
+ * Throws a `TypeError` if the JSON is not a valid AST, or was written with a
+ * newer version of the schema than this release supports.
+ *
+ * @param json The JSON string to be deserialized.
+ * @returns The deserialized AST.
+ * @example
+ * ```ts
+ * import { astFromJSON, astToJSON } from "@nick/comrak";
+ * import { parseMarkdown, renderHTML } from "@nick/comrak";
+ * import assert from "node:assert";
+ *
+ * const json = astToJSON(parseMarkdown("Some *emphasized* text."));
+ * assert.strictEqual(
+ *   renderHTML(astFromJSON(json)),
+ *   "<p>Some <em>emphasized</em> text.</p>\n",
+ * );
+ * ```
+ * @category Parsing
+ */
+export function astFromJSON(json: string): AST {
+  return ast_from_json(json);
+}
+
+/**
+ * A range of UTF-8 byte offsets into a source document, as returned by
+ * {@linkcode sourceposToOffsets}.
+ *
+ * @category Parsing
+ */
+export interface ByteRange {
+  /** The offset of the first byte in the range. */
+  start: number;
+  /** The offset immediately following the last byte in the range. */
+  end: number;
+}
+
+/**
+ * Converts a {@linkcode Sourcepos} from an AST, with its 1-based lines and
+ * columns, into the absolute UTF-8 byte offsets of the range it spans in the
+ * Markdown it was parsed from. Multi-byte characters are accounted for, since
+ * Comrak counts columns in bytes rather than characters.
+ *
+ * Note that these are offsets into the UTF-8 encoding of the document, not
+ * into the JavaScript string itself (which is UTF-16). They can be used with
+ * the bytes from a `TextEncoder`, or with editors that work in UTF-8.
+ *
+ * @param markdown The Markdown document the AST was parsed from.
+ * @param sourcepos The source position to convert.
+ * @returns The range of byte offsets, with an exclusive `end`.
+ * @throws {TypeError} If the position is outside of the document.
+ * @example
+ * ```ts
+ * import { sourceposToOffsets } from "@nick/comrak";
+ * import assert from "node:assert";
+ *
+ * const md = "# Héllo\n\n*wörld*\n";
+ * const range = sourceposToOffsets(md, {
+ *   start: { line: 3, column: 1 },
+ *   end: { line: 3, column: 8 },
+ * });
+ * assert.deepStrictEqual(range, { start: 10, end: 18 });
+ *
+ * const bytes = new TextEncoder().encode(md).slice(range.start, range.end);
+ * assert.strictEqual(new TextDecoder().decode(bytes), "*wörld*");
+ * ```
+ * @category Parsing
+ */
+export function sourceposToOffsets(
+  markdown: string,
+  sourcepos: Sourcepos,
+): ByteRange {
+  return sourcepos_to_offsets(markdown, sourcepos);
+}
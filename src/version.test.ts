@@ -0,0 +1,37 @@
+import { describe, it, type TestContext } from "node:test";
+
+import { version } from "./_wasm.ts";
+import { type Feature, hasFeature, versionInfo } from "./version.ts";
+
+describe("versionInfo", () => {
+  it("should include the Comrak version", (t: TestContext) => {
+    t.assert.strictEqual(versionInfo().comrak, version());
+  });
+
+  it("should include the crate version", (t: TestContext) => {
+    t.assert.match(versionInfo().crate, /^\d+\.\d+\.\d+/);
+  });
+
+  it("should list only known features", (t: TestContext) => {
+    const known = ["alloc", "shortcodes", "syntect", "threading"];
+    for (const feature of versionInfo().features) {
+      t.assert.ok(known.includes(feature), feature);
+    }
+  });
+});
+
+describe("hasFeature", () => {
+  it("should match the features of versionInfo", (t: TestContext) => {
+    const { features } = versionInfo();
+    for (const feature of ["alloc", "shortcodes", "syntect", "threading"]) {
+      t.assert.strictEqual(
+        hasFeature(feature as Feature),
+        features.includes(feature as Feature),
+      );
+    }
+  });
+
+  it("should return false for unknown features", (t: TestContext) => {
+    t.assert.strictEqual(hasFeature("unknown" as Feature), false);
+  });
+});
@@ -0,0 +1,81 @@
+/**
+ * This module provides the {@linkcode versionInfo} function, which describes
+ * the build of the WebAssembly module in use, for diagnosing issues caused by
+ * mismatched builds, and the {@linkcode hasFeature} function for detecting
+ * its optional capabilities.
+ *
+ * @module version
+ */
+import { has_feature, version_info } from "./_wasm.ts";
+
+/**
+ * The optional features that the WebAssembly module may be built with.
+ *
+ * - `"alloc"`: a custom, size-optimized memory allocator.
+ * - `"shortcodes"`: support for the `shortcodes` extension (e.g. `:rocket:`).
+ * - `"syntect"`: the built-in syntax highlighter, used by the
+ *   `SyntaxHighlighterAdapter` constructor.
+ * - `"threading"`: a thread-safe variant of the custom allocator.
+ *
+ * @category Utilities
+ */
+export type Feature = "alloc" | "shortcodes" | "syntect" | "threading";
+
+/**
+ * Information about the build of the WebAssembly module in use.
+ *
+ * @category Utilities
+ */
+export interface VersionInfo {
+  /** The version of the Comrak crate. */
+  comrak: string;
+  /** The version of the `comrak-wasm` crate, which provides the bindings. */
+  crate: string;
+  /** The optional features enabled in the build. */
+  features: Feature[];
+}
+
+/**
+ * Returns the versions of Comrak and of its WebAssembly bindings, along with
+ * the optional features the module was built with. Include this in bug
+ * reports to help identify the exact build in use.
+ *
+ * @returns The version information of the WebAssembly module.
+ * @example
+ * ```ts
+ * import { versionInfo } from "@nick/comrak";
+ * import assert from "node:assert";
+ *
+ * const info = versionInfo();
+ * assert.match(info.comrak, /^\d+\.\d+\.\d+/);
+ * assert.ok(Array.isArray(info.features));
+ * ```
+ * @category Utilities
+ */
+export function versionInfo(): VersionInfo {
+  return version_info();
+}
+
+/**
+ * Checks whether the WebAssembly module was built with the given optional
+ * feature. This allows callers to degrade gracefully when a capability isn't
+ * available, such as by falling back to a JS syntax highlighter when the
+ * built-in one (the `"syntect"` feature) isn't compiled in, rather than
+ * catching the error thrown when it is used.
+ *
+ * @param name The name of the feature to check for.
+ * @returns `true` if the feature is enabled, `false` otherwise.
+ * @example
+ * ```ts
+ * import { hasFeature } from "@nick/comrak";
+ * import { SyntaxHighlighterAdapter } from "@nick/comrak/wasm";
+ *
+ * const adapter = hasFeature("syntect")
+ *   ? new SyntaxHighlighterAdapter("base16-ocean.dark")
+ *   : null;
+ * ```
+ * @category Utilities
+ */
+export function hasFeature(name: Feature): boolean {
+  return has_feature(name);
+}